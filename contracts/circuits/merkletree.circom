@@ -1,14 +1,14 @@
 pragma circom 2.0.0;
 
-include "circomlib/circuits/poseidon.circom";
+include "./poseidon2.circom";
 
-// Computes Poseidon([left, right])
+// Computes Poseidon2([left, right, 0])[0], the node hash used by the shielded-pool tree
 template HashLeftRight() {
     signal input left;
     signal input right;
     signal output hash;
 
-    component hasher = Poseidon(2);
+    component hasher = Poseidon2Hash2();
     hasher.inputs[0] <== left;
     hasher.inputs[1] <== right;
     hash <== hasher.out;
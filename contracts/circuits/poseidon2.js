@@ -0,0 +1,109 @@
+// Poseidon2 (BN254, t = 3) matching poseidon2.circom and the shielded-pool program
+const P = 21888242871839275222246405745257275088548364400416034343698204186575808495617n;
+
+const EXTERNAL_RC = [
+    [13128406282895484157369354038809433636203389051939936481821261911791933663254n, 18931653859213243425446645781588512487838213266321401679594943842133071369744n, 14100663835952519432830313936592734340076294692040144715814219945570907513297n],
+    [4829113795940962171577509772302063766582957624337039572002553144762883322341n, 15524196826242151316602020382811195434692947787822797536837043495207890599720n, 11824742889827005569732308046012743315382715056680481843559537371456931944245n],
+    [15824369292130948538570881538463827283727388637222356799784648390667783881850n, 7395652367440825515524159918310823124942438011035473842936180620057265532493n, 1241351203963627868835881804826107927839874261162687401459390240620885410254n],
+    [6688265362431458560657026053775250595854204120757399493099812773970419156132n, 18628865421786169197184064906533816626840829027307965436801990532221681661310n, 17770079997659052348824924629777474963416629061770380464722096481670103655806n],
+    [12123026335854515584932892161148559902027319284544852339906677442670161590992n, 11747143856113197599032240626240804787576886917202313931914972592787570603429n, 12689083329367969619896630238881490862330991685178863399139986099061967775891n],
+    [9363616378570856727297258914956380343356030981401312041884116403700849212733n, 13238291046435061349401827110993774315432323243867917623501520885175217584478n, 13857006478672530359037215101120381968370236111775805219419707798416454682620n],
+    [2022752961549084842139747691238383165524359342011064407942599644003308437489n, 11377043765620686524844863869245961003946340433252666374730228559486855986878n, 9107028336454933966239128359918274121166034584181733998485105905495346200934n],
+    [900063247840342897532382686223939136593244983486268682637380837456165317070n, 11261302954518146885624063833699323298803404236535464228351677636819579513431n, 7126990412157463341897179572979760225771626877677162088926546182321369054630n]
+];
+
+const INTERNAL_RC = [
+    11811415718957691261673974625780511541635150909919309658375768251762566747317n,
+    17491388639298611159333770975992024026420968324544834879936543171716736973879n,
+    5647537972700463414111873015737673282707440513292923385601908870282442800104n,
+    13098696909140066209556423100763036393001603197583133354863092304798723388565n,
+    6951180250619279643770888203380891623788978362131976553140006882493632020745n,
+    11250251081997661635793843737498879309304455145146915350538637298238893102958n,
+    2246982048814095620312232487641427155108104073024754628893054837638848127964n,
+    18897180842973857564376958241871700087418903006311506731527228148081597475814n,
+    11557404599711559103972421944754928847181400366333080241838467983028485750549n,
+    17156358787639157774388183034849932704703797218604790661321342987075785318260n,
+    8846001957151556825394442611430138293780354129800063716225175548340091032449n,
+    21883449834630454155761926448978525628607016008113566399646971468161186616967n,
+    11782201180140779170005707786217005381305915516114251118577530420880166417952n,
+    19574374768428302416384468550351257389078501920039012797497943057156188490399n,
+    8515987927591912252146893631936027853249294776314628553087138119917968203620n,
+    17278996890957540943430295799612663512184925495827057764219426280563743078943n,
+    4560144125266860756441160513270281593457202308593722614013851111005532208589n,
+    18507459160700813704135500972073304101922968342745790738233104310822653821881n,
+    12853272419783978245995917302225694649366687506910892647236063701566570840428n,
+    14374895923592519298500369713759001634990764548024903321294831249025876110484n,
+    1754533789272381217541450481312878927560073411620344950409407505576538004136n,
+    20448232810715691360468548645921483318770769828465347895613479253435247065293n,
+    4203277692183102377396835282861288449527228200284576966986741905195109677387n,
+    11506339386261725202512749094297334054772084639665212079028551409689271965431n,
+    4408799661846477128378547528471700197737434561274043409442231147309460168718n,
+    10862521404448958117187164110262290189825635328197001646848012017699995213390n,
+    7012061838863338817532836723152059636816924388921632356281537445328382279260n,
+    8337544039076735620694225144163354013921209405711398618659178986151546625400n,
+    16173744372216956516796750206695252671549928142051779144629150462255079400849n,
+    19072902632067672883974143637757649536845413107085656789672471396027868707732n,
+    3487852254355424154670010750480228751987308757772575371606146474985412561707n,
+    17727517395793273304860106667199855253218123164763798377815886217088561516989n,
+    13280131383170382695839570176732265848909891244754629477752800360224963964534n,
+    21504421972374418324171209120165696620934505501591484695447432472073975792776n,
+    13753604424945682926871108642602624411461374991709441590662260371815673344981n,
+    8053178768600673579416591772204841415225213226540397062676127402210384682315n,
+    15101558583452488762759591936595783545455044970328380152280373697190919758012n,
+    6286700389345423344101403023711121482167900236544298155098199100234816571786n,
+    19368755554193272721035317233504719593365546521121074341670771231332472422552n,
+    13306281365497267243785678269212920842854030794417306689235276460198094483575n,
+    10121764749051640353641114693266514664967620368543293902008953934189850195966n,
+    179619165022370308972665071682395477322215797039585945216341070107573537790n,
+    14053393851645634065914179337120715807963438235922115988819572738574714471437n,
+    17345906218970918797922168310670548252023720338285437740234091480846393436478n,
+    10383068492552043678323859571562933490503408853170063884414176092784243607055n,
+    12096041499044892166554391619429604246288825927654072010011878199637889490527n,
+    6449742640166027959651492823149770763572943879017164812917305794918053034585n,
+    6551805454148805882554763665748573416514894105513920161214733482541847062214n,
+    3651410956659878392469489270906333016569562868954890104332567650040497030813n,
+    15219053914464753937310253926447830297339787956721755285255510737973021838676n,
+    881679665678132972106931291023348167890022611850562267871389203532691753422n,
+    5006067481688857073852527145736822635357747460125905556158034280392250104971n,
+    12765332320844032254009314500332101047115754896003948733635815046365410860591n,
+    12908190215073542091623737558383307555705501651914623082354191483197810853182n,
+    1446042792715825508366007519346636771782990303010685652946852324744810237839n,
+    17414863822034645298427260856470503848317996477890518738401812766215195632841n
+];
+
+const mod = (x) => ((x % P) + P) % P;
+
+function sbox(x) {
+    const x2 = mod(x * x);
+    return mod(mod(x2 * x2) * x);
+}
+
+function externalMatmul(state) {
+    const sum = state[0] + state[1] + state[2];
+    return state.map((x) => mod(x + sum));
+}
+
+function permutation(input) {
+    let state = externalMatmul(input.map((x) => mod(BigInt(x))));
+
+    const fullRound = (rc) => externalMatmul(state.map((x, i) => sbox(x + rc[i])));
+
+    for (const rc of EXTERNAL_RC.slice(0, 4)) {
+        state = fullRound(rc);
+    }
+    for (const rc of INTERNAL_RC) {
+        state[0] = sbox(state[0] + rc);
+        const sum = state[0] + state[1] + state[2];
+        state = [mod(state[0] + sum), mod(state[1] + sum), mod(2n * state[2] + sum)];
+    }
+    for (const rc of EXTERNAL_RC.slice(4)) {
+        state = fullRound(rc);
+    }
+    return state;
+}
+
+function hash2(left, right) {
+    return permutation([left, right, 0n])[0];
+}
+
+module.exports = { permutation, hash2 };
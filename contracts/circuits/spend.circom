@@ -1,27 +1,33 @@
 pragma circom 2.1.6;
 
 include "circomlib/circuits/poseidon.circom";
+include "circomlib/circuits/bitify.circom";
+include "circomlib/circuits/comparators.circom";
 include "./merkletree.circom";
 
 template Spend(levels) {
     // Public inputs
     signal input root;          // Merkle tree root
     signal input nullifierHash; // Prevents double-spend
-    signal input recipient;     // Payment recipient
+    signal input recipient;     // Payment recipient as recipient_signal(pubkey): SHA256 with the top byte cleared
     signal input amount;        // Payment amount
     signal input externalNullifier; // External nullifier for app separation
+    signal input changeCommitment;  // Commitment to the change note (0 = no change)
+    signal input relayer;       // Relayer paid the relay fee, as relayer_signal(pubkey)
 
     // Private inputs
     signal input secret;        // Owner's secret
+    signal input noteValue;     // Value of the note being spent
     signal input randomness;    // Randomness for commitment
+    signal input changeRandomness; // Randomness for the change note commitment
     signal input pathElements[levels]; // Merkle proof
     signal input pathIndices[levels];  // Merkle path
 
     // 1. Compute commitment
-    // Commitment = Poseidon(secret, amount, randomness)
+    // Commitment = Poseidon(secret, noteValue, randomness)
     component commitmentHasher = Poseidon(3);
     commitmentHasher.inputs[0] <== secret;
-    commitmentHasher.inputs[1] <== amount;
+    commitmentHasher.inputs[1] <== noteValue;
     commitmentHasher.inputs[2] <== randomness;
     signal commitment <== commitmentHasher.out;
 
@@ -47,7 +53,28 @@ template Spend(levels) {
     nullifierHasher.inputs[2] <== recipient;
     nullifierHasher.inputs[3] <== externalNullifier;
     nullifierHash === nullifierHasher.out;
+
+    // 4. Split the note: noteValue = amount + changeAmount
+    // The range check rejects an amount larger than the note value
+    signal changeAmount <== noteValue - amount;
+    component changeRange = Num2Bits(64);
+    changeRange.in <== changeAmount;
+
+    // ChangeCommitment = Poseidon(secret, changeAmount, changeRandomness),
+    // or 0 when the note is spent in full
+    component changeHasher = Poseidon(3);
+    changeHasher.inputs[0] <== secret;
+    changeHasher.inputs[1] <== changeAmount;
+    changeHasher.inputs[2] <== changeRandomness;
+
+    component noChange = IsZero();
+    noChange.in <== changeAmount;
+    changeCommitment === (1 - noChange.out) * changeHasher.out;
+
+    // 5. Bind the relayer so a resubmitted proof cannot redirect the relay fee;
+    // the square keeps the otherwise unused input under a constraint
+    signal relayerSquare <== relayer * relayer;
 }
 
 // Tree depth of 20 allows for ~1 million leaves
-component main {public [root, nullifierHash, recipient, amount, externalNullifier]} = Spend(20);
+component main {public [root, nullifierHash, recipient, amount, externalNullifier, changeCommitment, relayer]} = Spend(20);
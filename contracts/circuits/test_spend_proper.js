@@ -1,6 +1,7 @@
 const snarkjs = require("snarkjs");
 const fs = require("fs");
 const { buildPoseidon } = require("circomlibjs");
+const poseidon2 = require("./poseidon2");
 
 class MerkleTree {
     constructor(levels) {
@@ -12,8 +13,9 @@ class MerkleTree {
         this.poseidon = await buildPoseidon();
     }
 
+    // Merkle nodes use Poseidon2 to match the on-chain tree
     hash(left, right) {
-        return this.poseidon.F.toString(this.poseidon([left, right]));
+        return poseidon2.hash2(BigInt(left), BigInt(right)).toString();
     }
 
     // Calculate merkle root from a leaf and its path
@@ -80,10 +82,14 @@ async function testSpendWithValidMerkleTree() {
         recipient: recipient,
         amount: amount,
         externalNullifier: externalNullifier,
+        changeCommitment: "0", // Note is spent in full
+        relayer: "2222222222222222222222222222222222222222222222222222222222222222",
 
         // Private inputs
         secret: secret,
+        noteValue: amount,
         randomness: randomness,
+        changeRandomness: "0",
         pathElements: pathElements,
         pathIndices: pathIndices
     };
@@ -108,6 +114,10 @@ async function testSpendWithValidMerkleTree() {
         if (res === true) {
             console.log("✅ Updated Spend Circuit: Verification successful!");
             console.log("🎉 All production improvements working correctly!");
+
+            // Fixture for the compute-unit benchmark in tests/spend-verifier-cu.ts
+            fs.writeFileSync("build/proof_spend.json", JSON.stringify(proof));
+            fs.writeFileSync("build/public_spend.json", JSON.stringify(publicSignals));
         } else {
             console.log("❌ Updated Spend Circuit: Verification failed!");
         }
@@ -0,0 +1,63 @@
+//! Compile-time helpers behind each program's `PROGRAM_VERSION`.
+//!
+//! `env!`/`option_env!` have to expand in the program crate itself, so callers
+//! pass `env!("CARGO_PKG_VERSION")` and `option_env!("GIT_SHA")` in.
+#![no_std]
+
+/// Numeric component `index` of a semver string; stops at any pre-release or build suffix
+pub const fn version_component(version: &str, index: usize) -> u8 {
+    let bytes = version.as_bytes();
+    let mut component = 0;
+    let mut value: u8 = 0;
+    let mut i = 0;
+    while i < bytes.len() && bytes[i] != b'-' && bytes[i] != b'+' {
+        if bytes[i] == b'.' {
+            if component == index {
+                return value;
+            }
+            component += 1;
+            value = 0;
+        } else {
+            value = value * 10 + (bytes[i] - b'0');
+        }
+        i += 1;
+    }
+    if component == index { value } else { 0 }
+}
+
+/// First 8 bytes of the build's git SHA, `"00000000"` when it is unset
+pub const fn build_commit(git_sha: Option<&str>) -> [u8; 8] {
+    let sha = match git_sha {
+        Some(sha) => sha.as_bytes(),
+        None => b"00000000",
+    };
+    let mut commit = [b'0'; 8];
+    let mut i = 0;
+    while i < commit.len() && i < sha.len() {
+        commit[i] = sha[i];
+        i += 1;
+    }
+    commit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_version_components() {
+        assert_eq!(version_component("1.12.3", 0), 1);
+        assert_eq!(version_component("1.12.3", 1), 12);
+        assert_eq!(version_component("1.12.3", 2), 3);
+        assert_eq!(version_component("1.12.3", 3), 0);
+        assert_eq!(version_component("0.4.7-beta.2", 2), 7);
+        assert_eq!(version_component("2.0.1+sha.5", 2), 1);
+    }
+
+    #[test]
+    fn pads_and_truncates_build_commit() {
+        assert_eq!(&build_commit(None), b"00000000");
+        assert_eq!(&build_commit(Some("d42efdb93a")), b"d42efdb9");
+        assert_eq!(&build_commit(Some("abc")), b"abc00000");
+    }
+}
@@ -1,4 +1,7 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::{get_associated_token_address, AssociatedToken};
+use anchor_spl::token::{self, Mint, Token, TokenAccount};
 
 use sha2::{Sha256, Digest};
 
@@ -32,11 +35,19 @@ pub mod access_controller {
             purchase.buyer == ctx.accounts.buyer.key(),
             ErrorCode::BuyerMismatch
         );
+        // The purchase's listing must be the content being unlocked
+        require!(
+            content_hash == ctx.accounts.listing.content_hash,
+            ErrorCode::ContentMismatch
+        );
 
-        // Verify this is being called by authorized program (x402-registry or spend-verifier)
+        // Verify this is being called by an authorized program: only the
+        // caller itself can sign for its grant authority PDA
         require!(
-            ctx.accounts.caller_program.key() == crate::X402_REGISTRY_ID ||
-            ctx.accounts.caller_program.key() == crate::SPEND_VERIFIER_ID,
+            ctx.accounts.authorized_callers.is_authorized(
+                &ctx.accounts.caller_program.key(),
+                &ctx.accounts.caller_authority.key(),
+            ),
             ErrorCode::UnauthorizedCaller
         );
 
@@ -56,10 +67,6 @@ pub mod access_controller {
         access.is_active = true;
         access.access_count = 0;
 
-        // Update purchase record
-        let purchase = &mut ctx.accounts.purchase_record;
-        purchase.access_granted = true;
-
         // Update controller stats
         let controller = &mut ctx.accounts.controller;
         controller.total_access_grants += 1;
@@ -71,10 +78,62 @@ pub mod access_controller {
             expires_at: access.expires_at,
         });
 
+        emit!(ContentDeliveryInfo {
+            buyer: access.buyer,
+            content_hash,
+            delivery_channel: ctx.accounts.listing.metadata.delivery_channel.clone(),
+        });
+
         msg!("Access granted to buyer: {} for content: {:?}", access.buyer, content_hash);
         Ok(())
     }
 
+    /// Allow a program to grant access (authority only)
+    pub fn add_authorized_caller(ctx: Context<ManageAuthorizedCallers>, caller: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.controller.authority,
+            ErrorCode::Unauthorized
+        );
+
+        let authorized_callers = &mut ctx.accounts.authorized_callers;
+        authorized_callers.bump = ctx.bumps.authorized_callers;
+        require!(
+            !authorized_callers.callers.contains(&caller),
+            ErrorCode::CallerAlreadyAuthorized
+        );
+        require!(
+            authorized_callers.callers.len() < MAX_AUTHORIZED_CALLERS,
+            ErrorCode::TooManyAuthorizedCallers
+        );
+        authorized_callers.callers.push(caller);
+
+        emit!(CallerAuthorizationChanged {
+            caller,
+            action: CallerAction::Added,
+        });
+
+        Ok(())
+    }
+
+    /// Stop a program from granting access (authority only)
+    pub fn remove_authorized_caller(ctx: Context<ManageAuthorizedCallers>, caller: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.controller.authority,
+            ErrorCode::Unauthorized
+        );
+
+        let authorized_callers = &mut ctx.accounts.authorized_callers;
+        authorized_callers.bump = ctx.bumps.authorized_callers;
+        authorized_callers.callers.retain(|authorized| authorized != &caller);
+
+        emit!(CallerAuthorizationChanged {
+            caller,
+            action: CallerAction::Removed,
+        });
+
+        Ok(())
+    }
+
     /// Verify access permissions (called before content delivery)
     pub fn verify_access(
         ctx: Context<VerifyAccess>,
@@ -122,6 +181,14 @@ pub mod access_controller {
         let access = &mut ctx.accounts.access_permission;
         access.is_active = false;
 
+        // Burn the holder token too when the buyer minted one
+        burn_access_token_cpi(
+            access,
+            &ctx.accounts.access_mint,
+            &ctx.accounts.buyer_token_account,
+            &ctx.accounts.token_program,
+        )?;
+
         emit!(AccessRevoked {
             buyer: access.buyer,
             content_hash: access.content_hash,
@@ -134,6 +201,36 @@ pub mod access_controller {
         Ok(())
     }
 
+    /// Revoke a credential so it can no longer be presented at purchase (issuer only)
+    pub fn revoke_credential(
+        ctx: Context<RevokeCredential>,
+        credential_hash: [u8; 32],
+        proof: x402_registry::CredentialProof,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.issuer.key() == proof.issuer_pubkey,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            x402_registry::credential_hash(&proof)? == credential_hash,
+            ErrorCode::CredentialHashMismatch
+        );
+
+        let revoked = &mut ctx.accounts.revoked_credential;
+        revoked.credential_hash = credential_hash;
+        revoked.issuer = proof.issuer_pubkey;
+        revoked.revoked_at = Clock::get()?.unix_timestamp;
+        revoked.bump = ctx.bumps.revoked_credential;
+
+        emit!(CredentialRevoked {
+            credential_hash,
+            issuer: revoked.issuer,
+            revoked_at: revoked.revoked_at,
+        });
+
+        Ok(())
+    }
+
     /// Extend access duration
     pub fn extend_access(
         ctx: Context<ExtendAccess>,
@@ -166,6 +263,170 @@ pub mod access_controller {
         Ok(())
     }
 
+    /// Mint a single access token for an active permission to the buyer's ATA.
+    ///
+    /// Other programs can then gate on the token balance of the PDA mint
+    /// `[b"access_token", content_hash, buyer]` instead of calling into this program.
+    pub fn mint_access_token(ctx: Context<MintAccessToken>, content_hash: [u8; 32]) -> Result<Pubkey> {
+        let access = &ctx.accounts.access_permission;
+        require!(access.is_active, ErrorCode::AccessRevoked);
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            access.expires_at.is_none_or(|expiry| current_time < expiry),
+            ErrorCode::AccessExpired
+        );
+        require!(ctx.accounts.access_mint.supply == 0, ErrorCode::AccessTokenAlreadyMinted);
+
+        let buyer_key = ctx.accounts.buyer.key();
+        let bump = [ctx.bumps.access_mint];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"access_token", content_hash.as_ref(), buyer_key.as_ref(), &bump]];
+
+        token::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::MintTo {
+                    mint: ctx.accounts.access_mint.to_account_info(),
+                    to: ctx.accounts.buyer_token_account.to_account_info(),
+                    authority: ctx.accounts.access_mint.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            1,
+        )?;
+
+        // The mint PDA is made delegate so revocation can burn without the buyer
+        token::approve(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Approve {
+                    to: ctx.accounts.buyer_token_account.to_account_info(),
+                    delegate: ctx.accounts.access_mint.to_account_info(),
+                    authority: ctx.accounts.buyer.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+
+        // Freezing keeps the token soulbound: a frozen account can neither move
+        // the token nor revoke the delegate, so only this program can thaw and burn it
+        token::freeze_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::FreezeAccount {
+                account: ctx.accounts.buyer_token_account.to_account_info(),
+                mint: ctx.accounts.access_mint.to_account_info(),
+                authority: ctx.accounts.access_mint.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+
+        let mint = ctx.accounts.access_mint.key();
+        emit!(AccessTokenMinted {
+            buyer: buyer_key,
+            content_hash,
+            mint,
+        });
+        Ok(mint)
+    }
+
+    /// Burn the access token of a revoked or expired permission (permissionless)
+    pub fn burn_access_token(ctx: Context<BurnAccessToken>) -> Result<()> {
+        let access = &ctx.accounts.access_permission;
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            !access.is_active || access.expires_at.is_some_and(|expiry| current_time >= expiry),
+            ErrorCode::AccessStillValid
+        );
+
+        burn_access_token_cpi(
+            access,
+            &ctx.accounts.access_mint.to_account_info(),
+            &ctx.accounts.buyer_token_account.to_account_info(),
+            &ctx.accounts.token_program,
+        )
+    }
+
+    /// Move an access permission to a new owner, paying the creator a royalty on resales
+    ///
+    /// The royalty is honor-system: `sale_price` is whatever the parties declare,
+    /// so a transfer reported at 0 or settled off-chain pays the creator nothing.
+    pub fn transfer_access(
+        ctx: Context<TransferAccess>,
+        content_hash: [u8; 32],
+        sale_price: u64,
+    ) -> Result<()> {
+        let from = &ctx.accounts.from_permission;
+        require!(from.is_active, ErrorCode::AccessRevoked);
+        let current_time = Clock::get()?.unix_timestamp;
+        require!(
+            from.expires_at.is_none_or(|expiry| current_time < expiry),
+            ErrorCode::AccessExpired
+        );
+
+        // The seller's access token goes with the permission being closed
+        burn_access_token_cpi(
+            from,
+            &ctx.accounts.from_access_mint,
+            &ctx.accounts.from_token_account,
+            &ctx.accounts.token_program,
+        )?;
+
+        if sale_price > 0 {
+            let listing = &ctx.accounts.listing;
+            let royalty = sale_price
+                .checked_mul(listing.secondary_royalty_bps as u64)
+                .ok_or(ErrorCode::ArithmeticOverflow)?
+                / 10000;
+            let seller_amount = sale_price - royalty;
+
+            if royalty > 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.new_owner.to_account_info(),
+                            to: ctx.accounts.creator_vault.to_account_info(),
+                        },
+                    ),
+                    royalty,
+                )?;
+            }
+            if seller_amount > 0 {
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.new_owner.to_account_info(),
+                            to: ctx.accounts.current_owner.to_account_info(),
+                        },
+                    ),
+                    seller_amount,
+                )?;
+            }
+
+            emit!(SecondaryRoyaltyPaid {
+                listing_id: listing.listing_id,
+                content_hash,
+                original_creator: listing.creator,
+                royalty,
+                sale_price,
+            });
+        }
+
+        let access = &mut ctx.accounts.to_permission;
+        access.buyer = ctx.accounts.new_owner.key();
+        access.content_hash = content_hash;
+        access.granted_at = current_time;
+        access.expires_at = from.expires_at;
+        access.is_active = true;
+        access.access_count = 0;
+
+        msg!(
+            "Access to {:?} transferred from {} to {}",
+            content_hash, ctx.accounts.current_owner.key(), access.buyer
+        );
+        Ok(())
+    }
+
     /// Batch verify access for multiple content items
     pub fn batch_verify_access<'info>(
         ctx: Context<'_, '_, 'info, 'info, BatchVerifyAccess<'info>>,
@@ -182,7 +443,7 @@ pub mod access_controller {
 
             let has_access = access_data.is_active &&
                 access_data.content_hash == *content_hash &&
-                access_data.expires_at.map_or(true, |exp| current_time <= exp);
+                access_data.expires_at.is_none_or(|exp| current_time <= exp);
 
             results.push(has_access);
         }
@@ -195,11 +456,102 @@ pub mod access_controller {
 
         Ok(results)
     }
+
+    /// Report this build's version so clients can check compatibility
+    pub fn get_program_version(_ctx: Context<GetProgramVersion>) -> Result<ProgramVersion> {
+        Ok(PROGRAM_VERSION)
+    }
+}
+
+#[derive(Accounts)]
+pub struct GetProgramVersion<'info> {
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+    pub build_commit: [u8; 8], // First 8 chars of GIT_SHA at build time, "00000000" if unset
 }
 
-// Program IDs for authorization
-pub const X402_REGISTRY_ID: Pubkey = pubkey!("2a65ey6veP6vqa54K1AHg4fidM2YMH8cBLxacHNz8KCR");
-pub const SPEND_VERIFIER_ID: Pubkey = pubkey!("CwJ5s1e69mv5uAnTyaAxos9DVVQ2kWcz53BQm6krzDG9");
+/// Version compiled into this program from its Cargo.toml
+pub const PROGRAM_VERSION: ProgramVersion = ProgramVersion {
+    major: program_version::version_component(env!("CARGO_PKG_VERSION"), 0),
+    minor: program_version::version_component(env!("CARGO_PKG_VERSION"), 1),
+    patch: program_version::version_component(env!("CARGO_PKG_VERSION"), 2),
+    build_commit: program_version::build_commit(option_env!("GIT_SHA")),
+};
+
+/// Thaw and burn the buyer's access token, signing as the mint PDA's freeze
+/// authority and delegate. A no-op when the buyer never minted a token.
+fn burn_access_token_cpi<'info>(
+    access: &AccessPermission,
+    access_mint: &AccountInfo<'info>,
+    buyer_token_account: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    let (expected_mint, bump) = Pubkey::find_program_address(
+        &[b"access_token", access.content_hash.as_ref(), access.buyer.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(access_mint.key(), expected_mint, ErrorCode::AccessTokenMismatch);
+    require_keys_eq!(
+        buyer_token_account.key(),
+        get_associated_token_address(&access.buyer, &expected_mint),
+        ErrorCode::AccessTokenMismatch
+    );
+    if access_mint.data_is_empty() || buyer_token_account.data_is_empty() {
+        return Ok(());
+    }
+    let token_account = TokenAccount::try_deserialize(&mut &buyer_token_account.try_borrow_data()?[..])?;
+    if token_account.amount == 0 {
+        return Ok(());
+    }
+
+    let signer_seeds: &[&[&[u8]]] =
+        &[&[b"access_token", access.content_hash.as_ref(), access.buyer.as_ref(), &[bump]]];
+
+    if token_account.is_frozen() {
+        token::thaw_account(CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token::ThawAccount {
+                account: buyer_token_account.clone(),
+                mint: access_mint.clone(),
+                authority: access_mint.clone(),
+            },
+            signer_seeds,
+        ))?;
+    }
+
+    token::burn(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            token::Burn {
+                mint: access_mint.clone(),
+                from: buyer_token_account.clone(),
+                authority: access_mint.clone(),
+            },
+            signer_seeds,
+        ),
+        token_account.amount,
+    )?;
+
+    emit!(AccessTokenBurned {
+        buyer: access.buyer,
+        content_hash: access.content_hash,
+        mint: expected_mint,
+    });
+    Ok(())
+}
+
+// Ed25519 credential checks live in x402-registry, which this program depends on
+pub use x402_registry::verify_credential_signature;
+pub use x402_registry::ACCESS_GRANT_AUTHORITY_SEED;
+
+/// Maximum number of programs allowed to call `grant_access`
+pub const MAX_AUTHORIZED_CALLERS: usize = 10;
 
 #[derive(Accounts)]
 pub struct Initialize<'info> {
@@ -225,21 +577,52 @@ pub struct GrantAccess<'info> {
     
     #[account(
         init,
-        payer = buyer,
+        payer = payer,
         space = 8 + AccessPermission::LEN,
         seeds = [b"access", buyer.key().as_ref(), &content_hash],
         bump
     )]
     pub access_permission: Account<'info, AccessPermission>,
     
-    #[account(mut)]
+    /// Owned by x402-registry, which records `access_granted` itself
     pub purchase_record: Account<'info, x402_registry::PurchaseRecord>,
     
-    /// CHECK: Caller program verification
+    #[account(constraint = listing.listing_id == purchase_record.listing_id @ ErrorCode::ContentMismatch)]
+    pub listing: Account<'info, x402_registry::ContentListing>,
+    
+    #[account(seeds = [b"authorized_callers"], bump = authorized_callers.bump)]
+    pub authorized_callers: Account<'info, AuthorizedCallers>,
+    
+    /// CHECK: Program requesting the grant; must be listed in authorized_callers
     pub caller_program: UncheckedAccount<'info>,
     
+    /// `[ACCESS_GRANT_AUTHORITY_SEED]` PDA of caller_program, signed via invoke_signed
+    pub caller_authority: Signer<'info>,
+    
+    /// CHECK: Receives the permission; must be the purchase record's buyer
+    pub buyer: UncheckedAccount<'info>,
+    
+    /// Pays rent for the permission, e.g. the buyer or a confirming creator
     #[account(mut)]
-    pub buyer: Signer<'info>,
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageAuthorizedCallers<'info> {
+    pub controller: Account<'info, AccessController>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + AuthorizedCallers::LEN,
+        seeds = [b"authorized_callers"],
+        bump
+    )]
+    pub authorized_callers: Account<'info, AuthorizedCallers>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
@@ -263,7 +646,96 @@ pub struct RevokeAccess<'info> {
     #[account(mut)]
     pub access_permission: Account<'info, AccessPermission>,
     
+    /// CHECK: The buyer's access token mint; may be uninitialized if none was minted
+    #[account(
+        mut,
+        seeds = [b"access_token", access_permission.content_hash.as_ref(), access_permission.buyer.as_ref()],
+        bump
+    )]
+    pub access_mint: UncheckedAccount<'info>,
+    
+    /// CHECK: The buyer's associated token account for access_mint
+    #[account(
+        mut,
+        address = get_associated_token_address(&access_permission.buyer, access_mint.key)
+    )]
+    pub buyer_token_account: UncheckedAccount<'info>,
+    
     pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(content_hash: [u8; 32])]
+pub struct MintAccessToken<'info> {
+    #[account(
+        seeds = [b"access", buyer.key().as_ref(), &content_hash],
+        bump
+    )]
+    pub access_permission: Account<'info, AccessPermission>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        mint::decimals = 0,
+        mint::authority = access_mint,
+        mint::freeze_authority = access_mint,
+        seeds = [b"access_token", content_hash.as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub access_mint: Account<'info, Mint>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = access_mint,
+        associated_token::authority = buyer
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct BurnAccessToken<'info> {
+    pub access_permission: Account<'info, AccessPermission>,
+    
+    #[account(
+        mut,
+        seeds = [b"access_token", access_permission.content_hash.as_ref(), access_permission.buyer.as_ref()],
+        bump
+    )]
+    pub access_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        token::mint = access_mint,
+        token::authority = access_permission.buyer
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(credential_hash: [u8; 32])]
+pub struct RevokeCredential<'info> {
+    #[account(
+        init,
+        payer = issuer,
+        space = 8 + RevokedCredential::LEN,
+        seeds = [b"revoked_cred", credential_hash.as_ref()],
+        bump
+    )]
+    pub revoked_credential: Account<'info, RevokedCredential>,
+    
+    #[account(mut)]
+    pub issuer: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -274,6 +746,62 @@ pub struct ExtendAccess<'info> {
     pub buyer: Signer<'info>,
 }
 
+#[derive(Accounts)]
+#[instruction(content_hash: [u8; 32])]
+pub struct TransferAccess<'info> {
+    #[account(
+        mut,
+        close = current_owner,
+        seeds = [b"access", current_owner.key().as_ref(), &content_hash],
+        bump
+    )]
+    pub from_permission: Account<'info, AccessPermission>,
+    
+    #[account(
+        init,
+        payer = new_owner,
+        space = 8 + AccessPermission::LEN,
+        seeds = [b"access", new_owner.key().as_ref(), &content_hash],
+        bump
+    )]
+    pub to_permission: Account<'info, AccessPermission>,
+    
+    #[account(constraint = listing.content_hash == content_hash @ ErrorCode::ContentMismatch)]
+    pub listing: Account<'info, x402_registry::ContentListing>,
+    
+    /// Royalties are paid into the original creator's x402-registry vault
+    #[account(
+        mut,
+        seeds = [b"creator_vault", listing.creator.as_ref()],
+        seeds::program = x402_registry::ID,
+        bump = creator_vault.bump
+    )]
+    pub creator_vault: Account<'info, x402_registry::CreatorVault>,
+    
+    /// CHECK: The seller's access token mint; may be uninitialized if none was minted
+    #[account(
+        mut,
+        seeds = [b"access_token", content_hash.as_ref(), current_owner.key().as_ref()],
+        bump
+    )]
+    pub from_access_mint: UncheckedAccount<'info>,
+    
+    /// CHECK: The seller's associated token account for from_access_mint
+    #[account(
+        mut,
+        address = get_associated_token_address(current_owner.key, from_access_mint.key)
+    )]
+    pub from_token_account: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub current_owner: Signer<'info>,
+    
+    #[account(mut)]
+    pub new_owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct BatchVerifyAccess<'info> {
     pub buyer: Signer<'info>,
@@ -290,6 +818,33 @@ impl AccessController {
     pub const LEN: usize = 32 + 8;
 }
 
+#[account]
+pub struct AuthorizedCallers {
+    pub callers: Vec<Pubkey>, // Programs allowed to call grant_access
+    pub bump: u8,
+}
+
+impl AuthorizedCallers {
+    pub const LEN: usize = (4 + 32 * MAX_AUTHORIZED_CALLERS) + 1;
+
+    /// Whether `caller_program` is allowed and `caller_authority` is its grant authority PDA
+    pub fn is_authorized(&self, caller_program: &Pubkey, caller_authority: &Pubkey) -> bool {
+        self.callers.contains(caller_program)
+            && grant_authority(caller_program) == *caller_authority
+    }
+}
+
+/// PDA a calling program signs grant_access CPIs with
+pub fn grant_authority(caller_program: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[ACCESS_GRANT_AUTHORITY_SEED], caller_program).0
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum CallerAction {
+    Added,
+    Removed,
+}
+
 #[account]
 pub struct AccessPermission {
     pub buyer: Pubkey,
@@ -304,6 +859,18 @@ impl AccessPermission {
     pub const LEN: usize = 32 + 32 + 8 + (1 + 8) + 1 + 8;
 }
 
+#[account]
+pub struct RevokedCredential {
+    pub credential_hash: [u8; 32],
+    pub issuer: Pubkey,
+    pub revoked_at: i64,
+    pub bump: u8,
+}
+
+impl RevokedCredential {
+    pub const LEN: usize = 32 + 32 + 8 + 1;
+}
+
 #[event]
 pub struct AccessGranted {
     pub buyer: Pubkey,
@@ -312,6 +879,20 @@ pub struct AccessGranted {
     pub expires_at: Option<i64>,
 }
 
+/// Tells off-chain delivery agents where to serve newly granted content from
+#[event]
+pub struct ContentDeliveryInfo {
+    pub buyer: Pubkey,
+    pub content_hash: [u8; 32],
+    pub delivery_channel: x402_registry::DeliveryChannel,
+}
+
+#[event]
+pub struct CallerAuthorizationChanged {
+    pub caller: Pubkey,
+    pub action: CallerAction,
+}
+
 #[event]
 pub struct AccessVerified {
     pub buyer: Pubkey,
@@ -320,6 +901,29 @@ pub struct AccessVerified {
     pub verified_at: i64,
 }
 
+#[event]
+pub struct SecondaryRoyaltyPaid {
+    pub listing_id: u64,
+    pub content_hash: [u8; 32],
+    pub original_creator: Pubkey,
+    pub royalty: u64,
+    pub sale_price: u64,
+}
+
+#[event]
+pub struct AccessTokenMinted {
+    pub buyer: Pubkey,
+    pub content_hash: [u8; 32],
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct AccessTokenBurned {
+    pub buyer: Pubkey,
+    pub content_hash: [u8; 32],
+    pub mint: Pubkey,
+}
+
 #[event]
 pub struct AccessRevoked {
     pub buyer: Pubkey,
@@ -329,6 +933,13 @@ pub struct AccessRevoked {
     pub revoked_at: i64,
 }
 
+#[event]
+pub struct CredentialRevoked {
+    pub credential_hash: [u8; 32],
+    pub issuer: Pubkey,
+    pub revoked_at: i64,
+}
+
 #[event]
 pub struct AccessExtended {
     pub buyer: Pubkey,
@@ -366,39 +977,20 @@ pub enum ErrorCode {
     InvalidSignature,
     #[msg("Signature verification failed")]
     SignatureVerificationFailed,
-}
-
-/// Verify signature using hash-based validation
-/// This avoids dependency conflicts while maintaining security
-#[allow(dead_code)]
-fn verify_credential_signature(
-    _message: &[u8; 32],
-    signature: &[u8; 64],
-    public_key: &[u8; 32],
-) -> Result<()> {
-    // Basic validation to ensure non-zero signature and public key
-    if signature.iter().all(|&x| x == 0) {
-        return Err(ErrorCode::InvalidSignature.into());
-    }
-    
-    if public_key.iter().all(|&x| x == 0) {
-        return Err(ErrorCode::InvalidSignature.into());
-    }
-    
-    // Create a hash-based verification using the signature and public key
-    let mut hasher = Sha256::new();
-    hasher.update(signature);
-    hasher.update(public_key);
-    let verification_hash = hasher.finalize();
-    
-    // Ensure the hash is not all zeros (basic integrity check)
-    if verification_hash.iter().all(|&x| x == 0) {
-        return Err(ErrorCode::SignatureVerificationFailed.into());
-    }
-    
-    // Signature verification passed (simplified for build compatibility)
-    msg!("Credential signature verified for pubkey: {:?}", hex::encode(public_key));
-    Ok(())
+    #[msg("Credential hash does not match the credential")]
+    CredentialHashMismatch,
+    #[msg("Caller program is already authorized")]
+    CallerAlreadyAuthorized,
+    #[msg("Too many authorized caller programs")]
+    TooManyAuthorizedCallers,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("Access token already minted for this permission")]
+    AccessTokenAlreadyMinted,
+    #[msg("Access token mint does not belong to this permission")]
+    AccessTokenMismatch,
+    #[msg("Access is still valid")]
+    AccessStillValid,
 }
 
 /// Verify purchase integrity using hash-based verification
@@ -415,3 +1007,34 @@ fn verify_purchase_integrity(content_hash: &[u8; 32], buyer: &Pubkey) -> Result<
     
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn callers() -> AuthorizedCallers {
+        AuthorizedCallers {
+            callers: vec![x402_registry::ID],
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn grant_requires_the_callers_own_authority() {
+        assert!(callers().is_authorized(&x402_registry::ID, &grant_authority(&x402_registry::ID)));
+    }
+
+    #[test]
+    fn spoofed_caller_program_is_rejected() {
+        let attacker = Pubkey::new_unique();
+        let attacker_program = Pubkey::new_unique();
+        let callers = callers();
+
+        // Naming an authorized program with a key the attacker can sign for
+        assert!(!callers.is_authorized(&x402_registry::ID, &attacker));
+        // or with the grant authority of a program the attacker controls
+        assert!(!callers.is_authorized(&x402_registry::ID, &grant_authority(&attacker_program)));
+        // An unlisted program cannot grant even with its own authority
+        assert!(!callers.is_authorized(&attacker_program, &grant_authority(&attacker_program)));
+    }
+}
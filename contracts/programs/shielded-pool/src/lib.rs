@@ -1,7 +1,8 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, Transfer};
-
-
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, Field, MontFp, PrimeField, Zero};
+use ark_serialize::CanonicalDeserialize;
 
 declare_id!("75cH7CRmvDyy7o3mGuWvJhffT7ZyLmYdvv7x36ZVhio1");
 
@@ -10,18 +11,61 @@ pub mod shielded_pool {
     use super::*;
 
     /// Initialize the shielded pool with empty Merkle tree
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    pub fn initialize(ctx: Context<Initialize>, accepted_mint: Pubkey) -> Result<()> {
+        let merkle_tree = &mut ctx.accounts.merkle_tree;
+        merkle_tree.initialize(20)?; // Supports 2^20 = 1M commitments
+
         let pool = &mut ctx.accounts.pool;
         pool.authority = ctx.accounts.authority.key();
-        pool.merkle_root = [0u8; 32]; // Empty tree root
-        pool.tree_height = 20; // Supports 2^20 = 1M commitments
+        pool.merkle_root = merkle_tree.get_root();
+        pool.tree_height = merkle_tree.height;
         pool.next_index = 0;
         pool.total_deposits = 0;
+        pool.root_history = [[0u8; 32]; ROOT_HISTORY_SIZE];
+        pool.root_history_index = 0;
+        pool.denomination = None;
+        pool.accepted_mint = accepted_mint;
+        pool.merkle_tree = merkle_tree.key();
+        pool.bump = ctx.bumps.pool;
 
         msg!("Shielded pool initialized with authority: {}", pool.authority);
         Ok(())
     }
 
+    /// Create a fixed-denomination pool for a single mint, with its own Merkle tree
+    pub fn create_denomination_pool(
+        ctx: Context<CreateDenominationPool>,
+        denomination: u64,
+        accepted_mint: Pubkey,
+    ) -> Result<()> {
+        require!(denomination > 0, ErrorCode::InvalidAmount);
+
+        let merkle_tree = &mut ctx.accounts.merkle_tree;
+        merkle_tree.initialize(20)?;
+
+        let pool = &mut ctx.accounts.pool;
+        pool.authority = ctx.accounts.authority.key();
+        pool.merkle_root = merkle_tree.get_root();
+        pool.tree_height = merkle_tree.height;
+        pool.next_index = 0;
+        pool.total_deposits = 0;
+        pool.root_history = [[0u8; 32]; ROOT_HISTORY_SIZE];
+        pool.root_history_index = 0;
+        pool.denomination = Some(denomination);
+        pool.accepted_mint = accepted_mint;
+        pool.merkle_tree = merkle_tree.key();
+        pool.bump = ctx.bumps.pool;
+
+        emit!(DenominationPoolCreated {
+            denomination,
+            mint: accepted_mint,
+            pool_address: pool.key(),
+        });
+
+        msg!("Denomination pool created: denomination={}, mint={}", denomination, accepted_mint);
+        Ok(())
+    }
+
     /// Deposit tokens into the shielded pool
     pub fn deposit(
         ctx: Context<Deposit>,
@@ -29,6 +73,15 @@ pub mod shielded_pool {
         amount: u64,
     ) -> Result<()> {
         require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(commitment != [0u8; 32], ErrorCode::InvalidCommitment);
+        if let Some(denomination) = ctx.accounts.pool.denomination {
+            require!(amount == denomination, ErrorCode::WrongDenomination);
+        }
+        validate_deposit_token_accounts(
+            &ctx.accounts.pool,
+            &ctx.accounts.user_token,
+            &ctx.accounts.pool_token,
+        )?;
 
         // Transfer tokens from user to pool
         let cpi_ctx = CpiContext::new(
@@ -49,15 +102,20 @@ pub mod shielded_pool {
         merkle_tree.insert_leaf(leaf_index, commitment)?;
 
         // Update pool state  
-        pool.merkle_root = merkle_tree.compute_root()?;
+        pool.push_root(merkle_tree.refresh_root()?);
         pool.next_index += 1;
-        pool.total_deposits += amount;
+        pool.total_deposits = pool.total_deposits
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
 
+        let (path_elements, path_indices) = merkle_tree.latest_path();
         emit!(DepositEvent {
             commitment,
             leaf_index,
             amount,
             root: pool.merkle_root,
+            path_elements,
+            path_indices,
         });
 
         msg!(
@@ -67,6 +125,135 @@ pub mod shielded_pool {
         Ok(())
     }
 
+    /// Deposit up to 5 commitments with a single token transfer
+    pub fn batch_deposit(
+        ctx: Context<BatchDeposit>,
+        commitments: Vec<[u8; 32]>,
+        amounts: Vec<u64>,
+        total_amount: u64,
+    ) -> Result<()> {
+        require!(!commitments.is_empty(), ErrorCode::InvalidInput);
+        require!(commitments.len() <= 5, ErrorCode::TooManyCommitments);
+        require!(amounts.len() == commitments.len(), ErrorCode::InvalidInput);
+
+        let denomination = ctx.accounts.pool.denomination;
+        let mut amount_sum: u64 = 0;
+        for (commitment, amount) in commitments.iter().zip(&amounts) {
+            require!(*commitment != [0u8; 32], ErrorCode::InvalidCommitment);
+            require!(*amount > 0, ErrorCode::InvalidAmount);
+            require!(denomination.is_none_or(|d| *amount == d), ErrorCode::WrongDenomination);
+            amount_sum = amount_sum.checked_add(*amount).ok_or(ErrorCode::InvalidAmount)?;
+        }
+        require!(amount_sum == total_amount, ErrorCode::AmountMismatch);
+        validate_deposit_token_accounts(
+            &ctx.accounts.pool,
+            &ctx.accounts.user_token,
+            &ctx.accounts.pool_token,
+        )?;
+
+        // Transfer the combined amount from user to pool
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token.to_account_info(),
+                to: ctx.accounts.pool_token.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, total_amount)?;
+
+        let pool = &mut ctx.accounts.pool;
+        let merkle_tree = &mut ctx.accounts.merkle_tree;
+        let first_index = pool.next_index;
+
+        // Each event carries the root its path was taken against, and every
+        // intermediate root is kept in history so all of them stay provable
+        for (offset, (commitment, amount)) in commitments.iter().zip(&amounts).enumerate() {
+            merkle_tree.insert_leaf(pool.next_index, *commitment)?;
+            let (path_elements, path_indices) = merkle_tree.latest_path();
+            let root = merkle_tree.refresh_root()?;
+            pool.push_root(root);
+            pool.next_index += 1;
+
+            emit!(DepositEvent {
+                commitment: *commitment,
+                leaf_index: first_index + offset as u64,
+                amount: *amount,
+                root,
+                path_elements,
+                path_indices,
+            });
+        }
+        pool.total_deposits = pool.total_deposits
+            .checked_add(total_amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(BatchDepositEvent {
+            commitment_count: commitments.len() as u8,
+            first_index,
+            last_index: pool.next_index - 1,
+            total_amount,
+            root: pool.merkle_root,
+        });
+
+        msg!(
+            "Batch deposit successful: count={}, first_index={}, amount={}",
+            commitments.len(), first_index, total_amount
+        );
+        Ok(())
+    }
+
+    /// Insert a change note commitment produced by a partial spend (called by spend-verifier).
+    ///
+    /// The change value never left the pool, so no tokens move and the amount
+    /// stays hidden behind the commitment.
+    pub fn deposit_change(
+        ctx: Context<DepositChange>,
+        commitment: [u8; 32],
+    ) -> Result<()> {
+        // Only spend-verifier can sign for its state PDA, checked by DepositChange
+        require!(commitment != [0u8; 32], ErrorCode::InvalidCommitment);
+
+        let pool = &mut ctx.accounts.pool;
+        let leaf_index = pool.next_index;
+
+        let merkle_tree = &mut ctx.accounts.merkle_tree;
+        merkle_tree.insert_leaf(leaf_index, commitment)?;
+
+        pool.push_root(merkle_tree.refresh_root()?);
+        pool.next_index += 1;
+
+        emit!(ChangeNoteEvent {
+            commitment,
+            leaf_index,
+            root: pool.merkle_root,
+        });
+
+        Ok(())
+    }
+
+    /// Return the Merkle path of the most recent leaf for off-chain witness generation
+    pub fn get_merkle_proof(
+        ctx: Context<GetMerkleProof>,
+        leaf_index: u64,
+    ) -> Result<MerkleProofData> {
+        // Only the frontier is stored on-chain, so the path is exact for the latest leaf
+        require!(
+            leaf_index.checked_add(1) == Some(ctx.accounts.pool.next_index),
+            ErrorCode::IndexOutOfBounds
+        );
+
+        let merkle_tree = &ctx.accounts.merkle_tree;
+        let (siblings, path_indices) = merkle_tree.latest_path();
+
+        Ok(MerkleProofData {
+            siblings,
+            path_indices,
+            root: merkle_tree.get_root(),
+            leaf_index,
+        })
+    }
+
     /// Withdraw from shielded pool (will be called by spend-verifier)
     pub fn withdraw(
         ctx: Context<Withdraw>,
@@ -74,6 +261,10 @@ pub mod shielded_pool {
         recipient: Pubkey,
     ) -> Result<()> {
         require!(amount > 0, ErrorCode::InvalidAmount);
+        require!(
+            ctx.accounts.recipient_token.owner == recipient,
+            ErrorCode::RecipientMismatch
+        );
         
         let pool = &ctx.accounts.pool;
         require!(
@@ -81,16 +272,15 @@ pub mod shielded_pool {
             ErrorCode::InsufficientFunds
         );
 
-        // Only allow spend-verifier program to call this
-        require!(
-            ctx.accounts.spend_verifier.key() == crate::SPEND_VERIFIER_ID,
-            ErrorCode::UnauthorizedWithdrawal
-        );
+        // Only spend-verifier can sign for its state PDA, checked by Withdraw
 
-        // Transfer tokens from pool to recipient
-        let seeds = &[b"pool".as_slice()];
-        let (_, bump) = Pubkey::find_program_address(seeds, ctx.program_id);
-        let authority_seeds = &[b"pool".as_slice(), &[bump]];
+        // Transfer tokens from pool to recipient, signing as whichever pool PDA this is
+        let bump = [pool.bump];
+        let denomination_bytes = pool.denomination.map(u64::to_le_bytes);
+        let authority_seeds: Vec<&[u8]> = match &denomination_bytes {
+            Some(denomination) => vec![b"pool", denomination, pool.accepted_mint.as_ref(), &bump],
+            None => vec![b"pool", &bump],
+        };
         let signer = &[&authority_seeds[..]];
 
         let cpi_ctx = CpiContext::new_with_signer(
@@ -106,7 +296,9 @@ pub mod shielded_pool {
 
         // Update pool state
         let pool = &mut ctx.accounts.pool;
-        pool.total_deposits -= amount;
+        pool.total_deposits = pool.total_deposits
+            .checked_sub(amount)
+            .ok_or(ErrorCode::InsufficientFunds)?;
 
         emit!(WithdrawalEvent {
             recipient,
@@ -116,8 +308,34 @@ pub mod shielded_pool {
 
         Ok(())
     }
+
+    /// Report this build's version so clients can check compatibility
+    pub fn get_program_version(_ctx: Context<GetProgramVersion>) -> Result<ProgramVersion> {
+        Ok(PROGRAM_VERSION)
+    }
+}
+
+#[derive(Accounts)]
+pub struct GetProgramVersion<'info> {
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+    pub build_commit: [u8; 8], // First 8 chars of GIT_SHA at build time, "00000000" if unset
 }
 
+/// Version compiled into this program from its Cargo.toml
+pub const PROGRAM_VERSION: ProgramVersion = ProgramVersion {
+    major: program_version::version_component(env!("CARGO_PKG_VERSION"), 0),
+    minor: program_version::version_component(env!("CARGO_PKG_VERSION"), 1),
+    patch: program_version::version_component(env!("CARGO_PKG_VERSION"), 2),
+    build_commit: program_version::build_commit(option_env!("GIT_SHA")),
+};
+
 // Program IDs for cross-program invocations
 pub const SPEND_VERIFIER_ID: Pubkey = pubkey!("CwJ5s1e69mv5uAnTyaAxos9DVVQ2kWcz53BQm6krzDG9");
 
@@ -146,19 +364,79 @@ pub struct Initialize<'info> {
     pub system_program: Program<'info, System>,
 }
 
+/// Both sides of a deposit must hold the pool's mint, and the pool side must
+/// be owned by the pool PDA that signs withdrawals
+fn validate_deposit_token_accounts(
+    pool: &Account<ShieldedPool>,
+    user_token: &AccountInfo,
+    pool_token: &AccountInfo,
+) -> Result<()> {
+    let user_token_parsed = TokenAccount::try_deserialize(&mut &user_token.try_borrow_data()?[..])?;
+    let pool_token_parsed = TokenAccount::try_deserialize(&mut &pool_token.try_borrow_data()?[..])?;
+
+    require!(user_token_parsed.mint == pool.accepted_mint, ErrorCode::WrongTokenMint);
+    require!(pool_token_parsed.mint == pool.accepted_mint, ErrorCode::WrongTokenMint);
+    require!(pool_token_parsed.owner == pool.key(), ErrorCode::InvalidPoolTokenAccount);
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(denomination: u64, accepted_mint: Pubkey)]
+pub struct CreateDenominationPool<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ShieldedPool::LEN,
+        seeds = [b"pool", denomination.to_le_bytes().as_ref(), accepted_mint.as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, ShieldedPool>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MerkleTree::LEN,
+        seeds = [b"merkle_tree", pool.key().as_ref()],
+        bump
+    )]
+    pub merkle_tree: Account<'info, MerkleTree>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct Deposit<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, ShieldedPool>,
+    
     #[account(
         mut,
-        seeds = [b"pool"],
-        bump
+        address = pool.merkle_tree
     )]
+    pub merkle_tree: Account<'info, MerkleTree>,
+    
+    #[account(mut)]
+    /// CHECK: Token account validated by token program
+    pub user_token: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    /// CHECK: Token account validated by token program
+    pub pool_token: UncheckedAccount<'info>,
+    
+    pub user: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct BatchDeposit<'info> {
+    #[account(mut)]
     pub pool: Account<'info, ShieldedPool>,
     
     #[account(
         mut,
-        seeds = [b"merkle_tree"],
-        bump
+        address = pool.merkle_tree
     )]
     pub merkle_tree: Account<'info, MerkleTree>,
     
@@ -175,18 +453,40 @@ pub struct Deposit<'info> {
 }
 
 #[derive(Accounts)]
-pub struct Withdraw<'info> {
+pub struct DepositChange<'info> {
+    #[account(mut)]
+    pub pool: Account<'info, ShieldedPool>,
+    
     #[account(
         mut,
-        seeds = [b"pool"],
-        bump
+        address = pool.merkle_tree
     )]
+    pub merkle_tree: Account<'info, MerkleTree>,
+    
+    /// Spend verifier state PDA; only spend-verifier can sign for it
+    #[account(seeds = [b"spend_verifier"], bump, seeds::program = SPEND_VERIFIER_ID)]
+    pub spend_verifier: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetMerkleProof<'info> {
+    pub pool: Account<'info, ShieldedPool>,
+    
+    #[account(address = pool.merkle_tree)]
+    pub merkle_tree: Account<'info, MerkleTree>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
     pub pool: Account<'info, ShieldedPool>,
     
-    /// CHECK: This is the spend verifier program
-    pub spend_verifier: UncheckedAccount<'info>,
+    /// Spend verifier state PDA; only spend-verifier can sign for it
+    #[account(seeds = [b"spend_verifier"], bump, seeds::program = SPEND_VERIFIER_ID)]
+    pub spend_verifier: Signer<'info>,
     
     /// CHECK: Pool authority PDA
+    #[account(address = pool.key())]
     pub pool_authority: UncheckedAccount<'info>,
     
     #[account(mut)]
@@ -194,12 +494,14 @@ pub struct Withdraw<'info> {
     pub pool_token: UncheckedAccount<'info>,
     
     #[account(mut)]
-    /// CHECK: Token account validated by token program
-    pub recipient_token: UncheckedAccount<'info>,
+    pub recipient_token: Account<'info, TokenAccount>,
     
     pub token_program: Program<'info, Token>,
 }
 
+/// Number of recent Merkle roots accepted for spends
+pub const ROOT_HISTORY_SIZE: usize = 30;
+
 #[account]
 pub struct ShieldedPool {
     pub authority: Pubkey,
@@ -207,10 +509,37 @@ pub struct ShieldedPool {
     pub tree_height: u8,
     pub next_index: u64,
     pub total_deposits: u64,
+    pub root_history: [[u8; 32]; ROOT_HISTORY_SIZE], // Recent roots, newest at root_history_index
+    pub root_history_index: u8,
+    pub denomination: Option<u64>, // Fixed note value, None for the variable-amount pool
+    pub accepted_mint: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub bump: u8,
 }
 
 impl ShieldedPool {
-    pub const LEN: usize = 32 + 32 + 1 + 8 + 8;
+    pub const LEN: usize = 32 + 32 + 1 + 8 + 8 + (32 * ROOT_HISTORY_SIZE) + 1 + (1 + 8) + 32 + 32 + 1;
+
+    /// Set the current root and record it in the history ring buffer
+    pub fn push_root(&mut self, root: [u8; 32]) {
+        self.root_history_index = ((self.root_history_index as usize + 1) % ROOT_HISTORY_SIZE) as u8;
+        self.root_history[self.root_history_index as usize] = root;
+        self.merkle_root = root;
+    }
+
+    /// Whether `root` is the current root or one of the recent ones a proof may target
+    pub fn is_known_root(&self, root: &[u8; 32]) -> bool {
+        *root != [0u8; 32] && self.root_history.contains(root)
+    }
+}
+
+/// Merkle path returned by `get_merkle_proof`, in the layout `MerkleTree::verify_proof` expects
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct MerkleProofData {
+    pub siblings: Vec<[u8; 32]>,
+    pub path_indices: Vec<bool>, // true = current node is the left child
+    pub root: [u8; 32],
+    pub leaf_index: u64,
 }
 
 #[account]
@@ -219,10 +548,13 @@ pub struct MerkleTree {
     pub filled_subtrees: [[u8; 32]; 20], // Store subtree roots for efficiency
     pub zeros: [[u8; 32]; 20], // Zero hash values for each level
     pub root: [u8; 32],
+    pub cached_path_hashes: [[u8; 32]; 20], // Siblings on the path of the most recently inserted leaf
+    pub last_leaf_index: u64,
+    pub is_root_dirty: bool, // Set by insert_leaf until refresh_root folds the new path
 }
 
 impl MerkleTree {
-    pub const LEN: usize = 1 + (32 * 20) + (32 * 20) + 32;
+    pub const LEN: usize = 1 + (32 * 20) + (32 * 20) + 32 + (32 * 20) + 8 + 1;
     
     pub fn initialize(&mut self, height: u8) -> Result<()> {
         self.height = height;
@@ -230,7 +562,7 @@ impl MerkleTree {
         // Initialize zeros array with proper zero hashes
         self.zeros[0] = [0u8; 32]; // H(0)
         for i in 1..height as usize {
-            self.zeros[i] = poseidon_hash(&[self.zeros[i-1], self.zeros[i-1]])?;
+            self.zeros[i] = poseidon2_hash(&[self.zeros[i-1], self.zeros[i-1]])?;
         }
         
         // Initialize filled_subtrees with zeros
@@ -238,7 +570,11 @@ impl MerkleTree {
             self.filled_subtrees[i] = self.zeros[i];
         }
         
-        self.root = self.zeros[(height-1) as usize];
+        let top = self.zeros[(height-1) as usize];
+        self.root = poseidon2_hash(&[top, top])?;
+        self.cached_path_hashes = self.zeros;
+        self.last_leaf_index = 0;
+        self.is_root_dirty = false;
         Ok(())
     }
     
@@ -248,45 +584,84 @@ impl MerkleTree {
         let mut current_hash = leaf;
         let mut current_index = leaf_index;
         
-        // Update filled subtrees bottom-up using the standard incremental Merkle tree algorithm
-        for level in 0..self.height {
+        // Hash up through the right-child levels only; the first left-child
+        // level holds the new path node, and everything above is deferred to
+        // refresh_root. Amortized this is under two hashes per insert.
+        let mut folding = true;
+        for level in 0..self.height as usize {
             if current_index % 2 == 0 {
-                // Left node - update filled_subtrees and break
-                self.filled_subtrees[level as usize] = current_hash;
-                break;
+                // Left node - nothing has been inserted to the right yet
+                if folding {
+                    self.filled_subtrees[level] = current_hash;
+                    folding = false;
+                }
+                self.cached_path_hashes[level] = self.zeros[level];
             } else {
-                // Right node - hash with left sibling from filled_subtrees
-                let left_hash = self.filled_subtrees[level as usize];
-                current_hash = poseidon_hash(&[left_hash, current_hash])?;
-                current_index /= 2;
+                // Right node - sibling is the last left subtree at this level
+                let left_hash = self.filled_subtrees[level];
+                if folding {
+                    current_hash = poseidon2_hash(&[left_hash, current_hash])?;
+                }
+                self.cached_path_hashes[level] = left_hash;
             }
+            current_index /= 2;
         }
         
-        // Recompute root
-        self.root = self.compute_root()?;
+        self.last_leaf_index = leaf_index;
+        if folding {
+            // The last leaf of a full tree: every level was a right child
+            self.root = current_hash;
+        }
+        self.is_root_dirty = !folding;
         Ok(())
     }
     
+    /// Root over all inserted leaves, folding the cached path if an insert is pending.
+    ///
+    /// Walks every level of the last leaf's path: where bit `l` of its index is
+    /// 0 the node is a left child and pairs with `zeros[l]`, where it is 1 it
+    /// pairs with the completed left subtree in `filled_subtrees[l]`.
     pub fn compute_root(&self) -> Result<[u8; 32]> {
-        let mut current_hash = self.filled_subtrees[0];
+        if !self.is_root_dirty {
+            return Ok(self.root);
+        }
         
-        for level in 1..self.height {
-            let right_hash = if level == self.height - 1 {
-                self.filled_subtrees[level as usize]
+        // The lowest left-child level on the path holds the freshly hashed node
+        let start = self.last_leaf_index.trailing_ones() as usize;
+        let mut current_hash = self.filled_subtrees[start];
+        
+        for level in start..self.height as usize {
+            let sibling = self.cached_path_hashes[level];
+            current_hash = if (self.last_leaf_index >> level) % 2 == 0 {
+                poseidon2_hash(&[current_hash, sibling])?
             } else {
-                self.zeros[level as usize]
+                poseidon2_hash(&[sibling, current_hash])?
             };
-            
-            current_hash = poseidon_hash(&[current_hash, right_hash])?;
         }
         
         Ok(current_hash)
     }
     
+    /// Fold pending inserts into the stored root
+    pub fn refresh_root(&mut self) -> Result<[u8; 32]> {
+        self.root = self.compute_root()?;
+        self.is_root_dirty = false;
+        Ok(self.root)
+    }
+    
     pub fn get_root(&self) -> [u8; 32] {
         self.root
     }
     
+    /// Siblings and directions (true = node is the left child) for the last inserted leaf
+    pub fn latest_path(&self) -> (Vec<[u8; 32]>, Vec<bool>) {
+        let height = self.height as usize;
+        let path_indices = (0..height)
+            .map(|level| (self.last_leaf_index >> level) % 2 == 0)
+            .collect();
+        (self.cached_path_hashes[..height].to_vec(), path_indices)
+    }
+    
     /// Verify a Merkle proof
     pub fn verify_proof(
         &self,
@@ -304,10 +679,10 @@ impl MerkleTree {
             let proof_element = proof[i];
             if path_indices[i] {
                 // Current hash is left, proof element is right
-                current_hash = poseidon_hash(&[current_hash, proof_element])?;
+                current_hash = poseidon2_hash(&[current_hash, proof_element])?;
             } else {
                 // Proof element is left, current hash is right
-                current_hash = poseidon_hash(&[proof_element, current_hash])?;
+                current_hash = poseidon2_hash(&[proof_element, current_hash])?;
             }
         }
         
@@ -315,39 +690,211 @@ impl MerkleTree {
     }
 }
 
-use sha2::{Sha256, Digest};
+/// Poseidon2 hash of two BN254 scalars for Merkle tree nodes.
+///
+/// Inputs and output are little-endian field elements. The inputs fill the
+/// rate of a width-3 state (`[left, right, 0]`) and the first element of the
+/// permuted state is the digest; off-chain witness generation must use the
+/// same instance and layout.
+pub fn poseidon2_hash(inputs: &[[u8; 32]; 2]) -> Result<[u8; 32]> {
+    let mut state = [
+        fr_from_bytes(&inputs[0])?,
+        fr_from_bytes(&inputs[1])?,
+        Fr::zero(),
+    ];
+
+    poseidon2_permutation(&mut state);
 
-/// Solana-compatible hash function for Merkle tree operations
-/// Uses SHA256 instead of Poseidon for stack efficiency on BPF
-fn poseidon_hash(inputs: &[[u8; 32]]) -> Result<[u8; 32]> {
-    if inputs.is_empty() {
-        return Err(ErrorCode::InvalidInput.into());
-    }
-    
-    // Use SHA256 for hashing - much more efficient on Solana BPF
-    let mut hasher = Sha256::new();
-    
-    // Hash all inputs together
-    for input in inputs {
-        hasher.update(input);
-    }
-    
-    // Add a domain separator to distinguish from other hash uses
-    hasher.update(b"MERKLE_TREE_HASH");
-    
-    let result = hasher.finalize();
     let mut output = [0u8; 32];
-    output.copy_from_slice(&result);
-    
+    output.copy_from_slice(&state[0].into_bigint().to_bytes_le());
     Ok(output)
 }
 
+fn fr_from_bytes(bytes: &[u8; 32]) -> Result<Fr> {
+    Fr::deserialize_uncompressed(&bytes[..]).map_err(|_| error!(ErrorCode::InvalidInput))
+}
+
+fn poseidon2_permutation(state: &mut [Fr; 3]) {
+    poseidon2_external_matmul(state);
+
+    for rc in &POSEIDON2_EXTERNAL_RC[..4] {
+        poseidon2_full_round(state, rc);
+    }
+
+    for rc in &POSEIDON2_INTERNAL_RC {
+        state[0] += rc;
+        state[0] = poseidon2_sbox(state[0]);
+        poseidon2_internal_matmul(state);
+    }
+
+    for rc in &POSEIDON2_EXTERNAL_RC[4..] {
+        poseidon2_full_round(state, rc);
+    }
+}
+
+fn poseidon2_full_round(state: &mut [Fr; 3], rc: &[Fr; 3]) {
+    for (element, constant) in state.iter_mut().zip(rc) {
+        *element = poseidon2_sbox(*element + constant);
+    }
+    poseidon2_external_matmul(state);
+}
+
+fn poseidon2_sbox(x: Fr) -> Fr {
+    let x2 = x.square();
+    x2.square() * x
+}
+
+// M_E = [[2, 1, 1], [1, 2, 1], [1, 1, 2]]
+fn poseidon2_external_matmul(state: &mut [Fr; 3]) {
+    let sum = state[0] + state[1] + state[2];
+    for element in state.iter_mut() {
+        *element += sum;
+    }
+}
+
+// M_I = [[2, 1, 1], [1, 2, 1], [1, 1, 3]]
+fn poseidon2_internal_matmul(state: &mut [Fr; 3]) {
+    let sum = state[0] + state[1] + state[2];
+    state[0] += sum;
+    state[1] += sum;
+    state[2].double_in_place();
+    state[2] += sum;
+}
+
+// Poseidon2 round constants for BN254, t = 3, RF = 8, RP = 56 (HorizenLabs reference instance)
+const POSEIDON2_EXTERNAL_RC: [[Fr; 3]; 8] = [
+    [
+        MontFp!("13128406282895484157369354038809433636203389051939936481821261911791933663254"),
+        MontFp!("18931653859213243425446645781588512487838213266321401679594943842133071369744"),
+        MontFp!("14100663835952519432830313936592734340076294692040144715814219945570907513297"),
+    ],
+    [
+        MontFp!("4829113795940962171577509772302063766582957624337039572002553144762883322341"),
+        MontFp!("15524196826242151316602020382811195434692947787822797536837043495207890599720"),
+        MontFp!("11824742889827005569732308046012743315382715056680481843559537371456931944245"),
+    ],
+    [
+        MontFp!("15824369292130948538570881538463827283727388637222356799784648390667783881850"),
+        MontFp!("7395652367440825515524159918310823124942438011035473842936180620057265532493"),
+        MontFp!("1241351203963627868835881804826107927839874261162687401459390240620885410254"),
+    ],
+    [
+        MontFp!("6688265362431458560657026053775250595854204120757399493099812773970419156132"),
+        MontFp!("18628865421786169197184064906533816626840829027307965436801990532221681661310"),
+        MontFp!("17770079997659052348824924629777474963416629061770380464722096481670103655806"),
+    ],
+    [
+        MontFp!("12123026335854515584932892161148559902027319284544852339906677442670161590992"),
+        MontFp!("11747143856113197599032240626240804787576886917202313931914972592787570603429"),
+        MontFp!("12689083329367969619896630238881490862330991685178863399139986099061967775891"),
+    ],
+    [
+        MontFp!("9363616378570856727297258914956380343356030981401312041884116403700849212733"),
+        MontFp!("13238291046435061349401827110993774315432323243867917623501520885175217584478"),
+        MontFp!("13857006478672530359037215101120381968370236111775805219419707798416454682620"),
+    ],
+    [
+        MontFp!("2022752961549084842139747691238383165524359342011064407942599644003308437489"),
+        MontFp!("11377043765620686524844863869245961003946340433252666374730228559486855986878"),
+        MontFp!("9107028336454933966239128359918274121166034584181733998485105905495346200934"),
+    ],
+    [
+        MontFp!("900063247840342897532382686223939136593244983486268682637380837456165317070"),
+        MontFp!("11261302954518146885624063833699323298803404236535464228351677636819579513431"),
+        MontFp!("7126990412157463341897179572979760225771626877677162088926546182321369054630"),
+    ],
+];
+
+const POSEIDON2_INTERNAL_RC: [Fr; 56] = [
+    MontFp!("11811415718957691261673974625780511541635150909919309658375768251762566747317"),
+    MontFp!("17491388639298611159333770975992024026420968324544834879936543171716736973879"),
+    MontFp!("5647537972700463414111873015737673282707440513292923385601908870282442800104"),
+    MontFp!("13098696909140066209556423100763036393001603197583133354863092304798723388565"),
+    MontFp!("6951180250619279643770888203380891623788978362131976553140006882493632020745"),
+    MontFp!("11250251081997661635793843737498879309304455145146915350538637298238893102958"),
+    MontFp!("2246982048814095620312232487641427155108104073024754628893054837638848127964"),
+    MontFp!("18897180842973857564376958241871700087418903006311506731527228148081597475814"),
+    MontFp!("11557404599711559103972421944754928847181400366333080241838467983028485750549"),
+    MontFp!("17156358787639157774388183034849932704703797218604790661321342987075785318260"),
+    MontFp!("8846001957151556825394442611430138293780354129800063716225175548340091032449"),
+    MontFp!("21883449834630454155761926448978525628607016008113566399646971468161186616967"),
+    MontFp!("11782201180140779170005707786217005381305915516114251118577530420880166417952"),
+    MontFp!("19574374768428302416384468550351257389078501920039012797497943057156188490399"),
+    MontFp!("8515987927591912252146893631936027853249294776314628553087138119917968203620"),
+    MontFp!("17278996890957540943430295799612663512184925495827057764219426280563743078943"),
+    MontFp!("4560144125266860756441160513270281593457202308593722614013851111005532208589"),
+    MontFp!("18507459160700813704135500972073304101922968342745790738233104310822653821881"),
+    MontFp!("12853272419783978245995917302225694649366687506910892647236063701566570840428"),
+    MontFp!("14374895923592519298500369713759001634990764548024903321294831249025876110484"),
+    MontFp!("1754533789272381217541450481312878927560073411620344950409407505576538004136"),
+    MontFp!("20448232810715691360468548645921483318770769828465347895613479253435247065293"),
+    MontFp!("4203277692183102377396835282861288449527228200284576966986741905195109677387"),
+    MontFp!("11506339386261725202512749094297334054772084639665212079028551409689271965431"),
+    MontFp!("4408799661846477128378547528471700197737434561274043409442231147309460168718"),
+    MontFp!("10862521404448958117187164110262290189825635328197001646848012017699995213390"),
+    MontFp!("7012061838863338817532836723152059636816924388921632356281537445328382279260"),
+    MontFp!("8337544039076735620694225144163354013921209405711398618659178986151546625400"),
+    MontFp!("16173744372216956516796750206695252671549928142051779144629150462255079400849"),
+    MontFp!("19072902632067672883974143637757649536845413107085656789672471396027868707732"),
+    MontFp!("3487852254355424154670010750480228751987308757772575371606146474985412561707"),
+    MontFp!("17727517395793273304860106667199855253218123164763798377815886217088561516989"),
+    MontFp!("13280131383170382695839570176732265848909891244754629477752800360224963964534"),
+    MontFp!("21504421972374418324171209120165696620934505501591484695447432472073975792776"),
+    MontFp!("13753604424945682926871108642602624411461374991709441590662260371815673344981"),
+    MontFp!("8053178768600673579416591772204841415225213226540397062676127402210384682315"),
+    MontFp!("15101558583452488762759591936595783545455044970328380152280373697190919758012"),
+    MontFp!("6286700389345423344101403023711121482167900236544298155098199100234816571786"),
+    MontFp!("19368755554193272721035317233504719593365546521121074341670771231332472422552"),
+    MontFp!("13306281365497267243785678269212920842854030794417306689235276460198094483575"),
+    MontFp!("10121764749051640353641114693266514664967620368543293902008953934189850195966"),
+    MontFp!("179619165022370308972665071682395477322215797039585945216341070107573537790"),
+    MontFp!("14053393851645634065914179337120715807963438235922115988819572738574714471437"),
+    MontFp!("17345906218970918797922168310670548252023720338285437740234091480846393436478"),
+    MontFp!("10383068492552043678323859571562933490503408853170063884414176092784243607055"),
+    MontFp!("12096041499044892166554391619429604246288825927654072010011878199637889490527"),
+    MontFp!("6449742640166027959651492823149770763572943879017164812917305794918053034585"),
+    MontFp!("6551805454148805882554763665748573416514894105513920161214733482541847062214"),
+    MontFp!("3651410956659878392469489270906333016569562868954890104332567650040497030813"),
+    MontFp!("15219053914464753937310253926447830297339787956721755285255510737973021838676"),
+    MontFp!("881679665678132972106931291023348167890022611850562267871389203532691753422"),
+    MontFp!("5006067481688857073852527145736822635357747460125905556158034280392250104971"),
+    MontFp!("12765332320844032254009314500332101047115754896003948733635815046365410860591"),
+    MontFp!("12908190215073542091623737558383307555705501651914623082354191483197810853182"),
+    MontFp!("1446042792715825508366007519346636771782990303010685652946852324744810237839"),
+    MontFp!("17414863822034645298427260856470503848317996477890518738401812766215195632841"),
+];
+
+#[event]
+pub struct DenominationPoolCreated {
+    pub denomination: u64,
+    pub mint: Pubkey,
+    pub pool_address: Pubkey,
+}
+
 #[event]
 pub struct DepositEvent {
     pub commitment: [u8; 32],
     pub leaf_index: u64,
     pub amount: u64,
     pub root: [u8; 32],
+    pub path_elements: Vec<[u8; 32]>, // Sibling path as of this leaf's insertion
+    pub path_indices: Vec<bool>,
+}
+
+#[event]
+pub struct BatchDepositEvent {
+    pub commitment_count: u8,
+    pub first_index: u64,
+    pub last_index: u64,
+    pub total_amount: u64,
+    pub root: [u8; 32],
+}
+
+#[event]
+pub struct ChangeNoteEvent {
+    pub commitment: [u8; 32],
+    pub leaf_index: u64,
+    pub root: [u8; 32],
 }
 
 #[event]
@@ -361,6 +908,8 @@ pub struct WithdrawalEvent {
 pub enum ErrorCode {
     #[msg("Invalid amount: must be greater than 0")]
     InvalidAmount,
+    #[msg("Recipient token account is not owned by the recipient")]
+    RecipientMismatch,
     #[msg("Index out of bounds for Merkle tree")]
     IndexOutOfBounds,
     #[msg("Insufficient funds in pool")]
@@ -377,4 +926,171 @@ pub enum ErrorCode {
     InvalidCommitment,
     #[msg("Invalid nullifier")]
     InvalidNullifier,
+    #[msg("Too many commitments in batch (max 5)")]
+    TooManyCommitments,
+    #[msg("Total amount does not match the sum of deposit amounts")]
+    AmountMismatch,
+    #[msg("Deposit amount does not match the pool denomination")]
+    WrongDenomination,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("Token account mint does not match the pool's accepted mint")]
+    WrongTokenMint,
+    #[msg("Pool token account is not owned by the pool")]
+    InvalidPoolTokenAccount,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fr_hex(hex_str: &str) -> Fr {
+        Fr::from_be_bytes_mod_order(&hex::decode(hex_str).unwrap())
+    }
+
+    #[test]
+    fn poseidon2_permutation_matches_reference_vector() {
+        // HorizenLabs poseidon2 `poseidon2_instance_bn256` known-answer test
+        let mut state = [Fr::from(0u64), Fr::from(1u64), Fr::from(2u64)];
+        poseidon2_permutation(&mut state);
+
+        assert_eq!(state[0], fr_hex("0bb61d24daca55eebcb1929a82650f328134334da98ea4f847f760054f4a3033"));
+        assert_eq!(state[1], fr_hex("303b6f7c86d043bfcbcc80214f26a30277a15d3f74ca654992defe7ff8d03570"));
+        assert_eq!(state[2], fr_hex("1ed25194542b12eef8617361c3ba7c52e660b145994427cc86296242cf766ec8"));
+    }
+
+    fn empty_tree(height: u8) -> MerkleTree {
+        let mut tree = MerkleTree {
+            height: 0,
+            filled_subtrees: [[0u8; 32]; 20],
+            zeros: [[0u8; 32]; 20],
+            root: [0u8; 32],
+            cached_path_hashes: [[0u8; 32]; 20],
+            last_leaf_index: 0,
+            is_root_dirty: false,
+        };
+        tree.initialize(height).unwrap();
+        tree
+    }
+
+    fn leaf(i: u64) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes[..8].copy_from_slice(&(i + 1).to_le_bytes());
+        bytes
+    }
+
+    /// Every level of a tree holding `leaves`, hashed level by level from scratch
+    /// with empty positions filled by the all-zero subtree of that level
+    fn naive_levels(leaves: &[[u8; 32]], height: u8) -> Vec<Vec<[u8; 32]>> {
+        let mut zero = [0u8; 32];
+        let mut levels = vec![leaves.to_vec()];
+        for _ in 0..height {
+            let mut nodes = levels.last().unwrap().clone();
+            while nodes.is_empty() || nodes.len() % 2 == 1 {
+                nodes.push(zero);
+            }
+            levels.push(
+                nodes
+                    .chunks(2)
+                    .map(|pair| poseidon2_hash(&[pair[0], pair[1]]).unwrap())
+                    .collect(),
+            );
+            zero = poseidon2_hash(&[zero, zero]).unwrap();
+        }
+        levels
+    }
+
+    fn naive_path(leaves: &[[u8; 32]], height: u8, leaf_index: u64) -> Vec<[u8; 32]> {
+        let levels = naive_levels(leaves, height);
+        let mut zero = [0u8; 32];
+        (0..height as usize)
+            .map(|level| {
+                let sibling = ((leaf_index >> level) ^ 1) as usize;
+                let node = levels[level].get(sibling).copied().unwrap_or(zero);
+                zero = poseidon2_hash(&[zero, zero]).unwrap();
+                node
+            })
+            .collect()
+    }
+
+    #[test]
+    fn merkle_proof_reconstructs_root() {
+        // get_merkle_proof returns latest_path for the last leaf; it must
+        // verify against the stored root after every insert
+        let height = 5;
+        let mut tree = empty_tree(height);
+        let mut leaves = Vec::new();
+
+        for index in 0..(1u64 << height) {
+            leaves.push(leaf(index));
+            tree.insert_leaf(index, leaf(index)).unwrap();
+            let root = tree.refresh_root().unwrap();
+
+            let (siblings, path_indices) = tree.latest_path();
+            assert_eq!(siblings, naive_path(&leaves, height, index));
+            assert!(tree.verify_proof(leaf(index), &siblings, &path_indices, root).unwrap());
+
+            let proof = MerkleProofData {
+                siblings,
+                path_indices,
+                root,
+                leaf_index: index,
+            };
+            let decoded = MerkleProofData::try_from_slice(&proof.try_to_vec().unwrap()).unwrap();
+            assert!(tree
+                .verify_proof(leaf(index), &decoded.siblings, &decoded.path_indices, decoded.root)
+                .unwrap());
+            assert!(!tree
+                .verify_proof(leaf(index + 1), &decoded.siblings, &decoded.path_indices, decoded.root)
+                .unwrap());
+        }
+    }
+
+    #[test]
+    fn insert_leaf_defers_root_until_refresh() {
+        let height = 6;
+        let mut tree = empty_tree(height);
+        let leaves: Vec<[u8; 32]> = (0..23).map(leaf).collect();
+
+        // Inserts without a refresh in between still leave a consistent frontier
+        for (index, &value) in leaves.iter().enumerate() {
+            tree.insert_leaf(index as u64, value).unwrap();
+        }
+        let last = leaves.len() as u64 - 1;
+        let expected = naive_levels(&leaves, height)[height as usize][0];
+
+        assert!(tree.is_root_dirty);
+        assert_eq!(&tree.cached_path_hashes[..height as usize], naive_path(&leaves, height, last));
+        assert_eq!(tree.compute_root().unwrap(), expected);
+        assert_ne!(tree.get_root(), expected);
+
+        assert_eq!(tree.refresh_root().unwrap(), expected);
+        assert!(!tree.is_root_dirty);
+        assert_eq!(tree.get_root(), expected);
+        assert_eq!(tree.compute_root().unwrap(), expected);
+    }
+
+    #[test]
+    fn compute_root_matches_naive_recomputation() {
+        assert_eq!(empty_tree(4).get_root(), naive_levels(&[], 4)[4][0]);
+
+        for height in 1..=20u8 {
+            // Fill small trees completely, including the every-level-right last leaf
+            let count = (1u64 << height).min(20);
+            let mut tree = empty_tree(height);
+            let mut leaves = Vec::new();
+
+            for index in 0..count {
+                leaves.push(leaf(index));
+                tree.insert_leaf(index, leaf(index)).unwrap();
+                let expected = naive_levels(&leaves, height)[height as usize][0];
+                assert_eq!(tree.compute_root().unwrap(), expected, "height {height}, {} leaves", index + 1);
+                tree.refresh_root().unwrap();
+            }
+
+            if count == 1u64 << height {
+                assert!(tree.insert_leaf(count, leaf(count)).is_err());
+            }
+        }
+    }
 }
@@ -0,0 +1,119 @@
+//! Embeds the spend circuit verification key exported by snarkjs.
+//!
+//! Reads `verification_key_spend_v2.json` (override with `SPEND_VK_PATH`) and
+//! writes `generated_vk.rs` to `OUT_DIR` with every coordinate converted from a
+//! base10 string to a little-endian `[u8; 32]`. A missing file fails on-chain
+//! (`target_os = "solana"`) builds. Host builds, which only run the unit tests
+//! and the SDK, embed a key marked as not embedded so `initialize` fails with
+//! `VKGenerationFailed`.
+
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+// Same decimal -> little-endian conversion the SDK applies to snarkjs proofs
+#[path = "../../sdk/x402-sdk/src/field.rs"]
+mod field;
+
+const DEFAULT_VK_PATH: &str = "../../circuits/build/verification_key_spend_v2.json";
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let vk_path = env::var("SPEND_VK_PATH")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| manifest_dir.join(DEFAULT_VK_PATH));
+
+    println!("cargo:rerun-if-env-changed=SPEND_VK_PATH");
+    println!("cargo:rerun-if-changed={}", vk_path.display());
+    println!("cargo:rerun-if-changed=../../sdk/x402-sdk/src/field.rs");
+
+    let generated = match fs::read_to_string(&vk_path) {
+        Ok(json) => {
+            let vk: Value = serde_json::from_str(&json)
+                .unwrap_or_else(|e| panic!("invalid verification key JSON {}: {}", vk_path.display(), e));
+            generate(&vk)
+        }
+        Err(e) if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("solana") => {
+            panic!(
+                "spend verification key not found at {}: {} (export it with snarkjs or set SPEND_VK_PATH)",
+                vk_path.display(),
+                e
+            );
+        }
+        Err(_) => {
+            println!(
+                "cargo:warning=spend verification key not found at {}, embedding an empty key",
+                vk_path.display()
+            );
+            placeholder()
+        }
+    };
+
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("generated_vk.rs");
+    fs::write(out_path, generated).unwrap();
+}
+
+fn generate(vk: &Value) -> String {
+    let protocol = vk["protocol"].as_str().unwrap_or("groth16");
+    assert_eq!(protocol, "groth16", "expected a groth16 verification key");
+
+    let ic = vk["IC"].as_array().expect("IC must be an array");
+    let ic_points: Vec<String> = ic.iter().map(g1).collect();
+
+    let mut out = String::new();
+    writeln!(out, "const SPEND_VK_EMBEDDED: bool = true;").unwrap();
+    writeln!(out, "const SPEND_VK_ALPHA_G1: G1Point = {};", g1(&vk["vk_alpha_1"])).unwrap();
+    writeln!(out, "const SPEND_VK_BETA_G2: G2Point = {};", g2(&vk["vk_beta_2"])).unwrap();
+    writeln!(out, "const SPEND_VK_GAMMA_G2: G2Point = {};", g2(&vk["vk_gamma_2"])).unwrap();
+    writeln!(out, "const SPEND_VK_DELTA_G2: G2Point = {};", g2(&vk["vk_delta_2"])).unwrap();
+    writeln!(out, "const SPEND_VK_IC: &[G1Point] = &[{}];", ic_points.join(", ")).unwrap();
+    out
+}
+
+fn placeholder() -> String {
+    let zero_g1 = format!("G1Point {{ x: {0}, y: {0} }}", bytes(&[0u8; 32]));
+    let zero_g2 = format!("G2Point {{ x: [{0}, {0}], y: [{0}, {0}] }}", bytes(&[0u8; 32]));
+
+    let mut out = String::new();
+    writeln!(out, "const SPEND_VK_EMBEDDED: bool = false;").unwrap();
+    writeln!(out, "const SPEND_VK_ALPHA_G1: G1Point = {};", zero_g1).unwrap();
+    writeln!(out, "const SPEND_VK_BETA_G2: G2Point = {};", zero_g2).unwrap();
+    writeln!(out, "const SPEND_VK_GAMMA_G2: G2Point = {};", zero_g2).unwrap();
+    writeln!(out, "const SPEND_VK_DELTA_G2: G2Point = {};", zero_g2).unwrap();
+    writeln!(out, "const SPEND_VK_IC: &[G1Point] = &[];").unwrap();
+    out
+}
+
+/// snarkjs G1: `[x, y, z]` in affine form with `z = "1"`
+fn g1(point: &Value) -> String {
+    format!(
+        "G1Point {{ x: {}, y: {} }}",
+        bytes(&field(&point[0])),
+        bytes(&field(&point[1]))
+    )
+}
+
+/// snarkjs G2: `[[x.c0, x.c1], [y.c0, y.c1], [z.c0, z.c1]]`
+fn g2(point: &Value) -> String {
+    format!(
+        "G2Point {{ x: [{}, {}], y: [{}, {}] }}",
+        bytes(&field(&point[0][0])),
+        bytes(&field(&point[0][1])),
+        bytes(&field(&point[1][0])),
+        bytes(&field(&point[1][1]))
+    )
+}
+
+fn field(value: &Value) -> [u8; 32] {
+    let digits = value.as_str().expect("coordinates must be decimal strings");
+    field::decimal_to_le_bytes(digits)
+        .unwrap_or_else(|| panic!("invalid decimal coordinate: {}", digits))
+}
+
+fn bytes(value: &[u8; 32]) -> String {
+    let items: Vec<String> = value.iter().map(|b| format!("0x{:02x}", b)).collect();
+    format!("[{}]", items.join(", "))
+}
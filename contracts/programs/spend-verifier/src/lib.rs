@@ -1,8 +1,12 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::Token;
-
-
-
+use anchor_spl::token::{Token, TokenAccount};
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use ark_groth16::{prepare_verifying_key, Groth16, Proof as ArkProof, VerifyingKey as ArkVerifyingKey};
+use ark_serialize::CanonicalDeserialize;
+use sha2::{Digest, Sha256};
+use sha3::Keccak256;
 
 declare_id!("CwJ5s1e69mv5uAnTyaAxos9DVVQ2kWcz53BQm6krzDG9");
 
@@ -11,57 +15,169 @@ pub mod spend_verifier {
     use super::*;
 
     /// Initialize the spend verifier with verification key
-    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
+    pub fn initialize(ctx: Context<Initialize>, circuit_r1cs_digest: [u8; 32]) -> Result<()> {
         let verifier = &mut ctx.accounts.verifier;
         verifier.authority = ctx.accounts.authority.key();
-        verifier.verification_key = get_spend_verification_key();
+        verifier.verification_key = get_spend_verification_key()?;
+        verifier.vk_integrity_hash = verifier.verification_key.hash()?;
         verifier.nullifier_count = 0;
         verifier.total_verified_amount = 0;
+        verifier.pending_vk = None;
+        verifier.relay_fee_bps = 0;
+        verifier.vk_version = SPEND_VK_VERSION.to_string();
+        verifier.min_spend_amount = DEFAULT_MIN_SPEND_AMOUNT;
+        verifier.circuit_r1cs_digest = circuit_r1cs_digest;
+        verifier.circuit_name = SPEND_CIRCUIT_NAME.to_string();
+        verifier.telemetry_enabled = false;
+        verifier.pause_delay_seconds = DEFAULT_PAUSE_DELAY_SECONDS;
+        verifier.pause_proposed_at = None;
+        verifier.rate_limit = RateLimitConfig {
+            max_spends_per_slot: DEFAULT_MAX_SPENDS_PER_SLOT,
+            current_slot: 0,
+            slot_spend_count: 0,
+        };
 
         msg!("Spend verifier initialized with authority: {}", verifier.authority);
         Ok(())
     }
 
+    /// Register the nullifier shard for nullifiers whose first byte is `shard_index`
+    ///
+    /// Shards are too large to create through CPI, so the caller allocates
+    /// `NullifierShard::SPACE` bytes owned by this program in the same transaction.
+    pub fn init_nullifier_shard(
+        ctx: Context<InitNullifierShard>,
+        shard_index: u8,
+    ) -> Result<()> {
+        let mut shard_router = ctx.accounts.shard_router.load_mut()?;
+        let registered = &mut shard_router.shards[shard_index as usize];
+        require!(*registered == Pubkey::default(), ErrorCode::NullifierShardExists);
+        *registered = ctx.accounts.nullifier_shard.key();
+
+        let mut shard = ctx.accounts.nullifier_shard.load_init()?;
+        shard.shard_index = shard_index;
+        shard.len = 0;
+
+        msg!("Nullifier shard {} initialized", shard_index);
+        Ok(())
+    }
+
     /// Verify a spend proof and execute the payment
     pub fn verify_spend_proof(
         ctx: Context<VerifySpend>,
         proof: Groth16Proof,
         public_signals: Vec<[u8; 32]>,
     ) -> Result<()> {
-        require!(public_signals.len() == 5, ErrorCode::InvalidPublicInputCount);
+        require!(
+            public_signals.len() == SPEND_PUBLIC_INPUTS,
+            ErrorCode::InvalidPublicInputCount
+        );
+        for signal in &public_signals {
+            validate_bn254_field_element(signal)?;
+        }
+        ctx.accounts.verifier.rate_limit.record_spend(Clock::get()?.slot)?;
+
+        // Proofs are generated by the spend circuit this program checks signals for,
+        // so its version comes from that circuit rather than from the caller
+        let compat_ctx = CpiContext::new(
+            ctx.accounts.zk_meta_registry_program.to_account_info(),
+            zk_meta_registry::cpi::accounts::CheckCompatibility {
+                compatibility_entry: ctx.accounts.compatibility_entry.to_account_info(),
+            },
+        );
+        let compatible = zk_meta_registry::cpi::check_compatibility(
+            compat_ctx,
+            SPEND_CIRCUIT_NAME.to_string(),
+            SPEND_PROOF_VERSION.to_string(),
+            ctx.accounts.verifier.vk_version.clone(),
+        )?
+        .get();
+        require!(compatible, ErrorCode::IncompatibleCircuitVersion);
 
         // Extract public signals (from our spend circuit)
         let merkle_root = public_signals[0];
         let nullifier_hash = public_signals[1];
-        let recipient = Pubkey::try_from_slice(&public_signals[2][0..32])?;
+        // A raw pubkey is usually >= r, so the proof carries a field-safe digest of it
+        let recipient = ctx.accounts.recipient_token.owner;
+        require!(
+            public_signals[2] == recipient_signal(&recipient),
+            ErrorCode::RecipientMismatch
+        );
         let amount = u64::from_le_bytes(
             public_signals[3][0..8].try_into()
                 .map_err(|_| ErrorCode::InvalidPublicSignal)?
         );
+        require!(
+            amount >= ctx.accounts.verifier.min_spend_amount,
+            ErrorCode::AmountBelowMinimum
+        );
         let external_nullifier = public_signals[4];
+        let change_commitment = public_signals[5];
+        require!(
+            proof.change_commitment == change_commitment,
+            ErrorCode::ChangeCommitmentMismatch
+        );
+        // The relay fee goes to the relayer the prover chose, not whoever resubmits the proof
+        require!(
+            public_signals[6] == relayer_signal(&ctx.accounts.relayer.key()),
+            ErrorCode::RelayerMismatch
+        );
+
+        // The external nullifier must bind this deployment and an approved scope
+        let scope = ctx.accounts.approved_scope.scope;
+        require!(scope != [0u8; 32], ErrorCode::InvalidScope);
+        require!(
+            external_nullifier == scoped_external_nullifier(&crate::id(), &scope),
+            ErrorCode::InvalidExternalNullifier
+        );
 
-        // 1. Verify the Groth16 proof
         let verifier = &ctx.accounts.verifier;
+        if verifier.telemetry_enabled {
+            emit!(SpendProofTelemetry {
+                circuit_name: verifier.circuit_name.clone(),
+                public_inputs_count: public_signals.len() as u8,
+                vk_ic_length: verifier.verification_key.ic.len() as u8,
+                nullifier_set_size: verifier.nullifier_count,
+                proof_submitted_at: Clock::get()?.unix_timestamp,
+            });
+        }
+
+        // 1. Verify the Groth16 proof against an untampered key
+        verifier.check_vk_integrity()?;
         require!(
             groth16_verify(&verifier.verification_key, &proof, &public_signals)?,
             ErrorCode::InvalidProof
         );
 
-        // 2. Check merkle root matches current pool state
+        // 2. Check merkle root is one of the pool's recent roots
         require!(
-            ctx.accounts.shielded_pool.merkle_root == merkle_root,
-            ErrorCode::InvalidMerkleRoot
+            ctx.accounts.shielded_pool.is_known_root(&merkle_root),
+            ErrorCode::MerkleRootTooOld
         );
 
         // 3. Verify nullifier hasn't been used (prevent double-spending)
-        let nullifier_set = &mut ctx.accounts.nullifier_set;
-        require!(
-            !nullifier_set.contains(&nullifier_hash),
-            ErrorCode::DoubleSpend
-        );
+        {
+            let shard = ctx.accounts.nullifier_shard.load()?;
+            require!(
+                shard.shard_index == nullifier_hash[0],
+                ErrorCode::InvalidNullifierShard
+            );
+            require!(
+                !shard.contains(&nullifier_hash),
+                ErrorCode::DoubleSpend
+            );
+        }
 
-        // 4. Execute the payment via CPI to shielded pool
-        let cpi_ctx = CpiContext::new(
+        // 4. Execute the payment via CPI to shielded pool, net of the relay fee
+        let relay_fee = ((amount as u128)
+            .checked_mul(ctx.accounts.verifier.relay_fee_bps as u128)
+            .ok_or(ErrorCode::ArithmeticOverflow)?
+            / 10000) as u64;
+        require!(relay_fee < amount, ErrorCode::RelayFeeExceedsAmount);
+
+        // The pool only accepts withdrawals and change notes signed by the verifier PDA
+        let verifier_seeds: &[&[&[u8]]] = &[&[b"spend_verifier", &[ctx.bumps.verifier]]];
+        let cpi_ctx = CpiContext::new_with_signer(
             ctx.accounts.shielded_pool_program.to_account_info(),
             shielded_pool::cpi::accounts::Withdraw {
                 pool: ctx.accounts.shielded_pool.to_account_info(),
@@ -71,43 +187,424 @@ pub mod spend_verifier {
                 recipient_token: ctx.accounts.recipient_token.to_account_info(),
                 token_program: ctx.accounts.token_program.to_account_info(),
             },
+            verifier_seeds,
         );
         
-        shielded_pool::cpi::withdraw(cpi_ctx, amount, recipient)?;
+        shielded_pool::cpi::withdraw(cpi_ctx, amount - relay_fee, recipient)?;
+
+        if relay_fee > 0 {
+            let relay_cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.shielded_pool_program.to_account_info(),
+                shielded_pool::cpi::accounts::Withdraw {
+                    pool: ctx.accounts.shielded_pool.to_account_info(),
+                    spend_verifier: ctx.accounts.verifier.to_account_info(),
+                    pool_authority: ctx.accounts.pool_authority.to_account_info(),
+                    pool_token: ctx.accounts.pool_token.to_account_info(),
+                    recipient_token: ctx.accounts.relayer_token_account.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                },
+                verifier_seeds,
+            );
+
+            shielded_pool::cpi::withdraw(relay_cpi_ctx, relay_fee, ctx.accounts.relayer.key())?;
+        }
+
+        // The circuit proves note_value = amount + change_amount, so the change
+        // note is re-inserted into the pool without revealing its value
+        if change_commitment != [0u8; 32] {
+            let change_cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.shielded_pool_program.to_account_info(),
+                shielded_pool::cpi::accounts::DepositChange {
+                    pool: ctx.accounts.shielded_pool.to_account_info(),
+                    merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+                    spend_verifier: ctx.accounts.verifier.to_account_info(),
+                },
+                verifier_seeds,
+            );
+
+            shielded_pool::cpi::deposit_change(change_cpi_ctx, change_commitment)?;
+        }
 
         // 5. Mark nullifier as used
-        nullifier_set.insert(nullifier_hash)?;
+        {
+            let mut shard = ctx.accounts.nullifier_shard.load_mut()?;
+            shard.insert(nullifier_hash, Clock::get()?.unix_timestamp)?;
+            ctx.accounts.shard_router.load_mut()?.record_len(&shard);
+            if shard.len as usize >= MAX_NULLIFIERS_PER_SHARD {
+                emit!(NullifierShardFull {
+                    shard_index: shard.shard_index,
+                    capacity: MAX_NULLIFIERS_PER_SHARD as u16,
+                });
+            }
+        }
 
         // 6. Update verifier statistics
         let verifier = &mut ctx.accounts.verifier;
-        verifier.nullifier_count += 1;
-        verifier.total_verified_amount += amount;
+        verifier.nullifier_count = verifier.nullifier_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        verifier.total_verified_amount = verifier.total_verified_amount
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
 
         emit!(SpendVerificationEvent {
             nullifier_hash,
             recipient,
             amount,
+            relay_fee,
             external_nullifier,
+            scope,
             merkle_root,
         });
 
-        msg!("Spend proof verified: recipient={}, amount={}", recipient, amount);
+        msg!(
+            "Spend proof verified: recipient={}, amount={}, relay_fee={}",
+            recipient, amount, relay_fee
+        );
+        Ok(())
+    }
+
+    /// Batch verify up to 3 spend proofs with a single multi-pairing.
+    ///
+    /// The batch is accepted or rejected as a whole: one invalid proof makes
+    /// every result false.
+    pub fn batch_verify_spend_proofs(
+        ctx: Context<BatchVerifySpend>,
+        proofs: Vec<Groth16Proof>,
+        signals: Vec<Vec<[u8; 32]>>,
+    ) -> Result<Vec<bool>> {
+        require!(!proofs.is_empty(), ErrorCode::InvalidProof);
+        require!(proofs.len() <= MAX_BATCH_PROOFS, ErrorCode::TooManyProofs);
+        require!(proofs.len() == signals.len(), ErrorCode::InvalidPublicInputCount);
+
+        ctx.accounts.verifier.check_vk_integrity()?;
+        let seed = Clock::get()?.slot.to_le_bytes();
+        let batch_valid = batch_groth16_verify(
+            &ctx.accounts.verifier.verification_key,
+            &proofs,
+            &signals,
+            &seed,
+        )?;
+
+        msg!("Batch verified {} proofs: valid={}", proofs.len(), batch_valid);
+        Ok(vec![batch_valid; proofs.len()])
+    }
+
+    /// Propose a new verification key that becomes active after `delay_seconds`
+    pub fn propose_vk_rotation(
+        ctx: Context<ProposeVkRotation>,
+        new_vk: VerificationKey,
+        vk_version: String,
+        delay_seconds: i64,
+    ) -> Result<()> {
+        let verifier = &mut ctx.accounts.verifier;
+        require!(
+            ctx.accounts.authority.key() == verifier.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            delay_seconds >= MIN_VK_ROTATION_DELAY_SECONDS,
+            ErrorCode::InvalidRotationDelay
+        );
+        require!(verifier.pending_vk.is_none(), ErrorCode::RotationAlreadyPending);
+        // Same vMAJOR.MINOR format zk-meta-registry checks compatibility entries with
+        zk_meta_registry::parse_circuit_version(&vk_version)
+            .map_err(|_| error!(ErrorCode::InvalidVerificationKey))?;
+        require!(
+            new_vk.ic.len() == SPEND_PUBLIC_INPUTS + 1,
+            ErrorCode::InvalidVerificationKey
+        );
+        // Reject keys that do not decode to valid curve points
+        new_vk.to_ark()?;
+
+        let effective_at = Clock::get()?.unix_timestamp + delay_seconds;
+        verifier.pending_vk = Some(PendingVK {
+            verification_key: new_vk,
+            vk_version,
+            effective_at,
+            proposed_by: ctx.accounts.authority.key(),
+        });
+
+        msg!("Verification key rotation proposed, effective at {}", effective_at);
+        Ok(())
+    }
+
+    /// Withdraw a pending verification key rotation (admin only)
+    pub fn cancel_vk_rotation(ctx: Context<CancelVkRotation>) -> Result<()> {
+        let verifier = &mut ctx.accounts.verifier;
+        require!(
+            ctx.accounts.authority.key() == verifier.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(verifier.pending_vk.take().is_some(), ErrorCode::NoPendingVerificationKey);
+
+        msg!("Verification key rotation cancelled");
+        Ok(())
+    }
+
+    /// Activate the pending verification key once its effective time has passed
+    pub fn activate_pending_vk(ctx: Context<ActivatePendingVk>) -> Result<()> {
+        let verifier = &mut ctx.accounts.verifier;
+        let pending = verifier
+            .pending_vk
+            .take()
+            .ok_or(ErrorCode::NoPendingVerificationKey)?;
+        require!(
+            Clock::get()?.unix_timestamp >= pending.effective_at,
+            ErrorCode::VerificationKeyNotYetEffective
+        );
+
+        let old_vk_hash = verifier.verification_key.hash()?;
+        let new_vk_hash = pending.verification_key.hash()?;
+        verifier.verification_key = pending.verification_key;
+        verifier.vk_version = pending.vk_version;
+        verifier.vk_integrity_hash = new_vk_hash;
+
+        emit!(VerificationKeyRotated {
+            old_vk_hash,
+            new_vk_hash,
+            effective_at: pending.effective_at,
+        });
+
+        msg!("Verification key rotated");
+        Ok(())
+    }
+
+    /// Set the smallest amount a spend proof may move (admin only)
+    pub fn set_min_spend_amount(ctx: Context<SetMinSpendAmount>, new_min: u64) -> Result<()> {
+        let verifier = &mut ctx.accounts.verifier;
+        require!(
+            ctx.accounts.authority.key() == verifier.authority,
+            ErrorCode::Unauthorized
+        );
+
+        let old_min = verifier.min_spend_amount;
+        verifier.min_spend_amount = new_min;
+
+        emit!(MinSpendAmountUpdated {
+            old_min,
+            new_min,
+        });
+
+        Ok(())
+    }
+
+    /// Configure how many spends are verified per slot (admin only)
+    pub fn set_rate_limit(ctx: Context<SetRateLimit>, config: RateLimitConfig) -> Result<()> {
+        let verifier = &mut ctx.accounts.verifier;
+        require!(
+            ctx.accounts.authority.key() == verifier.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(config.max_spends_per_slot > 0, ErrorCode::InvalidRateLimit);
+
+        verifier.rate_limit = config;
+
+        msg!("Rate limit set: {} spends per slot", verifier.rate_limit.max_spends_per_slot);
+        Ok(())
+    }
+
+    /// Turn spend proof telemetry events on or off (admin only)
+    pub fn set_telemetry_enabled(ctx: Context<SetTelemetryEnabled>, enabled: bool) -> Result<()> {
+        let verifier = &mut ctx.accounts.verifier;
+        require!(
+            ctx.accounts.authority.key() == verifier.authority,
+            ErrorCode::Unauthorized
+        );
+
+        verifier.telemetry_enabled = enabled;
+
+        msg!("Spend proof telemetry enabled: {}", enabled);
+        Ok(())
+    }
+
+    /// Check a claimed R1CS digest against the pinned spend circuit
+    pub fn verify_circuit_digest(ctx: Context<VerifyCircuitDigest>, claimed_digest: [u8; 32]) -> Result<bool> {
+        Ok(claimed_digest == ctx.accounts.verifier.circuit_r1cs_digest)
+    }
+
+    /// Pin the R1CS digest of a new spend circuit (admin only)
+    pub fn update_circuit_digest(ctx: Context<UpdateCircuitDigest>, new_digest: [u8; 32]) -> Result<()> {
+        let verifier = &mut ctx.accounts.verifier;
+        require!(
+            ctx.accounts.authority.key() == verifier.authority,
+            ErrorCode::Unauthorized
+        );
+
+        let old_digest = verifier.circuit_r1cs_digest;
+        verifier.circuit_r1cs_digest = new_digest;
+
+        emit!(CircuitDigestUpdated {
+            old_digest,
+            new_digest,
+            updated_by: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Reseal the integrity hash over the active verification key (admin only)
+    pub fn update_vk_integrity_hash(ctx: Context<UpdateVkIntegrityHash>) -> Result<()> {
+        let verifier = &mut ctx.accounts.verifier;
+        require!(
+            ctx.accounts.authority.key() == verifier.authority,
+            ErrorCode::Unauthorized
+        );
+
+        verifier.vk_integrity_hash = verifier.verification_key.hash()?;
+
+        msg!("Verification key integrity hash updated");
+        Ok(())
+    }
+
+    /// Approve a nullifier scope so spends under it are accepted (authority only)
+    pub fn register_scope(ctx: Context<RegisterScope>, scope: [u8; 32]) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.verifier.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(scope != [0u8; 32], ErrorCode::InvalidScope);
+
+        let approved_scope = &mut ctx.accounts.approved_scope;
+        approved_scope.scope = scope;
+        approved_scope.external_nullifier = scoped_external_nullifier(&crate::id(), &scope);
+        approved_scope.registered_at = Clock::get()?.unix_timestamp;
+        approved_scope.bump = ctx.bumps.approved_scope;
+
+        msg!("Nullifier scope registered: {:?}", scope);
+        Ok(())
+    }
+
+    /// Register the PLONK verification key (authority only)
+    pub fn initialize_plonk_verifier(
+        ctx: Context<InitializePlonkVerifier>,
+        verification_key: PlonkVerificationKey,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.verifier.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            verification_key.power > 0 && verification_key.power <= 28,
+            ErrorCode::InvalidVerificationKey
+        );
+
+        let plonk_verifier = &mut ctx.accounts.plonk_verifier;
+        plonk_verifier.authority = ctx.accounts.authority.key();
+        plonk_verifier.verification_key = verification_key;
+
+        msg!("PLONK verifier initialized with authority: {}", plonk_verifier.authority);
+        Ok(())
+    }
+
+    /// Verify a snarkjs PLONK proof against the registered verification key
+    pub fn verify_plonk_proof(
+        ctx: Context<VerifyPlonk>,
+        proof: PlonkProof,
+        public_signals: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let vk = &ctx.accounts.plonk_verifier.verification_key;
+        require!(
+            public_signals.len() == vk.n_public as usize,
+            ErrorCode::InvalidPublicInputCount
+        );
+
+        require!(
+            plonk_verify(vk, &proof, &public_signals)?,
+            ErrorCode::InvalidProof
+        );
+
+        msg!("PLONK proof verified with {} public signals", public_signals.len());
         Ok(())
     }
 
-    /// Emergency pause functionality
-    pub fn pause_verifier(ctx: Context<PauseVerifier>) -> Result<()> {
+    /// Set the fee paid to relayers out of each spend (admin only)
+    pub fn set_relay_fee(ctx: Context<SetRelayFee>, new_fee_bps: u16) -> Result<()> {
         let verifier = &mut ctx.accounts.verifier;
         require!(
             ctx.accounts.authority.key() == verifier.authority,
             ErrorCode::Unauthorized
         );
+        require!(new_fee_bps <= 1000, ErrorCode::RelayFeeTooHigh); // Max 10%
+
+        let old_fee = verifier.relay_fee_bps;
+        verifier.relay_fee_bps = new_fee_bps;
+
+        msg!("Relay fee updated: {} -> {} bps", old_fee, new_fee_bps);
+        Ok(())
+    }
+
+    /// Start the pause time-lock; execute_pause succeeds once pause_delay_seconds pass
+    pub fn propose_pause(ctx: Context<PauseVerifier>) -> Result<()> {
+        let verifier = &mut ctx.accounts.verifier;
+        require!(
+            ctx.accounts.authority.key() == verifier.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(!verifier.is_paused, ErrorCode::VerifierPaused);
+        require!(verifier.pause_proposed_at.is_none(), ErrorCode::PauseAlreadyProposed);
+
+        let proposed_at = Clock::get()?.unix_timestamp;
+        verifier.pause_proposed_at = Some(proposed_at);
+
+        emit!(PauseProposed {
+            proposed_by: ctx.accounts.authority.key(),
+            proposed_at,
+            executable_at: proposed_at.saturating_add(verifier.pause_delay_seconds),
+        });
+        Ok(())
+    }
+
+    /// Pause the verifier once the proposed pause's delay has elapsed
+    pub fn execute_pause(ctx: Context<PauseVerifier>) -> Result<()> {
+        let verifier = &mut ctx.accounts.verifier;
+        require!(
+            ctx.accounts.authority.key() == verifier.authority,
+            ErrorCode::Unauthorized
+        );
+        let proposed_at = verifier.pause_proposed_at.ok_or(ErrorCode::NoPauseProposed)?;
+        require!(
+            Clock::get()?.unix_timestamp >= proposed_at.saturating_add(verifier.pause_delay_seconds),
+            ErrorCode::PauseDelayNotElapsed
+        );
 
         verifier.is_paused = true;
+        verifier.pause_proposed_at = None;
         msg!("Spend verifier paused by authority");
         Ok(())
     }
 
+    /// Withdraw a proposed pause during its delay window
+    pub fn cancel_pause(ctx: Context<PauseVerifier>) -> Result<()> {
+        let verifier = &mut ctx.accounts.verifier;
+        require!(
+            ctx.accounts.authority.key() == verifier.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(verifier.pause_proposed_at.is_some(), ErrorCode::NoPauseProposed);
+
+        verifier.pause_proposed_at = None;
+        msg!("Proposed spend verifier pause cancelled");
+        Ok(())
+    }
+
+    /// Change the pause time-lock (admin only, at least MIN_PAUSE_DELAY_SECONDS)
+    pub fn set_pause_delay(ctx: Context<SetPauseDelay>, pause_delay_seconds: i64) -> Result<()> {
+        let verifier = &mut ctx.accounts.verifier;
+        require!(
+            ctx.accounts.authority.key() == verifier.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            pause_delay_seconds >= MIN_PAUSE_DELAY_SECONDS,
+            ErrorCode::PauseDelayTooShort
+        );
+
+        verifier.pause_delay_seconds = pause_delay_seconds;
+        msg!("Pause delay set to {} seconds", pause_delay_seconds);
+        Ok(())
+    }
+
     /// Resume verifier operations
     pub fn unpause_verifier(ctx: Context<UnpauseVerifier>) -> Result<()> {
         let verifier = &mut ctx.accounts.verifier;
@@ -120,8 +617,34 @@ pub mod spend_verifier {
         msg!("Spend verifier resumed");
         Ok(())
     }
+
+    /// Report this build's version so clients can check compatibility
+    pub fn get_program_version(_ctx: Context<GetProgramVersion>) -> Result<ProgramVersion> {
+        Ok(PROGRAM_VERSION)
+    }
+}
+
+#[derive(Accounts)]
+pub struct GetProgramVersion<'info> {
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+    pub build_commit: [u8; 8], // First 8 chars of GIT_SHA at build time, "00000000" if unset
 }
 
+/// Version compiled into this program from its Cargo.toml
+pub const PROGRAM_VERSION: ProgramVersion = ProgramVersion {
+    major: program_version::version_component(env!("CARGO_PKG_VERSION"), 0),
+    minor: program_version::version_component(env!("CARGO_PKG_VERSION"), 1),
+    patch: program_version::version_component(env!("CARGO_PKG_VERSION"), 2),
+    build_commit: program_version::build_commit(option_env!("GIT_SHA")),
+};
+
 #[derive(Accounts)]
 pub struct Initialize<'info> {
     #[account(
@@ -136,17 +659,32 @@ pub struct Initialize<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + NullifierSet::LEN,
-        seeds = [b"nullifier_set"],
+        space = 8 + NullifierShardRouter::LEN,
+        seeds = [b"nul_shard_router"],
         bump
     )]
-    pub nullifier_set: Account<'info, NullifierSet>,
+    pub shard_router: AccountLoader<'info, NullifierShardRouter>,
     
     #[account(mut)]
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
+#[derive(Accounts)]
+#[instruction(shard_index: u8)]
+pub struct InitNullifierShard<'info> {
+    #[account(
+        mut,
+        seeds = [b"nul_shard_router"],
+        bump
+    )]
+    pub shard_router: AccountLoader<'info, NullifierShardRouter>,
+    
+    /// Allocated by the caller with `NullifierShard::SPACE` bytes
+    #[account(zero)]
+    pub nullifier_shard: AccountLoader<'info, NullifierShard>,
+}
+
 #[derive(Accounts)]
 pub struct VerifySpend<'info> {
     #[account(
@@ -159,15 +697,30 @@ pub struct VerifySpend<'info> {
     
     #[account(
         mut,
-        seeds = [b"nullifier_set"],
+        seeds = [b"nul_shard_router"],
         bump
     )]
-    pub nullifier_set: Account<'info, NullifierSet>,
+    pub shard_router: AccountLoader<'info, NullifierShardRouter>,
+    
+    #[account(
+        mut,
+        constraint = shard_router.load()?.is_registered(&nullifier_shard)? @ ErrorCode::InvalidNullifierShard
+    )]
+    pub nullifier_shard: AccountLoader<'info, NullifierShard>,
+    
+    #[account(
+        seeds = [b"scope", approved_scope.scope.as_ref()],
+        bump = approved_scope.bump
+    )]
+    pub approved_scope: Account<'info, ApprovedScope>,
     
     // Shielded pool accounts
     #[account(mut)]
     pub shielded_pool: Account<'info, shielded_pool::ShieldedPool>,
     
+    #[account(mut)]
+    pub merkle_tree: Account<'info, shielded_pool::MerkleTree>,
+    
     /// CHECK: Pool authority PDA
     pub pool_authority: UncheckedAccount<'info>,
     
@@ -176,30 +729,249 @@ pub struct VerifySpend<'info> {
     pub pool_token: UncheckedAccount<'info>,
     
     #[account(mut)]
-    /// CHECK: Token account validated by token program
-    pub recipient_token: UncheckedAccount<'info>,
+    pub recipient_token: Account<'info, TokenAccount>,
+    
+    /// Relayer submitting the spend, credited with the relay fee; must match
+    /// the proof's relayer signal
+    pub relayer: Signer<'info>,
+    
+    #[account(
+        mut,
+        token::authority = relayer
+    )]
+    pub relayer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [
+            b"compat".as_ref(),
+            &zk_meta_registry::seed_hash(SPEND_CIRCUIT_NAME),
+            &zk_meta_registry::seed_hash(SPEND_PROOF_VERSION)
+        ],
+        bump = compatibility_entry.bump,
+        seeds::program = zk_meta_registry_program.key()
+    )]
+    pub compatibility_entry: Account<'info, zk_meta_registry::CompatibilityEntry>,
     
     // Programs
     pub shielded_pool_program: Program<'info, shielded_pool::program::ShieldedPool>,
+    pub zk_meta_registry_program: Program<'info, zk_meta_registry::program::ZkMetaRegistry>,
     pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
-pub struct PauseVerifier<'info> {
+pub struct BatchVerifySpend<'info> {
     #[account(
-        mut,
         seeds = [b"spend_verifier"],
-        bump
+        bump,
+        constraint = !verifier.is_paused @ ErrorCode::VerifierPaused
     )]
     pub verifier: Account<'info, SpendVerifier>,
-    
-    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct UnpauseVerifier<'info> {
+#[instruction(scope: [u8; 32])]
+pub struct RegisterScope<'info> {
     #[account(
-        mut,
+        seeds = [b"spend_verifier"],
+        bump
+    )]
+    pub verifier: Account<'info, SpendVerifier>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ApprovedScope::LEN,
+        seeds = [b"scope", scope.as_ref()],
+        bump
+    )]
+    pub approved_scope: Account<'info, ApprovedScope>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeVkRotation<'info> {
+    #[account(
+        mut,
+        seeds = [b"spend_verifier"],
+        bump
+    )]
+    pub verifier: Account<'info, SpendVerifier>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CancelVkRotation<'info> {
+    #[account(
+        mut,
+        seeds = [b"spend_verifier"],
+        bump
+    )]
+    pub verifier: Account<'info, SpendVerifier>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ActivatePendingVk<'info> {
+    #[account(
+        mut,
+        seeds = [b"spend_verifier"],
+        bump
+    )]
+    pub verifier: Account<'info, SpendVerifier>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePlonkVerifier<'info> {
+    #[account(
+        seeds = [b"spend_verifier"],
+        bump
+    )]
+    pub verifier: Account<'info, SpendVerifier>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PlonkVerifier::LEN,
+        seeds = [b"plonk_verifier"],
+        bump
+    )]
+    pub plonk_verifier: Account<'info, PlonkVerifier>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyPlonk<'info> {
+    #[account(
+        seeds = [b"spend_verifier"],
+        bump,
+        constraint = !verifier.is_paused @ ErrorCode::VerifierPaused
+    )]
+    pub verifier: Account<'info, SpendVerifier>,
+    
+    #[account(
+        seeds = [b"plonk_verifier"],
+        bump
+    )]
+    pub plonk_verifier: Account<'info, PlonkVerifier>,
+}
+
+#[derive(Accounts)]
+pub struct SetRelayFee<'info> {
+    #[account(
+        mut,
+        seeds = [b"spend_verifier"],
+        bump
+    )]
+    pub verifier: Account<'info, SpendVerifier>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyCircuitDigest<'info> {
+    #[account(
+        seeds = [b"spend_verifier"],
+        bump
+    )]
+    pub verifier: Account<'info, SpendVerifier>,
+}
+
+#[derive(Accounts)]
+pub struct SetMinSpendAmount<'info> {
+    #[account(
+        mut,
+        seeds = [b"spend_verifier"],
+        bump
+    )]
+    pub verifier: Account<'info, SpendVerifier>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRateLimit<'info> {
+    #[account(
+        mut,
+        seeds = [b"spend_verifier"],
+        bump
+    )]
+    pub verifier: Account<'info, SpendVerifier>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetTelemetryEnabled<'info> {
+    #[account(
+        mut,
+        seeds = [b"spend_verifier"],
+        bump
+    )]
+    pub verifier: Account<'info, SpendVerifier>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCircuitDigest<'info> {
+    #[account(
+        mut,
+        seeds = [b"spend_verifier"],
+        bump
+    )]
+    pub verifier: Account<'info, SpendVerifier>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateVkIntegrityHash<'info> {
+    #[account(
+        mut,
+        seeds = [b"spend_verifier"],
+        bump
+    )]
+    pub verifier: Account<'info, SpendVerifier>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPauseDelay<'info> {
+    #[account(
+        mut,
+        seeds = [b"spend_verifier"],
+        bump
+    )]
+    pub verifier: Account<'info, SpendVerifier>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PauseVerifier<'info> {
+    #[account(
+        mut,
+        seeds = [b"spend_verifier"],
+        bump
+    )]
+    pub verifier: Account<'info, SpendVerifier>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnpauseVerifier<'info> {
+    #[account(
+        mut,
         seeds = [b"spend_verifier"],
         bump
     )]
@@ -215,36 +987,228 @@ pub struct SpendVerifier {
     pub nullifier_count: u64,
     pub total_verified_amount: u64,
     pub is_paused: bool,
+    pub pending_vk: Option<PendingVK>,
+    pub relay_fee_bps: u16, // Basis points paid to the relayer (100 = 1%)
+    pub vk_version: String,  // Circuit version of the loaded key, checked against zk-meta-registry
+    pub vk_integrity_hash: [u8; 32], // SHA256 of the Borsh-serialized verification key
+    pub min_spend_amount: u64,       // Smallest spend accepted, keeps dust out of the nullifier set
+    pub rate_limit: RateLimitConfig,
+    /// SHA256 of the spend circuit's compiled `.r1cs` file, byte for byte
+    /// (`sha256sum spend_v2.r1cs`), so auditors can match it to the sources
+    pub circuit_r1cs_digest: [u8; 32],
+    pub circuit_name: String,    // Reported in telemetry, max 32 chars
+    pub telemetry_enabled: bool, // Off by default to save compute
+    pub pause_delay_seconds: i64,      // Time-lock between propose_pause and execute_pause
+    pub pause_proposed_at: Option<i64>,
 }
 
 impl SpendVerifier {
-    pub const LEN: usize = 32 + VerificationKey::LEN + 8 + 8 + 1;
+    pub const LEN: usize = 32 + VerificationKey::LEN + 8 + 8 + 1 + (1 + PendingVK::LEN) + 2 + (4 + 16) + 32 + 8
+        + RateLimitConfig::LEN + 32 + (4 + 32) + 1 + 8 + (1 + 8);
+
+    /// Fail if the stored key no longer matches its integrity hash
+    pub fn check_vk_integrity(&self) -> Result<()> {
+        require!(
+            self.verification_key.hash()? == self.vk_integrity_hash,
+            ErrorCode::VerificationKeyTampered
+        );
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RateLimitConfig {
+    pub max_spends_per_slot: u8,
+    pub current_slot: u64,
+    pub slot_spend_count: u8, // Spends verified so far in current_slot
+}
+
+impl RateLimitConfig {
+    pub const LEN: usize = 1 + 8 + 1;
+
+    /// Count a spend in `slot`, starting a fresh window when the slot changes
+    pub fn record_spend(&mut self, slot: u64) -> Result<()> {
+        if self.current_slot != slot {
+            self.current_slot = slot;
+            self.slot_spend_count = 0;
+        }
+        self.slot_spend_count = self.slot_spend_count.saturating_add(1);
+        require!(
+            self.slot_spend_count <= self.max_spends_per_slot,
+            ErrorCode::RateLimitExceeded
+        );
+        Ok(())
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PendingVK {
+    pub verification_key: VerificationKey,
+    pub vk_version: String,
+    pub effective_at: i64,
+    pub proposed_by: Pubkey,
+}
+
+impl PendingVK {
+    pub const LEN: usize = VerificationKey::LEN + (4 + 16) + 8 + 32;
 }
 
 #[account]
-pub struct NullifierSet {
-    pub nullifiers: Vec<[u8; 32]>, // Store used nullifiers
+pub struct ApprovedScope {
+    pub scope: [u8; 32],
+    pub external_nullifier: [u8; 32], // Expected external nullifier signal for this scope
+    pub registered_at: i64,
+    pub bump: u8,
 }
 
-impl NullifierSet {
-    pub const LEN: usize = 4 + (32 * 1000000); // Support up to 1M nullifiers
-    
+impl ApprovedScope {
+    pub const LEN: usize = 32 + 32 + 8 + 1;
+}
+
+/// External nullifier for `(program_id, scope)`.
+///
+/// A single 32-byte signal cannot carry both values, so the circuit exposes
+/// SHA256(program_id || scope) with the top byte cleared to keep it a
+/// canonical BN254 scalar.
+pub fn scoped_external_nullifier(program_id: &Pubkey, scope: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(program_id.to_bytes());
+    hasher.update(scope);
+    let mut external_nullifier: [u8; 32] = hasher.finalize().into();
+    external_nullifier[31] = 0;
+    external_nullifier
+}
+
+/// Public signal committing to a spend's recipient.
+///
+/// Most pubkeys are not canonical BN254 scalars, so the circuit exposes
+/// SHA256(recipient) with the top byte cleared, like `scoped_external_nullifier`.
+pub fn recipient_signal(recipient: &Pubkey) -> [u8; 32] {
+    pubkey_signal(recipient)
+}
+
+/// Public signal committing to the relayer paid the relay fee, encoded like `recipient_signal`
+pub fn relayer_signal(relayer: &Pubkey) -> [u8; 32] {
+    pubkey_signal(relayer)
+}
+
+fn pubkey_signal(pubkey: &Pubkey) -> [u8; 32] {
+    let mut signal: [u8; 32] = Sha256::digest(pubkey.to_bytes()).into();
+    signal[31] = 0;
+    signal
+}
+
+/// Number of nullifier shards; a nullifier is routed to shard `nullifier[0]`
+pub const NUM_SHARDS: usize = 256;
+/// Entries per shard (320 KB of nullifiers)
+pub const MAX_NULLIFIERS_PER_SHARD: usize = 8192;
+
+#[account(zero_copy)]
+pub struct NullifierShardRouter {
+    pub shards: [Pubkey; NUM_SHARDS], // Registered shard account per index, default = not created
+    pub lens: [u16; NUM_SHARDS], // Live entries per shard, so clients can spot full shards without loading them
+}
+
+impl NullifierShardRouter {
+    pub const LEN: usize = 32 * NUM_SHARDS + 2 * NUM_SHARDS;
+
+    /// Mirror `shard`'s entry count after an insert
+    pub fn record_len(&mut self, shard: &NullifierShard) {
+        self.lens[shard.shard_index as usize] = shard.len;
+    }
+
+    /// Entries shard `shard_index` can still take before `NullifierSetFull`
+    pub fn remaining_capacity(&self, shard_index: u8) -> u16 {
+        MAX_NULLIFIERS_PER_SHARD as u16 - self.lens[shard_index as usize]
+    }
+
+    /// Whether `shard` is the account registered for the index it claims
+    pub fn is_registered(&self, shard: &AccountLoader<NullifierShard>) -> Result<bool> {
+        let shard_index = shard.load()?.shard_index;
+        Ok(self.shards[shard_index as usize] == shard.key())
+    }
+}
+
+#[account(zero_copy)]
+pub struct NullifierShard {
+    pub shard_index: u8,
+    pub _padding: [u8; 5],
+    pub len: u16, // Live entries at the front of `nullifiers`
+    pub nullifiers: [NullifierEntry; MAX_NULLIFIERS_PER_SHARD], // Sorted by hash
+}
+
+impl NullifierShard {
+    pub const SPACE: usize = 8 + std::mem::size_of::<NullifierShard>();
+
+    fn entries(&self) -> &[NullifierEntry] {
+        &self.nullifiers[..self.len as usize]
+    }
+
+    /// Position of `nullifier` in the sorted entries, O(log n)
+    fn search(&self, nullifier: &[u8; 32]) -> std::result::Result<usize, usize> {
+        self.entries().binary_search_by(|entry| entry.hash.cmp(nullifier))
+    }
+
+    /// Whether `nullifier` has been spent.
+    ///
+    /// Entries never expire: a note's commitment stays in every later Merkle
+    /// root, so forgetting its nullifier would let the note be spent again.
     pub fn contains(&self, nullifier: &[u8; 32]) -> bool {
-        self.nullifiers.contains(nullifier)
+        self.search(nullifier).is_ok()
     }
-    
-    pub fn insert(&mut self, nullifier: [u8; 32]) -> Result<()> {
-        require!(!self.contains(&nullifier), ErrorCode::DoubleSpend);
-        require!(
-            self.nullifiers.len() < 1000000,
-            ErrorCode::NullifierSetFull
-        );
-        
-        self.nullifiers.push(nullifier);
+
+    /// Record `nullifier` as spent at `now`, keeping the entries sorted
+    pub fn insert(&mut self, nullifier: [u8; 32], now: i64) -> Result<()> {
+        let Err(index) = self.search(&nullifier) else {
+            return err!(ErrorCode::DoubleSpend);
+        };
+        let len = self.len as usize;
+        require!(len < MAX_NULLIFIERS_PER_SHARD, ErrorCode::NullifierSetFull);
+
+        self.nullifiers.copy_within(index..len, index + 1);
+        self.nullifiers[index] = NullifierEntry {
+            hash: nullifier,
+            inserted_at: now,
+        };
+        self.len += 1;
         Ok(())
     }
+
+}
+
+#[zero_copy]
+pub struct NullifierEntry {
+    pub hash: [u8; 32],
+    pub inserted_at: i64, // When the nullifier was spent
 }
 
+/// Public signals of the spend circuit:
+/// merkle_root, nullifier_hash, recipient_signal(recipient), amount, external_nullifier,
+/// change_commitment, relayer_signal(relayer)
+pub const SPEND_PUBLIC_INPUTS: usize = 7;
+
+/// Minimum spend amount in lamports set at initialization
+pub const DEFAULT_MIN_SPEND_AMOUNT: u64 = 1000;
+
+/// Spends verified per slot set at initialization
+pub const DEFAULT_MAX_SPENDS_PER_SLOT: u8 = 16;
+
+/// Pause time-lock set at initialization
+pub const DEFAULT_PAUSE_DELAY_SECONDS: i64 = 24 * 60 * 60;
+
+/// Shortest pause time-lock `set_pause_delay` accepts
+pub const MIN_PAUSE_DELAY_SECONDS: i64 = 60 * 60;
+
+/// Shortest notice `propose_vk_rotation` must give before a new key takes effect
+pub const MIN_VK_ROTATION_DELAY_SECONDS: i64 = 48 * 60 * 60;
+
+/// Circuit name and embedded key version registered in zk-meta-registry
+pub const SPEND_CIRCUIT_NAME: &str = "spend";
+pub const SPEND_VK_VERSION: &str = "v2.0";
+
+/// Version of the spend circuit whose public-signal layout this program verifies
+pub const SPEND_PROOF_VERSION: &str = "v2.0";
+
 // Verification Key structure (from our spend circuit)
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
 pub struct VerificationKey {
@@ -252,11 +1216,17 @@ pub struct VerificationKey {
     pub beta_g2: G2Point, 
     pub gamma_g2: G2Point,
     pub delta_g2: G2Point,
-    pub ic: Vec<G1Point>, // 6 points for our 5 public inputs + 1
+    pub ic: Vec<G1Point>, // 7 points for our 6 public inputs + 1
 }
 
 impl VerificationKey {
-    pub const LEN: usize = G1Point::LEN + (G2Point::LEN * 3) + (G1Point::LEN * 6);
+    pub const LEN: usize =
+        G1Point::LEN + (G2Point::LEN * 3) + (4 + G1Point::LEN * (SPEND_PUBLIC_INPUTS + 1));
+
+    /// SHA256 over the Borsh encoding, used to identify keys in events
+    pub fn hash(&self) -> Result<[u8; 32]> {
+        Ok(Sha256::digest(self.try_to_vec()?).into())
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -284,255 +1254,535 @@ pub struct Groth16Proof {
     pub pi_a: G1Point,
     pub pi_b: G2Point,
     pub pi_c: G1Point,
+    pub change_commitment: [u8; 32], // Change note commitment, zero when the note is spent in full
 }
 
-// Embedded verification key from our spend circuit
-// Production verification key for spend circuit (embedded at compile time)
-// In production, this would be generated from your actual circuit compilation
-fn get_spend_verification_key() -> VerificationKey {
-    VerificationKey {
-        alpha_g1: G1Point {
-            // Production alpha_g1 point from spend circuit
-            x: [
-                0x19, 0x8e, 0x93, 0x93, 0xf2, 0xa3, 0x4e, 0x5c,
-                0x17, 0x86, 0x78, 0x07, 0xd7, 0xd2, 0x5b, 0x3c,
-                0x15, 0xf2, 0x5a, 0x4a, 0x8a, 0x7a, 0x8d, 0x7e,
-                0x1e, 0xa2, 0x0f, 0xb0, 0x9c, 0x78, 0xd5, 0x2e
-            ],
-            y: [
-                0x1b, 0xa5, 0x93, 0x32, 0xf0, 0xd4, 0x5b, 0xc0,
-                0x20, 0x6a, 0x8b, 0x5e, 0xea, 0x92, 0x75, 0x3c,
-                0x05, 0x1e, 0x56, 0xa7, 0x89, 0x6a, 0x54, 0x3e,
-                0x2c, 0xe2, 0x25, 0xb3, 0x9c, 0x83, 0xd5, 0x4f
-            ],
-        },
-        beta_g2: G2Point {
-            x: [
-                [
-                    0x26, 0x0e, 0x01, 0xb2, 0x51, 0xf6, 0xf1, 0xc7,
-                    0xed, 0xd6, 0x5c, 0x9e, 0x5a, 0x29, 0x8b, 0x3c,
-                    0x2e, 0x5b, 0xa7, 0x5a, 0x1c, 0x6a, 0x3d, 0x7e,
-                    0x1a, 0x5c, 0x2b, 0xf0, 0xac, 0x78, 0xd5, 0x2e
-                ],
-                [
-                    0x19, 0x8e, 0x93, 0x93, 0xf2, 0xa3, 0x4e, 0x5c,
-                    0x17, 0x86, 0x78, 0x07, 0xd7, 0xd2, 0x5b, 0x3c,
-                    0x15, 0xf2, 0x5a, 0x4a, 0x8a, 0x7a, 0x8d, 0x7e,
-                    0x1e, 0xa2, 0x0f, 0xb0, 0x9c, 0x78, 0xd5, 0x2e
-                ]
-            ],
-            y: [
-                [
-                    0x1c, 0xa5, 0x93, 0x32, 0xf0, 0xd4, 0x5b, 0xc0,
-                    0x20, 0x6a, 0x8b, 0x5e, 0xea, 0x92, 0x75, 0x3c,
-                    0x05, 0x1e, 0x56, 0xa7, 0x89, 0x6a, 0x54, 0x3e,
-                    0x2c, 0xe2, 0x25, 0xb3, 0x9c, 0x83, 0xd5, 0x4f
-                ],
-                [
-                    0x27, 0x0e, 0x01, 0xb2, 0x51, 0xf6, 0xf1, 0xc7,
-                    0xed, 0xd6, 0x5c, 0x9e, 0x5a, 0x29, 0x8b, 0x3c,
-                    0x2e, 0x5b, 0xa7, 0x5a, 0x1c, 0x6a, 0x3d, 0x7e,
-                    0x1a, 0x5c, 0x2b, 0xf0, 0xac, 0x78, 0xd5, 0x2e
-                ]
-            ],
-        },
-        gamma_g2: G2Point {
-            x: [
-                [
-                    0x28, 0x0e, 0x01, 0xb2, 0x51, 0xf6, 0xf1, 0xc7,
-                    0xed, 0xd6, 0x5c, 0x9e, 0x5a, 0x29, 0x8b, 0x3c,
-                    0x2e, 0x5b, 0xa7, 0x5a, 0x1c, 0x6a, 0x3d, 0x7e,
-                    0x1a, 0x5c, 0x2b, 0xf0, 0xac, 0x78, 0xd5, 0x2e
-                ],
-                [
-                    0x29, 0x8e, 0x93, 0x93, 0xf2, 0xa3, 0x4e, 0x5c,
-                    0x17, 0x86, 0x78, 0x07, 0xd7, 0xd2, 0x5b, 0x3c,
-                    0x15, 0xf2, 0x5a, 0x4a, 0x8a, 0x7a, 0x8d, 0x7e,
-                    0x1e, 0xa2, 0x0f, 0xb0, 0x9c, 0x78, 0xd5, 0x2e
-                ]
-            ],
-            y: [
-                [
-                    0x30, 0xa5, 0x93, 0x32, 0xf0, 0xd4, 0x5b, 0xc0,
-                    0x20, 0x6a, 0x8b, 0x5e, 0xea, 0x92, 0x75, 0x3c,
-                    0x05, 0x1e, 0x56, 0xa7, 0x89, 0x6a, 0x54, 0x3e,
-                    0x2c, 0xe2, 0x25, 0xb3, 0x9c, 0x83, 0xd5, 0x4f
-                ],
-                [
-                    0x31, 0x0e, 0x01, 0xb2, 0x51, 0xf6, 0xf1, 0xc7,
-                    0xed, 0xd6, 0x5c, 0x9e, 0x5a, 0x29, 0x8b, 0x3c,
-                    0x2e, 0x5b, 0xa7, 0x5a, 0x1c, 0x6a, 0x3d, 0x7e,
-                    0x1a, 0x5c, 0x2b, 0xf0, 0xac, 0x78, 0xd5, 0x2e
-                ]
-            ],
-        },
-        delta_g2: G2Point {
-            x: [
-                [
-                    0x32, 0x0e, 0x01, 0xb2, 0x51, 0xf6, 0xf1, 0xc7,
-                    0xed, 0xd6, 0x5c, 0x9e, 0x5a, 0x29, 0x8b, 0x3c,
-                    0x2e, 0x5b, 0xa7, 0x5a, 0x1c, 0x6a, 0x3d, 0x7e,
-                    0x1a, 0x5c, 0x2b, 0xf0, 0xac, 0x78, 0xd5, 0x2e
-                ],
-                [
-                    0x33, 0x8e, 0x93, 0x93, 0xf2, 0xa3, 0x4e, 0x5c,
-                    0x17, 0x86, 0x78, 0x07, 0xd7, 0xd2, 0x5b, 0x3c,
-                    0x15, 0xf2, 0x5a, 0x4a, 0x8a, 0x7a, 0x8d, 0x7e,
-                    0x1e, 0xa2, 0x0f, 0xb0, 0x9c, 0x78, 0xd5, 0x2e
-                ]
-            ],
-            y: [
-                [
-                    0x34, 0xa5, 0x93, 0x32, 0xf0, 0xd4, 0x5b, 0xc0,
-                    0x20, 0x6a, 0x8b, 0x5e, 0xea, 0x92, 0x75, 0x3c,
-                    0x05, 0x1e, 0x56, 0xa7, 0x89, 0x6a, 0x54, 0x3e,
-                    0x2c, 0xe2, 0x25, 0xb3, 0x9c, 0x83, 0xd5, 0x4f
-                ],
-                [
-                    0x35, 0x0e, 0x01, 0xb2, 0x51, 0xf6, 0xf1, 0xc7,
-                    0xed, 0xd6, 0x5c, 0x9e, 0x5a, 0x29, 0x8b, 0x3c,
-                    0x2e, 0x5b, 0xa7, 0x5a, 0x1c, 0x6a, 0x3d, 0x7e,
-                    0x1a, 0x5c, 0x2b, 0xf0, 0xac, 0x78, 0xd5, 0x2e
-                ]
-            ],
-        },
-        ic: vec![
-            // IC points for public inputs (merkle_root, nullifier_hash, recipient, amount, external_nullifier)
-            G1Point {
-                x: [
-                    0x36, 0x8e, 0x93, 0x93, 0xf2, 0xa3, 0x4e, 0x5c,
-                    0x17, 0x86, 0x78, 0x07, 0xd7, 0xd2, 0x5b, 0x3c,
-                    0x15, 0xf2, 0x5a, 0x4a, 0x8a, 0x7a, 0x8d, 0x7e,
-                    0x1e, 0xa2, 0x0f, 0xb0, 0x9c, 0x78, 0xd5, 0x2e
-                ],
-                y: [
-                    0x37, 0xa5, 0x93, 0x32, 0xf0, 0xd4, 0x5b, 0xc0,
-                    0x20, 0x6a, 0x8b, 0x5e, 0xea, 0x92, 0x75, 0x3c,
-                    0x05, 0x1e, 0x56, 0xa7, 0x89, 0x6a, 0x54, 0x3e,
-                    0x2c, 0xe2, 0x25, 0xb3, 0x9c, 0x83, 0xd5, 0x4f
-                ],
-            },
-            G1Point {
-                x: [
-                    0x38, 0x8e, 0x93, 0x93, 0xf2, 0xa3, 0x4e, 0x5c,
-                    0x17, 0x86, 0x78, 0x07, 0xd7, 0xd2, 0x5b, 0x3c,
-                    0x15, 0xf2, 0x5a, 0x4a, 0x8a, 0x7a, 0x8d, 0x7e,
-                    0x1e, 0xa2, 0x0f, 0xb0, 0x9c, 0x78, 0xd5, 0x2e
-                ],
-                y: [
-                    0x39, 0xa5, 0x93, 0x32, 0xf0, 0xd4, 0x5b, 0xc0,
-                    0x20, 0x6a, 0x8b, 0x5e, 0xea, 0x92, 0x75, 0x3c,
-                    0x05, 0x1e, 0x56, 0xa7, 0x89, 0x6a, 0x54, 0x3e,
-                    0x2c, 0xe2, 0x25, 0xb3, 0x9c, 0x83, 0xd5, 0x4f
-                ],
-            },
-            G1Point {
-                x: [
-                    0x40, 0x8e, 0x93, 0x93, 0xf2, 0xa3, 0x4e, 0x5c,
-                    0x17, 0x86, 0x78, 0x07, 0xd7, 0xd2, 0x5b, 0x3c,
-                    0x15, 0xf2, 0x5a, 0x4a, 0x8a, 0x7a, 0x8d, 0x7e,
-                    0x1e, 0xa2, 0x0f, 0xb0, 0x9c, 0x78, 0xd5, 0x2e
-                ],
-                y: [
-                    0x41, 0xa5, 0x93, 0x32, 0xf0, 0xd4, 0x5b, 0xc0,
-                    0x20, 0x6a, 0x8b, 0x5e, 0xea, 0x92, 0x75, 0x3c,
-                    0x05, 0x1e, 0x56, 0xa7, 0x89, 0x6a, 0x54, 0x3e,
-                    0x2c, 0xe2, 0x25, 0xb3, 0x9c, 0x83, 0xd5, 0x4f
-                ],
-            },
-            G1Point {
-                x: [
-                    0x42, 0x8e, 0x93, 0x93, 0xf2, 0xa3, 0x4e, 0x5c,
-                    0x17, 0x86, 0x78, 0x07, 0xd7, 0xd2, 0x5b, 0x3c,
-                    0x15, 0xf2, 0x5a, 0x4a, 0x8a, 0x7a, 0x8d, 0x7e,
-                    0x1e, 0xa2, 0x0f, 0xb0, 0x9c, 0x78, 0xd5, 0x2e
-                ],
-                y: [
-                    0x43, 0xa5, 0x93, 0x32, 0xf0, 0xd4, 0x5b, 0xc0,
-                    0x20, 0x6a, 0x8b, 0x5e, 0xea, 0x92, 0x75, 0x3c,
-                    0x05, 0x1e, 0x56, 0xa7, 0x89, 0x6a, 0x54, 0x3e,
-                    0x2c, 0xe2, 0x25, 0xb3, 0x9c, 0x83, 0xd5, 0x4f
-                ],
-            },
-            G1Point {
-                x: [
-                    0x44, 0x8e, 0x93, 0x93, 0xf2, 0xa3, 0x4e, 0x5c,
-                    0x17, 0x86, 0x78, 0x07, 0xd7, 0xd2, 0x5b, 0x3c,
-                    0x15, 0xf2, 0x5a, 0x4a, 0x8a, 0x7a, 0x8d, 0x7e,
-                    0x1e, 0xa2, 0x0f, 0xb0, 0x9c, 0x78, 0xd5, 0x2e
-                ],
-                y: [
-                    0x45, 0xa5, 0x93, 0x32, 0xf0, 0xd4, 0x5b, 0xc0,
-                    0x20, 0x6a, 0x8b, 0x5e, 0xea, 0x92, 0x75, 0x3c,
-                    0x05, 0x1e, 0x56, 0xa7, 0x89, 0x6a, 0x54, 0x3e,
-                    0x2c, 0xe2, 0x25, 0xb3, 0x9c, 0x83, 0xd5, 0x4f
-                ],
-            },
-            G1Point {
-                x: [
-                    0x46, 0x8e, 0x93, 0x93, 0xf2, 0xa3, 0x4e, 0x5c,
-                    0x17, 0x86, 0x78, 0x07, 0xd7, 0xd2, 0x5b, 0x3c,
-                    0x15, 0xf2, 0x5a, 0x4a, 0x8a, 0x7a, 0x8d, 0x7e,
-                    0x1e, 0xa2, 0x0f, 0xb0, 0x9c, 0x78, 0xd5, 0x2e
-                ],
-                y: [
-                    0x47, 0xa5, 0x93, 0x32, 0xf0, 0xd4, 0x5b, 0xc0,
-                    0x20, 0x6a, 0x8b, 0x5e, 0xea, 0x92, 0x75, 0x3c,
-                    0x05, 0x1e, 0x56, 0xa7, 0x89, 0x6a, 0x54, 0x3e,
-                    0x2c, 0xe2, 0x25, 0xb3, 0x9c, 0x83, 0xd5, 0x4f
-                ],
-            },
-        ],
-    }
+#[account]
+pub struct PlonkVerifier {
+    pub authority: Pubkey,
+    pub verification_key: PlonkVerificationKey,
 }
 
-// Production-grade Groth16 verification using structured verification key
-fn groth16_verify(
+impl PlonkVerifier {
+    pub const LEN: usize = 32 + PlonkVerificationKey::LEN;
+}
+
+// PLONK verification key as exported by `snarkjs zkey export verificationkey`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PlonkVerificationKey {
+    pub q_m: G1Point,
+    pub q_l: G1Point,
+    pub q_r: G1Point,
+    pub q_o: G1Point,
+    pub q_c: G1Point,
+    pub sigma_1: G1Point,
+    pub sigma_2: G1Point,
+    pub sigma_3: G1Point,
+    pub x_2: G2Point,
+    pub omega: [u8; 32], // Generator of the evaluation domain
+    pub power: u8,       // Domain size is 2^power
+    pub n_public: u8,
+}
+
+impl PlonkVerificationKey {
+    pub const LEN: usize = (G1Point::LEN * 8) + G2Point::LEN + 32 + 1 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PlonkProof {
+    pub a: G1Point,
+    pub b: G1Point,
+    pub c: G1Point,
+    pub z: G1Point,
+    pub t1: G1Point,
+    pub t2: G1Point,
+    pub t3: G1Point,
+    pub wxi: G1Point,
+    pub wxiw: G1Point,
+    pub evaluations: Vec<[u8; 32]>, // eval_a, eval_b, eval_c, eval_s1, eval_s2, eval_zw
+}
+
+// Spend circuit verification key constants generated by build.rs from the snarkjs export
+include!(concat!(env!("OUT_DIR"), "/generated_vk.rs"));
+
+/// Spend circuit verification key, embedded at build time
+fn get_spend_verification_key() -> Result<VerificationKey> {
+    require!(SPEND_VK_EMBEDDED, ErrorCode::VKGenerationFailed);
+    require!(SPEND_VK_IC.len() == SPEND_PUBLIC_INPUTS + 1, ErrorCode::VKGenerationFailed);
+
+    Ok(VerificationKey {
+        alpha_g1: SPEND_VK_ALPHA_G1,
+        beta_g2: SPEND_VK_BETA_G2,
+        gamma_g2: SPEND_VK_GAMMA_G2,
+        delta_g2: SPEND_VK_DELTA_G2,
+        ic: SPEND_VK_IC.to_vec(),
+    })
+}
+
+// Groth16 verification over BN254.
+//
+// Curve points and public signals are little-endian encodings of their field
+// elements (matching ark-serialize). A G1/G2 point with every coordinate zero
+// is treated as the point at infinity.
+pub fn groth16_verify(
     vk: &VerificationKey,
     proof: &Groth16Proof,
     public_signals: &[[u8; 32]],
 ) -> Result<bool> {
-    // Validate verification key structure
-    require!(vk.ic.len() >= 1, ErrorCode::InvalidVerificationKey);
-    require!(proof.pi_a.x != [0u8; 32], ErrorCode::InvalidProof);
-    
-    // Validate proof structure - check that G2 point is not zero
-    let g2_point_non_zero = proof.pi_b.x[0] != [0u8; 32] || proof.pi_b.x[1] != [0u8; 32];
-    
-    // Perform verification using the structured components
-    let proof_valid = 
-        vk.ic.len() == public_signals.len() + 1 && // IC length should match public inputs + 1
-        proof.pi_a.x != [0u8; 32] && // Proof G1 points should not be zero
-        g2_point_non_zero && // G2 point should not be zero
-        proof.pi_c.x != [0u8; 32] &&
-        vk.alpha_g1.x != [0u8; 32] && // VK points should not be zero
-        public_signals.len() > 0;
-    
+    require!(!vk.ic.is_empty(), ErrorCode::InvalidVerificationKey);
+    require!(
+        vk.ic.len() == public_signals.len() + 1,
+        ErrorCode::InvalidPublicInputCount
+    );
+
+    let prepared_vk = prepare_verifying_key(&vk.to_ark()?);
+    let ark_proof = proof.to_ark()?;
+
+    let inputs: Vec<Fr> = public_signals
+        .iter()
+        .map(|signal| Fr::from_le_bytes_mod_order(signal))
+        .collect();
+
+    let proof_valid = Groth16::<Bn254>::verify_proof(&prepared_vk, &ark_proof, &inputs)
+        .map_err(|_| ErrorCode::InvalidProof)?;
+
     if proof_valid {
-        msg!("Groth16 verification successful - inputs: {}, ic_len: {}", 
-             public_signals.len(), vk.ic.len());
+        msg!("Groth16 verification successful - inputs: {}", public_signals.len());
     } else {
-        msg!("Groth16 verification failed - structural validation");
+        msg!("Groth16 verification failed - pairing check did not hold");
     }
-    
+
     Ok(proof_valid)
 }
 
+/// Maximum number of proofs accepted by `batch_verify_spend_proofs`
+pub const MAX_BATCH_PROOFS: usize = 3;
+
+// Batched Groth16 verification using a random linear combination.
+//
+// With random r_i, every proof in the batch is valid (with overwhelming
+// probability) iff
+//   prod e(r_i * A_i, B_i) = e(sum(r_i) * alpha, beta)
+//                            * e(sum(r_i * L_i), gamma) * e(sum(r_i * C_i), delta)
+// where L_i is the IC combination of proof i's public inputs. All N + 3 pairs
+// go through one Miller loop and one final exponentiation instead of 4 * N
+// pairings.
+fn batch_groth16_verify(
+    vk: &VerificationKey,
+    proofs: &[Groth16Proof],
+    signals: &[Vec<[u8; 32]>],
+    seed: &[u8],
+) -> Result<bool> {
+    require!(!vk.ic.is_empty(), ErrorCode::InvalidVerificationKey);
+
+    let ark_vk = vk.to_ark()?;
+    let ark_proofs = proofs
+        .iter()
+        .map(|proof| proof.to_ark())
+        .collect::<Result<Vec<_>>>()?;
+
+    // r_i = H(proofs || signals || seed || i), so a prover cannot choose
+    // proofs that cancel each other out
+    let mut transcript = Sha256::new();
+    for (proof, public_signals) in proofs.iter().zip(signals) {
+        transcript.update(proof.try_to_vec()?);
+        for signal in public_signals {
+            transcript.update(signal);
+        }
+    }
+    transcript.update(seed);
+
+    let mut g1_terms = Vec::with_capacity(proofs.len() + 3);
+    let mut g2_terms = Vec::with_capacity(proofs.len() + 3);
+    let mut r_sum = Fr::zero();
+    let mut acc_inputs = G1Projective::zero();
+    let mut acc_c = G1Projective::zero();
+
+    for (i, (proof, public_signals)) in ark_proofs.iter().zip(signals).enumerate() {
+        require!(
+            vk.ic.len() == public_signals.len() + 1,
+            ErrorCode::InvalidPublicInputCount
+        );
+
+        let mut hasher = transcript.clone();
+        hasher.update((i as u64).to_le_bytes());
+        let r = Fr::from_le_bytes_mod_order(&hasher.finalize());
+
+        let mut prepared_inputs = G1Projective::from(ark_vk.gamma_abc_g1[0]);
+        for (signal, ic) in public_signals.iter().zip(&ark_vk.gamma_abc_g1[1..]) {
+            // Reducing mod r would let two encodings of one input verify alike
+            validate_bn254_field_element(signal)?;
+            prepared_inputs += *ic * Fr::from_le_bytes_mod_order(signal);
+        }
+
+        g1_terms.push((proof.a * r).into_affine());
+        g2_terms.push(proof.b);
+        r_sum += r;
+        acc_inputs += prepared_inputs * r;
+        acc_c += proof.c * r;
+    }
+
+    g1_terms.push((-(ark_vk.alpha_g1 * r_sum)).into_affine());
+    g2_terms.push(ark_vk.beta_g2);
+    g1_terms.push((-acc_inputs).into_affine());
+    g2_terms.push(ark_vk.gamma_g2);
+    g1_terms.push((-acc_c).into_affine());
+    g2_terms.push(ark_vk.delta_g2);
+
+    let batch_valid = Bn254::multi_pairing(g1_terms, g2_terms).0.is_one();
+
+    if batch_valid {
+        msg!("Batch Groth16 verification successful - proofs: {}", proofs.len());
+    } else {
+        msg!("Batch Groth16 verification failed - at least one proof is invalid");
+    }
+
+    Ok(batch_valid)
+}
+
+// snarkjs uses k1 = 2 and k2 = 3 as the coset shifts for the copy constraints
+const PLONK_K1: u64 = 2;
+const PLONK_K2: u64 = 3;
+
+/// Keccak256 Fiat-Shamir transcript, byte-compatible with snarkjs
+struct PlonkTranscript {
+    data: Vec<u8>,
+}
+
+impl PlonkTranscript {
+    fn new() -> Self {
+        Self { data: Vec::new() }
+    }
+
+    fn add_scalar(&mut self, scalar: &Fr) {
+        self.data.extend_from_slice(&scalar.into_bigint().to_bytes_be());
+    }
+
+    fn add_point(&mut self, point: &G1Affine) {
+        let (x, y) = point.xy().map(|(x, y)| (*x, *y)).unwrap_or((Fq::zero(), Fq::zero()));
+        self.data.extend_from_slice(&x.into_bigint().to_bytes_be());
+        self.data.extend_from_slice(&y.into_bigint().to_bytes_be());
+    }
+
+    fn challenge(&mut self) -> Fr {
+        let digest = Keccak256::digest(&self.data);
+        self.data.clear();
+        Fr::from_be_bytes_mod_order(&digest)
+    }
+}
+
+// PLONK verification over BN254, following the snarkjs verifier
+fn plonk_verify(
+    vk: &PlonkVerificationKey,
+    proof: &PlonkProof,
+    public_signals: &[[u8; 32]],
+) -> Result<bool> {
+    require!(proof.evaluations.len() == 6, ErrorCode::InvalidProof);
+
+    let invalid_vk = || error!(ErrorCode::InvalidVerificationKey);
+    let invalid_proof = || error!(ErrorCode::InvalidProof);
+
+    let q_m = vk.q_m.to_affine().ok_or_else(invalid_vk)?;
+    let q_l = vk.q_l.to_affine().ok_or_else(invalid_vk)?;
+    let q_r = vk.q_r.to_affine().ok_or_else(invalid_vk)?;
+    let q_o = vk.q_o.to_affine().ok_or_else(invalid_vk)?;
+    let q_c = vk.q_c.to_affine().ok_or_else(invalid_vk)?;
+    let s1 = vk.sigma_1.to_affine().ok_or_else(invalid_vk)?;
+    let s2 = vk.sigma_2.to_affine().ok_or_else(invalid_vk)?;
+    let s3 = vk.sigma_3.to_affine().ok_or_else(invalid_vk)?;
+    let x_2 = vk.x_2.to_affine().ok_or_else(invalid_vk)?;
+    let omega = fr_from_bytes(&vk.omega).ok_or_else(invalid_vk)?;
+
+    let a = proof.a.to_affine().ok_or_else(invalid_proof)?;
+    let b = proof.b.to_affine().ok_or_else(invalid_proof)?;
+    let c = proof.c.to_affine().ok_or_else(invalid_proof)?;
+    let z = proof.z.to_affine().ok_or_else(invalid_proof)?;
+    let t1 = proof.t1.to_affine().ok_or_else(invalid_proof)?;
+    let t2 = proof.t2.to_affine().ok_or_else(invalid_proof)?;
+    let t3 = proof.t3.to_affine().ok_or_else(invalid_proof)?;
+    let wxi = proof.wxi.to_affine().ok_or_else(invalid_proof)?;
+    let wxiw = proof.wxiw.to_affine().ok_or_else(invalid_proof)?;
+
+    let evaluations = proof
+        .evaluations
+        .iter()
+        .map(|e| fr_from_bytes(e).ok_or_else(invalid_proof))
+        .collect::<Result<Vec<_>>>()?;
+    let (eval_a, eval_b, eval_c) = (evaluations[0], evaluations[1], evaluations[2]);
+    let (eval_s1, eval_s2, eval_zw) = (evaluations[3], evaluations[4], evaluations[5]);
+
+    let public_inputs = public_signals
+        .iter()
+        .map(|signal| fr_from_bytes(signal).ok_or_else(|| error!(ErrorCode::InvalidPublicSignal)))
+        .collect::<Result<Vec<_>>>()?;
+
+    // Fiat-Shamir challenges
+    let mut transcript = PlonkTranscript::new();
+    for point in [&q_m, &q_l, &q_r, &q_o, &q_c, &s1, &s2, &s3] {
+        transcript.add_point(point);
+    }
+    for input in &public_inputs {
+        transcript.add_scalar(input);
+    }
+    for point in [&a, &b, &c] {
+        transcript.add_point(point);
+    }
+    let beta = transcript.challenge();
+
+    transcript.add_scalar(&beta);
+    let gamma = transcript.challenge();
+
+    transcript.add_scalar(&beta);
+    transcript.add_scalar(&gamma);
+    transcript.add_point(&z);
+    let alpha = transcript.challenge();
+
+    transcript.add_scalar(&alpha);
+    for point in [&t1, &t2, &t3] {
+        transcript.add_point(point);
+    }
+    let xi = transcript.challenge();
+
+    transcript.add_scalar(&xi);
+    for evaluation in &evaluations {
+        transcript.add_scalar(evaluation);
+    }
+    let mut v = [Fr::zero(); 6];
+    v[1] = transcript.challenge();
+    for i in 2..6 {
+        v[i] = v[i - 1] * v[1];
+    }
+
+    transcript.add_point(&wxi);
+    transcript.add_point(&wxiw);
+    let u = transcript.challenge();
+
+    // Vanishing polynomial and Lagrange evaluations at xi
+    let n = Fr::from(1u64 << vk.power);
+    let mut xin = xi;
+    for _ in 0..vk.power {
+        xin.square_in_place();
+    }
+    let zh = xin - Fr::one();
+
+    let lagrange_count = public_inputs.len().max(1);
+    let mut lagrange = Vec::with_capacity(lagrange_count);
+    let mut w = Fr::one();
+    for _ in 0..lagrange_count {
+        let denominator = (n * (xi - w)).inverse().ok_or_else(invalid_proof)?;
+        lagrange.push(w * zh * denominator);
+        w *= omega;
+    }
+
+    let pi = public_inputs
+        .iter()
+        .zip(lagrange.iter())
+        .fold(Fr::zero(), |acc, (input, l)| acc - *input * l);
+
+    // r0: constant part of the linearisation polynomial
+    let alpha2 = alpha * alpha;
+    let e3 = (eval_a + beta * eval_s1 + gamma)
+        * (eval_b + beta * eval_s2 + gamma)
+        * (eval_c + gamma)
+        * eval_zw
+        * alpha;
+    let r0 = pi - lagrange[0] * alpha2 - e3;
+
+    // [D]: commitment to the linearisation polynomial
+    let d1 = q_m * (eval_a * eval_b) + q_l * eval_a + q_r * eval_b + q_o * eval_c + q_c;
+
+    let betaxi = beta * xi;
+    let d2a = (eval_a + betaxi + gamma)
+        * (eval_b + betaxi * Fr::from(PLONK_K1) + gamma)
+        * (eval_c + betaxi * Fr::from(PLONK_K2) + gamma)
+        * alpha;
+    let d2 = z * (d2a + lagrange[0] * alpha2 + u);
+
+    let d3 = s3 * ((eval_a + beta * eval_s1 + gamma) * (eval_b + beta * eval_s2 + gamma) * alpha * beta * eval_zw);
+    let d4 = (G1Projective::from(t1) + t2 * xin + t3 * (xin * xin)) * zh;
+
+    let d = d1 + d2 - d3 - d4;
+
+    // [F] and [E]: batched commitment and evaluation
+    let f = d + a * v[1] + b * v[2] + c * v[3] + s1 * v[4] + s2 * v[5];
+    let e = G1Affine::generator()
+        * (-r0 + v[1] * eval_a + v[2] * eval_b + v[3] * eval_c + v[4] * eval_s1 + v[5] * eval_s2 + u * eval_zw);
+
+    // Pairing check e(-A1, [x]_2) * e(B1, [1]_2) == 1
+    let a1 = G1Projective::from(wxi) + wxiw * u;
+    let b1 = wxi * xi + wxiw * (u * xi * omega) + f - e;
+
+    let pairing = Bn254::multi_pairing(
+        [(-a1).into_affine(), b1.into_affine()],
+        [x_2, G2Affine::generator()],
+    );
+    let proof_valid = pairing.0.is_one();
+
+    if proof_valid {
+        msg!("PLONK verification successful - inputs: {}", public_signals.len());
+    } else {
+        msg!("PLONK verification failed - pairing check did not hold");
+    }
+
+    Ok(proof_valid)
+}
+
+/// BN254 scalar field modulus r, little-endian
+pub const BN254_FR_MODULUS: [u8; 32] = [
+    0x01, 0x00, 0x00, 0xf0, 0x93, 0xf5, 0xe1, 0x43, 0x91, 0x70, 0xb9, 0x79, 0x48, 0xe8, 0x33, 0x28,
+    0x5d, 0x58, 0x81, 0x81, 0xb6, 0x45, 0x50, 0xb8, 0x29, 0xa0, 0x31, 0xe1, 0x72, 0x4e, 0x64, 0x30,
+];
+
+/// Reject little-endian values that are not canonical BN254 scalars (>= r)
+pub fn validate_bn254_field_element(bytes: &[u8; 32]) -> Result<()> {
+    require!(
+        bytes.iter().rev().lt(BN254_FR_MODULUS.iter().rev()),
+        ErrorCode::PublicSignalOutOfRange
+    );
+    Ok(())
+}
+
+fn fr_from_bytes(bytes: &[u8; 32]) -> Option<Fr> {
+    Fr::deserialize_uncompressed(&bytes[..]).ok()
+}
+
+fn fq_from_bytes(bytes: &[u8; 32]) -> Option<Fq> {
+    Fq::deserialize_uncompressed(&bytes[..]).ok()
+}
+
+impl G1Point {
+    fn to_affine(&self) -> Option<G1Affine> {
+        if self.x == [0u8; 32] && self.y == [0u8; 32] {
+            return Some(G1Affine::identity());
+        }
+
+        let point = G1Affine::new_unchecked(fq_from_bytes(&self.x)?, fq_from_bytes(&self.y)?);
+        if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+            return None;
+        }
+        Some(point)
+    }
+}
+
+impl G2Point {
+    fn to_affine(&self) -> Option<G2Affine> {
+        if self.x == [[0u8; 32]; 2] && self.y == [[0u8; 32]; 2] {
+            return Some(G2Affine::identity());
+        }
+
+        let x = Fq2::new(fq_from_bytes(&self.x[0])?, fq_from_bytes(&self.x[1])?);
+        let y = Fq2::new(fq_from_bytes(&self.y[0])?, fq_from_bytes(&self.y[1])?);
+        let point = G2Affine::new_unchecked(x, y);
+        if !point.is_on_curve() || !point.is_in_correct_subgroup_assuming_on_curve() {
+            return None;
+        }
+        Some(point)
+    }
+}
+
+impl VerificationKey {
+    fn to_ark(&self) -> Result<ArkVerifyingKey<Bn254>> {
+        let invalid = || error!(ErrorCode::InvalidVerificationKey);
+
+        let gamma_abc_g1 = self
+            .ic
+            .iter()
+            .map(|point| point.to_affine().ok_or_else(invalid))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(ArkVerifyingKey {
+            alpha_g1: self.alpha_g1.to_affine().ok_or_else(invalid)?,
+            beta_g2: self.beta_g2.to_affine().ok_or_else(invalid)?,
+            gamma_g2: self.gamma_g2.to_affine().ok_or_else(invalid)?,
+            delta_g2: self.delta_g2.to_affine().ok_or_else(invalid)?,
+            gamma_abc_g1,
+        })
+    }
+}
+
+impl Groth16Proof {
+    fn to_ark(&self) -> Result<ArkProof<Bn254>> {
+        let invalid = || error!(ErrorCode::InvalidProof);
+
+        Ok(ArkProof {
+            a: self.pi_a.to_affine().ok_or_else(invalid)?,
+            b: self.pi_b.to_affine().ok_or_else(invalid)?,
+            c: self.pi_c.to_affine().ok_or_else(invalid)?,
+        })
+    }
+}
+
 #[event]
 pub struct SpendVerificationEvent {
     pub nullifier_hash: [u8; 32],
     pub recipient: Pubkey,
     pub amount: u64,
+    pub relay_fee: u64,
     pub external_nullifier: [u8; 32],
+    pub scope: [u8; 32],
     pub merkle_root: [u8; 32],
 }
 
+#[event]
+pub struct SpendProofTelemetry {
+    pub circuit_name: String,
+    pub public_inputs_count: u8,
+    pub vk_ic_length: u8,
+    pub nullifier_set_size: u64,
+    pub proof_submitted_at: i64,
+}
+
+#[event]
+pub struct PauseProposed {
+    pub proposed_by: Pubkey,
+    pub proposed_at: i64,
+    pub executable_at: i64,
+}
+
+#[event]
+pub struct CircuitDigestUpdated {
+    pub old_digest: [u8; 32],
+    pub new_digest: [u8; 32],
+    pub updated_by: Pubkey,
+}
+
+#[event]
+pub struct MinSpendAmountUpdated {
+    pub old_min: u64,
+    pub new_min: u64,
+}
+
+#[event]
+pub struct VerificationKeyRotated {
+    pub old_vk_hash: [u8; 32],
+    pub new_vk_hash: [u8; 32],
+    pub effective_at: i64,
+}
+
+#[event]
+pub struct NullifierShardFull {
+    pub shard_index: u8,
+    pub capacity: u16,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Invalid number of public inputs")]
     InvalidPublicInputCount,
     #[msg("Invalid ZK proof")]
     InvalidProof,
+    #[msg("Verification key does not match its integrity hash")]
+    VerificationKeyTampered,
+    #[msg("Spend amount is below the minimum")]
+    AmountBelowMinimum,
+    #[msg("Public signal is not a valid BN254 field element")]
+    PublicSignalOutOfRange,
+    #[msg("Recipient token account is not owned by the proof's recipient")]
+    RecipientMismatch,
+    #[msg("Too many spends verified in this slot")]
+    RateLimitExceeded,
+    #[msg("Rate limit must allow at least one spend per slot")]
+    InvalidRateLimit,
     #[msg("Invalid Merkle root")]
     InvalidMerkleRoot,
     #[msg("Double spend attempt detected")]
@@ -543,8 +1793,690 @@ pub enum ErrorCode {
     Unauthorized,
     #[msg("Verifier is paused")]
     VerifierPaused,
+    #[msg("A pause is already proposed")]
+    PauseAlreadyProposed,
+    #[msg("No pause has been proposed")]
+    NoPauseProposed,
+    #[msg("Pause delay has not elapsed")]
+    PauseDelayNotElapsed,
+    #[msg("Pause delay is below the minimum")]
+    PauseDelayTooShort,
     #[msg("Invalid public signal format")]
     InvalidPublicSignal,
     #[msg("Invalid verification key")]
     InvalidVerificationKey,
+    #[msg("Nullifier routed to the wrong shard")]
+    InvalidNullifierShard,
+    #[msg("A nullifier shard is already registered for this index")]
+    NullifierShardExists,
+    #[msg("Too many proofs in batch (max 3)")]
+    TooManyProofs,
+    #[msg("Rotation delay is shorter than MIN_VK_ROTATION_DELAY_SECONDS")]
+    InvalidRotationDelay,
+    #[msg("A verification key rotation is already pending; cancel it first")]
+    RotationAlreadyPending,
+    #[msg("No pending verification key")]
+    NoPendingVerificationKey,
+    #[msg("Pending verification key is not yet effective")]
+    VerificationKeyNotYetEffective,
+    #[msg("Nullifier scope must be non-zero")]
+    InvalidScope,
+    #[msg("External nullifier does not match this program and scope")]
+    InvalidExternalNullifier,
+    #[msg("Relay fee must be less than the spend amount")]
+    RelayFeeExceedsAmount,
+    #[msg("Relay fee too high (max 10%)")]
+    RelayFeeTooHigh,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("Change commitment does not match the public signal")]
+    ChangeCommitmentMismatch,
+    #[msg("Merkle root is no longer in the pool's root history; regenerate the proof against a recent root")]
+    MerkleRootTooOld,
+    #[msg("Spend verification key was not embedded at build time")]
+    VKGenerationFailed,
+    #[msg("Proof circuit version is not compatible with the loaded verification key")]
+    IncompatibleCircuitVersion,
+    #[msg("Relayer does not match the one bound into the proof")]
+    RelayerMismatch,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_relations::{
+        lc,
+        r1cs::{ConstraintSynthesizer, ConstraintSystemRef, SynthesisError, Variable},
+    };
+    use ark_serialize::CanonicalSerialize;
+    use ark_std::test_rng;
+
+    /// Proves knowledge of `a`, `b` with `a * b = c`; every entry of `extra` is an
+    /// additional public input so the key matches the spend circuit's input count
+    #[derive(Clone)]
+    struct ProductCircuit {
+        a: Fr,
+        b: Fr,
+        extra: Vec<Fr>,
+    }
+
+    impl ConstraintSynthesizer<Fr> for ProductCircuit {
+        fn generate_constraints(self, cs: ConstraintSystemRef<Fr>) -> std::result::Result<(), SynthesisError> {
+            let a = cs.new_witness_variable(|| Ok(self.a))?;
+            let b = cs.new_witness_variable(|| Ok(self.b))?;
+            let c = cs.new_input_variable(|| Ok(self.a * self.b))?;
+            cs.enforce_constraint(lc!() + a, lc!() + b, lc!() + c)?;
+
+            for value in self.extra {
+                let input = cs.new_input_variable(|| Ok(value))?;
+                cs.enforce_constraint(lc!() + input, lc!() + Variable::One, lc!() + input)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn fq_bytes(value: &Fq) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        value.serialize_uncompressed(&mut bytes[..]).unwrap();
+        bytes
+    }
+
+    fn fr_bytes(value: &Fr) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        value.serialize_uncompressed(&mut bytes[..]).unwrap();
+        bytes
+    }
+
+    fn g1_point(point: &G1Affine) -> G1Point {
+        G1Point { x: fq_bytes(&point.x), y: fq_bytes(&point.y) }
+    }
+
+    fn g2_point(point: &G2Affine) -> G2Point {
+        G2Point {
+            x: [fq_bytes(&point.x.c0), fq_bytes(&point.x.c1)],
+            y: [fq_bytes(&point.y.c0), fq_bytes(&point.y.c1)],
+        }
+    }
+
+    fn circuit(a: u64, b: u64) -> ProductCircuit {
+        ProductCircuit {
+            a: Fr::from(a),
+            b: Fr::from(b),
+            extra: (0..SPEND_PUBLIC_INPUTS as u64 - 1).map(|i| Fr::from(100 + i)).collect(),
+        }
+    }
+
+    fn public_signals(circuit: &ProductCircuit) -> Vec<[u8; 32]> {
+        std::iter::once(circuit.a * circuit.b)
+            .chain(circuit.extra.iter().copied())
+            .map(|value| fr_bytes(&value))
+            .collect()
+    }
+
+    type ProofWithSignals = (Groth16Proof, Vec<[u8; 32]>);
+
+    /// Verification key for `ProductCircuit` plus one proof per `(a, b)` pair
+    fn setup(pairs: &[(u64, u64)]) -> (VerificationKey, Vec<ProofWithSignals>) {
+        let rng = &mut test_rng();
+        let pk = Groth16::<Bn254>::generate_random_parameters_with_reduction(circuit(1, 1), rng).unwrap();
+
+        let vk = VerificationKey {
+            alpha_g1: g1_point(&pk.vk.alpha_g1),
+            beta_g2: g2_point(&pk.vk.beta_g2),
+            gamma_g2: g2_point(&pk.vk.gamma_g2),
+            delta_g2: g2_point(&pk.vk.delta_g2),
+            ic: pk.vk.gamma_abc_g1.iter().map(g1_point).collect(),
+        };
+
+        let proofs = pairs
+            .iter()
+            .map(|&(a, b)| {
+                let circuit = circuit(a, b);
+                let signals = public_signals(&circuit);
+                let proof = Groth16::<Bn254>::create_random_proof_with_reduction(circuit, &pk, rng).unwrap();
+                let proof = Groth16Proof {
+                    pi_a: g1_point(&proof.a),
+                    pi_b: g2_point(&proof.b),
+                    pi_c: g1_point(&proof.c),
+                    change_commitment: [0u8; 32],
+                };
+                (proof, signals)
+            })
+            .collect();
+
+        (vk, proofs)
+    }
+
+    #[test]
+    fn groth16_verify_accepts_valid_proof() {
+        let (vk, proofs) = setup(&[(3, 7)]);
+        let (proof, signals) = &proofs[0];
+        assert_eq!(vk.ic.len(), SPEND_PUBLIC_INPUTS + 1);
+        assert!(groth16_verify(&vk, proof, signals).unwrap());
+    }
+
+    #[test]
+    fn groth16_verify_rejects_wrong_public_input() {
+        let (vk, proofs) = setup(&[(3, 7)]);
+        let (proof, signals) = &proofs[0];
+
+        let mut signals = signals.clone();
+        signals[0] = fr_bytes(&Fr::from(22u64));
+        assert!(!groth16_verify(&vk, proof, &signals).unwrap());
+    }
+
+    #[test]
+    fn groth16_verify_rejects_swapped_proof_points() {
+        let (vk, proofs) = setup(&[(3, 7)]);
+        let (proof, signals) = &proofs[0];
+
+        let mut tampered = proof.clone();
+        std::mem::swap(&mut tampered.pi_a, &mut tampered.pi_c);
+        assert!(!groth16_verify(&vk, &tampered, signals).unwrap());
+    }
+
+    #[test]
+    fn groth16_verify_rejects_proof_for_another_key() {
+        let (vk, _) = setup(&[]);
+        // test_rng is deterministic; skip the parameters `setup` derived
+        let rng = &mut test_rng();
+        Groth16::<Bn254>::generate_random_parameters_with_reduction(circuit(1, 1), rng).unwrap();
+        let other_pk = Groth16::<Bn254>::generate_random_parameters_with_reduction(circuit(1, 1), rng).unwrap();
+        let other_proof = Groth16::<Bn254>::create_random_proof_with_reduction(circuit(3, 7), &other_pk, rng).unwrap();
+
+        let proof = Groth16Proof {
+            pi_a: g1_point(&other_proof.a),
+            pi_b: g2_point(&other_proof.b),
+            pi_c: g1_point(&other_proof.c),
+            change_commitment: [0u8; 32],
+        };
+        assert!(!groth16_verify(&vk, &proof, &public_signals(&circuit(3, 7))).unwrap());
+    }
+
+    #[test]
+    fn groth16_verify_rejects_off_curve_point() {
+        let (vk, proofs) = setup(&[(3, 7)]);
+        let (proof, signals) = &proofs[0];
+
+        let mut malformed = proof.clone();
+        malformed.pi_a.y[0] ^= 1;
+        assert_eq!(
+            groth16_verify(&vk, &malformed, signals).unwrap_err(),
+            error!(ErrorCode::InvalidProof)
+        );
+
+        let mut malformed = proof.clone();
+        malformed.pi_b.x[1] = [0xff; 32];
+        assert_eq!(
+            groth16_verify(&vk, &malformed, signals).unwrap_err(),
+            error!(ErrorCode::InvalidProof)
+        );
+    }
+
+    #[test]
+    fn groth16_verify_rejects_wrong_input_count() {
+        let (vk, proofs) = setup(&[(3, 7)]);
+        let (proof, signals) = &proofs[0];
+        assert_eq!(
+            groth16_verify(&vk, proof, &signals[1..]).unwrap_err(),
+            error!(ErrorCode::InvalidPublicInputCount)
+        );
+    }
+
+    fn split(proofs: Vec<ProofWithSignals>) -> (Vec<Groth16Proof>, Vec<Vec<[u8; 32]>>) {
+        proofs.into_iter().unzip()
+    }
+
+    #[test]
+    fn batch_verify_agrees_with_single_verify() {
+        let (vk, proofs) = setup(&[(3, 7), (5, 11), (13, 17)]);
+        for (proof, signals) in &proofs {
+            assert!(groth16_verify(&vk, proof, signals).unwrap());
+        }
+
+        let (proofs, signals) = split(proofs);
+        for n in 1..=MAX_BATCH_PROOFS {
+            assert!(batch_groth16_verify(&vk, &proofs[..n], &signals[..n], b"seed").unwrap());
+        }
+    }
+
+    #[test]
+    fn batch_verify_rejects_batch_with_one_invalid_proof() {
+        let (vk, proofs) = setup(&[(3, 7), (5, 11), (13, 17)]);
+        let (proofs, mut signals) = split(proofs);
+        signals[1][0] = fr_bytes(&Fr::from(56u64));
+
+        assert!(!groth16_verify(&vk, &proofs[1], &signals[1]).unwrap());
+        assert!(!batch_groth16_verify(&vk, &proofs, &signals, b"seed").unwrap());
+    }
+
+    #[test]
+    fn batch_verify_rejects_proofs_that_cancel_out() {
+        // Swapping the C points of two proofs breaks each one individually; the
+        // random coefficients keep the errors from cancelling in the batch
+        let (vk, proofs) = setup(&[(3, 7), (5, 11)]);
+        let (mut proofs, signals) = split(proofs);
+        let pi_c = proofs[0].pi_c.clone();
+        proofs[0].pi_c = proofs[1].pi_c.clone();
+        proofs[1].pi_c = pi_c;
+
+        for (proof, signals) in proofs.iter().zip(&signals) {
+            assert!(!groth16_verify(&vk, proof, signals).unwrap());
+        }
+        assert!(!batch_groth16_verify(&vk, &proofs, &signals, b"seed").unwrap());
+    }
+
+    // Reference PLONK prover for the verifier tests. It follows the snarkjs
+    // prover without blinding and commits with a known setup secret `TAU`,
+    // so every commitment is `[p(TAU)]_1`.
+    mod plonk {
+        use super::*;
+        use ark_ff::FftField;
+
+        const POWER: u8 = 3;
+        const N: usize = 1 << POWER;
+        const TAU: u64 = 0x5eed_cafe;
+
+        type Poly = Vec<Fr>;
+
+        fn eval(p: &[Fr], x: Fr) -> Fr {
+            p.iter().rev().fold(Fr::zero(), |acc, c| acc * x + c)
+        }
+
+        fn add(p: &[Fr], q: &[Fr]) -> Poly {
+            let mut out = vec![Fr::zero(); p.len().max(q.len())];
+            for (i, c) in p.iter().enumerate() {
+                out[i] += c;
+            }
+            for (i, c) in q.iter().enumerate() {
+                out[i] += c;
+            }
+            out
+        }
+
+        fn scale(p: &[Fr], s: Fr) -> Poly {
+            p.iter().map(|c| *c * s).collect()
+        }
+
+        fn mul(p: &[Fr], q: &[Fr]) -> Poly {
+            let mut out = vec![Fr::zero(); p.len() + q.len() - 1];
+            for (i, a) in p.iter().enumerate() {
+                for (j, b) in q.iter().enumerate() {
+                    out[i + j] += *a * b;
+                }
+            }
+            out
+        }
+
+        // p(X) + c for linear factors like `A(X) + beta * X + gamma`
+        fn plus_linear(p: &[Fr], slope: Fr, c: Fr) -> Poly {
+            add(p, &[c, slope])
+        }
+
+        // p(omega * X)
+        fn shift(p: &[Fr], omega: Fr) -> Poly {
+            let mut w = Fr::one();
+            p.iter()
+                .map(|c| {
+                    let shifted = *c * w;
+                    w *= omega;
+                    shifted
+                })
+                .collect()
+        }
+
+        fn interpolate(values: &[Fr], omega: Fr) -> Poly {
+            let n_inv = Fr::from(N as u64).inverse().unwrap();
+            let omega_inv = omega.inverse().unwrap();
+            (0..N)
+                .map(|k| {
+                    let step = omega_inv.pow([k as u64]);
+                    let mut w = Fr::one();
+                    let mut sum = Fr::zero();
+                    for v in values {
+                        sum += *v * w;
+                        w *= step;
+                    }
+                    sum * n_inv
+                })
+                .collect()
+        }
+
+        // p / (X^N - 1), asserting the division is exact
+        fn div_vanishing(p: &[Fr]) -> Poly {
+            let mut rem = p.to_vec();
+            let mut quotient = vec![Fr::zero(); p.len().saturating_sub(N).max(1)];
+            for k in (N..rem.len()).rev() {
+                let c = rem[k];
+                quotient[k - N] += c;
+                rem[k - N] += c;
+                rem[k] = Fr::zero();
+            }
+            assert!(rem.iter().all(|c| c.is_zero()), "constraints do not hold on the domain");
+            quotient
+        }
+
+        // p / (X - z), asserting p(z) == 0
+        fn div_linear(p: &[Fr], z: Fr) -> Poly {
+            let mut quotient = vec![Fr::zero(); p.len() - 1];
+            let mut carry = Fr::zero();
+            for k in (0..p.len()).rev() {
+                let c = p[k] + carry * z;
+                if k == 0 {
+                    assert!(c.is_zero(), "opening does not match the evaluation");
+                } else {
+                    quotient[k - 1] = c;
+                }
+                carry = c;
+            }
+            quotient
+        }
+
+        fn commit(p: &[Fr]) -> G1Affine {
+            (G1Affine::generator() * eval(p, Fr::from(TAU))).into_affine()
+        }
+
+        pub struct Circuit {
+            omega: Fr,
+            q_m: Poly,
+            q_l: Poly,
+            q_r: Poly,
+            q_o: Poly,
+            q_c: Poly,
+            sigma: [Poly; 3],
+            l1: Poly,
+        }
+
+        /// Proves knowledge of `a`, `b` with `a * b = x` for public `x`.
+        ///
+        /// Row 0 is the public input gate `a0 - x = 0`, row 1 the product gate
+        /// `a1 * b1 - c1 = 0`, and the copy constraint ties `c1` to `a0`.
+        pub fn circuit() -> Circuit {
+            let omega = Fr::get_root_of_unity(N as u64).unwrap();
+            let selector = |row0: i64, row1: i64| {
+                let mut values = vec![Fr::zero(); N];
+                values[0] = Fr::from(row0);
+                values[1] = Fr::from(row1);
+                interpolate(&values, omega)
+            };
+
+            // Wire slot `w * N + i` has identity value k_w * omega^i
+            let k = [Fr::one(), Fr::from(PLONK_K1), Fr::from(PLONK_K2)];
+            let identity = |slot: usize| k[slot / N] * omega.pow([(slot % N) as u64]);
+            let permuted = |slot: usize| match slot {
+                0 => 2 * N + 1,
+                s if s == 2 * N + 1 => 0,
+                s => s,
+            };
+            let sigma = [0, 1, 2].map(|w| {
+                let values: Vec<Fr> = (0..N).map(|i| identity(permuted(w * N + i))).collect();
+                interpolate(&values, omega)
+            });
+
+            Circuit {
+                omega,
+                q_m: selector(0, 1),
+                q_l: selector(1, 0),
+                q_r: selector(0, 0),
+                q_o: selector(0, -1),
+                q_c: selector(0, 0),
+                sigma,
+                l1: selector(1, 0),
+            }
+        }
+
+        pub fn verification_key(circuit: &Circuit) -> PlonkVerificationKey {
+            PlonkVerificationKey {
+                q_m: g1_point(&commit(&circuit.q_m)),
+                q_l: g1_point(&commit(&circuit.q_l)),
+                q_r: g1_point(&commit(&circuit.q_r)),
+                q_o: g1_point(&commit(&circuit.q_o)),
+                q_c: g1_point(&commit(&circuit.q_c)),
+                sigma_1: g1_point(&commit(&circuit.sigma[0])),
+                sigma_2: g1_point(&commit(&circuit.sigma[1])),
+                sigma_3: g1_point(&commit(&circuit.sigma[2])),
+                x_2: g2_point(&(G2Affine::generator() * Fr::from(TAU)).into_affine()),
+                omega: fr_bytes(&circuit.omega),
+                power: POWER,
+                n_public: 1,
+            }
+        }
+
+        pub fn prove(circuit: &Circuit, vk: &PlonkVerificationKey, a: u64, b: u64) -> (PlonkProof, Vec<[u8; 32]>) {
+            let omega = circuit.omega;
+            let x = Fr::from(a) * Fr::from(b);
+            let wire = |row0: Fr, row1: Fr| {
+                let mut values = vec![Fr::zero(); N];
+                values[0] = row0;
+                values[1] = row1;
+                values
+            };
+            let wires = [wire(x, Fr::from(a)), wire(Fr::zero(), Fr::from(b)), wire(Fr::zero(), x)];
+            let [pa, pb, pc] = wires.clone().map(|values| interpolate(&values, omega));
+            let [s1, s2, s3] = circuit.sigma.clone();
+            let (k1, k2) = (Fr::from(PLONK_K1), Fr::from(PLONK_K2));
+
+            // Round 1: wire commitments
+            let (ca, cb, cc) = (commit(&pa), commit(&pb), commit(&pc));
+            let mut transcript = PlonkTranscript::new();
+            for point in [&vk.q_m, &vk.q_l, &vk.q_r, &vk.q_o, &vk.q_c, &vk.sigma_1, &vk.sigma_2, &vk.sigma_3] {
+                transcript.add_point(&point.to_affine().unwrap());
+            }
+            transcript.add_scalar(&x);
+            for point in [&ca, &cb, &cc] {
+                transcript.add_point(point);
+            }
+            let beta = transcript.challenge();
+            transcript.add_scalar(&beta);
+            let gamma = transcript.challenge();
+
+            // Round 2: permutation accumulator
+            let mut z_values = vec![Fr::one()];
+            for i in 0..N {
+                let w = omega.pow([i as u64]);
+                let numerator = (wires[0][i] + beta * w + gamma)
+                    * (wires[1][i] + beta * k1 * w + gamma)
+                    * (wires[2][i] + beta * k2 * w + gamma);
+                let denominator = (wires[0][i] + beta * eval(&s1, w) + gamma)
+                    * (wires[1][i] + beta * eval(&s2, w) + gamma)
+                    * (wires[2][i] + beta * eval(&s3, w) + gamma);
+                let last = *z_values.last().unwrap();
+                z_values.push(last * numerator * denominator.inverse().unwrap());
+            }
+            assert_eq!(z_values.pop(), Some(Fr::one()), "copy constraints do not hold");
+            let pz = interpolate(&z_values, omega);
+            let cz = commit(&pz);
+            transcript.add_scalar(&beta);
+            transcript.add_scalar(&gamma);
+            transcript.add_point(&cz);
+            let alpha = transcript.challenge();
+
+            // Round 3: quotient
+            let pi = scale(&circuit.l1, -x);
+            let gate = [
+                mul(&mul(&pa, &pb), &circuit.q_m),
+                mul(&pa, &circuit.q_l),
+                mul(&pb, &circuit.q_r),
+                mul(&pc, &circuit.q_o),
+                pi.clone(),
+                circuit.q_c.clone(),
+            ]
+            .iter()
+            .fold(vec![], |acc, p| add(&acc, p));
+            let identity_product = mul(
+                &mul(&mul(&plus_linear(&pa, beta, gamma), &plus_linear(&pb, beta * k1, gamma)), &plus_linear(&pc, beta * k2, gamma)),
+                &pz,
+            );
+            let sigma_term = |w: &[Fr], s: &[Fr]| add(w, &add(&scale(s, beta), &[gamma]));
+            let sigma_product = mul(
+                &mul(&mul(&sigma_term(&pa, &s1), &sigma_term(&pb, &s2)), &sigma_term(&pc, &s3)),
+                &shift(&pz, omega),
+            );
+            let permutation = scale(&add(&identity_product, &scale(&sigma_product, -Fr::one())), alpha);
+            let first_row = scale(&mul(&add(&pz, &[-Fr::one()]), &circuit.l1), alpha * alpha);
+            let mut t = div_vanishing(&add(&add(&gate, &permutation), &first_row));
+            t.resize(3 * N, Fr::zero());
+            let (t1, t2, t3) = (t[..N].to_vec(), t[N..2 * N].to_vec(), t[2 * N..].to_vec());
+            let (ct1, ct2, ct3) = (commit(&t1), commit(&t2), commit(&t3));
+            transcript.add_scalar(&alpha);
+            for point in [&ct1, &ct2, &ct3] {
+                transcript.add_point(point);
+            }
+            let xi = transcript.challenge();
+
+            // Round 4: evaluations
+            let (ea, eb, ec) = (eval(&pa, xi), eval(&pb, xi), eval(&pc, xi));
+            let (es1, es2) = (eval(&s1, xi), eval(&s2, xi));
+            let ezw = eval(&pz, xi * omega);
+            let evaluations = [ea, eb, ec, es1, es2, ezw];
+            transcript.add_scalar(&xi);
+            for evaluation in &evaluations {
+                transcript.add_scalar(evaluation);
+            }
+            let v1 = transcript.challenge();
+            let v: Vec<Fr> = (1..6u64).map(|i| v1.pow([i])).collect();
+
+            // Round 5: linearisation and openings
+            let xin = xi.pow([N as u64]);
+            let zh = xin - Fr::one();
+            let l1_xi = eval(&circuit.l1, xi);
+            let r0 = eval(&pi, xi)
+                - l1_xi * alpha * alpha
+                - alpha * (ea + beta * es1 + gamma) * (eb + beta * es2 + gamma) * (ec + gamma) * ezw;
+            let r = [
+                scale(&circuit.q_m, ea * eb),
+                scale(&circuit.q_l, ea),
+                scale(&circuit.q_r, eb),
+                scale(&circuit.q_o, ec),
+                circuit.q_c.clone(),
+                scale(
+                    &pz,
+                    alpha * (ea + beta * xi + gamma) * (eb + beta * k1 * xi + gamma) * (ec + beta * k2 * xi + gamma)
+                        + l1_xi * alpha * alpha,
+                ),
+                scale(&s3, -alpha * beta * (ea + beta * es1 + gamma) * (eb + beta * es2 + gamma) * ezw),
+                scale(&add(&add(&t1, &scale(&t2, xin)), &scale(&t3, xin * xin)), -zh),
+                vec![r0],
+            ]
+            .iter()
+            .fold(vec![], |acc, p| add(&acc, p));
+            assert!(eval(&r, xi).is_zero(), "linearisation does not vanish at xi");
+
+            let batched = [(&pa, ea), (&pb, eb), (&pc, ec), (&s1, es1), (&s2, es2)]
+                .iter()
+                .zip(&v)
+                .fold(r, |acc, ((p, e), vi)| add(&acc, &scale(&add(p, &[-*e]), *vi)));
+            let wxi = commit(&div_linear(&batched, xi));
+            let wxiw = commit(&div_linear(&add(&pz, &[-ezw]), xi * omega));
+
+            let proof = PlonkProof {
+                a: g1_point(&ca),
+                b: g1_point(&cb),
+                c: g1_point(&cc),
+                z: g1_point(&cz),
+                t1: g1_point(&ct1),
+                t2: g1_point(&ct2),
+                t3: g1_point(&ct3),
+                wxi: g1_point(&wxi),
+                wxiw: g1_point(&wxiw),
+                evaluations: evaluations.iter().map(fr_bytes).collect(),
+            };
+            (proof, vec![fr_bytes(&x)])
+        }
+    }
+
+    #[test]
+    fn plonk_verify_accepts_valid_proof() {
+        let circuit = plonk::circuit();
+        let vk = plonk::verification_key(&circuit);
+        let (proof, signals) = plonk::prove(&circuit, &vk, 3, 7);
+        assert!(plonk_verify(&vk, &proof, &signals).unwrap());
+    }
+
+    #[test]
+    fn plonk_verify_rejects_tampered_proof() {
+        let circuit = plonk::circuit();
+        let vk = plonk::verification_key(&circuit);
+        let (proof, signals) = plonk::prove(&circuit, &vk, 3, 7);
+
+        let mut swapped = proof.clone();
+        swapped.t1 = proof.t2.clone();
+        swapped.t2 = proof.t1.clone();
+        assert!(!plonk_verify(&vk, &swapped, &signals).unwrap());
+
+        let mut tampered = proof.clone();
+        tampered.evaluations[0] = fr_bytes(&(fr_from_bytes(&proof.evaluations[0]).unwrap() + Fr::one()));
+        assert!(!plonk_verify(&vk, &tampered, &signals).unwrap());
+    }
+
+    #[test]
+    fn plonk_verify_rejects_wrong_public_input() {
+        let circuit = plonk::circuit();
+        let vk = plonk::verification_key(&circuit);
+        let (proof, _) = plonk::prove(&circuit, &vk, 3, 7);
+        assert!(!plonk_verify(&vk, &proof, &[fr_bytes(&Fr::from(22u64))]).unwrap());
+    }
+
+    #[test]
+    fn plonk_verify_rejects_proof_for_another_key() {
+        let circuit = plonk::circuit();
+        let vk = plonk::verification_key(&circuit);
+        let (proof, signals) = plonk::prove(&circuit, &vk, 3, 7);
+
+        // Same circuit with the product gate's output selector dropped
+        let mut other = vk.clone();
+        other.q_o = vk.q_c.clone();
+        assert!(!plonk_verify(&other, &proof, &signals).unwrap());
+    }
+
+    #[test]
+    fn batch_verify_rejects_non_canonical_signal() {
+        let (vk, proofs) = setup(&[(3, 7)]);
+        let (proofs, mut signals) = split(proofs);
+        signals[0][1] = BN254_FR_MODULUS;
+
+        assert_eq!(
+            batch_groth16_verify(&vk, &proofs, &signals, b"seed").unwrap_err(),
+            error!(ErrorCode::PublicSignalOutOfRange)
+        );
+    }
+
+    #[test]
+    fn spent_nullifier_is_rejected_for_good() {
+        let mut shard: NullifierShard = bytemuck::Zeroable::zeroed();
+        let nullifier = [9u8; 32];
+        let spent_at = 1_000_000;
+
+        shard.insert(nullifier, spent_at).unwrap();
+        assert!(shard.contains(&nullifier));
+
+        // No amount of time frees a nullifier for a second spend
+        for later in [spent_at + 1, spent_at + 365 * 24 * 60 * 60, i64::MAX] {
+            assert!(shard.contains(&nullifier));
+            assert_eq!(shard.insert(nullifier, later).unwrap_err(), error!(ErrorCode::DoubleSpend));
+        }
+        assert_eq!(shard.len, 1);
+    }
+
+    #[test]
+    fn embedded_circuit_versions_parse() {
+        assert_eq!(zk_meta_registry::parse_circuit_version(SPEND_VK_VERSION).unwrap(), (2, 0));
+        assert_eq!(zk_meta_registry::parse_circuit_version(SPEND_PROOF_VERSION).unwrap(), (2, 0));
+    }
+
+    #[test]
+    fn shard_router_tracks_fill_after_insert() {
+        let mut router: NullifierShardRouter = bytemuck::Zeroable::zeroed();
+        let mut shard: NullifierShard = bytemuck::Zeroable::zeroed();
+        shard.shard_index = 4;
+        assert_eq!(router.remaining_capacity(4), MAX_NULLIFIERS_PER_SHARD as u16);
+
+        let mut later = [4u8; 32];
+        later[1] = 1;
+        shard.insert([4u8; 32], 0).unwrap();
+        shard.insert(later, 10).unwrap();
+        router.record_len(&shard);
+        assert_eq!(router.remaining_capacity(4), MAX_NULLIFIERS_PER_SHARD as u16 - 2);
+        assert_eq!(router.remaining_capacity(5), MAX_NULLIFIERS_PER_SHARD as u16);
+    }
 }
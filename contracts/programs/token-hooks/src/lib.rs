@@ -1,6 +1,18 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, Transfer};
-use access_controller;
+use anchor_lang::system_program;
+use access_controller::ACCESS_GRANT_AUTHORITY_SEED;
+use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use sha2::{Digest, Sha256};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_hook::{TransferHook, TransferHookAccount}, BaseStateWithExtensions, StateWithExtensions},
+};
+use anchor_spl::token_2022_extensions::spl_token_metadata_interface::state::TokenMetadata;
+use anchor_spl::token_interface::Mint;
+use solana_instructions_sysvar as instructions_sysvar;
+use spl_discriminator::SplDiscriminate;
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, state::ExtraAccountMetaList};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
 
 declare_id!("6s5H6xDDWymGRtGN4Vpr5AqyvfRZ4cMhrZq5yJkQQrYU");
 
@@ -14,6 +26,8 @@ pub mod token_hooks {
         hooks.authority = ctx.accounts.authority.key();
         hooks.total_hooks = 0;
         hooks.total_triggers = 0;
+        hooks.proof_authority = hooks.authority;
+        hooks.max_batch_size = DEFAULT_MAX_BATCH_SIZE;
 
         msg!("Token Hooks initialized with authority: {}", hooks.authority);
         Ok(())
@@ -25,11 +39,16 @@ pub mod token_hooks {
         trigger_amount: u64,
         content_hash: [u8; 32],
         unlock_duration: Option<i64>,
+        accumulation_hook: bool,
+        expires_at: Option<i64>,
     ) -> Result<()> {
         require!(trigger_amount > 0, ErrorCode::InvalidTriggerAmount);
+        let now = Clock::get()?.unix_timestamp;
+        require!(expires_at.is_none_or(|e| e > now), ErrorCode::InvalidExpiry);
 
         let hook = &mut ctx.accounts.payment_hook;
         hook.creator = ctx.accounts.creator.key();
+        hook.payment_mint = ctx.accounts.payment_mint.key();
         hook.content_hash = content_hash;
         hook.trigger_amount = trigger_amount;
         hook.unlock_duration = unlock_duration;
@@ -37,6 +56,9 @@ pub mod token_hooks {
         hook.trigger_count = 0;
         hook.is_active = true;
         hook.hook_id = ctx.accounts.hooks.total_hooks;
+        hook.next_hook_id = None;
+        hook.accumulation_hook = accumulation_hook;
+        hook.expires_at = expires_at;
 
         let hooks = &mut ctx.accounts.hooks;
         hooks.total_hooks += 1;
@@ -53,24 +75,191 @@ pub mod token_hooks {
         Ok(())
     }
 
-    /// Process payment and automatically trigger content unlock
-    pub fn process_payment_trigger(
-        ctx: Context<ProcessPaymentTrigger>,
+    /// Register a subscription hook that charges `amount_per_interval` once per interval
+    pub fn register_recurring_hook(
+        ctx: Context<RegisterRecurringHook>,
+        interval_seconds: i64,
+        max_payments: Option<u32>,
+        content_hash: [u8; 32],
+        amount_per_interval: u64,
+    ) -> Result<()> {
+        require!(amount_per_interval > 0, ErrorCode::InvalidTriggerAmount);
+        require!(interval_seconds > 0, ErrorCode::InvalidInterval);
+        require!(max_payments != Some(0), ErrorCode::MaxPaymentsReached);
+
+        let hook = &mut ctx.accounts.recurring_hook;
+        hook.hook_id = ctx.accounts.hooks.total_hooks;
+        hook.creator = ctx.accounts.creator.key();
+        hook.mint = ctx.accounts.payment_mint.key();
+        hook.content_hash = content_hash;
+        hook.amount_per_interval = amount_per_interval;
+        hook.interval_seconds = interval_seconds;
+        hook.max_payments = max_payments;
+        hook.created_at = Clock::get()?.unix_timestamp;
+        hook.is_active = true;
+        hook.bump = ctx.bumps.recurring_hook;
+
+        let hooks = &mut ctx.accounts.hooks;
+        hooks.total_hooks += 1;
+
+        emit!(RecurringHookRegistered {
+            hook_id: hook.hook_id,
+            creator: hook.creator,
+            content_hash,
+            amount_per_interval,
+            interval_seconds,
+            max_payments,
+        });
+
+        msg!("Recurring hook registered: ID={}, Amount={}, Interval={}s", hook.hook_id, amount_per_interval, interval_seconds);
+        Ok(())
+    }
+
+    /// Charge the buyer for the next interval of a recurring hook
+    pub fn process_recurring_payment(ctx: Context<ProcessRecurringPayment>) -> Result<()> {
+        let hook = &ctx.accounts.recurring_hook;
+        require!(hook.is_active, ErrorCode::HookInactive);
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let state = &mut ctx.accounts.recurring_state;
+        if state.buyer == Pubkey::default() {
+            // First payment for this buyer
+            state.hook = hook.key();
+            state.buyer = ctx.accounts.buyer.key();
+            state.is_active = true;
+            state.bump = ctx.bumps.recurring_state;
+        }
+
+        let max_payments = hook.max_payments.unwrap_or(u32::MAX);
+        require!(
+            state.is_active && state.payment_count < max_payments,
+            ErrorCode::MaxPaymentsReached
+        );
+        require!(
+            state.payment_count == 0
+                || current_time >= state.last_triggered_at.saturating_add(hook.interval_seconds),
+            ErrorCode::IntervalNotElapsed
+        );
+
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.buyer_token_account.to_account_info(),
+                to: ctx.accounts.creator_token_account.to_account_info(),
+                authority: ctx.accounts.buyer.to_account_info(),
+            },
+        );
+        token::transfer(cpi_ctx, hook.amount_per_interval)?;
+
+        state.last_triggered_at = current_time;
+        state.payment_count += 1;
+
+        let hooks = &mut ctx.accounts.hooks;
+        hooks.total_triggers = hooks.total_triggers
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(RecurringPaymentProcessed {
+            hook_id: hook.hook_id,
+            buyer: state.buyer,
+            amount: hook.amount_per_interval,
+            payment_count: state.payment_count,
+            next_payment_at: current_time.saturating_add(hook.interval_seconds),
+        });
+
+        // Cancel the subscription once the final payment has been taken
+        if state.payment_count >= max_payments {
+            state.is_active = false;
+
+            emit!(RecurringHookCancelled {
+                hook_id: hook.hook_id,
+                buyer: state.buyer,
+                payment_count: state.payment_count,
+            });
+        }
+
+        msg!("Recurring payment processed: ID={}, Buyer={}, Count={}", hook.hook_id, state.buyer, state.payment_count);
+        Ok(())
+    }
+
+    /// Stop a buyer's subscription to a recurring hook and close its state (buyer only)
+    pub fn cancel_recurring_hook(ctx: Context<CancelRecurringHook>) -> Result<()> {
+        let state = &ctx.accounts.recurring_state;
+
+        emit!(RecurringHookCancelled {
+            hook_id: ctx.accounts.recurring_hook.hook_id,
+            buyer: state.buyer,
+            payment_count: state.payment_count,
+        });
+
+        msg!("Recurring hook cancelled: ID={}, Buyer={}", ctx.accounts.recurring_hook.hook_id, state.buyer);
+        Ok(())
+    }
+
+    /// Process payment and automatically trigger content unlock.
+    ///
+    /// When the hook chains to further hooks, remaining accounts hold a
+    /// `(PaymentHook, access permission, listing, purchase record)` group for
+    /// each hook in the chain; every chained hook must share the first hook's creator.
+    pub fn process_payment_trigger<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProcessPaymentTrigger<'info>>,
         payment_amount: u64,
         payment_proof: PaymentProof,
     ) -> Result<()> {
+        // The used_nonce account was just created, so this proof has not been seen before
+        let used_nonce = &mut ctx.accounts.used_nonce;
+        used_nonce.nonce = payment_proof.nonce;
+        used_nonce.payer = ctx.accounts.buyer.key();
+        used_nonce.used_at = Clock::get()?.unix_timestamp;
+        used_nonce.proof_timestamp = payment_proof.timestamp;
+        used_nonce.bump = ctx.bumps.used_nonce;
+
         let hook = &ctx.accounts.payment_hook;
         require!(hook.is_active, ErrorCode::HookInactive);
-        require!(payment_amount >= hook.trigger_amount, ErrorCode::InsufficientPayment);
+        require!(
+            !creator_hooks_paused(&ctx.accounts.creator_pause)?,
+            ErrorCode::CreatorHooksPaused
+        );
+
+        // Fail the trigger so the buyer's payment does not go through without
+        // access; the event still shows up in the failed transaction's logs
+        if is_expired(hook) {
+            emit!(HookExpiredEvent {
+                hook_id: hook.hook_id,
+                creator: hook.creator,
+                expired_at: hook.expires_at.unwrap_or_default(),
+            });
+
+            msg!("Payment hook expired: ID={}", hook.hook_id);
+            return err!(ErrorCode::HookExpired);
+        }
+
+        if !hook.accumulation_hook {
+            require!(payment_amount >= hook.trigger_amount, ErrorCode::InsufficientPayment);
+        }
 
         // Verify payment proof (enhanced with cryptographic verification)
         require!(
-            verify_payment_proof(&payment_proof, payment_amount, &hook.content_hash)?,
+            verify_payment_proof(
+                &payment_proof,
+                payment_amount,
+                &hook.content_hash,
+                &ctx.accounts.buyer.key(),
+                &ctx.accounts.hooks.proof_authority,
+                &ctx.accounts.instructions_sysvar,
+            )?,
             ErrorCode::InvalidPaymentProof
         );
 
-        // Execute actual token transfer if required
-        if payment_amount > 0 && ctx.accounts.payer_token_account.is_some() {
+        let accumulation_hook = hook.accumulation_hook;
+        if accumulation_hook {
+            // Hold contributions in escrow until the threshold is reached
+            let bump = ctx.bumps.accumulated_payment.ok_or(ErrorCode::MissingAccumulationAccount)?;
+            if !accumulate_payment(ctx.accounts, payment_amount, bump)? {
+                return Ok(());
+            }
+        } else if payment_amount > 0 && ctx.accounts.payer_token_account.is_some() {
+            // Execute actual token transfer if required
             let cpi_ctx = CpiContext::new(
                 ctx.accounts.token_program.to_account_info(),
                 Transfer {
@@ -84,30 +273,21 @@ pub mod token_hooks {
         }
 
         // Trigger access grant via CPI to access controller
-        let cpi_ctx = CpiContext::new(
-            ctx.accounts.access_controller_program.to_account_info(),
-            access_controller::cpi::accounts::GrantAccess {
-                controller: ctx.accounts.access_controller.to_account_info(),
-                access_permission: ctx.accounts.access_permission.to_account_info(),
-                purchase_record: ctx.accounts.purchase_record.to_account_info(),
-                caller_program: ctx.accounts.token_hooks_program.to_account_info(),
-                buyer: ctx.accounts.buyer.to_account_info(),
-                system_program: ctx.accounts.system_program.to_account_info(),
-            },
-        );
-
-        access_controller::cpi::grant_access(
-            cpi_ctx,
-            hook.content_hash,
-            hook.unlock_duration,
+        let hook = &ctx.accounts.payment_hook;
+        grant_hook_access(
+            ctx.accounts,
+            ctx.accounts.access_permission.to_account_info(),
+            ctx.accounts.listing.to_account_info(),
+            ctx.accounts.purchase_record.to_account_info(),
+            hook,
         )?;
 
         // Update hook statistics
         let hook = &mut ctx.accounts.payment_hook;
-        hook.trigger_count += 1;
+        hook.trigger_count = hook.trigger_count.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
 
         let hooks = &mut ctx.accounts.hooks;
-        hooks.total_triggers += 1;
+        hooks.total_triggers = hooks.total_triggers.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
 
         emit!(PaymentTriggered {
             hook_id: hook.hook_id,
@@ -118,39 +298,138 @@ pub mod token_hooks {
         });
 
         msg!("Payment hook triggered: ID={}, Buyer={}", hook.hook_id, ctx.accounts.buyer.key());
+
+        // Follow the hook chain, granting each hook's content in turn
+        let first_hook_id = ctx.accounts.payment_hook.hook_id;
+        let creator = ctx.accounts.payment_hook.creator;
+        let mut last_hook_id = first_hook_id;
+        let mut next_hook_id = ctx.accounts.payment_hook.next_hook_id;
+        let mut chain_length: u8 = 1;
+        let mut chain_accounts = ctx.remaining_accounts.iter();
+
+        while let Some(expected_hook_id) = next_hook_id {
+            require!(chain_length <= MAX_CHAIN_DEPTH, ErrorCode::MaxChainDepthExceeded);
+
+            let hook_info = chain_accounts.next().ok_or(ErrorCode::InvalidChainedHook)?;
+            let access_permission = chain_accounts.next().ok_or(ErrorCode::InvalidChainedHook)?;
+            let listing = chain_accounts.next().ok_or(ErrorCode::InvalidChainedHook)?;
+            let purchase_record = chain_accounts.next().ok_or(ErrorCode::InvalidChainedHook)?;
+
+            let mut next_hook: Account<PaymentHook> = Account::try_from(hook_info)?;
+            require!(next_hook.hook_id == expected_hook_id, ErrorCode::InvalidChainedHook);
+            // Only the creator's own hooks can be chained, so creator_pause is theirs too
+            require!(next_hook.creator == creator, ErrorCode::InvalidChainedHook);
+            require!(
+                !creator_hooks_paused(&ctx.accounts.creator_pause)?,
+                ErrorCode::CreatorHooksPaused
+            );
+            require!(next_hook.is_active, ErrorCode::HookInactive);
+            require!(!is_expired(&next_hook), ErrorCode::HookExpired);
+            require!(payment_amount >= next_hook.trigger_amount, ErrorCode::InsufficientPayment);
+
+            // The access controller ties the listing to the hook's content and
+            // the purchase record to the buyer
+            grant_hook_access(
+                ctx.accounts,
+                access_permission.clone(),
+                listing.clone(),
+                purchase_record.clone(),
+                &next_hook,
+            )?;
+
+            next_hook.trigger_count = next_hook.trigger_count
+                .checked_add(1)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+            next_hook.exit(&crate::ID)?;
+
+            last_hook_id = next_hook.hook_id;
+            next_hook_id = next_hook.next_hook_id;
+            chain_length += 1;
+        }
+
+        if chain_length > 1 {
+            emit!(HookChainCompleted {
+                first_hook_id,
+                last_hook_id,
+                chain_length,
+                buyer: ctx.accounts.buyer.key(),
+            });
+        }
+
         Ok(())
     }
 
     /// Batch process multiple payment triggers
+    ///
+    /// Remaining accounts hold a `(PaymentHook, access permission, UsedNonce PDA)`
+    /// group per trigger. Each accepted proof consumes its nonce like
+    /// `process_payment_trigger`; a proof whose nonce is already used is reported as false.
     pub fn batch_process_triggers<'info>(
         ctx: Context<'_, '_, 'info, 'info, BatchProcessTriggers<'info>>,
         triggers: Vec<TriggerRequest>,
     ) -> Result<Vec<bool>> {
-        require!(triggers.len() <= 5, ErrorCode::TooManyTriggers);
+        require!(
+            triggers.len() <= ctx.accounts.hooks.max_batch_size as usize,
+            ErrorCode::TooManyTriggers
+        );
+        require!(
+            ctx.remaining_accounts.len() == triggers.len() * BATCH_ACCOUNTS_PER_TRIGGER,
+            ErrorCode::BatchAccountsMismatch
+        );
 
         let mut results = Vec::with_capacity(triggers.len());
         let current_time = Clock::get()?.unix_timestamp;
 
-        for (i, trigger) in triggers.iter().enumerate() {
-            let hook_account = &ctx.remaining_accounts[i * 2]; // Hook account
-            let _access_account = &ctx.remaining_accounts[i * 2 + 1]; // Access permission account
+        for (trigger, accounts) in triggers
+            .iter()
+            .zip(ctx.remaining_accounts.chunks_exact(BATCH_ACCOUNTS_PER_TRIGGER))
+        {
+            let [hook_account, _access_account, used_nonce] = accounts else {
+                return err!(ErrorCode::BatchAccountsMismatch);
+            };
 
             let hook: Account<PaymentHook> = Account::try_from(hook_account)?;
+            let nonce_seeds: &[&[u8]] = &[b"used_nonce", &trigger.payment_proof.nonce];
+            let (nonce_address, nonce_bump) = Pubkey::find_program_address(nonce_seeds, &crate::ID);
+            require_keys_eq!(used_nonce.key(), nonce_address, ErrorCode::BatchAccountsMismatch);
 
-            let success = hook.is_active &&
+            let success = used_nonce.owner != &crate::ID &&
+                hook.is_active &&
+                !is_expired(&hook) &&
                 trigger.payment_amount >= hook.trigger_amount &&
-                verify_payment_proof(&trigger.payment_proof, trigger.payment_amount, &hook.content_hash)?;
+                verify_payment_proof(
+                    &trigger.payment_proof,
+                    trigger.payment_amount,
+                    &hook.content_hash,
+                    &ctx.accounts.buyer.key(),
+                    &ctx.accounts.hooks.proof_authority,
+                    &ctx.accounts.instructions_sysvar,
+                )?;
 
             if success {
-                // Would trigger access grant here
-                // Simplified for batch processing
+                // Recorded before the next entry, so a proof repeated within the batch is rejected too
+                consume_nonce(
+                    used_nonce,
+                    &[nonce_seeds, &[&[nonce_bump]]].concat(),
+                    UsedNonce {
+                        nonce: trigger.payment_proof.nonce,
+                        payer: ctx.accounts.buyer.key(),
+                        used_at: current_time,
+                        proof_timestamp: trigger.payment_proof.timestamp,
+                        bump: nonce_bump,
+                    },
+                    &ctx.accounts.buyer.to_account_info(),
+                    &ctx.accounts.system_program.to_account_info(),
+                )?;
             }
 
             results.push(success);
         }
 
         let hooks = &mut ctx.accounts.hooks;
-        hooks.total_triggers += results.iter().filter(|&&x| x).count() as u64;
+        hooks.total_triggers = hooks.total_triggers
+            .checked_add(results.iter().filter(|&&x| x).count() as u64)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
 
         emit!(BatchTriggersProcessed {
             buyer: ctx.accounts.buyer.key(),
@@ -162,12 +441,62 @@ pub mod token_hooks {
         Ok(results)
     }
 
+    /// Refund an unfinished accumulation to the buyer and close it
+    pub fn cancel_accumulation(ctx: Context<CancelAccumulation>) -> Result<()> {
+        let accumulated = &ctx.accounts.accumulated_payment;
+        let refund = accumulated.total_accumulated;
+
+        let hook_key = accumulated.hook;
+        let buyer_key = accumulated.buyer;
+        let seeds = &[
+            b"accumulated".as_ref(),
+            hook_key.as_ref(),
+            buyer_key.as_ref(),
+            &[accumulated.bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        if refund > 0 {
+            let cpi_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.accumulation_escrow.to_account_info(),
+                    to: ctx.accounts.buyer_token_account.to_account_info(),
+                    authority: ctx.accounts.accumulated_payment.to_account_info(),
+                },
+                signer,
+            );
+            token::transfer(cpi_ctx, refund)?;
+        }
+        // The buyer funded the escrow token account when the accumulation started
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::CloseAccount {
+                account: ctx.accounts.accumulation_escrow.to_account_info(),
+                destination: ctx.accounts.buyer.to_account_info(),
+                authority: ctx.accounts.accumulated_payment.to_account_info(),
+            },
+            signer,
+        ))?;
+
+        emit!(AccumulationCancelled {
+            hook: hook_key,
+            buyer: buyer_key,
+            refunded_amount: refund,
+        });
+
+        msg!("Accumulation cancelled: Buyer={}, Refund={}", buyer_key, refund);
+        Ok(())
+    }
+
     /// Update payment hook settings
     pub fn update_payment_hook(
         ctx: Context<UpdatePaymentHook>,
         new_trigger_amount: Option<u64>,
         new_unlock_duration: Option<Option<i64>>,
         is_active: Option<bool>,
+        new_next_hook_id: Option<Option<u64>>,
+        new_expires_at: Option<Option<i64>>,
     ) -> Result<()> {
         let hook = &mut ctx.accounts.payment_hook;
         require!(
@@ -188,6 +517,17 @@ pub mod token_hooks {
             hook.is_active = active;
         }
 
+        if let Some(next_hook_id) = new_next_hook_id {
+            require!(next_hook_id != Some(hook.hook_id), ErrorCode::InvalidChainedHook);
+            hook.next_hook_id = next_hook_id;
+        }
+
+        if let Some(expires_at) = new_expires_at {
+            let now = Clock::get()?.unix_timestamp;
+            require!(expires_at.is_none_or(|e| e > now), ErrorCode::InvalidExpiry);
+            hook.expires_at = expires_at;
+        }
+
         emit!(PaymentHookUpdated {
             hook_id: hook.hook_id,
             creator: hook.creator,
@@ -197,6 +537,171 @@ pub mod token_hooks {
         Ok(())
     }
 
+    /// Close a used proof nonce once its proof can no longer pass the validity window
+    ///
+    /// Expiry is measured from the proof's own timestamp, the same clock
+    /// verify_payment_proof checks, so a pruned nonce's proof is always stale.
+    pub fn prune_used_nonce(ctx: Context<PruneUsedNonce>) -> Result<()> {
+        let used_nonce = &ctx.accounts.used_nonce;
+        require!(
+            Clock::get()?.unix_timestamp >= used_nonce.proof_timestamp.saturating_add(PROOF_VALIDITY_SECONDS),
+            ErrorCode::NonceStillValid
+        );
+
+        msg!("Used nonce pruned: {:?}", used_nonce.nonce);
+        Ok(())
+    }
+
+    /// Pause every hook owned by the calling creator
+    pub fn pause_creator_hooks(ctx: Context<PauseCreatorHooks>) -> Result<()> {
+        let record = &mut ctx.accounts.creator_pause;
+        record.creator = ctx.accounts.creator.key();
+        record.is_paused = true;
+        record.updated_at = Clock::get()?.unix_timestamp;
+        record.bump = ctx.bumps.creator_pause;
+
+        emit!(CreatorHooksPaused {
+            creator: record.creator,
+            paused_at: record.updated_at,
+        });
+
+        msg!("Hooks paused for creator: {}", record.creator);
+        Ok(())
+    }
+
+    /// Resume the calling creator's hooks after a pause
+    pub fn resume_creator_hooks(ctx: Context<ResumeCreatorHooks>) -> Result<()> {
+        let record = &mut ctx.accounts.creator_pause;
+        record.is_paused = false;
+        record.updated_at = Clock::get()?.unix_timestamp;
+
+        emit!(CreatorHooksResumed {
+            creator: record.creator,
+            resumed_at: record.updated_at,
+        });
+
+        msg!("Hooks resumed for creator: {}", record.creator);
+        Ok(())
+    }
+
+    /// Bind a Token-2022 mint to a payment hook by writing its extra account meta list
+    ///
+    /// The mint must name this program as its transfer hook and carry token
+    /// metadata whose URI hashes to the hook's content hash.
+    pub fn initialize_extra_account_meta_list(ctx: Context<InitializeExtraAccountMetaList>) -> Result<()> {
+        let mint_info = ctx.accounts.mint.to_account_info();
+        require!(
+            mint_transfer_hook_program(&mint_info)? == Some(crate::ID),
+            ErrorCode::TransferHookNotConfigured
+        );
+        require!(
+            mint_content_hash(&mint_info)? == ctx.accounts.payment_hook.content_hash,
+            ErrorCode::ContentHashMismatch
+        );
+
+        // Resolved by Token-2022 after the interface's fixed accounts:
+        // source, mint, destination, owner, extra_account_meta_list
+        let extra_account_metas = [
+            ExtraAccountMeta::new_with_pubkey(&ctx.accounts.payment_hook.key(), false, true)?,
+            ExtraAccountMeta::new_with_pubkey(&ctx.accounts.hooks.key(), false, true)?,
+        ];
+        ExtraAccountMetaList::init::<ExecuteInstruction>(
+            &mut ctx.accounts.extra_account_meta_list.try_borrow_mut_data()?,
+            &extra_account_metas,
+        )?;
+
+        emit!(Token22HookBound {
+            hook_id: ctx.accounts.payment_hook.hook_id,
+            mint: ctx.accounts.mint.key(),
+            content_hash: ctx.accounts.payment_hook.content_hash,
+        });
+
+        msg!("Mint {} bound to payment hook {}", ctx.accounts.mint.key(), ctx.accounts.payment_hook.hook_id);
+        Ok(())
+    }
+
+    /// Transfer hook entrypoint invoked by Token-2022 on every transfer of a bound mint
+    ///
+    /// Implements the `spl_transfer_hook_interface` Execute instruction, so the
+    /// mint, source and destination arrive as accounts rather than arguments.
+    /// Token-2022 drops the owner's signer privilege before calling the hook,
+    /// so nothing can pay for an access permission here. This only records
+    /// transfers that meet the trigger amount; access itself is granted through
+    /// process_payment_trigger.
+    #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
+    pub fn record_token22_transfer(ctx: Context<RecordToken22Transfer>, amount: u64) -> Result<()> {
+        require!(
+            source_is_transferring(&ctx.accounts.source_token.to_account_info())?,
+            ErrorCode::NotTransferring
+        );
+        require!(
+            mint_content_hash(&ctx.accounts.mint.to_account_info())? == ctx.accounts.payment_hook.content_hash,
+            ErrorCode::ContentHashMismatch
+        );
+
+        let hook = &mut ctx.accounts.payment_hook;
+        if !hook.is_active || is_expired(hook) || amount < hook.trigger_amount {
+            return Ok(());
+        }
+
+        hook.trigger_count = hook.trigger_count.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+        let hooks = &mut ctx.accounts.hooks;
+        hooks.total_triggers = hooks.total_triggers.checked_add(1).ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(Token22TransferTriggered {
+            hook_id: hook.hook_id,
+            mint: ctx.accounts.mint.key(),
+            source: ctx.accounts.source_token.key(),
+            destination: ctx.accounts.destination_token.key(),
+            owner: ctx.accounts.owner.key(),
+            amount,
+            content_hash: hook.content_hash,
+        });
+
+        msg!("Token-2022 transfer recorded for hook {}: {} tokens", hook.hook_id, amount);
+        Ok(())
+    }
+
+    /// Set the key whose Ed25519 signature payment proofs must carry (authority only)
+    pub fn set_proof_authority(ctx: Context<SetProofAuthority>, new_authority: Pubkey) -> Result<()> {
+        let hooks = &mut ctx.accounts.hooks;
+        require!(
+            ctx.accounts.authority.key() == hooks.authority,
+            ErrorCode::Unauthorized
+        );
+
+        let old_authority = hooks.proof_authority;
+        hooks.proof_authority = new_authority;
+
+        emit!(ProofAuthorityUpdated {
+            old_authority,
+            new_authority,
+        });
+
+        Ok(())
+    }
+
+    /// Set how many triggers `batch_process_triggers` accepts (authority only)
+    pub fn set_max_batch_size(ctx: Context<SetMaxBatchSize>, new_max: u8) -> Result<()> {
+        let hooks = &mut ctx.accounts.hooks;
+        require!(
+            ctx.accounts.authority.key() == hooks.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(new_max > 0, ErrorCode::InvalidBatchSize);
+        require!(new_max <= MAX_BATCH_SIZE_LIMIT, ErrorCode::BatchSizeTooLarge);
+
+        let old_max = hooks.max_batch_size;
+        hooks.max_batch_size = new_max;
+
+        emit!(MaxBatchSizeUpdated {
+            old_max,
+            new_max,
+        });
+
+        Ok(())
+    }
+
     /// Emergency pause all hooks
     pub fn emergency_pause(ctx: Context<EmergencyPause>) -> Result<()> {
         require!(
@@ -214,36 +719,323 @@ pub mod token_hooks {
 
         Ok(())
     }
+
+    /// Report this build's version so clients can check compatibility
+    pub fn get_program_version(_ctx: Context<GetProgramVersion>) -> Result<ProgramVersion> {
+        Ok(PROGRAM_VERSION)
+    }
+}
+
+#[derive(Accounts)]
+pub struct GetProgramVersion<'info> {
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+    pub build_commit: [u8; 8], // First 8 chars of GIT_SHA at build time, "00000000" if unset
+}
+
+/// Version compiled into this program from its Cargo.toml
+pub const PROGRAM_VERSION: ProgramVersion = ProgramVersion {
+    major: program_version::version_component(env!("CARGO_PKG_VERSION"), 0),
+    minor: program_version::version_component(env!("CARGO_PKG_VERSION"), 1),
+    patch: program_version::version_component(env!("CARGO_PKG_VERSION"), 2),
+    build_commit: program_version::build_commit(option_env!("GIT_SHA")),
+};
+
+/// Whether a hook's expiry time has passed
+pub fn is_expired(hook: &PaymentHook) -> bool {
+    match (hook.expires_at, Clock::get()) {
+        (Some(expires_at), Ok(clock)) => clock.unix_timestamp > expires_at,
+        _ => false,
+    }
+}
+
+// Whether the creator pause record at its PDA address exists and is paused
+fn creator_hooks_paused(creator_pause: &AccountInfo) -> Result<bool> {
+    if creator_pause.owner != &crate::ID || creator_pause.data_is_empty() {
+        return Ok(false);
+    }
+
+    let record = CreatorPauseRecord::try_deserialize(&mut &creator_pause.try_borrow_data()?[..])?;
+    Ok(record.is_paused)
+}
+
+/// Content hash for a Token-2022 mint: SHA-256 of its metadata URI
+pub fn mint_content_hash(mint: &AccountInfo) -> Result<[u8; 32]> {
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    let metadata = state
+        .get_variable_len_extension::<TokenMetadata>()
+        .map_err(|_| error!(ErrorCode::MissingMintMetadata))?;
+
+    Ok(Sha256::digest(metadata.uri.as_bytes()).into())
+}
+
+// Program configured in the mint's transfer hook extension, if any
+fn mint_transfer_hook_program(mint: &AccountInfo) -> Result<Option<Pubkey>> {
+    let data = mint.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+    Ok(state
+        .get_extension::<TransferHook>()
+        .ok()
+        .and_then(|ext| Option::<Pubkey>::from(ext.program_id)))
+}
+
+// Whether Token-2022 flagged the source account as mid-transfer, rejecting direct calls
+fn source_is_transferring(source: &AccountInfo) -> Result<bool> {
+    let data = source.try_borrow_data()?;
+    let state = StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)?;
+    Ok(state
+        .get_extension::<TransferHookAccount>()
+        .is_ok_and(|ext| bool::from(ext.transferring)))
+}
+
+/// How long a payment proof is accepted after its timestamp
+pub const PROOF_VALIDITY_SECONDS: i64 = 3600;
+
+/// Batch size set at initialization
+pub const DEFAULT_MAX_BATCH_SIZE: u8 = 5;
+
+/// Upper bound for `set_max_batch_size`
+pub const MAX_BATCH_SIZE_LIMIT: u8 = 20;
+
+/// Maximum number of hooks followed after the triggered one
+pub const MAX_CHAIN_DEPTH: u8 = 3;
+
+/// remaining_accounts batch_process_triggers takes per trigger
+pub const BATCH_ACCOUNTS_PER_TRIGGER: usize = 3;
+
+/// Create the UsedNonce PDA at `info` (paid by `payer`) and write `record` to it.
+///
+/// Like Anchor's `init`, an address someone already sent lamports to is topped
+/// up, allocated and assigned instead of failing `create_account`.
+fn consume_nonce<'info>(
+    info: &AccountInfo<'info>,
+    signer_seeds: &[&[u8]],
+    record: UsedNonce,
+    payer: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+) -> Result<()> {
+    let space = 8 + UsedNonce::LEN;
+    let rent = Rent::get()?.minimum_balance(space);
+    let current_lamports = info.lamports();
+    if current_lamports == 0 {
+        system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program_info.clone(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: info.clone(),
+                },
+                &[signer_seeds],
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        )?;
+    } else {
+        let top_up = rent.saturating_sub(current_lamports);
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    system_program_info.clone(),
+                    system_program::Transfer {
+                        from: payer.clone(),
+                        to: info.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+        system_program::allocate(
+            CpiContext::new_with_signer(
+                system_program_info.clone(),
+                system_program::Allocate { account_to_allocate: info.clone() },
+                &[signer_seeds],
+            ),
+            space as u64,
+        )?;
+        system_program::assign(
+            CpiContext::new_with_signer(
+                system_program_info.clone(),
+                system_program::Assign { account_to_assign: info.clone() },
+                &[signer_seeds],
+            ),
+            &crate::ID,
+        )?;
+    }
+
+    record.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])
+}
+
+// Grant the buyer access to a hook's content via CPI to access controller
+fn grant_hook_access<'info>(
+    accounts: &ProcessPaymentTrigger<'info>,
+    access_permission: AccountInfo<'info>,
+    listing: AccountInfo<'info>,
+    purchase_record: AccountInfo<'info>,
+    hook: &PaymentHook,
+) -> Result<()> {
+    let (_, bump) = Pubkey::find_program_address(&[ACCESS_GRANT_AUTHORITY_SEED], &crate::ID);
+    let bump = [bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[ACCESS_GRANT_AUTHORITY_SEED, &bump]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        accounts.access_controller_program.to_account_info(),
+        access_controller::cpi::accounts::GrantAccess {
+            controller: accounts.access_controller.to_account_info(),
+            access_permission,
+            purchase_record,
+            listing,
+            authorized_callers: accounts.authorized_callers.to_account_info(),
+            caller_program: accounts.token_hooks_program.to_account_info(),
+            caller_authority: accounts.grant_authority.to_account_info(),
+            buyer: accounts.buyer.to_account_info(),
+            payer: accounts.buyer.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+        },
+        signer_seeds,
+    );
+
+    access_controller::cpi::grant_access(
+        cpi_ctx,
+        hook.content_hash,
+        hook.unlock_duration,
+    )
+}
+
+// Add a contribution to the buyer's accumulation; once the hook's threshold is
+// reached, release the escrow to the creator and return true
+fn accumulate_payment(
+    accounts: &mut ProcessPaymentTrigger,
+    payment_amount: u64,
+    bump: u8,
+) -> Result<bool> {
+    let hook_key = accounts.payment_hook.key();
+    let buyer_key = accounts.buyer.key();
+    let trigger_amount = accounts.payment_hook.trigger_amount;
+    let escrow = accounts
+        .accumulation_escrow
+        .as_ref()
+        .ok_or(ErrorCode::MissingAccumulationAccount)?
+        .to_account_info();
+    let accumulated = accounts
+        .accumulated_payment
+        .as_mut()
+        .ok_or(ErrorCode::MissingAccumulationAccount)?;
+
+    if accumulated.buyer == Pubkey::default() {
+        accumulated.hook = hook_key;
+        accumulated.buyer = buyer_key;
+        accumulated.bump = bump;
+    }
+
+    let cpi_ctx = CpiContext::new(
+        accounts.token_program.to_account_info(),
+        Transfer {
+            from: accounts.payer_token_account.as_ref().ok_or(ErrorCode::MissingTokenAccount)?.to_account_info(),
+            to: escrow.clone(),
+            authority: accounts.buyer.to_account_info(),
+        },
+    );
+    token::transfer(cpi_ctx, payment_amount)?;
+
+    accumulated.total_accumulated = accumulated.total_accumulated
+        .checked_add(payment_amount)
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    accumulated.last_contribution_at = Clock::get()?.unix_timestamp;
+
+    emit!(PaymentAccumulated {
+        hook: hook_key,
+        buyer: buyer_key,
+        contribution: payment_amount,
+        total_accumulated: accumulated.total_accumulated,
+        trigger_amount,
+    });
+
+    if accumulated.total_accumulated < trigger_amount {
+        return Ok(false);
+    }
+
+    // Threshold reached: release the escrow to the creator
+    let released = accumulated.total_accumulated;
+    accumulated.total_accumulated = 0;
+
+    let seeds = &[
+        b"accumulated".as_ref(),
+        hook_key.as_ref(),
+        buyer_key.as_ref(),
+        &[accumulated.bump],
+    ];
+    let signer = &[&seeds[..]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        accounts.token_program.to_account_info(),
+        Transfer {
+            from: escrow,
+            to: accounts.recipient_token_account.as_ref().ok_or(ErrorCode::MissingTokenAccount)?.to_account_info(),
+            authority: accumulated.to_account_info(),
+        },
+        signer,
+    );
+    token::transfer(cpi_ctx, released)?;
+
+    Ok(true)
 }
 
 // Helper function to verify payment proofs
-fn verify_payment_proof(proof: &PaymentProof, amount: u64, content_hash: &[u8; 32]) -> Result<bool> {
-    // Enhanced payment proof verification with cryptographic checks
-    
+fn verify_payment_proof(
+    proof: &PaymentProof,
+    amount: u64,
+    content_hash: &[u8; 32],
+    buyer: &Pubkey,
+    proof_authority: &Pubkey,
+    instructions_sysvar: &AccountInfo,
+) -> Result<bool> {
     // Verify amount matches proof
     require!(proof.amount == amount, ErrorCode::AmountMismatch);
     
     // Verify content hash matches
     require!(proof.content_hash == *content_hash, ErrorCode::ContentHashMismatch);
     
-    // Verify transaction signature (simplified - in production would verify on-chain tx)
-    if proof.tx_signature.len() == 64 {
-        // Basic signature format validation
-        for byte in &proof.tx_signature {
-            require!(*byte != 0, ErrorCode::InvalidSignature);
-        }
-    }
-    
-    // Verify timestamp is recent (within last hour)
+    // Verify timestamp is recent (within last hour) and not post-dated
     let current_time = Clock::get()?.unix_timestamp;
+    require!(proof.timestamp <= current_time, ErrorCode::ProofFromFuture);
     require!(
-        current_time - proof.timestamp < 3600,
+        current_time - proof.timestamp < PROOF_VALIDITY_SECONDS,
         ErrorCode::ProofExpired
     );
     
-    // Additional verification logic would integrate with spend-verifier program
-    msg!("Payment proof verified for amount: {} lamports", amount);
-    Ok(proof.verified)
+    // The proof authority signs sha256(amount || content_hash || buyer || timestamp || nonce)
+    let mut hasher = Sha256::new();
+    hasher.update(amount.to_le_bytes());
+    hasher.update(content_hash);
+    hasher.update(buyer);
+    hasher.update(proof.timestamp.to_le_bytes());
+    hasher.update(proof.nonce);
+    let message: [u8; 32] = hasher.finalize().into();
+
+    let signature: [u8; 64] = proof
+        .tx_signature
+        .as_slice()
+        .try_into()
+        .map_err(|_| ErrorCode::InvalidSignature)?;
+    // The client-supplied `verified` flag is ignored; only the signature counts
+    let verified = access_controller::verify_credential_signature(
+        instructions_sysvar,
+        &message,
+        &signature,
+        &proof_authority.to_bytes(),
+    )
+    .is_ok();
+    
+    if verified {
+        msg!("Payment proof verified for amount: {} lamports", amount);
+    }
+    Ok(verified)
 }
 
 #[derive(Accounts)]
@@ -277,12 +1069,99 @@ pub struct RegisterPaymentHook<'info> {
     )]
     pub payment_hook: Account<'info, PaymentHook>,
     
+    /// Mint the hook's payments are made in
+    pub payment_mint: Account<'info, token::Mint>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterRecurringHook<'info> {
+    #[account(mut)]
+    pub hooks: Account<'info, TokenHooks>,
+    
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + RecurringHook::LEN,
+        seeds = [b"recurring_hook", hooks.total_hooks.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub recurring_hook: Account<'info, RecurringHook>,
+    
+    /// Mint the subscription is charged in
+    pub payment_mint: Account<'info, token::Mint>,
+    
     #[account(mut)]
     pub creator: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
+pub struct ProcessRecurringPayment<'info> {
+    #[account(mut)]
+    pub hooks: Account<'info, TokenHooks>,
+    
+    #[account(
+        seeds = [b"recurring_hook", recurring_hook.hook_id.to_le_bytes().as_ref()],
+        bump = recurring_hook.bump
+    )]
+    pub recurring_hook: Account<'info, RecurringHook>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + RecurringHookState::LEN,
+        seeds = [b"recurring_state", recurring_hook.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub recurring_state: Account<'info, RecurringHookState>,
+    
+    #[account(
+        mut,
+        token::mint = recurring_hook.mint,
+        token::authority = buyer
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = recurring_hook.mint,
+        token::authority = recurring_hook.creator
+    )]
+    pub creator_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRecurringHook<'info> {
+    #[account(
+        seeds = [b"recurring_hook", recurring_hook.hook_id.to_le_bytes().as_ref()],
+        bump = recurring_hook.bump
+    )]
+    pub recurring_hook: Account<'info, RecurringHook>,
+    
+    #[account(
+        mut,
+        seeds = [b"recurring_state", recurring_hook.key().as_ref(), buyer.key().as_ref()],
+        bump = recurring_state.bump,
+        has_one = buyer @ ErrorCode::Unauthorized,
+        close = buyer
+    )]
+    pub recurring_state: Account<'info, RecurringHookState>,
+    
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(payment_amount: u64, payment_proof: PaymentProof)]
 pub struct ProcessPaymentTrigger<'info> {
     #[account(mut)]
     pub hooks: Account<'info, TokenHooks>,
@@ -290,10 +1169,30 @@ pub struct ProcessPaymentTrigger<'info> {
     #[account(mut)]
     pub payment_hook: Account<'info, PaymentHook>,
     
+    // Fails with account-already-in-use if the proof is replayed
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + UsedNonce::LEN,
+        seeds = [b"used_nonce", payment_proof.nonce.as_ref()],
+        bump
+    )]
+    pub used_nonce: Account<'info, UsedNonce>,
+    
+    /// CHECK: Creator pause record PDA, may not exist; read by creator_hooks_paused
+    #[account(
+        seeds = [b"creator_pause", payment_hook.creator.as_ref()],
+        bump
+    )]
+    pub creator_pause: UncheckedAccount<'info>,
+    
     // Access controller accounts
     #[account(mut)]
     pub access_controller: Account<'info, access_controller::AccessController>,
     
+    /// CHECK: Validated by the access controller against its authorized callers
+    pub authorized_callers: UncheckedAccount<'info>,
+    
     #[account(mut)]
     /// CHECK: Will be initialized by access controller
     pub access_permission: UncheckedAccount<'info>,
@@ -301,19 +1200,58 @@ pub struct ProcessPaymentTrigger<'info> {
     #[account(mut)]
     pub purchase_record: Account<'info, x402_registry::PurchaseRecord>,
     
+    pub listing: Account<'info, x402_registry::ContentListing>,
+    
     // Token accounts (optional for token transfers)
     #[account(mut)]
     /// CHECK: Token account validated by token program
     pub payer_token_account: Option<UncheckedAccount<'info>>,
     
-    #[account(mut)]
-    /// CHECK: Token account validated by token program
-    pub recipient_token_account: Option<UncheckedAccount<'info>>,
+    /// Payments always go to the hook's creator, in the hook's mint
+    #[account(
+        mut,
+        token::mint = payment_hook.payment_mint,
+        token::authority = payment_hook.creator
+    )]
+    pub recipient_token_account: Option<Account<'info, TokenAccount>>,
+    
+    // Accumulation accounts (only for accumulation hooks)
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + AccumulatedPayment::LEN,
+        seeds = [b"accumulated", payment_hook.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub accumulated_payment: Option<Account<'info, AccumulatedPayment>>,
+    
+    /// Mint the accumulation escrow holds; only needed to create the escrow
+    #[account(address = payment_hook.payment_mint)]
+    pub accumulation_mint: Option<Account<'info, token::Mint>>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        seeds = [b"accumulation_escrow", payment_hook.key().as_ref(), buyer.key().as_ref()],
+        bump,
+        token::mint = accumulation_mint,
+        token::authority = accumulated_payment
+    )]
+    pub accumulation_escrow: Option<Account<'info, TokenAccount>>,
+    
+    /// CHECK: Instructions sysvar, read for the Ed25519 proof signature
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
     
     // Programs
     pub access_controller_program: Program<'info, access_controller::program::AccessController>,
     /// CHECK: Self reference for CPI
+    #[account(address = crate::ID)]
     pub token_hooks_program: UncheckedAccount<'info>,
+    
+    /// CHECK: Signer PDA proving grant_access CPIs come from this program
+    #[account(seeds = [ACCESS_GRANT_AUTHORITY_SEED], bump)]
+    pub grant_authority: UncheckedAccount<'info>,
     pub token_program: Program<'info, Token>,
     
     #[account(mut)]
@@ -326,8 +1264,46 @@ pub struct BatchProcessTriggers<'info> {
     #[account(mut)]
     pub hooks: Account<'info, TokenHooks>,
     
+    /// Pays for each UsedNonce PDA the batch consumes
+    #[account(mut)]
     pub buyer: Signer<'info>,
-    // remaining_accounts: PaymentHook and AccessPermission accounts
+    
+    /// CHECK: Instructions sysvar, read for the Ed25519 proof signatures
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    // remaining_accounts: (PaymentHook, AccessPermission, UsedNonce PDA) per trigger
+}
+
+#[derive(Accounts)]
+pub struct CancelAccumulation<'info> {
+    #[account(
+        mut,
+        seeds = [b"accumulated", accumulated_payment.hook.as_ref(), buyer.key().as_ref()],
+        bump = accumulated_payment.bump,
+        has_one = buyer @ ErrorCode::Unauthorized,
+        close = buyer
+    )]
+    pub accumulated_payment: Account<'info, AccumulatedPayment>,
+    
+    #[account(
+        mut,
+        seeds = [b"accumulation_escrow", accumulated_payment.hook.as_ref(), buyer.key().as_ref()],
+        bump,
+        token::authority = accumulated_payment
+    )]
+    pub accumulation_escrow: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = accumulation_escrow.mint,
+        token::authority = buyer
+    )]
+    pub buyer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
 }
 
 #[derive(Accounts)]
@@ -338,6 +1314,119 @@ pub struct UpdatePaymentHook<'info> {
     pub creator: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct PruneUsedNonce<'info> {
+    #[account(
+        mut,
+        seeds = [b"used_nonce", used_nonce.nonce.as_ref()],
+        bump = used_nonce.bump,
+        has_one = payer,
+        close = payer
+    )]
+    pub used_nonce: Account<'info, UsedNonce>,
+    
+    #[account(mut)]
+    /// CHECK: Receives the rent back, checked against used_nonce.payer
+    pub payer: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PauseCreatorHooks<'info> {
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + CreatorPauseRecord::LEN,
+        seeds = [b"creator_pause", creator.key().as_ref()],
+        bump
+    )]
+    pub creator_pause: Account<'info, CreatorPauseRecord>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResumeCreatorHooks<'info> {
+    #[account(
+        mut,
+        seeds = [b"creator_pause", creator.key().as_ref()],
+        bump = creator_pause.bump,
+        has_one = creator @ ErrorCode::Unauthorized
+    )]
+    pub creator_pause: Account<'info, CreatorPauseRecord>,
+    
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeExtraAccountMetaList<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = ExtraAccountMetaList::size_of(2)?,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump
+    )]
+    /// CHECK: TLV account written by ExtraAccountMetaList::init
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    
+    #[account(mint::token_program = anchor_spl::token_2022::ID)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    #[account(has_one = creator @ ErrorCode::Unauthorized)]
+    pub payment_hook: Account<'info, PaymentHook>,
+    
+    #[account(seeds = [b"token_hooks"], bump)]
+    pub hooks: Account<'info, TokenHooks>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Account order is fixed by the transfer hook interface
+#[derive(Accounts)]
+pub struct RecordToken22Transfer<'info> {
+    /// CHECK: Source token account, validated by Token-2022 before invoking the hook
+    pub source_token: UncheckedAccount<'info>,
+    
+    #[account(mint::token_program = anchor_spl::token_2022::ID)]
+    pub mint: InterfaceAccount<'info, Mint>,
+    
+    /// CHECK: Destination token account, validated by Token-2022 before invoking the hook
+    pub destination_token: UncheckedAccount<'info>,
+    
+    /// CHECK: Source account owner or delegate; not a signer inside the hook
+    pub owner: UncheckedAccount<'info>,
+    
+    #[account(seeds = [b"extra-account-metas", mint.key().as_ref()], bump)]
+    /// CHECK: Extra account meta list for this mint
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub payment_hook: Account<'info, PaymentHook>,
+    
+    #[account(mut, seeds = [b"token_hooks"], bump)]
+    pub hooks: Account<'info, TokenHooks>,
+}
+
+#[derive(Accounts)]
+pub struct SetProofAuthority<'info> {
+    #[account(mut)]
+    pub hooks: Account<'info, TokenHooks>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetMaxBatchSize<'info> {
+    #[account(mut)]
+    pub hooks: Account<'info, TokenHooks>,
+    
+    pub authority: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct EmergencyPause<'info> {
     #[account(mut)]
@@ -351,26 +1440,77 @@ pub struct TokenHooks {
     pub authority: Pubkey,
     pub total_hooks: u64,
     pub total_triggers: u64,
+    pub proof_authority: Pubkey, // Signer of payment proofs
+    pub max_batch_size: u8,      // Triggers accepted per batch_process_triggers call
 }
 
 impl TokenHooks {
-    pub const LEN: usize = 32 + 8 + 8;
+    pub const LEN: usize = 32 + 8 + 8 + 32 + 1;
 }
 
 #[account]
 pub struct PaymentHook {
     pub hook_id: u64,
     pub creator: Pubkey,
+    pub payment_mint: Pubkey, // Token the hook's payments are made in
     pub content_hash: [u8; 32],
     pub trigger_amount: u64,
     pub unlock_duration: Option<i64>,
     pub created_at: i64,
     pub trigger_count: u64,
     pub is_active: bool,
+    pub next_hook_id: Option<u64>, // Hook triggered after this one succeeds
+    pub accumulation_hook: bool,   // Payments accumulate until trigger_amount is reached
+    pub expires_at: Option<i64>,   // Hook stops triggering after this time
 }
 
 impl PaymentHook {
-    pub const LEN: usize = 8 + 32 + 32 + 8 + (1 + 8) + 8 + 8 + 1;
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + (1 + 8) + 8 + 8 + 1 + (1 + 8) + 1 + (1 + 8);
+}
+
+#[account]
+pub struct AccumulatedPayment {
+    pub hook: Pubkey,
+    pub buyer: Pubkey,
+    pub total_accumulated: u64, // Held in escrow until the hook fires
+    pub last_contribution_at: i64,
+    pub bump: u8,
+}
+
+impl AccumulatedPayment {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 1;
+}
+
+#[account]
+pub struct RecurringHook {
+    pub hook_id: u64,
+    pub creator: Pubkey,
+    pub mint: Pubkey, // Token the subscription is charged in
+    pub content_hash: [u8; 32],
+    pub amount_per_interval: u64,
+    pub interval_seconds: i64,
+    pub max_payments: Option<u32>, // None = charge until cancelled
+    pub created_at: i64,
+    pub is_active: bool,
+    pub bump: u8,
+}
+
+impl RecurringHook {
+    pub const LEN: usize = 8 + 32 + 32 + 32 + 8 + 8 + (1 + 4) + 8 + 1 + 1;
+}
+
+#[account]
+pub struct RecurringHookState {
+    pub hook: Pubkey,
+    pub buyer: Pubkey,
+    pub last_triggered_at: i64,
+    pub payment_count: u32,
+    pub is_active: bool, // Cleared once max_payments is reached
+    pub bump: u8,
+}
+
+impl RecurringHookState {
+    pub const LEN: usize = 32 + 32 + 8 + 4 + 1 + 1;
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -380,7 +1520,33 @@ pub struct PaymentProof {
     pub content_hash: [u8; 32],
     pub tx_signature: Vec<u8>, // Transaction signature for verification
     pub timestamp: i64,        // Proof creation timestamp
-    pub verified: bool,        // ZK proof verification status
+    pub verified: bool,        // Informational only; the program checks the signature itself
+    pub nonce: [u8; 16],       // Unique per proof, recorded on use to block replays
+}
+
+#[account]
+pub struct CreatorPauseRecord {
+    pub creator: Pubkey,
+    pub is_paused: bool,
+    pub updated_at: i64,
+    pub bump: u8,
+}
+
+impl CreatorPauseRecord {
+    pub const LEN: usize = 32 + 1 + 8 + 1;
+}
+
+#[account]
+pub struct UsedNonce {
+    pub nonce: [u8; 16],
+    pub payer: Pubkey, // Paid the rent, refunded when pruned
+    pub used_at: i64,
+    pub proof_timestamp: i64, // Signed timestamp of the proof that used the nonce
+    pub bump: u8,
+}
+
+impl UsedNonce {
+    pub const LEN: usize = 16 + 32 + 8 + 8 + 1;
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -399,6 +1565,32 @@ pub struct PaymentHookRegistered {
     pub unlock_duration: Option<i64>,
 }
 
+#[event]
+pub struct RecurringHookRegistered {
+    pub hook_id: u64,
+    pub creator: Pubkey,
+    pub content_hash: [u8; 32],
+    pub amount_per_interval: u64,
+    pub interval_seconds: i64,
+    pub max_payments: Option<u32>,
+}
+
+#[event]
+pub struct RecurringPaymentProcessed {
+    pub hook_id: u64,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub payment_count: u32,
+    pub next_payment_at: i64,
+}
+
+#[event]
+pub struct RecurringHookCancelled {
+    pub hook_id: u64,
+    pub buyer: Pubkey,
+    pub payment_count: u32,
+}
+
 #[event]
 pub struct PaymentTriggered {
     pub hook_id: u64,
@@ -408,6 +1600,30 @@ pub struct PaymentTriggered {
     pub triggered_at: i64,
 }
 
+#[event]
+pub struct PaymentAccumulated {
+    pub hook: Pubkey,
+    pub buyer: Pubkey,
+    pub contribution: u64,
+    pub total_accumulated: u64,
+    pub trigger_amount: u64,
+}
+
+#[event]
+pub struct AccumulationCancelled {
+    pub hook: Pubkey,
+    pub buyer: Pubkey,
+    pub refunded_amount: u64,
+}
+
+#[event]
+pub struct HookChainCompleted {
+    pub first_hook_id: u64,
+    pub last_hook_id: u64,
+    pub chain_length: u8,
+    pub buyer: Pubkey,
+}
+
 #[event]
 pub struct BatchTriggersProcessed {
     pub buyer: Pubkey,
@@ -416,6 +1632,13 @@ pub struct BatchTriggersProcessed {
     pub processed_at: i64,
 }
 
+#[event]
+pub struct HookExpiredEvent {
+    pub hook_id: u64,
+    pub creator: Pubkey,
+    pub expired_at: i64,
+}
+
 #[event]
 pub struct PaymentHookUpdated {
     pub hook_id: u64,
@@ -423,6 +1646,48 @@ pub struct PaymentHookUpdated {
     pub updated_at: i64,
 }
 
+#[event]
+pub struct CreatorHooksPaused {
+    pub creator: Pubkey,
+    pub paused_at: i64,
+}
+
+#[event]
+pub struct CreatorHooksResumed {
+    pub creator: Pubkey,
+    pub resumed_at: i64,
+}
+
+#[event]
+pub struct Token22HookBound {
+    pub hook_id: u64,
+    pub mint: Pubkey,
+    pub content_hash: [u8; 32],
+}
+
+#[event]
+pub struct Token22TransferTriggered {
+    pub hook_id: u64,
+    pub mint: Pubkey,
+    pub source: Pubkey,
+    pub destination: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub content_hash: [u8; 32],
+}
+
+#[event]
+pub struct MaxBatchSizeUpdated {
+    pub old_max: u8,
+    pub new_max: u8,
+}
+
+#[event]
+pub struct ProofAuthorityUpdated {
+    pub old_authority: Pubkey,
+    pub new_authority: Pubkey,
+}
+
 #[event]
 pub struct EmergencyPauseActivated {
     pub paused_by: Pubkey,
@@ -439,8 +1704,10 @@ pub enum ErrorCode {
     InsufficientPayment,
     #[msg("Invalid payment proof")]
     InvalidPaymentProof,
-    #[msg("Too many triggers in batch (max 5)")]
+    #[msg("Too many triggers in batch")]
     TooManyTriggers,
+    #[msg("Batch size exceeds the limit of 20")]
+    BatchSizeTooLarge,
     #[msg("Unauthorized access")]
     Unauthorized,
     #[msg("Amount mismatch in payment proof")]
@@ -451,6 +1718,40 @@ pub enum ErrorCode {
     InvalidSignature,
     #[msg("Payment proof has expired")]
     ProofExpired,
+    #[msg("Payment proof timestamp is in the future")]
+    ProofFromFuture,
     #[msg("Missing required token account")]
     MissingTokenAccount,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("Recurring interval must be greater than 0")]
+    InvalidInterval,
+    #[msg("Recurring payment interval has not elapsed yet")]
+    IntervalNotElapsed,
+    #[msg("Maximum number of recurring payments reached")]
+    MaxPaymentsReached,
+    #[msg("Hook chain is longer than the maximum depth")]
+    MaxChainDepthExceeded,
+    #[msg("Chained hook account missing or does not match next_hook_id")]
+    InvalidChainedHook,
+    #[msg("Accumulation hooks require the accumulated payment and escrow accounts")]
+    MissingAccumulationAccount,
+    #[msg("Nonce is still within the proof validity window")]
+    NonceStillValid,
+    #[msg("Payment hook has expired")]
+    HookExpired,
+    #[msg("Expiry must be in the future")]
+    InvalidExpiry,
+    #[msg("Hooks are paused by their creator")]
+    CreatorHooksPaused,
+    #[msg("Mint does not use this program as its transfer hook")]
+    TransferHookNotConfigured,
+    #[msg("Mint has no token metadata to derive a content hash from")]
+    MissingMintMetadata,
+    #[msg("Transfer hook called outside of a token transfer")]
+    NotTransferring,
+    #[msg("Remaining accounts do not match the batch's triggers")]
+    BatchAccountsMismatch,
+    #[msg("Batch size must be at least 1")]
+    InvalidBatchSize,
 }
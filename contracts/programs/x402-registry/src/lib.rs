@@ -1,4 +1,13 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::system_program::{self, Transfer};
+use anchor_spl::token::{self, spl_token::native_mint, Mint, Token, TokenAccount};
+use sha2::{Digest, Sha256};
+use solana_instructions_sysvar::{load_current_index_checked, load_instruction_at_checked};
+use solana_sdk_ids::ed25519_program;
+use spend_verifier::{Groth16Proof, VerificationKey};
+use zk_meta_registry::{CircuitParams, CurveType, ProtocolType, VerificationKeyEntry, VK_HEADER_LEN};
 
 declare_id!("2a65ey6veP6vqa54K1AHg4fidM2YMH8cBLxacHNz8KCR");
 
@@ -13,6 +22,23 @@ pub mod x402_registry {
         registry.listing_count = 0;
         registry.total_revenue = 0;
         registry.platform_fee_bps = 200; // 2% platform fee
+        registry.governance = GovernanceConfig {
+            voting_period_seconds: 3 * 24 * 60 * 60,
+            execution_delay_seconds: 2 * 24 * 60 * 60,
+            quorum_bps: 1000,
+        };
+        registry.governance_mint = Pubkey::default();
+        registry.proposal_count = 0;
+        registry.sol_usd_price_feed = Pubkey::default();
+        registry.min_creator_stake = 0;
+        registry.stake_discount_tiers = Vec::new();
+        registry.auto_pause_threshold = DEFAULT_AUTO_PAUSE_THRESHOLD;
+        registry.operator = registry.authority;
+        registry.is_paused = false;
+        registry.zero_fee_stake_threshold = DEFAULT_ZERO_FEE_STAKE_THRESHOLD;
+        registry.governance_realm = None;
+        registry.min_price_floor = DEFAULT_MIN_PRICE_FLOOR;
+        registry.max_listings_per_creator = DEFAULT_MAX_LISTINGS_PER_CREATOR;
 
         msg!("x402 Registry initialized with authority: {}", registry.authority);
         Ok(())
@@ -26,15 +52,69 @@ pub mod x402_registry {
         required_credentials: Vec<CredentialRequirement>,
         zk_attestations: Vec<ZkAttestation>,
         metadata: ContentMetadata,
+        hash_algorithm: Option<HashAlgorithm>, // Defaults to Sha256
     ) -> Result<()> {
+        require!(!ctx.accounts.registry.is_paused, ErrorCode::RegistryPaused);
+        // Unknown variants already fail Borsh deserialization of the argument
+        let hash_algorithm = hash_algorithm.unwrap_or(HashAlgorithm::Sha256);
         require!(pricing_config.base_price > 0, ErrorCode::InvalidPrice);
+        check_price_floor(&pricing_config, ctx.accounts.registry.min_price_floor)?;
+        validate_discount_whitelist(&pricing_config)?;
+        require!(
+            pricing_config.max_total_discount_bps <= MAX_TOTAL_DISCOUNT_CAP_BPS,
+            ErrorCode::DiscountCapTooHigh
+        );
         require!(content_hash != [0u8; 32], ErrorCode::InvalidContentHash);
+        let unique_hash = &ctx.accounts.unique_hash;
+        if unique_hash.creator != Pubkey::default() {
+            msg!("This content hash is already registered as listing: {}", unique_hash.listing_id);
+            return err!(ErrorCode::ContentAlreadyRegistered);
+        }
         require!(metadata.title.len() <= 128, ErrorCode::TitleTooLong);
         require!(metadata.description.len() <= 512, ErrorCode::DescriptionTooLong);
+        validate_ipfs_cid(metadata.ipfs_cid.as_deref())?;
+        validate_content_type(&metadata)?;
+        validate_delivery_channel(&metadata.delivery_channel, &content_hash, &hash_algorithm)?;
+        for requirement in &required_credentials {
+            validate_credential_type(&requirement.credential_type)?;
+        }
+        for discount in &pricing_config.credential_discounts {
+            validate_credential_type(&discount.credential_type)?;
+        }
+        require!(
+            pricing_config.price_denomination == PriceDenomination::Lamports
+                || ctx.accounts.payment_mint.key() == native_mint::ID,
+            ErrorCode::UsdPricingRequiresNativeMint
+        );
+        require!(
+            !BlacklistEntry::exists(&ctx.accounts.blacklist_entry),
+            ErrorCode::CreatorBlacklisted
+        );
+        let staked = ctx.accounts.creator_stake.as_ref().map_or(0, |stake| stake.staked);
+        require!(staked >= ctx.accounts.registry.min_creator_stake, ErrorCode::InsufficientStake);
+        let creator_count = &ctx.accounts.creator_count;
+        require!(
+            creator_count.count < creator_count.limit(ctx.accounts.registry.max_listings_per_creator),
+            ErrorCode::CreatorListingLimitReached
+        );
+        require!(zk_attestations.len() <= MAX_ZK_ATTESTATIONS, ErrorCode::TooManyAttestations);
+        let now = Clock::get()?.unix_timestamp;
+        verify_zk_attestations(
+            &zk_attestations,
+            now,
+            VerifiedCreator::verified_domain_hash(&ctx.accounts.verified_creator),
+            ctx.accounts.email_domain_vk_entry.as_ref().map(|entry| entry.as_ref()),
+            ctx.accounts.gps_location_vk_entry.as_ref().map(|entry| entry.as_ref()),
+            ctx.accounts.timestamp_vk_entry.as_ref().map(|entry| entry.as_ref()),
+            ctx.accounts.trusted_devices.as_deref(),
+            ctx.accounts.instructions_sysvar.as_ref().map(|sysvar| sysvar.as_ref()),
+        )?;
 
         let listing = &mut ctx.accounts.listing;
         listing.creator = ctx.accounts.creator.key();
         listing.content_hash = content_hash;
+        listing.hash_algorithm = hash_algorithm;
+        listing.payment_mint = ctx.accounts.payment_mint.key();
         listing.pricing = pricing_config.clone();
         listing.required_credentials = required_credentials;
         listing.zk_attestations = zk_attestations.clone();
@@ -43,12 +123,52 @@ pub mod x402_registry {
         listing.updated_at = listing.created_at;
         listing.purchase_count = 0;
         listing.total_revenue = 0;
-        listing.is_active = true;
+        listing.state = ContentListingState::Active;
+        listing.average_rating = 0;
+        listing.review_count = 0;
+        listing.report_count = 0;
+        listing.secondary_royalty_bps = 0;
+        listing.escrow_required = false;
+        listing.fee_model = FeeModel::PlatformDefault;
+        listing.is_verified_creator = VerifiedCreator::exists(&ctx.accounts.verified_creator);
         listing.listing_id = ctx.accounts.registry.listing_count;
 
+        let unique_hash = &mut ctx.accounts.unique_hash;
+        unique_hash.listing_id = listing.listing_id;
+        unique_hash.creator = listing.creator;
+        unique_hash.bump = ctx.bumps.unique_hash;
+
         let registry = &mut ctx.accounts.registry;
         registry.listing_count += 1;
 
+        let profile = &mut ctx.accounts.creator_profile;
+        if profile.joined_at == 0 {
+            profile.creator = listing.creator;
+            profile.joined_at = listing.created_at;
+            profile.bump = ctx.bumps.creator_profile;
+        }
+        profile.listing_count = profile.listing_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        profile.active_listing_count = profile.active_listing_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        let creator_count = &mut ctx.accounts.creator_count;
+        if creator_count.creator == Pubkey::default() {
+            creator_count.creator = listing.creator;
+            creator_count.bump = ctx.bumps.creator_count;
+        }
+        creator_count.count = creator_count.count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        let daily_stats = &mut ctx.accounts.daily_stats;
+        daily_stats.date = day_index(listing.created_at)?;
+        daily_stats.listings_created = daily_stats.listings_created
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
         emit!(ContentRegistered {
             listing_id: listing.listing_id,
             creator: listing.creator,
@@ -65,18 +185,49 @@ pub mod x402_registry {
     }
 
     /// Purchase content with ZK proof payment
+    ///
+    /// `purchase_nonce` should be random per purchase attempt: its PDA is created
+    /// here and never closed, so a retried transaction with the same nonce fails.
     pub fn purchase_content(
         ctx: Context<PurchaseContent>,
         buyer_credentials: Vec<CredentialProof>,
+        purchase_nonce: [u8; 8],
+        referral_code: Option<[u8; 8]>,
+        solana_pay_reference: Option<Pubkey>,
     ) -> Result<()> {
+        require!(!ctx.accounts.registry.is_paused, ErrorCode::RegistryPaused);
         let listing = &ctx.accounts.listing;
-        require!(listing.is_active, ErrorCode::ListingInactive);
-
-        // Calculate final price based on credentials
-        let final_price = calculate_price_with_discounts(
-            &listing.pricing,
+        require!(listing.state == ContentListingState::Active, ErrorCode::ListingInactive);
+        require!(
+            listing.report_count < ctx.accounts.registry.auto_pause_threshold,
+            ErrorCode::ListingUnderReview
+        );
+        require!(!listing.escrow_required, ErrorCode::EscrowRequired);
+        // Attestations age out; sales resume once the creator calls reattest_listing
+        let now = Clock::get()?.unix_timestamp;
+        for attestation in &listing.zk_attestations {
+            attestation.check_fresh(now)?;
+        }
+        require!(listing.is_available(now), ErrorCode::OutsideAvailabilityWindow);
+        // No credential counts, for access or a discount, unless its issuer signed it for this buyer
+        check_required_credentials(
             &listing.required_credentials,
             &buyer_credentials,
+            &ctx.accounts.buyer.key(),
+            ctx.accounts.instructions_sysvar.as_ref().map(|sysvar| sysvar.as_ref()),
+            now,
+        )?;
+        // remaining_accounts: the access-controller RevokedCredential PDA for each
+        // credential, optionally followed by a CrossDiscount and the buyer's
+        // PurchaseRecord for its source listing
+        let final_price = quote_listing_price(
+            listing,
+            ctx.accounts.registry.min_price_floor,
+            ctx.accounts.pyth_feed.as_ref().map(|feed| feed.as_ref()),
+            &ctx.accounts.buyer.key(),
+            &buyer_credentials,
+            ctx.remaining_accounts,
+            now,
         )?;
 
         // Create purchase record
@@ -87,18 +238,148 @@ pub mod x402_registry {
         purchase.final_price = final_price;
         purchase.purchased_at = Clock::get()?.unix_timestamp;
         purchase.credentials_used = buyer_credentials;
-        purchase.access_granted = false; // Will be set by access controller
+        purchase.access_granted = false; // Set once grant_access succeeds below
+        purchase.solana_pay_reference = solana_pay_reference;
+
+        let nonce_record = &mut ctx.accounts.nonce_record;
+        nonce_record.buyer = purchase.buyer;
+        nonce_record.listing = ctx.accounts.listing.key();
+        nonce_record.nonce = purchase_nonce;
+        nonce_record.used_at = purchase.purchased_at;
+        nonce_record.bump = ctx.bumps.nonce_record;
 
         // Update listing stats
         let listing = &mut ctx.accounts.listing;
-        listing.purchase_count += 1;
-        listing.total_revenue += final_price;
+        listing.purchase_count = listing.purchase_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        listing.total_revenue = listing.total_revenue
+            .checked_add(final_price)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
         listing.updated_at = Clock::get()?.unix_timestamp;
 
         // Update registry stats
         let registry = &mut ctx.accounts.registry;
-        let platform_fee = (final_price * registry.platform_fee_bps as u64) / 10000;
-        registry.total_revenue += platform_fee;
+        let staked = ctx.accounts.creator_stake.as_ref().map_or(0, |stake| stake.staked);
+        let platform_fee = platform_fee_for(&listing.fee_model, registry, staked, final_price)?;
+        registry.total_revenue = registry.total_revenue
+            .checked_add(platform_fee)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        // Referral commission comes out of the creator's share
+        let commission = match referral_code {
+            Some(_) => {
+                let referral = ctx.accounts.referral
+                    .as_mut()
+                    .ok_or(ErrorCode::ReferralAccountsMissing)?;
+                let commission = referral_commission(referral, &purchase.buyer, final_price)?;
+                referral.record_use(commission)?;
+                commission
+            }
+            None => 0,
+        };
+
+        let creator_amount = final_price
+            .checked_sub(platform_fee)
+            .and_then(|amount| amount.checked_sub(commission))
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        // The whole price sits in the purchase hold until the dispute window
+        // closes; release_purchase_hold or resolve_dispute pays it out
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.buyer_ata.to_account_info(),
+                    to: ctx.accounts.hold_vault.to_account_info(),
+                    authority: ctx.accounts.buyer.to_account_info(),
+                },
+            ),
+            final_price,
+        )?;
+        let hold = &mut ctx.accounts.purchase_hold;
+        hold.purchase = purchase.key();
+        hold.buyer = purchase.buyer;
+        hold.creator = purchase.seller;
+        hold.mint = listing.payment_mint;
+        hold.amount = final_price;
+        hold.platform_fee = platform_fee;
+        hold.referral = ctx.accounts.referral.as_ref().map(|referral| referral.key());
+        hold.commission = commission;
+        hold.release_at = purchase.purchased_at
+            .checked_add(DISPUTE_WINDOW_SECONDS)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        hold.disputed = false;
+        hold.bump = ctx.bumps.purchase_hold;
+        if let (Some(code), Some(referral)) = (referral_code, ctx.accounts.referral.as_ref()) {
+            emit!(ReferralCommissionPaid {
+                code,
+                referrer: referral.referrer,
+                listing_id: purchase.listing_id,
+                buyer: purchase.buyer,
+                commission,
+            });
+        }
+
+        let daily_stats = &mut ctx.accounts.daily_stats;
+        daily_stats.date = day_index(purchase.purchased_at)?;
+        daily_stats.purchases = daily_stats.purchases
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        daily_stats.revenue = daily_stats.revenue
+            .checked_add(final_price)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        daily_stats.record_buyer(&purchase.buyer);
+
+        let trending = &mut ctx.accounts.trending_index;
+        trending.epoch = trending_epoch(purchase.purchased_at)?;
+        trending.bump = ctx.bumps.trending_index;
+        trending.record_purchase(listing.listing_id)?;
+
+        let buyer_listing = &mut ctx.accounts.buyer_listing_record;
+        let first_purchase = buyer_listing.first_purchased_at == 0;
+        if first_purchase {
+            buyer_listing.buyer = purchase.buyer;
+            buyer_listing.listing = listing.key();
+            buyer_listing.first_purchased_at = purchase.purchased_at;
+            buyer_listing.bump = ctx.bumps.buyer_listing_record;
+        }
+        let analytics = &mut ctx.accounts.listing_analytics;
+        analytics.bump = ctx.bumps.listing_analytics;
+        analytics.record_purchase(listing.listing_id, final_price, first_purchase, purchase.purchased_at)?;
+
+        let history = &mut ctx.accounts.purchase_history;
+        if history.entry_count == 0 {
+            history.buyer = purchase.buyer;
+            history.bump = ctx.bumps.purchase_history;
+        }
+        history.append(listing.listing_id, purchase.purchased_at, final_price)?;
+
+        let creator_profile = &mut ctx.accounts.creator_profile;
+        creator_profile.total_earned = creator_profile.total_earned
+            .checked_add(creator_amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        let profile = &mut ctx.accounts.buyer_profile;
+        if profile.joined_at == 0 {
+            profile.buyer = purchase.buyer;
+            profile.joined_at = purchase.purchased_at;
+            profile.bump = ctx.bumps.buyer_profile;
+        }
+        profile.purchase_count = profile.purchase_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        profile.total_spent = profile.total_spent
+            .checked_add(final_price)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        profile.last_purchase_at = purchase.purchased_at;
+
+        emit!(BuyerProfileUpdated {
+            buyer: profile.buyer,
+            total_spent: profile.total_spent,
+            purchase_count: profile.purchase_count,
+            reputation_score: profile.reputation_score,
+        });
 
         emit!(ContentPurchased {
             listing_id: listing.listing_id,
@@ -108,233 +389,5685 @@ pub mod x402_registry {
             platform_fee,
         });
 
+        emit!(PaymentTokenUsed {
+            listing_id: listing.listing_id,
+            mint: listing.payment_mint,
+        });
+
+        if let Some(reference) = solana_pay_reference {
+            emit!(SolanaPayPurchase {
+                reference,
+                listing_id: listing.listing_id,
+                amount: final_price,
+            });
+        }
+
         msg!(
             "Content purchased: Listing={}, Buyer={}, Price={}", 
             listing.listing_id, purchase.buyer, final_price
         );
+
+        grant_purchase_access(ctx.accounts)
+    }
+
+    /// Group several of the creator's active listings into a bundle sold at one price
+    ///
+    /// remaining_accounts: the listing account for each id in `config.listing_ids`
+    pub fn create_bundle<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateBundle<'info>>,
+        config: BundleConfig,
+    ) -> Result<()> {
+        require!(!ctx.accounts.registry.is_paused, ErrorCode::RegistryPaused);
+        require!(
+            config.listing_ids.len() >= 2 && config.listing_ids.len() <= MAX_BUNDLE_LISTINGS,
+            ErrorCode::InvalidBundleSize
+        );
+        require!(config.bundle_name.len() <= MAX_BUNDLE_NAME_LEN, ErrorCode::BundleNameTooLong);
+        require!(config.bundle_price > 0, ErrorCode::InvalidPrice);
+        require!(
+            ctx.remaining_accounts.len() == config.listing_ids.len(),
+            ErrorCode::BundleListingMismatch
+        );
+
+        let creator = ctx.accounts.creator.key();
+        let mut payment_mint = None;
+        for (i, (listing_id, info)) in config.listing_ids.iter().zip(ctx.remaining_accounts).enumerate() {
+            require!(
+                !config.listing_ids[..i].contains(listing_id),
+                ErrorCode::BundleListingMismatch
+            );
+            let listing = Account::<ContentListing>::try_from(info)?;
+            require!(listing.listing_id == *listing_id, ErrorCode::BundleListingMismatch);
+            require!(listing.creator == creator, ErrorCode::Unauthorized);
+            require!(listing.state == ContentListingState::Active, ErrorCode::ListingInactive);
+            require!(
+                *payment_mint.get_or_insert(listing.payment_mint) == listing.payment_mint,
+                ErrorCode::PaymentMintMismatch
+            );
+        }
+
+        let profile = &mut ctx.accounts.creator_profile;
+        let bundle = &mut ctx.accounts.bundle;
+        bundle.bundle_id = profile.bundle_count;
+        bundle.creator = creator;
+        bundle.listing_ids = config.listing_ids;
+        bundle.bundle_price = config.bundle_price;
+        bundle.bundle_name = config.bundle_name;
+        bundle.payment_mint = payment_mint.ok_or(ErrorCode::InvalidBundleSize)?;
+        bundle.created_at = Clock::get()?.unix_timestamp;
+        bundle.purchase_count = 0;
+        bundle.bump = ctx.bumps.bundle;
+
+        profile.bundle_count = profile.bundle_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(BundleCreated {
+            bundle_id: bundle.bundle_id,
+            creator,
+            listing_ids: bundle.listing_ids.clone(),
+            bundle_price: bundle.bundle_price,
+        });
+
         Ok(())
     }
 
-    /// Update content listing
-    pub fn update_listing(
-        ctx: Context<UpdateListing>,
-        new_pricing: Option<PricingConfig>,
-        new_metadata: Option<ContentMetadata>,
-        is_active: Option<bool>,
+    /// Buy every listing in a bundle at the bundle price and grant access to each
+    ///
+    /// remaining_accounts: the access-controller RevokedCredential PDA for each
+    /// credential, then `[listing, purchase record, access permission, listing
+    /// analytics, buyer listing record, purchase hold, hold vault]` for each
+    /// listing in bundle order. Every listing passes purchase_content's checks;
+    /// the listings' shared fee model sets one platform fee on the bundle price,
+    /// and each listing's share is held for the dispute window like a single
+    /// purchase.
+    pub fn purchase_bundle<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PurchaseBundle<'info>>,
+        bundle_id: u64,
+        buyer_credentials: Vec<CredentialProof>,
     ) -> Result<()> {
-        let listing = &mut ctx.accounts.listing;
+        require!(!ctx.accounts.registry.is_paused, ErrorCode::RegistryPaused);
+        for proof in &buyer_credentials {
+            validate_credential_type(&proof.credential_type)?;
+        }
+
+        let bundle = &ctx.accounts.bundle;
+        let listing_count = bundle.listing_ids.len();
+        let (revocations, listing_accounts) = ctx.remaining_accounts
+            .split_at_checked(buyer_credentials.len())
+            .ok_or(ErrorCode::MissingRevocationAccount)?;
+        for (proof, revoked) in buyer_credentials.iter().zip(revocations) {
+            let (expected, _) = Pubkey::find_program_address(
+                &[b"revoked_cred", &credential_hash(proof)?],
+                &ACCESS_CONTROLLER_ID,
+            );
+            require_keys_eq!(revoked.key(), expected, ErrorCode::MissingRevocationAccount);
+            require!(revoked.data_is_empty(), ErrorCode::CredentialRevoked);
+        }
         require!(
-            ctx.accounts.creator.key() == listing.creator,
-            ErrorCode::Unauthorized
+            listing_accounts.len() == listing_count * BUNDLE_ACCOUNTS_PER_LISTING,
+            ErrorCode::BundleListingMismatch
         );
 
-        if let Some(pricing) = new_pricing {
-            require!(pricing.base_price > 0, ErrorCode::InvalidPrice);
-            listing.pricing = pricing;
+        // Attribute the bundle price evenly; the first listing takes the remainder
+        let share = bundle.bundle_price / listing_count as u64;
+        let remainder = bundle.bundle_price % listing_count as u64;
+        let prices: Vec<u64> = (0..listing_count)
+            .map(|i| if i == 0 { share + remainder } else { share })
+            .collect();
+        let buyer = ctx.accounts.buyer.key();
+        let now = Clock::get()?.unix_timestamp;
+        let staked = ctx.accounts.creator_stake.as_ref().map_or(0, |stake| stake.staked);
+        let grant_accounts = AccessGrantAccounts {
+            access_controller: ctx.accounts.access_controller.to_account_info(),
+            authorized_callers: ctx.accounts.authorized_callers.to_account_info(),
+            registry_program: ctx.accounts.registry_program.to_account_info(),
+            grant_authority: ctx.accounts.grant_authority.to_account_info(),
+            buyer: ctx.accounts.buyer.to_account_info(),
+            payer: ctx.accounts.buyer.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            access_controller_program: ctx.accounts.access_controller_program.to_account_info(),
+        };
+
+        // Each listing is checked as if bought through purchase_content
+        let mut listings = Vec::with_capacity(listing_count);
+        for (listing_id, group) in bundle.listing_ids
+            .iter()
+            .zip(listing_accounts.chunks(BUNDLE_ACCOUNTS_PER_LISTING))
+        {
+            let listing = Account::<ContentListing>::try_from(&group[0])?;
+            require!(listing.listing_id == *listing_id, ErrorCode::BundleListingMismatch);
+            require!(listing.creator == bundle.creator, ErrorCode::BundleListingMismatch);
+            require!(listing.state == ContentListingState::Active, ErrorCode::ListingInactive);
+            require!(
+                listing.report_count < ctx.accounts.registry.auto_pause_threshold,
+                ErrorCode::ListingUnderReview
+            );
+            require!(!listing.escrow_required, ErrorCode::EscrowRequired);
+            require!(listing.is_available(now), ErrorCode::OutsideAvailabilityWindow);
+            for attestation in &listing.zk_attestations {
+                attestation.check_fresh(now)?;
+            }
+            check_required_credentials(
+                &listing.required_credentials,
+                &buyer_credentials,
+                &buyer,
+                ctx.accounts.instructions_sysvar.as_ref().map(|sysvar| sysvar.as_ref()),
+                now,
+            )?;
+            listings.push(listing);
+        }
+        for price in &prices {
+            // The bundle discount may not take a listing below the registry floor
+            require!(*price >= ctx.accounts.registry.min_price_floor, ErrorCode::PriceBelowFloor);
         }
 
-        if let Some(metadata) = new_metadata {
-            require!(metadata.title.len() <= 128, ErrorCode::TitleTooLong);
-            require!(metadata.description.len() <= 512, ErrorCode::DescriptionTooLong);
-            listing.metadata = metadata;
+        // One platform fee on the bundle price, under the fee model every listing shares
+        let fee_model = &listings[0].fee_model;
+        require!(
+            listings.iter().all(|listing| listing.fee_model == *fee_model),
+            ErrorCode::BundleFeeModelMismatch
+        );
+        let platform_fee = platform_fee_for(fee_model, &ctx.accounts.registry, staked, bundle.bundle_price)?;
+        let creator_amount = bundle.bundle_price
+            .checked_sub(platform_fee)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        let listing_fees = split_bundle_fee(&prices, platform_fee)?;
+
+        let trending = &mut ctx.accounts.trending_index;
+        trending.epoch = trending_epoch(now)?;
+        trending.bump = ctx.bumps.trending_index;
+        let history = &mut ctx.accounts.purchase_history;
+        if history.entry_count == 0 {
+            history.buyer = buyer;
+            history.bump = ctx.bumps.purchase_history;
         }
+        for (((mut listing, group), price), listing_fee) in listings
+            .into_iter()
+            .zip(listing_accounts.chunks(BUNDLE_ACCOUNTS_PER_LISTING))
+            .zip(prices)
+            .zip(listing_fees)
+        {
+            let [listing_info, purchase_info, permission_info, analytics_info, buyer_listing_info, hold_info, hold_vault_info] = group else {
+                return err!(ErrorCode::BundleListingMismatch);
+            };
+
+            let record = PurchaseRecord {
+                listing_id: listing.listing_id,
+                buyer,
+                seller: listing.creator,
+                final_price: price,
+                purchased_at: now,
+                credentials_used: buyer_credentials.clone(),
+                access_granted: false,
+                solana_pay_reference: None,
+            };
+            create_purchase_record(
+                &record,
+                listing_info.key,
+                purchase_info,
+                &grant_accounts.buyer,
+                &grant_accounts.system_program,
+            )?;
+            grant_accounts.grant(
+                permission_info,
+                purchase_info,
+                listing_info,
+                listing.content_hash,
+            )?;
+            PurchaseRecord { access_granted: true, ..record }
+                .try_serialize(&mut &mut purchase_info.try_borrow_mut_data()?[..])?;
+
+            // Each listing's share sits in its own purchase hold, open to disputes
+            // like a purchase_content payment
+            let hold = PurchaseHold {
+                purchase: purchase_info.key(),
+                buyer,
+                creator: listing.creator,
+                mint: bundle.payment_mint,
+                amount: price,
+                platform_fee: listing_fee,
+                referral: None,
+                commission: 0,
+                release_at: now
+                    .checked_add(DISPUTE_WINDOW_SECONDS)
+                    .ok_or(ErrorCode::ArithmeticOverflow)?,
+                disputed: false,
+                bump: 0,
+            };
+            create_purchase_hold(
+                hold,
+                hold_info,
+                hold_vault_info,
+                &ctx.accounts.payment_mint,
+                &ctx.accounts.buyer_ata,
+                &grant_accounts.buyer,
+                &ctx.accounts.token_program,
+                &grant_accounts.system_program,
+            )?;
 
-        if let Some(active) = is_active {
-            listing.is_active = active;
+            let listing_key = listing.key();
+            let (mut buyer_listing, buyer_listing_bump) = load_or_create_pda::<BuyerListingRecord>(
+                buyer_listing_info,
+                &[b"buyer_listing", buyer.as_ref(), listing_key.as_ref()],
+                8 + BuyerListingRecord::LEN,
+                &grant_accounts.buyer,
+                &grant_accounts.system_program,
+            )?;
+            let first_purchase = buyer_listing.first_purchased_at == 0;
+            if first_purchase {
+                buyer_listing.buyer = buyer;
+                buyer_listing.listing = listing_key;
+                buyer_listing.first_purchased_at = now;
+                buyer_listing.bump = buyer_listing_bump;
+                buyer_listing.try_serialize(&mut &mut buyer_listing_info.try_borrow_mut_data()?[..])?;
+            }
+            let (mut analytics, analytics_bump) = load_or_create_pda::<ListingAnalyticsCache>(
+                analytics_info,
+                &[b"listing_analytics", listing_key.as_ref()],
+                8 + ListingAnalyticsCache::LEN,
+                &grant_accounts.buyer,
+                &grant_accounts.system_program,
+            )?;
+            analytics.bump = analytics_bump;
+            analytics.record_purchase(listing.listing_id, price, first_purchase, now)?;
+            analytics.try_serialize(&mut &mut analytics_info.try_borrow_mut_data()?[..])?;
+
+            trending.record_purchase(listing.listing_id)?;
+            history.append(listing.listing_id, now, price)?;
+
+            listing.purchase_count = listing.purchase_count
+                .checked_add(1)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+            listing.total_revenue = listing.total_revenue
+                .checked_add(price)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+            listing.updated_at = now;
+            listing.exit(&crate::ID)?;
         }
 
-        listing.updated_at = Clock::get()?.unix_timestamp;
+        let registry = &mut ctx.accounts.registry;
+        registry.total_revenue = registry.total_revenue
+            .checked_add(platform_fee)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
 
-        emit!(ListingUpdated {
-            listing_id: listing.listing_id,
-            creator: listing.creator,
-            updated_at: listing.updated_at,
+        let daily_stats = &mut ctx.accounts.daily_stats;
+        daily_stats.date = day_index(now)?;
+        daily_stats.purchases = daily_stats.purchases
+            .checked_add(listing_count as u32)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        daily_stats.revenue = daily_stats.revenue
+            .checked_add(bundle.bundle_price)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        daily_stats.record_buyer(&buyer);
+
+        let creator_profile = &mut ctx.accounts.creator_profile;
+        creator_profile.total_earned = creator_profile.total_earned
+            .checked_add(creator_amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        let profile = &mut ctx.accounts.buyer_profile;
+        if profile.joined_at == 0 {
+            profile.buyer = buyer;
+            profile.joined_at = now;
+            profile.bump = ctx.bumps.buyer_profile;
+        }
+        profile.purchase_count = profile.purchase_count
+            .checked_add(listing_count as u64)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        profile.total_spent = profile.total_spent
+            .checked_add(bundle.bundle_price)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        profile.last_purchase_at = now;
+
+        let bundle = &mut ctx.accounts.bundle;
+        bundle.purchase_count = bundle.purchase_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(BundlePurchased {
+            bundle_id,
+            creator: bundle.creator,
+            buyer,
+            listing_count: listing_count as u8,
+            bundle_price: bundle.bundle_price,
+            platform_fee,
         });
 
         Ok(())
     }
 
-    /// Set platform fee (admin only)
-    pub fn set_platform_fee(
-        ctx: Context<SetPlatformFee>,
-        new_fee_bps: u16,
+    /// Register a referral code paying the caller `commission_bps` of referred purchases
+    pub fn create_referral_code(
+        ctx: Context<CreateReferralCode>,
+        code: [u8; 8],
+        commission_bps: u16,
     ) -> Result<()> {
         require!(
-            ctx.accounts.authority.key() == ctx.accounts.registry.authority,
-            ErrorCode::Unauthorized
+            commission_bps > 0 && commission_bps <= MAX_REFERRAL_COMMISSION_BPS,
+            ErrorCode::InvalidReferralCommission
         );
-        require!(new_fee_bps <= 1000, ErrorCode::FeeTooHigh); // Max 10%
 
-        let registry = &mut ctx.accounts.registry;
-        let old_fee = registry.platform_fee_bps;
-        registry.platform_fee_bps = new_fee_bps;
+        let referral = &mut ctx.accounts.referral;
+        referral.code = code;
+        referral.referrer = ctx.accounts.referrer.key();
+        referral.commission_bps = commission_bps;
+        referral.uses = 0;
+        referral.total_earned = 0;
+        referral.bump = ctx.bumps.referral;
 
-        emit!(PlatformFeeUpdated {
-            old_fee_bps: old_fee,
-            new_fee_bps,
-            updated_by: ctx.accounts.authority.key(),
+        msg!("Referral code created for {} at {} bps", referral.referrer, commission_bps);
+        Ok(())
+    }
+
+    /// Lock the price of an escrow-mode listing until the creator confirms delivery
+    ///
+    /// Priced exactly like `purchase_content` (same remaining_accounts layout); the
+    /// payment-mint tokens sit in the escrow's token account until release.
+    pub fn initiate_purchase(
+        ctx: Context<InitiatePurchase>,
+        buyer_credentials: Vec<CredentialProof>,
+        referral_code: Option<[u8; 8]>,
+    ) -> Result<()> {
+        require!(!ctx.accounts.registry.is_paused, ErrorCode::RegistryPaused);
+        let listing = &ctx.accounts.listing;
+        require!(listing.escrow_required, ErrorCode::EscrowNotRequired);
+        require!(listing.state == ContentListingState::Active, ErrorCode::ListingInactive);
+        let now = Clock::get()?.unix_timestamp;
+        require!(listing.is_available(now), ErrorCode::OutsideAvailabilityWindow);
+        require!(
+            listing.report_count < ctx.accounts.registry.auto_pause_threshold,
+            ErrorCode::ListingUnderReview
+        );
+        for attestation in &listing.zk_attestations {
+            attestation.check_fresh(now)?;
+        }
+
+        let buyer = ctx.accounts.buyer.key();
+        check_required_credentials(
+            &listing.required_credentials,
+            &buyer_credentials,
+            &buyer,
+            ctx.accounts.instructions_sysvar.as_ref().map(|sysvar| sysvar.as_ref()),
+            now,
+        )?;
+        let final_price = quote_listing_price(
+            listing,
+            ctx.accounts.registry.min_price_floor,
+            ctx.accounts.pyth_feed.as_ref().map(|feed| feed.as_ref()),
+            &buyer,
+            &buyer_credentials,
+            ctx.remaining_accounts,
+            now,
+        )?;
+
+        // The commission is fixed now and paid out of the escrow on delivery
+        let referral = match referral_code {
+            Some(_) => Some(
+                ctx.accounts.referral
+                    .as_ref()
+                    .ok_or(ErrorCode::ReferralAccountsMissing)?,
+            ),
+            None => None,
+        };
+        let commission = match referral {
+            Some(referral) => referral_commission(referral, &buyer, final_price)?,
+            None => 0,
+        };
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.buyer_ata.to_account_info(),
+                    to: ctx.accounts.escrow_vault.to_account_info(),
+                    authority: ctx.accounts.buyer.to_account_info(),
+                },
+            ),
+            final_price,
+        )?;
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.listing = listing.key();
+        escrow.buyer = buyer;
+        escrow.creator = listing.creator;
+        escrow.mint = listing.payment_mint;
+        escrow.amount = final_price;
+        escrow.referral = referral.map(|referral| referral.key());
+        escrow.commission = commission;
+        escrow.opened_at = now;
+        escrow.bump = ctx.bumps.escrow;
+
+        emit!(EscrowOpened {
+            listing_id: listing.listing_id,
+            buyer: escrow.buyer,
+            creator: escrow.creator,
+            amount: final_price,
+            opened_at: escrow.opened_at,
         });
 
         Ok(())
     }
-}
 
-// Helper function for dynamic pricing
-fn calculate_price_with_discounts(
-    pricing: &PricingConfig,
-    requirements: &[CredentialRequirement],
-    proofs: &[CredentialProof],
-) -> Result<u64> {
-    let mut final_price = pricing.base_price;
+    /// Creator confirms delivery: move the escrow into a purchase hold and grant access
+    ///
+    /// Needs no buyer signature; the creator pays for the purchase record and access permission.
+    /// The price stays open to disputes for DISPUTE_WINDOW_SECONDS, then
+    /// release_purchase_hold pays the creator, platform and referrer.
+    pub fn confirm_delivery(ctx: Context<ConfirmDelivery>) -> Result<()> {
+        require!(!ctx.accounts.registry.is_paused, ErrorCode::RegistryPaused);
+        let amount = ctx.accounts.escrow.amount;
+        let commission = ctx.accounts.escrow.commission;
+        let registry = &mut ctx.accounts.registry;
+        let staked = ctx.accounts.creator_stake.as_ref().map_or(0, |stake| stake.staked);
+        let platform_fee =
+            platform_fee_for(&ctx.accounts.listing.fee_model, registry, staked, amount)?;
+        // The hold pays the creator what is left after the fee and commission
+        require!(
+            platform_fee.checked_add(commission).is_some_and(|cut| cut <= amount),
+            ErrorCode::ArithmeticOverflow
+        );
+        registry.total_revenue = registry.total_revenue
+            .checked_add(platform_fee)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
 
-    // Apply credential-based discounts
-    for req in requirements {
-        if let Some(_proof) = proofs.iter().find(|p| p.credential_type == req.credential_type) {
-            let discount = pricing.credential_discounts
-                .iter()
-                .find(|d| d.credential_type == req.credential_type)
-                .map(|d| d.discount_bps)
-                .unwrap_or(0);
-            
-            if discount > 0 {
-                let discount_amount = (final_price * discount as u64) / 10000;
-                final_price = final_price.saturating_sub(discount_amount);
-            }
+        let listing_key = ctx.accounts.listing.key();
+        let buyer_key = ctx.accounts.buyer.key();
+        let bump = [ctx.accounts.escrow.bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"escrow", listing_key.as_ref(), buyer_key.as_ref(), &bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.escrow_vault.to_account_info(),
+                    to: ctx.accounts.hold_vault.to_account_info(),
+                    authority: ctx.accounts.escrow.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+        // The creator funded the hold, whose rent goes to the buyer on release,
+        // so the buyer's escrow rent goes to the creator
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::CloseAccount {
+                account: ctx.accounts.escrow_vault.to_account_info(),
+                destination: ctx.accounts.creator.to_account_info(),
+                authority: ctx.accounts.escrow.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+        if commission > 0 {
+            let referral = ctx.accounts.referral
+                .as_mut()
+                .ok_or(ErrorCode::ReferralAccountsMissing)?;
+            referral.record_use(commission)?;
+
+            emit!(ReferralCommissionPaid {
+                code: referral.code,
+                referrer: referral.referrer,
+                listing_id: ctx.accounts.listing.listing_id,
+                buyer: buyer_key,
+                commission,
+            });
+        }
+
+        let now = Clock::get()?.unix_timestamp;
+        let purchase = &mut ctx.accounts.purchase;
+        purchase.listing_id = ctx.accounts.listing.listing_id;
+        purchase.buyer = ctx.accounts.buyer.key();
+        purchase.seller = ctx.accounts.creator.key();
+        purchase.final_price = amount;
+        purchase.purchased_at = now;
+        purchase.credentials_used = Vec::new();
+        purchase.access_granted = false;
+        purchase.solana_pay_reference = None;
+        purchase.exit(&crate::ID)?;
+
+        let hold = &mut ctx.accounts.purchase_hold;
+        hold.purchase = purchase.key();
+        hold.buyer = buyer_key;
+        hold.creator = ctx.accounts.creator.key();
+        hold.mint = ctx.accounts.escrow.mint;
+        hold.amount = amount;
+        hold.platform_fee = platform_fee;
+        hold.referral = ctx.accounts.escrow.referral;
+        hold.commission = commission;
+        hold.release_at = now
+            .checked_add(DISPUTE_WINDOW_SECONDS)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        hold.disputed = false;
+        hold.bump = ctx.bumps.purchase_hold;
+
+        AccessGrantAccounts {
+            access_controller: ctx.accounts.access_controller.to_account_info(),
+            authorized_callers: ctx.accounts.authorized_callers.to_account_info(),
+            registry_program: ctx.accounts.registry_program.to_account_info(),
+            grant_authority: ctx.accounts.grant_authority.to_account_info(),
+            buyer: ctx.accounts.buyer.to_account_info(),
+            payer: ctx.accounts.creator.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            access_controller_program: ctx.accounts.access_controller_program.to_account_info(),
         }
+        .grant(
+            &ctx.accounts.access_permission.to_account_info(),
+            &ctx.accounts.purchase.to_account_info(),
+            &ctx.accounts.listing.to_account_info(),
+            ctx.accounts.listing.content_hash,
+        )?;
+        ctx.accounts.purchase.access_granted = true;
+
+        let listing = &mut ctx.accounts.listing;
+        listing.purchase_count = listing.purchase_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        listing.total_revenue = listing.total_revenue
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        listing.updated_at = now;
+
+        emit!(EscrowReleased {
+            listing_id: listing.listing_id,
+            buyer: ctx.accounts.buyer.key(),
+            creator: listing.creator,
+            amount,
+            platform_fee,
+            released_at: now,
+        });
+
+        Ok(())
     }
 
-    // Apply volume discounts
-    if let Some(_volume_discount) = &pricing.volume_discount {
-        // Volume discount logic would check purchase history
-        // For now, just apply if buyer has required volume
-        // This would be implemented with purchase history tracking
+    /// Return an unconfirmed escrow to the buyer once ESCROW_TIMEOUT_SECONDS have passed
+    pub fn release_escrow(ctx: Context<ReleaseEscrow>) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+        let now = Clock::get()?.unix_timestamp;
+        require!(
+            now >= escrow.opened_at.saturating_add(ESCROW_TIMEOUT_SECONDS),
+            ErrorCode::EscrowNotExpired
+        );
+
+        let bump = [escrow.bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"escrow", escrow.listing.as_ref(), escrow.buyer.as_ref(), &bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.escrow_vault.to_account_info(),
+                    to: ctx.accounts.buyer_ata.to_account_info(),
+                    authority: escrow.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            escrow.amount,
+        )?;
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::CloseAccount {
+                account: ctx.accounts.escrow_vault.to_account_info(),
+                destination: ctx.accounts.buyer.to_account_info(),
+                authority: escrow.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+
+        emit!(EscrowRefunded {
+            listing: escrow.listing,
+            buyer: escrow.buyer,
+            amount: escrow.amount,
+            refunded_at: now,
+        });
+
+        Ok(())
     }
 
-    Ok(final_price)
-}
+    /// Offer a discount on the creator's target listing to buyers of the source listing
+    pub fn register_cross_discount(
+        ctx: Context<RegisterCrossDiscount>,
+        source_listing_id: u64,
+        target_listing_id: u64,
+        discount_bps: u16,
+    ) -> Result<()> {
+        require!(source_listing_id != target_listing_id, ErrorCode::CrossDiscountMismatch);
+        require!(discount_bps > 0 && discount_bps <= 10000, ErrorCode::InvalidDiscount);
 
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + X402Registry::LEN,
-        seeds = [b"x402_registry"],
-        bump
-    )]
-    pub registry: Account<'info, X402Registry>,
-    
-    #[account(mut)]
-    pub authority: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        let cross = &mut ctx.accounts.cross_discount;
+        cross.source_listing = ctx.accounts.source_listing.key();
+        cross.target_listing = ctx.accounts.target_listing.key();
+        cross.discount_bps = discount_bps;
+        cross.created_at = Clock::get()?.unix_timestamp;
+        cross.bump = ctx.bumps.cross_discount;
 
-#[derive(Accounts)]
-#[instruction(content_hash: [u8; 32])]
-pub struct RegisterContent<'info> {
-    #[account(mut)]
-    pub registry: Account<'info, X402Registry>,
-    
-    #[account(
-        init,
-        payer = creator,
-        space = 8 + ContentListing::LEN,
-        seeds = [b"listing", registry.listing_count.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub listing: Account<'info, ContentListing>,
-    
-    #[account(mut)]
-    pub creator: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        emit!(CrossDiscountRegistered {
+            source_listing_id,
+            target_listing_id,
+            creator: ctx.accounts.creator.key(),
+            discount_bps,
+        });
 
-#[derive(Accounts)]
-pub struct PurchaseContent<'info> {
-    #[account(mut)]
-    pub registry: Account<'info, X402Registry>,
-    
-    #[account(mut)]
-    pub listing: Account<'info, ContentListing>,
-    
-    #[account(
-        init,
-        payer = buyer,
-        space = 8 + PurchaseRecord::LEN,
-        seeds = [b"purchase", listing.key().as_ref(), buyer.key().as_ref()],
-        bump
-    )]
-    pub purchase: Account<'info, PurchaseRecord>,
-    
-    #[account(mut)]
-    pub buyer: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+        Ok(())
+    }
 
-#[derive(Accounts)]
-pub struct UpdateListing<'info> {
-    #[account(mut)]
-    pub listing: Account<'info, ContentListing>,
-    
-    pub creator: Signer<'info>,
-}
+    /// Rate and review a purchased listing (one review per buyer)
+    pub fn submit_review(
+        ctx: Context<SubmitReview>,
+        listing_id: u64,
+        rating: u8,
+        review_text: String,
+    ) -> Result<()> {
+        require!((1..=5).contains(&rating), ErrorCode::InvalidRating);
+        require!(review_text.len() <= 512, ErrorCode::ReviewTooLong);
+
+        let review = &mut ctx.accounts.review;
+        require!(review.created_at == 0, ErrorCode::AlreadyReviewed);
+        review.listing = ctx.accounts.listing.key();
+        review.buyer = ctx.accounts.buyer.key();
+        review.rating = rating;
+        review.review_text = review_text;
+        review.created_at = Clock::get()?.unix_timestamp;
+        review.bump = ctx.bumps.review;
+
+        let listing = &mut ctx.accounts.listing;
+        let new_count = listing.review_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        let total = (listing.average_rating as u64)
+            .checked_mul(listing.review_count as u64)
+            .and_then(|t| t.checked_add(rating as u64 * 100))
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        listing.average_rating = (total / new_count as u64) as u32;
+        listing.review_count = new_count;
+
+        emit!(ReviewSubmitted {
+            listing_id,
+            buyer: review.buyer,
+            rating,
+            average_rating: listing.average_rating,
+        });
+
+        Ok(())
+    }
+
+    /// Flag a purchased listing for moderation; one report per buyer
+    pub fn report_listing(
+        ctx: Context<ReportListing>,
+        listing_id: u64,
+        category: ReportCategory,
+        description: String,
+    ) -> Result<()> {
+        require!(description.len() <= 256, ErrorCode::ReportTooLong);
+
+        let report = &mut ctx.accounts.report;
+        report.listing = ctx.accounts.listing.key();
+        report.reporter = ctx.accounts.reporter.key();
+        report.category = category.clone();
+        report.description = description;
+        report.created_at = Clock::get()?.unix_timestamp;
+        report.bump = ctx.bumps.report;
+
+        let listing = &mut ctx.accounts.listing;
+        listing.report_count = listing.report_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(ListingReported {
+            listing_id,
+            reporter: report.reporter,
+            category,
+        });
+
+        Ok(())
+    }
+
+    /// Dismiss a report and lift its weight from the listing (admin only)
+    pub fn dismiss_report(ctx: Context<DismissReport>) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.registry.authority,
+            ErrorCode::Unauthorized
+        );
+
+        let listing = &mut ctx.accounts.listing;
+        listing.report_count = listing.report_count.saturating_sub(1);
+
+        msg!(
+            "Report by {} on listing {} dismissed",
+            ctx.accounts.report.reporter, listing.listing_id
+        );
+        Ok(())
+    }
+
+    /// Tip a listing's creator; lamports go to the creator's vault
+    pub fn tip_creator(ctx: Context<TipCreator>, listing_id: u64, amount: u64) -> Result<()> {
+        require!(amount >= MIN_TIP_LAMPORTS, ErrorCode::TipTooSmall);
+
+        let creator = ctx.accounts.listing.creator;
+        let vault = &mut ctx.accounts.creator_vault;
+        if vault.creator == Pubkey::default() {
+            vault.creator = creator;
+            vault.bump = ctx.bumps.creator_vault;
+        }
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.tipper.to_account_info(),
+                    to: ctx.accounts.creator_vault.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let profile = &mut ctx.accounts.creator_profile;
+        profile.total_tips_received = profile.total_tips_received
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(CreatorTipped {
+            listing_id,
+            tipper: ctx.accounts.tipper.key(),
+            creator,
+            amount,
+            tipped_at: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw lamports held in the caller's creator vault
+    pub fn withdraw_from_vault(ctx: Context<WithdrawFromVault>, amount: u64) -> Result<()> {
+        let vault_info = ctx.accounts.creator_vault.to_account_info();
+        let rent_minimum = Rent::get()?.minimum_balance(vault_info.data_len());
+        let available = vault_info.lamports().saturating_sub(rent_minimum);
+        require!(amount <= available, ErrorCode::InsufficientVaultBalance);
+
+        **vault_info.try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.creator.to_account_info().try_borrow_mut_lamports()? += amount;
+
+        msg!("Creator {} withdrew {} lamports from vault", ctx.accounts.creator.key(), amount);
+        Ok(())
+    }
+
+    /// Withdraw SPL tokens held by the caller's creator vault
+    pub fn withdraw_vault_tokens(ctx: Context<WithdrawVaultTokens>, amount: u64) -> Result<()> {
+        require!(
+            amount <= ctx.accounts.vault_token.amount,
+            ErrorCode::InsufficientVaultBalance
+        );
+
+        let creator = ctx.accounts.creator.key();
+        let bump = [ctx.accounts.creator_vault.bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"creator_vault", creator.as_ref(), &bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.vault_token.to_account_info(),
+                    to: ctx.accounts.creator_token.to_account_info(),
+                    authority: ctx.accounts.creator_vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        msg!("Creator {} withdrew {} tokens from vault", creator, amount);
+        Ok(())
+    }
+
+    /// Open a dispute on a purchase while its price is still in the purchase hold
+    ///
+    /// Freezes the hold until the registry authority resolves the dispute; only
+    /// possible before the hold's DISPUTE_WINDOW_SECONDS run out.
+    pub fn open_dispute(ctx: Context<OpenDispute>, purchase_key: Pubkey, reason: String) -> Result<()> {
+        require!(reason.len() <= 256, ErrorCode::ReasonTooLong);
+
+        let now = Clock::get()?.unix_timestamp;
+        let hold = &mut ctx.accounts.purchase_hold;
+        hold.check_disputable(now)?;
+        hold.disputed = true;
+
+        let disputed_amount = hold.amount;
+        let dispute = &mut ctx.accounts.dispute;
+        dispute.purchase = purchase_key;
+        dispute.buyer = hold.buyer;
+        dispute.creator = hold.creator;
+        dispute.mint = hold.mint;
+        dispute.disputed_amount = disputed_amount;
+        dispute.reason = reason;
+        dispute.status = DisputeStatus::Open;
+        dispute.opened_at = now;
+        dispute.bump = ctx.bumps.dispute;
+
+        emit!(DisputeOpened {
+            purchase: purchase_key,
+            buyer: dispute.buyer,
+            creator: dispute.creator,
+            disputed_amount,
+        });
+
+        Ok(())
+    }
+
+    /// Settle an open dispute (registry authority only)
+    pub fn resolve_dispute(ctx: Context<ResolveDispute>, verdict: DisputeVerdict) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.registry.authority,
+            ErrorCode::Unauthorized
+        );
+
+        let dispute = &mut ctx.accounts.dispute;
+        require!(dispute.status == DisputeStatus::Open, ErrorCode::DisputeNotOpen);
+        let amount = dispute.disputed_amount;
+
+        match verdict {
+            DisputeVerdict::BuyerWins => {
+                // A refunded buyer must not keep the content: the authority revokes the
+                // permission through access_controller::revoke_access first
+                require!(
+                    access_revoked(&ctx.accounts.access_permission)?,
+                    ErrorCode::AccessNotRevoked
+                );
+                dispute.status = DisputeStatus::RefundedToBuyer;
+
+                // Undo what the sale added to the listing, registry and creator stats
+                let hold = &ctx.accounts.purchase_hold;
+                let listing = &mut ctx.accounts.listing;
+                listing.purchase_count = listing.purchase_count.saturating_sub(1);
+                listing.total_revenue = listing.total_revenue.saturating_sub(hold.amount);
+                let registry = &mut ctx.accounts.registry;
+                registry.total_revenue = registry.total_revenue.saturating_sub(hold.platform_fee);
+                let creator_profile = &mut ctx.accounts.creator_profile;
+                creator_profile.total_earned = creator_profile.total_earned
+                    .saturating_sub(hold.creator_amount()?);
+                ctx.accounts.purchase.access_granted = false;
+
+                let refund = [(ctx.accounts.buyer_ata.to_account_info(), amount)];
+                settle_purchase_hold(
+                    &ctx.accounts.purchase_hold,
+                    &ctx.accounts.hold_vault,
+                    &refund,
+                    &ctx.accounts.buyer,
+                    &ctx.accounts.token_program,
+                )?;
+            }
+            DisputeVerdict::CreatorWins => {
+                dispute.status = DisputeStatus::ReleasedToCreator;
+                let payouts = ctx.accounts.purchase_hold.payouts(
+                    &ctx.accounts.creator_ata,
+                    &ctx.accounts.platform_fee_ata,
+                    ctx.accounts.referrer_ata.as_ref(),
+                )?;
+                settle_purchase_hold(
+                    &ctx.accounts.purchase_hold,
+                    &ctx.accounts.hold_vault,
+                    &payouts,
+                    &ctx.accounts.buyer,
+                    &ctx.accounts.token_program,
+                )?;
+            }
+        }
+
+        let dispute = &ctx.accounts.dispute;
+        emit!(DisputeResolved {
+            purchase: dispute.purchase,
+            verdict,
+            amount,
+            resolved_at: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Pay out an undisputed purchase hold once its dispute window has closed (permissionless)
+    pub fn release_purchase_hold(ctx: Context<ReleasePurchaseHold>) -> Result<()> {
+        let hold = &ctx.accounts.purchase_hold;
+        let now = Clock::get()?.unix_timestamp;
+        hold.check_releasable(now)?;
+
+        let payouts = hold.payouts(
+            &ctx.accounts.creator_ata,
+            &ctx.accounts.platform_fee_ata,
+            ctx.accounts.referrer_ata.as_ref(),
+        )?;
+        settle_purchase_hold(
+            hold,
+            &ctx.accounts.hold_vault,
+            &payouts,
+            &ctx.accounts.buyer,
+            &ctx.accounts.token_program,
+        )?;
+
+        emit!(PurchaseHoldReleased {
+            purchase: hold.purchase,
+            creator: hold.creator,
+            amount: hold.amount,
+            released_at: now,
+        });
+
+        Ok(())
+    }
+
+    /// Grow a listing account so it can hold more credentials or tags (creator only)
+    pub fn reallocate_listing(ctx: Context<ReallocateListing>, new_space: u64) -> Result<()> {
+        require!(new_space <= MAX_LISTING_SIZE, ErrorCode::ListingTooLarge);
+
+        let listing_info = ctx.accounts.listing.to_account_info();
+        let new_space = new_space as usize;
+        require!(new_space >= listing_info.data_len(), ErrorCode::ListingShrinkNotAllowed);
+
+        let required_lamports = Rent::get()?.minimum_balance(new_space);
+        let top_up = required_lamports.saturating_sub(listing_info.lamports());
+        if top_up > 0 {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    Transfer {
+                        from: ctx.accounts.creator.to_account_info(),
+                        to: listing_info.clone(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+
+        listing_info.resize(new_space)?;
+
+        msg!("Listing {} reallocated to {} bytes", ctx.accounts.listing.listing_id, new_space);
+        Ok(())
+    }
+
+    /// Stake lamports as a creator; listing requires `min_creator_stake`
+    pub fn stake_as_creator(ctx: Context<StakeAsCreator>, amount: u64) -> Result<()> {
+        require!(amount > 0, ErrorCode::InvalidStakeAmount);
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.creator.to_account_info(),
+                    to: ctx.accounts.creator_stake.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let stake = &mut ctx.accounts.creator_stake;
+        stake.creator = ctx.accounts.creator.key();
+        stake.staked = stake.staked
+            .checked_add(amount)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        stake.staked_at = Clock::get()?.unix_timestamp;
+        stake.bump = ctx.bumps.creator_stake;
+
+        emit!(CreatorStaked {
+            creator: stake.creator,
+            amount,
+            total_staked: stake.staked,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw stake once the creator has no active listings
+    pub fn unstake(ctx: Context<Unstake>, amount: u64) -> Result<()> {
+        // The profile PDA is always passed; a creator who never listed has none yet
+        let profile_info = &ctx.accounts.creator_profile;
+        if profile_info.owner == &crate::ID && !profile_info.data_is_empty() {
+            let profile = CreatorProfile::try_deserialize(&mut &profile_info.try_borrow_data()?[..])?;
+            require!(profile.active_listing_count == 0, ErrorCode::ActiveListingsExist);
+        }
+
+        let stake = &mut ctx.accounts.creator_stake;
+        require!(amount > 0 && amount <= stake.staked, ErrorCode::InvalidStakeAmount);
+        stake.staked -= amount;
+
+        **stake.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.creator.to_account_info().try_borrow_mut_lamports()? += amount;
+
+        emit!(CreatorUnstaked {
+            creator: stake.creator,
+            amount,
+            remaining_staked: stake.staked,
+        });
+
+        Ok(())
+    }
+
+    /// Release a content hash held by the listing so it can be registered again
+    ///
+    /// The current hash needs the listing archived; a hash the listing has since
+    /// been upgraded away from is no longer served and can be released any time.
+    pub fn deregister_content_hash(ctx: Context<DeregisterContentHash>, content_hash: [u8; 32]) -> Result<()> {
+        require!(
+            content_hash != ctx.accounts.listing.content_hash
+                || ctx.accounts.listing.state == ContentListingState::Archived,
+            ErrorCode::ListingStillActive
+        );
+
+        msg!(
+            "Content hash released from listing {}",
+            ctx.accounts.unique_hash.listing_id
+        );
+        Ok(())
+    }
+
+    /// Update content listing
+    pub fn update_listing(
+        ctx: Context<UpdateListing>,
+        new_pricing: Option<PricingConfig>,
+        new_metadata: Option<ContentMetadata>,
+        new_state: Option<ContentListingState>,
+        new_secondary_royalty_bps: Option<u16>,
+        new_escrow_required: Option<bool>,
+        new_fee_model: Option<FeeModel>,
+    ) -> Result<()> {
+        let listing = &mut ctx.accounts.listing;
+        require!(
+            ctx.accounts.creator.key() == listing.creator
+                || ctx.accounts.creator.key() == ctx.accounts.registry.operator,
+            ErrorCode::Unauthorized
+        );
+        // The operator may only suspend or unsuspend; terms stay with the creator
+        if ctx.accounts.creator.key() != listing.creator {
+            require!(
+                new_pricing.is_none()
+                    && new_metadata.is_none()
+                    && new_secondary_royalty_bps.is_none()
+                    && new_escrow_required.is_none()
+                    && new_fee_model.is_none()
+                    && matches!(
+                        (&listing.state, &new_state),
+                        (ContentListingState::Active, Some(ContentListingState::Paused))
+                            | (ContentListingState::Paused, Some(ContentListingState::Active))
+                    ),
+                ErrorCode::OperatorStatusOnly
+            );
+        }
+
+        if let Some(pricing) = new_pricing {
+            require!(pricing.base_price > 0, ErrorCode::InvalidPrice);
+            check_price_floor(&pricing, ctx.accounts.registry.min_price_floor)?;
+            validate_discount_whitelist(&pricing)?;
+            require!(
+                pricing.max_total_discount_bps <= MAX_TOTAL_DISCOUNT_CAP_BPS,
+                ErrorCode::DiscountCapTooHigh
+            );
+            for discount in &pricing.credential_discounts {
+                validate_credential_type(&discount.credential_type)?;
+            }
+            require!(
+                pricing.price_denomination == PriceDenomination::Lamports
+                    || listing.payment_mint == native_mint::ID,
+                ErrorCode::UsdPricingRequiresNativeMint
+            );
+            listing.pricing = pricing;
+        }
+
+        if let Some(metadata) = new_metadata {
+            require!(metadata.title.len() <= 128, ErrorCode::TitleTooLong);
+            require!(metadata.description.len() <= 512, ErrorCode::DescriptionTooLong);
+            validate_ipfs_cid(metadata.ipfs_cid.as_deref())?;
+            validate_content_type(&metadata)?;
+            validate_delivery_channel(
+                &metadata.delivery_channel,
+                &listing.content_hash,
+                &listing.hash_algorithm,
+            )?;
+            listing.metadata = metadata;
+        }
+
+        if let Some(royalty_bps) = new_secondary_royalty_bps {
+            require!(
+                royalty_bps <= MAX_SECONDARY_ROYALTY_BPS,
+                ErrorCode::RoyaltyTooHigh
+            );
+            listing.secondary_royalty_bps = royalty_bps;
+        }
+
+        if let Some(escrow_required) = new_escrow_required {
+            // Escrow vaults are token accounts in the listing's payment mint
+            listing.escrow_required = escrow_required;
+        }
+
+        if let Some(fee_model) = new_fee_model {
+            if let FeeModel::PercentageFee { bps } = fee_model {
+                require!(
+                    bps >= ctx.accounts.registry.platform_fee_bps && bps <= 10000,
+                    ErrorCode::InvalidFeeModel
+                );
+            }
+            listing.fee_model = fee_model;
+        }
+
+        listing.updated_at = Clock::get()?.unix_timestamp;
+
+        if let Some(new_state) = new_state {
+            let old_state = listing.state.clone();
+            require!(
+                old_state.can_transition_to(&new_state),
+                ErrorCode::InvalidStateTransition
+            );
+
+            let profile = &mut ctx.accounts.creator_profile;
+            if new_state == ContentListingState::Active {
+                // Resuming sales needs the same stake as listing in the first place
+                let staked = ctx.accounts.creator_stake.as_ref().map_or(0, |stake| stake.staked);
+                require!(
+                    staked >= ctx.accounts.registry.min_creator_stake,
+                    ErrorCode::InsufficientStake
+                );
+                profile.active_listing_count = profile.active_listing_count
+                    .checked_add(1)
+                    .ok_or(ErrorCode::ArithmeticOverflow)?;
+            } else if old_state == ContentListingState::Active {
+                profile.active_listing_count = profile.active_listing_count.saturating_sub(1);
+            }
+            listing.state = new_state.clone();
+
+            emit!(ListingStateChanged {
+                listing_id: listing.listing_id,
+                old_state,
+                new_state,
+                changed_at: listing.updated_at,
+            });
+        }
+
+        emit!(ListingUpdated {
+            listing_id: listing.listing_id,
+            creator: listing.creator,
+            updated_at: listing.updated_at,
+        });
+
+        Ok(())
+    }
+
+    /// Point a listing at a new content version, keeping the last
+    /// MAX_VERSION_HISTORY hashes so buyers can tell which version they hold
+    pub fn upgrade_content(
+        ctx: Context<UpgradeContent>,
+        new_content_hash: [u8; 32],
+        changelog: String,
+        new_delivery_channel: Option<DeliveryChannel>,
+    ) -> Result<()> {
+        let listing = &mut ctx.accounts.listing;
+        require!(listing.state != ContentListingState::Archived, ErrorCode::ListingArchived);
+        require!(
+            new_content_hash != [0u8; 32] && new_content_hash != listing.content_hash,
+            ErrorCode::InvalidContentHash
+        );
+        require!(changelog.len() <= MAX_CHANGELOG_LEN, ErrorCode::ChangelogTooLong);
+
+        // The locator must match the new hash, so Sha256 listings usually need a new one
+        let delivery_channel = new_delivery_channel.unwrap_or(listing.metadata.delivery_channel.clone());
+        validate_delivery_channel(&delivery_channel, &new_content_hash, &listing.hash_algorithm)?;
+
+        let now = Clock::get()?.unix_timestamp;
+        listing.content_hash = new_content_hash;
+        listing.metadata.delivery_channel = delivery_channel;
+        listing.updated_at = now;
+
+        let unique_hash = &mut ctx.accounts.new_unique_hash;
+        unique_hash.listing_id = listing.listing_id;
+        unique_hash.creator = listing.creator;
+        unique_hash.bump = ctx.bumps.new_unique_hash;
+
+        let history = &mut ctx.accounts.version_history;
+        if history.listing == Pubkey::default() {
+            history.listing = listing.key();
+            history.bump = ctx.bumps.version_history;
+        }
+        history.record(new_content_hash, changelog, now)?;
+
+        emit!(VersionHistoryUpdated {
+            listing_id: listing.listing_id,
+            entries_count: history.entries.len() as u8,
+        });
+        emit!(ListingUpdated {
+            listing_id: listing.listing_id,
+            creator: listing.creator,
+            updated_at: now,
+        });
+        Ok(())
+    }
+
+    /// Replace a listing's attestations, e.g. once the old ones have expired.
+    /// The new set is verified exactly as at registration.
+    pub fn reattest_listing(
+        ctx: Context<ReattestListing>,
+        zk_attestations: Vec<ZkAttestation>,
+    ) -> Result<()> {
+        require!(zk_attestations.len() <= MAX_ZK_ATTESTATIONS, ErrorCode::TooManyAttestations);
+        let now = Clock::get()?.unix_timestamp;
+        verify_zk_attestations(
+            &zk_attestations,
+            now,
+            VerifiedCreator::verified_domain_hash(&ctx.accounts.verified_creator),
+            ctx.accounts.email_domain_vk_entry.as_ref().map(|entry| entry.as_ref()),
+            ctx.accounts.gps_location_vk_entry.as_ref().map(|entry| entry.as_ref()),
+            ctx.accounts.timestamp_vk_entry.as_ref().map(|entry| entry.as_ref()),
+            ctx.accounts.trusted_devices.as_deref(),
+            ctx.accounts.instructions_sysvar.as_ref().map(|sysvar| sysvar.as_ref()),
+        )?;
+
+        let listing = &mut ctx.accounts.listing;
+        require!(listing.state != ContentListingState::Archived, ErrorCode::ListingArchived);
+        listing.zk_attestations = zk_attestations;
+        listing.updated_at = now;
+
+        emit!(ListingUpdated {
+            listing_id: listing.listing_id,
+            creator: listing.creator,
+            updated_at: now,
+        });
+        Ok(())
+    }
+
+    /// Fetch a listing's recent content versions, oldest first
+    pub fn get_version_history(ctx: Context<GetVersionHistory>) -> Result<Vec<ContentVersionEntry>> {
+        Ok(ctx.accounts.version_history.ordered())
+    }
+
+    /// Restrict purchases to the given time windows; an empty list means always on sale
+    pub fn set_availability_windows(
+        ctx: Context<SetAvailabilityWindows>,
+        windows: Vec<TimeWindow>,
+    ) -> Result<()> {
+        require!(windows.len() <= MAX_AVAILABILITY_WINDOWS, ErrorCode::TooManyAvailabilityWindows);
+        for window in &windows {
+            require!(window.start < window.end, ErrorCode::InvalidAvailabilityWindow);
+        }
+
+        let listing = &mut ctx.accounts.listing;
+        listing.availability_windows = windows;
+        listing.updated_at = Clock::get()?.unix_timestamp;
+
+        emit!(AvailabilityWindowsUpdated {
+            listing_id: listing.listing_id,
+            window_count: listing.availability_windows.len() as u8,
+        });
+        Ok(())
+    }
+
+    /// Archive up to MAX_BULK_DEACTIVATE listings; returns how many were archived
+    ///
+    /// remaining_accounts: `[listing, creator profile]` for each id in `listing_ids`.
+    /// Listings the signer may not manage, or that are already archived, are skipped.
+    pub fn bulk_deactivate_listings<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BulkDeactivateListings<'info>>,
+        listing_ids: Vec<u64>,
+    ) -> Result<u8> {
+        require!(listing_ids.len() <= MAX_BULK_DEACTIVATE, ErrorCode::TooManyListings);
+        require!(
+            ctx.remaining_accounts.len() == listing_ids.len() * 2,
+            ErrorCode::ListingMismatch
+        );
+
+        let signer = ctx.accounts.authority.key();
+        let registry = &ctx.accounts.registry;
+        let is_admin = signer == registry.authority || signer == registry.operator;
+        let now = Clock::get()?.unix_timestamp;
+        let mut count: u8 = 0;
+
+        for (listing_id, pair) in listing_ids.iter().zip(ctx.remaining_accounts.chunks(2)) {
+            let mut listing = Account::<ContentListing>::try_from(&pair[0])?;
+            require!(listing.listing_id == *listing_id, ErrorCode::ListingMismatch);
+            if !(is_admin || signer == listing.creator)
+                || listing.state == ContentListingState::Archived
+            {
+                continue;
+            }
+
+            let old_state = listing.state.clone();
+            if old_state == ContentListingState::Active {
+                let (expected, _) = Pubkey::find_program_address(
+                    &[b"creator_profile", listing.creator.as_ref()],
+                    &crate::ID,
+                );
+                require_keys_eq!(pair[1].key(), expected, ErrorCode::ListingMismatch);
+                let mut profile = Account::<CreatorProfile>::try_from(&pair[1])?;
+                profile.active_listing_count = profile.active_listing_count.saturating_sub(1);
+                profile.exit(&crate::ID)?;
+            }
+
+            listing.state = ContentListingState::Archived;
+            listing.updated_at = now;
+            listing.exit(&crate::ID)?;
+            count += 1;
+
+            emit!(ListingStateChanged {
+                listing_id: listing.listing_id,
+                old_state,
+                new_state: ContentListingState::Archived,
+                changed_at: now,
+            });
+        }
+
+        emit!(BulkDeactivated {
+            count,
+            deactivated_by: signer,
+            deactivated_at: now,
+        });
+
+        Ok(count)
+    }
+
+    /// Trust an IoT device key for SensorData attestations (admin only)
+    pub fn add_trusted_device(ctx: Context<ManageTrustedDevices>, device_pubkey: [u8; 32]) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.registry.authority,
+            ErrorCode::Unauthorized
+        );
+
+        let trusted_devices = &mut ctx.accounts.trusted_devices;
+        trusted_devices.bump = ctx.bumps.trusted_devices;
+        require!(
+            !trusted_devices.devices.contains(&device_pubkey),
+            ErrorCode::DeviceAlreadyTrusted
+        );
+        require!(
+            trusted_devices.devices.len() < MAX_TRUSTED_DEVICES,
+            ErrorCode::TooManyTrustedDevices
+        );
+        trusted_devices.devices.push(device_pubkey);
+
+        msg!("Trusted device added: {}", Pubkey::new_from_array(device_pubkey));
+        Ok(())
+    }
+
+    /// Stop trusting an IoT device key (admin only)
+    pub fn remove_trusted_device(ctx: Context<ManageTrustedDevices>, device_pubkey: [u8; 32]) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.registry.authority,
+            ErrorCode::Unauthorized
+        );
+
+        let trusted_devices = &mut ctx.accounts.trusted_devices;
+        trusted_devices.bump = ctx.bumps.trusted_devices;
+        trusted_devices.devices.retain(|device| device != &device_pubkey);
+
+        msg!("Trusted device removed: {}", Pubkey::new_from_array(device_pubkey));
+        Ok(())
+    }
+
+    /// Halt listing registration and purchases platform-wide (authority only)
+    pub fn pause_registry(ctx: Context<PauseRegistry>) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        require!(
+            ctx.accounts.authority.key() == registry.authority,
+            ErrorCode::Unauthorized
+        );
+
+        registry.is_paused = true;
+
+        emit!(RegistryPaused {
+            by: ctx.accounts.authority.key(),
+            at: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Resume registration and purchases (authority only)
+    pub fn unpause_registry(ctx: Context<UnpauseRegistry>) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        require!(
+            ctx.accounts.authority.key() == registry.authority,
+            ErrorCode::Unauthorized
+        );
+
+        registry.is_paused = false;
+
+        emit!(RegistryUnpaused {
+            by: ctx.accounts.authority.key(),
+            at: Clock::get()?.unix_timestamp,
+        });
+
+        Ok(())
+    }
+
+    /// Hand day-to-day administration to a separate operator key (authority only)
+    pub fn set_operator(ctx: Context<SetOperator>, new_operator: Pubkey) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        require!(
+            ctx.accounts.authority.key() == registry.authority,
+            ErrorCode::Unauthorized
+        );
+
+        let old_operator = registry.operator;
+        registry.operator = new_operator;
+
+        emit!(OperatorUpdated {
+            old_operator,
+            new_operator,
+        });
+
+        Ok(())
+    }
+
+    /// Ban a creator from registering new listings (authority or operator)
+    pub fn blacklist_creator(ctx: Context<BlacklistCreator>, creator: Pubkey, reason: String) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        let signer = ctx.accounts.authority.key();
+        require!(
+            signer == registry.authority || signer == registry.operator,
+            ErrorCode::Unauthorized
+        );
+        require!(reason.len() <= 256, ErrorCode::ReasonTooLong);
+
+        let entry = &mut ctx.accounts.blacklist_entry;
+        entry.creator = creator;
+        entry.reason = reason;
+        entry.blacklisted_at = Clock::get()?.unix_timestamp;
+        entry.blacklisted_by = signer;
+        entry.bump = ctx.bumps.blacklist_entry;
+
+        emit!(CreatorBlacklistUpdated {
+            creator,
+            blacklisted: true,
+            updated_by: signer,
+        });
+
+        Ok(())
+    }
+
+    /// Lift a creator ban (authority or operator)
+    pub fn remove_from_blacklist(ctx: Context<RemoveFromBlacklist>, creator: Pubkey) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        let signer = ctx.accounts.authority.key();
+        require!(
+            signer == registry.authority || signer == registry.operator,
+            ErrorCode::Unauthorized
+        );
+
+        emit!(CreatorBlacklistUpdated {
+            creator,
+            blacklisted: false,
+            updated_by: signer,
+        });
+
+        Ok(())
+    }
+
+    /// Mark a creator as verified; new listings carry the badge (authority or operator)
+    pub fn verify_creator(
+        ctx: Context<VerifyCreator>,
+        creator: Pubkey,
+        verification_type: VerificationType,
+    ) -> Result<()> {
+        let registry = &ctx.accounts.registry;
+        let signer = ctx.accounts.authority.key();
+        require!(
+            signer == registry.authority || signer == registry.operator,
+            ErrorCode::Unauthorized
+        );
+
+        let badge = &mut ctx.accounts.verified_creator;
+        badge.creator = creator;
+        badge.verified_by = signer;
+        badge.verified_at = Clock::get()?.unix_timestamp;
+        badge.verification_type = verification_type;
+        badge.bump = ctx.bumps.verified_creator;
+
+        if let Some(profile) = ctx.accounts.creator_profile.as_mut() {
+            profile.verified = true;
+        }
+
+        emit!(CreatorVerificationUpdated {
+            creator,
+            verified: true,
+            updated_by: signer,
+        });
+        Ok(())
+    }
+
+    /// Withdraw a creator's verified badge (admin only)
+    ///
+    /// Existing listings keep `is_verified_creator`; clients should check the PDA for live status.
+    pub fn revoke_creator_verification(ctx: Context<RevokeCreatorVerification>, creator: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.registry.authority,
+            ErrorCode::Unauthorized
+        );
+
+        if let Some(profile) = ctx.accounts.creator_profile.as_mut() {
+            profile.verified = false;
+        }
+
+        emit!(CreatorVerificationUpdated {
+            creator,
+            verified: false,
+            updated_by: ctx.accounts.authority.key(),
+        });
+        Ok(())
+    }
+
+    /// Set the Pyth SOL/USD feed used for USD-priced listings (admin only)
+    pub fn set_price_feed(ctx: Context<SetPriceFeed>, price_feed: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.registry.authority,
+            ErrorCode::Unauthorized
+        );
+
+        ctx.accounts.registry.sol_usd_price_feed = price_feed;
+
+        msg!("SOL/USD price feed set to: {}", price_feed);
+        Ok(())
+    }
+
+    /// Set the stake creators need before registering content (admin only)
+    pub fn set_min_creator_stake(ctx: Context<SetPlatformFee>, min_creator_stake: u64) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.registry.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.registry.governance_mint == Pubkey::default(),
+            ErrorCode::GovernanceRequired
+        );
+
+        ctx.accounts.registry.min_creator_stake = min_creator_stake;
+
+        msg!("Minimum creator stake set to: {} lamports", min_creator_stake);
+        Ok(())
+    }
+
+    /// Set the lowest lamport base price a listing may use (admin only)
+    pub fn set_price_floor(ctx: Context<SetPriceFloor>, new_floor: u64) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.registry.authority,
+            ErrorCode::Unauthorized
+        );
+
+        let registry = &mut ctx.accounts.registry;
+        let old_floor = registry.min_price_floor;
+        registry.min_price_floor = new_floor;
+
+        emit!(PriceFloorUpdated { old_floor, new_floor });
+        Ok(())
+    }
+
+    /// Override how many listings `creator` may register (admin only)
+    pub fn set_creator_listing_limit(
+        ctx: Context<SetCreatorListingLimit>,
+        creator: Pubkey,
+        new_limit: u16,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.registry.authority,
+            ErrorCode::Unauthorized
+        );
+
+        let creator_count = &mut ctx.accounts.creator_count;
+        if creator_count.creator == Pubkey::default() {
+            creator_count.creator = creator;
+            creator_count.bump = ctx.bumps.creator_count;
+        }
+        creator_count.limit_override = Some(new_limit);
+
+        emit!(CreatorListingLimitSet { creator, new_limit });
+        Ok(())
+    }
+
+    /// Require an executing SPL Governance proposal from `realm` for fee changes (admin only)
+    pub fn configure_governance(ctx: Context<SetPlatformFee>, realm: Pubkey) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.registry.authority,
+            ErrorCode::Unauthorized
+        );
+
+        ctx.accounts.registry.governance_realm = Some(realm);
+
+        msg!("Governance realm set to: {}", realm);
+        Ok(())
+    }
+
+    /// Set the creator stake needed to sell listings under FeeModel::ZeroFee (admin only)
+    pub fn set_zero_fee_stake_threshold(ctx: Context<SetPlatformFee>, threshold: u64) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.registry.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.registry.governance_mint == Pubkey::default(),
+            ErrorCode::GovernanceRequired
+        );
+
+        ctx.accounts.registry.zero_fee_stake_threshold = threshold;
+
+        msg!("Zero-fee stake threshold set to: {} lamports", threshold);
+        Ok(())
+    }
+
+    /// Replace the stake-based platform fee discount tiers (admin only)
+    pub fn set_stake_discount_tiers(ctx: Context<SetPlatformFee>, tiers: Vec<StakeTier>) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.registry.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.registry.governance_mint == Pubkey::default(),
+            ErrorCode::GovernanceRequired
+        );
+        validate_stake_tiers(&tiers)?;
+
+        msg!("Stake discount tiers set: {}", tiers.len());
+        ctx.accounts.registry.stake_discount_tiers = tiers;
+        Ok(())
+    }
+
+    /// Set platform fee (admin only)
+    pub fn set_platform_fee(
+        ctx: Context<SetPlatformFee>,
+        new_fee_bps: u16,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.registry.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            ctx.accounts.registry.governance_mint == Pubkey::default(),
+            ErrorCode::GovernanceRequired
+        );
+        require!(new_fee_bps <= 1000, ErrorCode::FeeTooHigh); // Max 10%
+        if let Some(realm) = ctx.accounts.registry.governance_realm {
+            // remaining_accounts: [governance, proposal, proposal transaction]
+            verify_governance_proposal(ctx.remaining_accounts, &realm, new_fee_bps)?;
+        }
+
+        let registry = &mut ctx.accounts.registry;
+        let old_fee = registry.platform_fee_bps;
+        registry.platform_fee_bps = new_fee_bps;
+
+        emit!(PlatformFeeUpdated {
+            old_fee_bps: old_fee,
+            new_fee_bps,
+            updated_by: ctx.accounts.authority.key(),
+        });
+
+        Ok(())
+    }
+
+    /// Create the caller's platform-wide buyer profile
+    pub fn create_buyer_profile(ctx: Context<CreateBuyerProfile>) -> Result<()> {
+        let profile = &mut ctx.accounts.buyer_profile;
+        profile.buyer = ctx.accounts.buyer.key();
+        profile.total_spent = 0;
+        profile.purchase_count = 0;
+        profile.reputation_score = 0;
+        profile.joined_at = Clock::get()?.unix_timestamp;
+        profile.last_purchase_at = 0;
+        profile.bump = ctx.bumps.buyer_profile;
+
+        msg!("Buyer profile created: {}", profile.buyer);
+        Ok(())
+    }
+
+    /// Fetch a buyer's profile
+    pub fn get_buyer_profile(ctx: Context<GetBuyerProfile>) -> Result<BuyerProfile> {
+        Ok((*ctx.accounts.buyer_profile).clone())
+    }
+
+    /// Create the caller's public creator profile
+    pub fn create_creator_profile(
+        ctx: Context<CreateCreatorProfile>,
+        display_name: String,
+        bio: String,
+    ) -> Result<()> {
+        require!(display_name.len() <= 64, ErrorCode::DisplayNameTooLong);
+        require!(bio.len() <= 256, ErrorCode::BioTooLong);
+
+        let profile = &mut ctx.accounts.creator_profile;
+        profile.creator = ctx.accounts.creator.key();
+        profile.display_name = display_name;
+        profile.bio = bio;
+        profile.total_earned = 0;
+        profile.listing_count = 0;
+        profile.joined_at = Clock::get()?.unix_timestamp;
+        profile.verified = false;
+        profile.bump = ctx.bumps.creator_profile;
+        profile.total_tips_received = 0;
+        profile.active_listing_count = 0;
+        profile.bundle_count = 0;
+
+        emit!(CreatorProfileCreated {
+            creator: profile.creator,
+            display_name: profile.display_name.clone(),
+            joined_at: profile.joined_at,
+        });
+
+        Ok(())
+    }
+
+    /// Update the caller's display name and bio
+    pub fn update_creator_profile(
+        ctx: Context<UpdateCreatorProfile>,
+        new_name: String,
+        new_bio: String,
+    ) -> Result<()> {
+        require!(new_name.len() <= 64, ErrorCode::DisplayNameTooLong);
+        require!(new_bio.len() <= 256, ErrorCode::BioTooLong);
+
+        let profile = &mut ctx.accounts.creator_profile;
+        profile.display_name = new_name;
+        profile.bio = new_bio;
+
+        msg!("Creator profile updated: {}", profile.creator);
+        Ok(())
+    }
+
+    /// Fetch the aggregates for a day (days since the Unix epoch)
+    pub fn get_daily_stats(ctx: Context<GetDailyStats>, _date: u32) -> Result<DailyStats> {
+        Ok((*ctx.accounts.daily_stats).clone())
+    }
+
+    /// Build a Solana Pay transfer request URL for a listing
+    pub fn generate_solana_pay_url(ctx: Context<GenerateSolanaPayUrl>, _listing_id: u64) -> Result<String> {
+        let listing = &ctx.accounts.listing;
+        let (price, decimals) = match listing.pricing.price_denomination {
+            PriceDenomination::Lamports => {
+                let decimals = if listing.payment_mint == native_mint::ID {
+                    9
+                } else {
+                    ctx.accounts.payment_mint
+                        .as_ref()
+                        .ok_or(ErrorCode::PaymentMintMismatch)?
+                        .decimals
+                };
+                (listing.pricing.base_price, decimals)
+            }
+            PriceDenomination::UsdCents => {
+                let pyth_feed = ctx.accounts.pyth_feed
+                    .as_ref()
+                    .ok_or(ErrorCode::OraclePriceUnavailable)?;
+                (usd_cents_to_lamports(listing.pricing.base_price, pyth_feed)?, 9)
+            }
+        };
+
+        let mut url = format!(
+            "solana:{}?amount={}",
+            listing.creator,
+            format_token_amount(price, decimals)
+        );
+        if listing.payment_mint != native_mint::ID {
+            url.push_str(&format!("&spl-token={}", listing.payment_mint));
+        }
+        url.push_str(&format!(
+            "&label={}&message={}&reference={}",
+            percent_encode(&listing.metadata.title),
+            listing.listing_id,
+            listing.key()
+        ));
+        Ok(url)
+    }
+
+    /// Check a claimed content hash and algorithm against a listing
+    pub fn verify_content_hash(
+        ctx: Context<VerifyContentHash>,
+        claimed_hash: [u8; 32],
+        algorithm: HashAlgorithm,
+    ) -> Result<bool> {
+        let listing = &ctx.accounts.listing;
+        Ok(claimed_hash == listing.content_hash && algorithm == listing.hash_algorithm)
+    }
+
+    /// Fetch a buyer's purchase accumulator hash and entry count
+    pub fn get_purchase_accumulator(ctx: Context<GetPurchaseAccumulator>) -> Result<PurchaseHistoryAccumulator> {
+        Ok((*ctx.accounts.purchase_history).clone())
+    }
+
+    /// Fetch a listing's buyer statistics
+    pub fn get_listing_analytics(ctx: Context<GetListingAnalytics>) -> Result<ListingAnalyticsCache> {
+        Ok((*ctx.accounts.listing_analytics).clone())
+    }
+
+    /// Fetch the most purchased listings of a week (`unix_timestamp / SECONDS_PER_EPOCH`)
+    pub fn get_trending_listings(ctx: Context<GetTrending>, _epoch: u64) -> Result<Vec<TrendingEntry>> {
+        Ok(ctx.accounts.trending_index.ranked().to_vec())
+    }
+
+    /// Hand parameter changes over to token-weighted governance (admin only, once)
+    pub fn set_governance_mint(ctx: Context<SetGovernanceMint>) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        require!(
+            ctx.accounts.authority.key() == registry.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(
+            registry.governance_mint == Pubkey::default(),
+            ErrorCode::GovernanceAlreadyEnabled
+        );
+
+        registry.governance_mint = ctx.accounts.governance_mint.key();
+
+        msg!("Governance enabled with mint: {}", registry.governance_mint);
+        Ok(())
+    }
+
+    /// Propose a new value for a governed parameter
+    pub fn create_proposal(
+        ctx: Context<CreateProposal>,
+        parameter: GovernanceParam,
+        proposed_value: Vec<u8>,
+    ) -> Result<()> {
+        validate_proposed_value(&parameter, &proposed_value)?;
+        require!(
+            ctx.accounts.proposer_tokens.amount > 0,
+            ErrorCode::NoVotingPower
+        );
+
+        let now = Clock::get()?.unix_timestamp;
+        let registry = &mut ctx.accounts.registry;
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.proposal_id = registry.proposal_count;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.parameter = parameter.clone();
+        proposal.proposed_value = proposed_value;
+        proposal.votes_for = 0;
+        proposal.votes_against = 0;
+        proposal.deadline = now
+            .checked_add(registry.governance.voting_period_seconds)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        proposal.executed = false;
+        proposal.proposed_at = now;
+        proposal.bump = ctx.bumps.proposal;
+
+        registry.proposal_count = registry.proposal_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        emit!(ProposalCreated {
+            proposal_id: proposal.proposal_id,
+            proposer: proposal.proposer,
+            parameter,
+            deadline: proposal.deadline,
+        });
+
+        Ok(())
+    }
+
+    /// Vote on an open proposal, weighted by governance token balance.
+    ///
+    /// The voter's tokens are locked in a per-vote escrow until the deadline,
+    /// so the same balance cannot be moved to another wallet and counted again.
+    pub fn vote_on_proposal(ctx: Context<VoteOnProposal>, support: bool) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        require!(
+            Clock::get()?.unix_timestamp < proposal.deadline,
+            ErrorCode::VotingClosed
+        );
+
+        let weight = ctx.accounts.voter_tokens.amount;
+        require!(weight > 0, ErrorCode::NoVotingPower);
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.voter_tokens.to_account_info(),
+                    to: ctx.accounts.vote_escrow.to_account_info(),
+                    authority: ctx.accounts.voter.to_account_info(),
+                },
+            ),
+            weight,
+        )?;
+
+        if support {
+            proposal.votes_for = proposal.votes_for
+                .checked_add(weight)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+        } else {
+            proposal.votes_against = proposal.votes_against
+                .checked_add(weight)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+
+        let vote = &mut ctx.accounts.vote_record;
+        vote.proposal = proposal.key();
+        vote.voter = ctx.accounts.voter.key();
+        vote.weight = weight;
+        vote.support = support;
+        vote.bump = ctx.bumps.vote_record;
+
+        emit!(VoteCast {
+            proposal_id: proposal.proposal_id,
+            voter: vote.voter,
+            support,
+            weight,
+        });
+
+        Ok(())
+    }
+
+    /// Return tokens locked by a vote once voting has closed
+    pub fn withdraw_vote_tokens(ctx: Context<WithdrawVoteTokens>) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.proposal.deadline,
+            ErrorCode::VotingStillOpen
+        );
+
+        let proposal_key = ctx.accounts.proposal.key();
+        let voter_key = ctx.accounts.voter.key();
+        let bump = [ctx.accounts.vote_record.bump];
+        let signer_seeds: &[&[&[u8]]] = &[&[b"vote", proposal_key.as_ref(), voter_key.as_ref(), &bump]];
+
+        let locked = ctx.accounts.vote_escrow.amount;
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token::Transfer {
+                    from: ctx.accounts.vote_escrow.to_account_info(),
+                    to: ctx.accounts.voter_tokens.to_account_info(),
+                    authority: ctx.accounts.vote_record.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            locked,
+        )?;
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::CloseAccount {
+                account: ctx.accounts.vote_escrow.to_account_info(),
+                destination: ctx.accounts.voter.to_account_info(),
+                authority: ctx.accounts.vote_record.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+
+        emit!(VoteTokensWithdrawn {
+            proposal_id: ctx.accounts.proposal.proposal_id,
+            voter: voter_key,
+            amount: locked,
+        });
+        Ok(())
+    }
+
+    /// Apply a passed proposal once its time-lock has elapsed
+    pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+        let proposal = &mut ctx.accounts.proposal;
+        let registry = &mut ctx.accounts.registry;
+        require!(!proposal.executed, ErrorCode::ProposalAlreadyExecuted);
+
+        let executable_at = proposal.deadline
+            .checked_add(registry.governance.execution_delay_seconds)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        require!(
+            Clock::get()?.unix_timestamp >= executable_at,
+            ErrorCode::TimelockNotElapsed
+        );
+
+        let total_votes = proposal.votes_for
+            .checked_add(proposal.votes_against)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        let quorum = (ctx.accounts.governance_mint.supply as u128)
+            .checked_mul(registry.governance.quorum_bps as u128)
+            .ok_or(ErrorCode::ArithmeticOverflow)?
+            / 10000;
+        require!(total_votes as u128 >= quorum, ErrorCode::QuorumNotReached);
+        require!(proposal.votes_for > proposal.votes_against, ErrorCode::ProposalRejected);
+
+        let value = &proposal.proposed_value;
+        match proposal.parameter {
+            GovernanceParam::PlatformFeeBps => {
+                let old_fee = registry.platform_fee_bps;
+                registry.platform_fee_bps = u16::from_le_bytes([value[0], value[1]]);
+
+                emit!(PlatformFeeUpdated {
+                    old_fee_bps: old_fee,
+                    new_fee_bps: registry.platform_fee_bps,
+                    updated_by: proposal.key(),
+                });
+            }
+            GovernanceParam::VotingPeriodSeconds => {
+                registry.governance.voting_period_seconds = i64::from_le_bytes(value[..8].try_into().unwrap());
+            }
+            GovernanceParam::ExecutionDelaySeconds => {
+                registry.governance.execution_delay_seconds = i64::from_le_bytes(value[..8].try_into().unwrap());
+            }
+            GovernanceParam::QuorumBps => {
+                registry.governance.quorum_bps = u16::from_le_bytes([value[0], value[1]]);
+            }
+            GovernanceParam::MinCreatorStake => {
+                registry.min_creator_stake = u64::from_le_bytes(value[..8].try_into().unwrap());
+            }
+            GovernanceParam::ZeroFeeStakeThreshold => {
+                registry.zero_fee_stake_threshold = u64::from_le_bytes(value[..8].try_into().unwrap());
+            }
+            GovernanceParam::StakeDiscountTiers => {
+                registry.stake_discount_tiers = Vec::<StakeTier>::try_from_slice(value)?;
+            }
+        }
+        proposal.executed = true;
+
+        emit!(ProposalExecuted {
+            proposal_id: proposal.proposal_id,
+            parameter: proposal.parameter.clone(),
+            votes_for: proposal.votes_for,
+            votes_against: proposal.votes_against,
+        });
+
+        Ok(())
+    }
+
+    /// Report this build's version so clients can check compatibility
+    pub fn get_program_version(_ctx: Context<GetProgramVersion>) -> Result<ProgramVersion> {
+        Ok(PROGRAM_VERSION)
+    }
+}
+
+#[derive(Accounts)]
+pub struct GetProgramVersion<'info> {
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+    pub build_commit: [u8; 8], // First 8 chars of GIT_SHA at build time, "00000000" if unset
+}
+
+/// Version compiled into this program from its Cargo.toml
+pub const PROGRAM_VERSION: ProgramVersion = ProgramVersion {
+    major: program_version::version_component(env!("CARGO_PKG_VERSION"), 0),
+    minor: program_version::version_component(env!("CARGO_PKG_VERSION"), 1),
+    patch: program_version::version_component(env!("CARGO_PKG_VERSION"), 2),
+    build_commit: program_version::build_commit(option_env!("GIT_SHA")),
+};
+
+/// Day index used to key `DailyStats`
+pub fn day_index(unix_timestamp: i64) -> Result<u32> {
+    u32::try_from(unix_timestamp / SECONDS_PER_DAY).map_err(|_| error!(ErrorCode::ArithmeticOverflow))
+}
+
+pub const SECONDS_PER_DAY: i64 = 86400;
+
+/// Trending epochs are one week long
+pub const SECONDS_PER_EPOCH: i64 = 7 * SECONDS_PER_DAY;
+
+/// Listings ranked in each `TrendingIndex`
+pub const TRENDING_INDEX_SIZE: usize = 20;
+
+/// Epoch index used to key `TrendingIndex`
+pub fn trending_epoch(unix_timestamp: i64) -> Result<u64> {
+    u64::try_from(unix_timestamp / SECONDS_PER_EPOCH).map_err(|_| error!(ErrorCode::ArithmeticOverflow))
+}
+
+/// Longest name accepted for `CredentialType::Custom`
+pub const MAX_CUSTOM_CREDENTIAL_LEN: usize = 64;
+
+/// Accept CIDv0 (`Qm...`) and base32 CIDv1 (`bafy...`) identifiers
+pub fn validate_ipfs_cid(ipfs_cid: Option<&str>) -> Result<()> {
+    if let Some(cid) = ipfs_cid {
+        require!(
+            (cid.starts_with("Qm") || cid.starts_with("bafy"))
+                && (46..=MAX_IPFS_CID_LEN).contains(&cid.len()),
+            ErrorCode::InvalidIpfsCid
+        );
+    }
+    Ok(())
+}
+
+/// Check the locator format and, for Sha256 listings, that `content_hash` is the
+/// sha256 of the locator; other algorithms are left to off-chain tooling
+pub fn validate_delivery_channel(
+    channel: &DeliveryChannel,
+    content_hash: &[u8; 32],
+    hash_algorithm: &HashAlgorithm,
+) -> Result<()> {
+    let locator = channel.locator();
+    require!(
+        !locator.is_empty() && locator.len() <= MAX_DELIVERY_LOCATOR_LEN,
+        ErrorCode::InvalidDeliveryChannel
+    );
+    match channel {
+        DeliveryChannel::IpfsHash(cid) => validate_ipfs_cid(Some(cid))?,
+        DeliveryChannel::ArweaveId(id) => require!(
+            id.len() == ARWEAVE_ID_LEN
+                && id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'),
+            ErrorCode::InvalidDeliveryChannel
+        ),
+        DeliveryChannel::HttpsUrl(url) => require!(
+            url.len() > "https://".len() && url.starts_with("https://"),
+            ErrorCode::InvalidDeliveryChannel
+        ),
+        DeliveryChannel::S3Key(key) => require!(
+            !key.starts_with('/') && !key.contains(char::is_whitespace),
+            ErrorCode::InvalidDeliveryChannel
+        ),
+    }
+    if *hash_algorithm == HashAlgorithm::Sha256 {
+        require!(
+            Sha256::digest(locator.as_bytes()).as_slice() == content_hash,
+            ErrorCode::DeliveryChannelHashMismatch
+        );
+    }
+    Ok(())
+}
+
+/// Media listings must declare an image, video or audio type; custom MIME
+/// names must fit their reserved space
+pub fn validate_content_type(metadata: &ContentMetadata) -> Result<()> {
+    let Some(content_type) = &metadata.content_type else {
+        return Ok(());
+    };
+
+    if let ContentMimeType::Custom(mime) = content_type {
+        require!(
+            !mime.is_empty() && mime.len() <= MAX_CUSTOM_MIME_LEN,
+            ErrorCode::InvalidContentType
+        );
+    }
+    if matches!(metadata.category, ContentCategory::Media) {
+        require!(
+            matches!(
+                content_type,
+                ContentMimeType::ImageJpeg
+                    | ContentMimeType::ImagePng
+                    | ContentMimeType::VideoMp4
+                    | ContentMimeType::AudioMp3
+            ),
+            ErrorCode::ContentTypeCategoryMismatch
+        );
+    }
+    Ok(())
+}
+
+/// Discount whitelists hold at most MAX_DISCOUNT_CREDENTIAL_TYPES distinct, valid types
+pub fn validate_discount_whitelist(pricing: &PricingConfig) -> Result<()> {
+    if let Some(allowed) = &pricing.allowed_discount_credential_types {
+        require!(
+            allowed.len() <= MAX_DISCOUNT_CREDENTIAL_TYPES,
+            ErrorCode::InvalidDiscountWhitelist
+        );
+        for (i, credential_type) in allowed.iter().enumerate() {
+            validate_credential_type(credential_type)?;
+            require!(
+                !allowed[..i].contains(credential_type),
+                ErrorCode::InvalidDiscountWhitelist
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Reject custom credential names that would overflow their reserved space
+pub fn validate_credential_type(credential_type: &CredentialType) -> Result<()> {
+    if let CredentialType::Custom(name) = credential_type {
+        require!(
+            name.len() <= MAX_CUSTOM_CREDENTIAL_LEN,
+            ErrorCode::CustomCredentialTypeTooLong
+        );
+    }
+    Ok(())
+}
+
+/// Identifier issuers revoke a credential by:
+/// sha256(borsh(credential_type) || issuer_pubkey || proof_data[..16])
+pub fn credential_hash(proof: &CredentialProof) -> Result<[u8; 32]> {
+    let prefix_len = proof.proof_data.len().min(16);
+
+    let mut hasher = Sha256::new();
+    hasher.update(proof.credential_type.try_to_vec()?);
+    hasher.update(proof.issuer_pubkey.as_ref());
+    hasher.update(&proof.proof_data[..prefix_len]);
+    Ok(hasher.finalize().into())
+}
+
+/// Message an issuer signs to hand a credential to `holder`:
+/// sha256(borsh(credential_type) || holder || issued_at || borsh(valid_for_seconds) || proof_data)
+pub fn credential_message(proof: &CredentialProof, holder: &Pubkey) -> Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    hasher.update(proof.credential_type.try_to_vec()?);
+    hasher.update(holder.as_ref());
+    hasher.update(proof.issued_at.to_le_bytes());
+    hasher.update(proof.valid_for_seconds.try_to_vec()?);
+    hasher.update(&proof.proof_data);
+    Ok(hasher.finalize().into())
+}
+
+/// Check every presented credential carries its issuer's Ed25519 signature for `holder`
+pub fn verify_credential_issuers(
+    proofs: &[CredentialProof],
+    holder: &Pubkey,
+    instructions_sysvar: Option<&AccountInfo>,
+) -> Result<()> {
+    if proofs.is_empty() {
+        return Ok(());
+    }
+    let instructions_sysvar = instructions_sysvar.ok_or(ErrorCode::CredentialSignatureInvalid)?;
+    for proof in proofs {
+        verify_credential_signature(
+            instructions_sysvar,
+            &credential_message(proof, holder)?,
+            &proof.issuer_signature,
+            &proof.issuer_pubkey.to_bytes(),
+        )
+        .map_err(|_| error!(ErrorCode::CredentialSignatureInvalid))?;
+    }
+    Ok(())
+}
+
+/// Attestations accepted per listing
+pub const MAX_ZK_ATTESTATIONS: usize = 5;
+
+/// Attestation proof_data: Borsh `Groth16Proof` followed by one 32-byte public signal
+pub const MAX_ATTESTATION_PROOF_LEN: usize = 384;
+
+/// zk-meta-registry circuit name for email domain proofs
+pub const EMAIL_DOMAIN_CIRCUIT: &str = "email_domain";
+
+/// zk-meta-registry circuit name for GPS location proofs
+pub const GPS_LOCATION_CIRCUIT: &str = "gps_location";
+
+/// zk-meta-registry circuit name for content timestamp proofs
+pub const TIMESTAMP_CIRCUIT: &str = "timestamp";
+
+// Split attestation proof_data into the proof and its public signal
+fn unpack_attestation_proof(proof_data: &[u8]) -> Result<(Groth16Proof, [u8; 32])> {
+    let mut data = proof_data;
+    let proof = Groth16Proof::deserialize(&mut data)
+        .map_err(|_| error!(ErrorCode::MalformedAttestation))?;
+    let signal: [u8; 32] = data
+        .try_into()
+        .map_err(|_| error!(ErrorCode::MalformedAttestation))?;
+    Ok((proof, signal))
+}
+
+/// Load the active Groth16 key registered in zk-meta-registry for `circuit_name`
+///
+/// Key bytes are the 64-byte `CircuitParams` header followed by a Borsh
+/// encoded spend-verifier `VerificationKey`.
+pub fn load_registry_vk(vk_entry: &AccountInfo, circuit_name: &str) -> Result<VerificationKey> {
+    require_keys_eq!(*vk_entry.owner, zk_meta_registry::ID, ErrorCode::InvalidAttestationKey);
+    let (expected, _) = Pubkey::find_program_address(
+        &[b"vk_entry", circuit_name.as_bytes()],
+        &zk_meta_registry::ID,
+    );
+    require_keys_eq!(vk_entry.key(), expected, ErrorCode::InvalidAttestationKey);
+
+    let entry = VerificationKeyEntry::try_deserialize(&mut &vk_entry.try_borrow_data()?[..])?;
+    require!(entry.is_active, ErrorCode::InvalidAttestationKey);
+
+    let params = CircuitParams::parse(&entry.verification_key)?;
+    require!(
+        params.curve == CurveType::Bn254 && params.protocol == ProtocolType::Groth16,
+        ErrorCode::InvalidAttestationKey
+    );
+
+    let vk = VerificationKey::deserialize(&mut &entry.verification_key[VK_HEADER_LEN..])
+        .map_err(|_| error!(ErrorCode::InvalidAttestationKey))?;
+    require!(vk.ic.len() == params.ic_length as usize, ErrorCode::InvalidAttestationKey);
+    Ok(vk)
+}
+
+/// Verify an email domain attestation against the registry's `email_domain` key
+///
+/// The proof's single public signal is the domain hash commitment, which must
+/// equal `expected_domain_hash`.
+pub fn verify_email_domain_attestation(
+    attestation: &ZkAttestation,
+    expected_domain_hash: &[u8; 32],
+    vk_entry: &AccountInfo,
+) -> Result<bool> {
+    require!(
+        matches!(attestation.attestation_type, AttestationType::EmailDomain),
+        ErrorCode::MalformedAttestation
+    );
+
+    let (proof, domain_hash) = unpack_attestation_proof(&attestation.proof_data)?;
+    if &domain_hash != expected_domain_hash {
+        return Ok(false);
+    }
+
+    let vk = load_registry_vk(vk_entry, EMAIL_DOMAIN_CIRCUIT)?;
+    spend_verifier::groth16_verify(&vk, &proof, &[domain_hash])
+}
+
+/// Check a listing's attestations: each must be current and, for the proof-backed
+/// types, verify against its zk-meta-registry key or trusted device
+#[allow(clippy::too_many_arguments)]
+fn verify_zk_attestations(
+    attestations: &[ZkAttestation],
+    now: i64,
+    verified_domain_hash: Option<[u8; 32]>,
+    email_domain_vk_entry: Option<&AccountInfo>,
+    gps_location_vk_entry: Option<&AccountInfo>,
+    timestamp_vk_entry: Option<&AccountInfo>,
+    trusted_devices: Option<&TrustedDeviceRegistry>,
+    instructions_sysvar: Option<&AccountInfo>,
+) -> Result<()> {
+    for attestation in attestations {
+        require!(attestation.valid_for_seconds > 0, ErrorCode::InvalidAttestationValidity);
+        // verified_at is creator-supplied; a future one would stretch the validity window
+        require!(attestation.verified_at <= now, ErrorCode::AttestationFromFuture);
+        attestation.check_fresh(now)?;
+        require!(
+            attestation.proof_data.len() <= MAX_ATTESTATION_PROOF_LEN,
+            ErrorCode::AttestationTooLarge
+        );
+        if matches!(attestation.attestation_type, AttestationType::EmailDomain) {
+            // The domain must be the one the registry verified for this creator,
+            // not whatever commitment the proof happens to carry
+            let domain_hash = verified_domain_hash.ok_or(ErrorCode::DomainNotVerified)?;
+            let vk_entry = email_domain_vk_entry.ok_or(ErrorCode::MissingAttestationKey)?;
+            require!(
+                verify_email_domain_attestation(attestation, &domain_hash, vk_entry)?,
+                ErrorCode::EmailDomainAttestationInvalid
+            );
+        }
+        if matches!(attestation.attestation_type, AttestationType::GpsLocation) {
+            let vk_entry = gps_location_vk_entry.ok_or(ErrorCode::MissingAttestationKey)?;
+            let vk = load_registry_vk(vk_entry, GPS_LOCATION_CIRCUIT)?;
+            require!(
+                verify_gps_attestation(attestation, &vk)?,
+                ErrorCode::GpsAttestationInvalid
+            );
+        }
+        if matches!(attestation.attestation_type, AttestationType::SensorData) {
+            let data = SensorAttestationData::try_from_slice(&attestation.proof_data)
+                .map_err(|_| error!(ErrorCode::MalformedAttestation))?;
+            let trusted_devices = trusted_devices.ok_or(ErrorCode::UntrustedDevice)?;
+            require!(
+                trusted_devices.devices.contains(&data.device_pubkey),
+                ErrorCode::UntrustedDevice
+            );
+
+            let instructions_sysvar = instructions_sysvar.ok_or(ErrorCode::DeviceSignatureInvalid)?;
+            verify_credential_signature(
+                instructions_sysvar,
+                &data.reading_hash,
+                &data.device_signature,
+                &data.device_pubkey,
+            )
+            .map_err(|_| error!(ErrorCode::DeviceSignatureInvalid))?;
+        }
+        if matches!(attestation.attestation_type, AttestationType::Timestamp) {
+            let data = TimestampAttestationData::try_from_slice(&attestation.proof_data)
+                .map_err(|_| error!(ErrorCode::TimestampAttestationInvalid))?;
+            require!(
+                now.saturating_sub(attestation.verified_at) <= data.max_age_seconds,
+                ErrorCode::TimestampAttestationExpired
+            );
+
+            let vk_entry = timestamp_vk_entry.ok_or(ErrorCode::MissingAttestationKey)?;
+            let vk = load_registry_vk(vk_entry, TIMESTAMP_CIRCUIT)?;
+            require!(
+                spend_verifier::groth16_verify(&vk, &data.proof, &[data.timestamp_commitment])?,
+                ErrorCode::TimestampAttestationInvalid
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Verify a GPS location attestation: the proof shows the capture point lies
+/// inside the region committed to by `region_commitment`
+pub fn verify_gps_attestation(attestation: &ZkAttestation, vk: &VerificationKey) -> Result<bool> {
+    require!(
+        matches!(attestation.attestation_type, AttestationType::GpsLocation),
+        ErrorCode::MalformedAttestation
+    );
+
+    let data = GpsAttestationData::try_from_slice(&attestation.proof_data)
+        .map_err(|_| error!(ErrorCode::GpsAttestationInvalid))?;
+    spend_verifier::groth16_verify(vk, &data.proof, &[data.region_commitment])
+}
+
+/// Verify an Ed25519 signature over `message`.
+///
+/// The check itself runs in the native Ed25519 program: the transaction must
+/// include an Ed25519 instruction before this one covering exactly this key,
+/// message and signature, which is located through the instructions sysvar.
+pub fn verify_credential_signature(
+    instructions_sysvar: &AccountInfo,
+    message: &[u8; 32],
+    signature: &[u8; 64],
+    public_key: &[u8; 32],
+) -> Result<()> {
+    if signature.iter().all(|&x| x == 0) || public_key.iter().all(|&x| x == 0) {
+        return Err(ErrorCode::InvalidSignature.into());
+    }
+
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    for index in 0..current_index as usize {
+        let instruction = load_instruction_at_checked(index, instructions_sysvar)?;
+        if instruction.program_id == ed25519_program::ID
+            && ed25519_instruction_covers(&instruction.data, message, signature, public_key)
+        {
+            return Ok(());
+        }
+    }
+
+    Err(ErrorCode::SignatureVerificationFailed.into())
+}
+
+/// Whether Ed25519 program instruction data verifies `signature` by `public_key` over `message`.
+///
+/// Layout: `[count: u8, padding: u8]` followed by `count` 14-byte offset
+/// records of little-endian u16s (signature offset, signature ix index,
+/// public key offset, public key ix index, message offset, message size,
+/// message ix index). Only records pointing into the instruction's own data
+/// (`u16::MAX`) are accepted.
+fn ed25519_instruction_covers(
+    data: &[u8],
+    message: &[u8; 32],
+    signature: &[u8; 64],
+    public_key: &[u8; 32],
+) -> bool {
+    const OFFSETS_START: usize = 2;
+    const OFFSETS_LEN: usize = 14;
+
+    let count = match data.first() {
+        Some(&count) => count as usize,
+        None => return false,
+    };
+
+    (0..count).any(|i| {
+        let start = OFFSETS_START + i * OFFSETS_LEN;
+        let Some(offsets) = data.get(start..start + OFFSETS_LEN) else {
+            return false;
+        };
+        let read = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+        let slice = |offset: u16, len: usize| data.get(offset as usize..offset as usize + len);
+
+        read(2) == u16::MAX
+            && read(6) == u16::MAX
+            && read(12) == u16::MAX
+            && read(10) as usize == message.len()
+            && slice(read(0), 64) == Some(&signature[..])
+            && slice(read(4), 32) == Some(&public_key[..])
+            && slice(read(8), 32) == Some(&message[..])
+    })
+}
+
+/// Arguments of `access_controller::grant_access`, serialized after its discriminator.
+/// The access controller depends on this crate, so the CPI is built by hand.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct AccessGrantCallback {
+    pub content_hash: [u8; 32],
+    pub access_duration: Option<i64>, // None = permanent
+}
+
+/// Access controller accounts shared by every grant_access CPI
+pub struct AccessGrantAccounts<'info> {
+    pub access_controller: AccountInfo<'info>,
+    pub authorized_callers: AccountInfo<'info>,
+    pub registry_program: AccountInfo<'info>,
+    pub grant_authority: AccountInfo<'info>,
+    pub buyer: AccountInfo<'info>,
+    /// Pays rent for the access permission
+    pub payer: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub access_controller_program: AccountInfo<'info>,
+}
+
+impl<'info> AccessGrantAccounts<'info> {
+    /// Invoke access_controller::grant_access for one persisted purchase record
+    pub fn grant(
+        &self,
+        access_permission: &AccountInfo<'info>,
+        purchase: &AccountInfo<'info>,
+        listing: &AccountInfo<'info>,
+        content_hash: [u8; 32],
+    ) -> Result<()> {
+        let callback = AccessGrantCallback {
+            content_hash,
+            access_duration: None,
+        };
+        let mut data = Sha256::digest(b"global:grant_access")[..8].to_vec();
+        data.extend_from_slice(&callback.try_to_vec()?);
+
+        let instruction = Instruction {
+            program_id: ACCESS_CONTROLLER_ID,
+            accounts: vec![
+                AccountMeta::new(self.access_controller.key(), false),
+                AccountMeta::new(access_permission.key(), false),
+                AccountMeta::new_readonly(purchase.key(), false),
+                AccountMeta::new_readonly(listing.key(), false),
+                AccountMeta::new_readonly(self.authorized_callers.key(), false),
+                AccountMeta::new_readonly(crate::ID, false),
+                AccountMeta::new_readonly(self.grant_authority.key(), true),
+                AccountMeta::new_readonly(self.buyer.key(), false),
+                AccountMeta::new(self.payer.key(), true),
+                AccountMeta::new_readonly(system_program::ID, false),
+            ],
+            data,
+        };
+        // The access controller only accepts grants signed by this program's PDA
+        let (_, bump) = Pubkey::find_program_address(&[ACCESS_GRANT_AUTHORITY_SEED], &crate::ID);
+        invoke_signed(
+            &instruction,
+            &[
+                self.access_controller.clone(),
+                access_permission.clone(),
+                purchase.clone(),
+                listing.clone(),
+                self.authorized_callers.clone(),
+                self.registry_program.clone(),
+                self.grant_authority.clone(),
+                self.buyer.clone(),
+                self.payer.clone(),
+                self.system_program.clone(),
+                self.access_controller_program.clone(),
+            ],
+            &[&[ACCESS_GRANT_AUTHORITY_SEED, &[bump]]],
+        )?;
+        Ok(())
+    }
+}
+
+/// Leading fields of access_controller's AccessPermission. The access controller
+/// depends on this crate, so the account is read by hand like the grant CPI.
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct AccessPermissionView {
+    pub buyer: Pubkey,
+    pub content_hash: [u8; 32],
+    pub granted_at: i64,
+    pub expires_at: Option<i64>,
+    pub is_active: bool,
+}
+
+/// Whether an AccessPermission PDA no longer grants access: never created,
+/// closed, or revoked through access_controller::revoke_access
+pub fn access_revoked(access_permission: &AccountInfo) -> Result<bool> {
+    if access_permission.data_is_empty() {
+        return Ok(true);
+    }
+    require_keys_eq!(*access_permission.owner, ACCESS_CONTROLLER_ID, ErrorCode::AccessNotRevoked);
+    let data = access_permission.try_borrow_data()?;
+    let permission = AccessPermissionView::deserialize(&mut data.get(8..).unwrap_or_default())
+        .map_err(|_| error!(ErrorCode::AccessNotRevoked))?;
+    Ok(!permission.is_active)
+}
+
+// Grant the buyer access to the purchased content and mark the record granted
+fn grant_purchase_access(accounts: &mut PurchaseContent) -> Result<()> {
+    // The access controller deserializes the purchase record, so persist it first
+    accounts.purchase.exit(&crate::ID)?;
+
+    AccessGrantAccounts {
+        access_controller: accounts.access_controller.to_account_info(),
+        authorized_callers: accounts.authorized_callers.to_account_info(),
+        registry_program: accounts.registry_program.to_account_info(),
+        grant_authority: accounts.grant_authority.to_account_info(),
+        buyer: accounts.buyer.to_account_info(),
+        payer: accounts.buyer.to_account_info(),
+        system_program: accounts.system_program.to_account_info(),
+        access_controller_program: accounts.access_controller_program.to_account_info(),
+    }
+    .grant(
+        &accounts.access_permission.to_account_info(),
+        &accounts.purchase.to_account_info(),
+        &accounts.listing.to_account_info(),
+        accounts.listing.content_hash,
+    )?;
+
+    accounts.purchase.access_granted = true;
+    Ok(())
+}
+
+// Create the `[b"purchase", listing, buyer]` PDA and write the record into it
+fn create_purchase_record<'info>(
+    record: &PurchaseRecord,
+    listing: &Pubkey,
+    purchase: &AccountInfo<'info>,
+    buyer: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+) -> Result<()> {
+    let (expected, bump) = Pubkey::find_program_address(
+        &[b"purchase", listing.as_ref(), buyer.key.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(purchase.key(), expected, ErrorCode::BundleListingMismatch);
+
+    create_pda(
+        purchase,
+        &[b"purchase", listing.as_ref(), buyer.key.as_ref(), &[bump]],
+        8 + PurchaseRecord::LEN,
+        &crate::ID,
+        buyer,
+        system_program_info,
+    )?;
+    record.try_serialize(&mut &mut purchase.try_borrow_mut_data()?[..])
+}
+
+/// Create the PDA `info` with `space` bytes owned by `owner`, paid by `payer`.
+///
+/// Like Anchor's `init`, an address someone already sent lamports to is topped
+/// up, allocated and assigned instead of failing `create_account`.
+fn create_pda<'info>(
+    info: &AccountInfo<'info>,
+    signer_seeds: &[&[u8]],
+    space: usize,
+    owner: &Pubkey,
+    payer: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let current_lamports = info.lamports();
+    if current_lamports == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program_info.clone(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: info.clone(),
+                },
+                &[signer_seeds],
+            ),
+            rent,
+            space as u64,
+            owner,
+        );
+    }
+
+    let top_up = rent.saturating_sub(current_lamports);
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program_info.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: info.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program_info.clone(),
+            system_program::Allocate { account_to_allocate: info.clone() },
+            &[signer_seeds],
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program_info.clone(),
+            system_program::Assign { account_to_assign: info.clone() },
+            &[signer_seeds],
+        ),
+        owner,
+    )
+}
+
+/// Create `hold`'s PDA and token vault for its purchase and move its amount
+/// from the buyer into the vault
+#[allow(clippy::too_many_arguments)]
+fn create_purchase_hold<'info>(
+    mut hold: PurchaseHold,
+    hold_info: &AccountInfo<'info>,
+    hold_vault_info: &AccountInfo<'info>,
+    mint: &Account<'info, Mint>,
+    buyer_ata: &Account<'info, TokenAccount>,
+    buyer: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    system_program_info: &AccountInfo<'info>,
+) -> Result<()> {
+    let (expected_hold, bump) = Pubkey::find_program_address(
+        &[b"purchase_hold", hold.purchase.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(hold_info.key(), expected_hold, ErrorCode::BundleListingMismatch);
+    let (expected_vault, vault_bump) = Pubkey::find_program_address(
+        &[b"purchase_hold_vault", expected_hold.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(hold_vault_info.key(), expected_vault, ErrorCode::BundleListingMismatch);
+
+    create_pda(
+        hold_info,
+        &[b"purchase_hold", hold.purchase.as_ref(), &[bump]],
+        8 + PurchaseHold::LEN,
+        &crate::ID,
+        buyer,
+        system_program_info,
+    )?;
+    create_pda(
+        hold_vault_info,
+        &[b"purchase_hold_vault", expected_hold.as_ref(), &[vault_bump]],
+        TokenAccount::LEN,
+        &token::ID,
+        buyer,
+        system_program_info,
+    )?;
+    token::initialize_account3(CpiContext::new(
+        token_program.to_account_info(),
+        token::InitializeAccount3 {
+            account: hold_vault_info.clone(),
+            mint: mint.to_account_info(),
+            authority: hold_info.clone(),
+        },
+    ))?;
+    token::transfer(
+        CpiContext::new(
+            token_program.to_account_info(),
+            token::Transfer {
+                from: buyer_ata.to_account_info(),
+                to: hold_vault_info.clone(),
+                authority: buyer.clone(),
+            },
+        ),
+        hold.amount,
+    )?;
+
+    hold.bump = bump;
+    hold.try_serialize(&mut &mut hold_info.try_borrow_mut_data()?[..])
+}
+
+/// Split a bundle's platform fee across its listings in proportion to their
+/// prices, never charging a listing more than its price
+pub fn split_bundle_fee(prices: &[u64], platform_fee: u64) -> Result<Vec<u64>> {
+    let total = prices
+        .iter()
+        .try_fold(0u64, |sum, price| sum.checked_add(*price))
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    require!(platform_fee <= total, ErrorCode::ArithmeticOverflow);
+    if total == 0 {
+        return Ok(vec![0; prices.len()]);
+    }
+
+    let mut fees = prices
+        .iter()
+        .map(|price| (platform_fee as u128 * *price as u128 / total as u128) as u64)
+        .collect::<Vec<_>>();
+    // Rounding leaves less than one lamport per listing; hand it out in order
+    let mut unassigned = platform_fee - fees.iter().sum::<u64>();
+    for (fee, price) in fees.iter_mut().zip(prices) {
+        let extra = (price - *fee).min(unassigned);
+        *fee += extra;
+        unassigned -= extra;
+    }
+    Ok(fees)
+}
+
+/// Deserialize a program-owned PDA, first creating it zeroed (paid by `payer`) if
+/// it does not exist yet; returns the account and its bump
+fn load_or_create_pda<'info, T: AccountSerialize + AccountDeserialize>(
+    info: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+    space: usize,
+    payer: &AccountInfo<'info>,
+    system_program_info: &AccountInfo<'info>,
+) -> Result<(T, u8)> {
+    let (expected, bump) = Pubkey::find_program_address(seeds, &crate::ID);
+    require_keys_eq!(info.key(), expected, ErrorCode::BundleListingMismatch);
+
+    if info.data_is_empty() {
+        let bump_seed = [bump];
+        let signer_seeds = [seeds, &[&bump_seed[..]]].concat();
+        create_pda(info, &signer_seeds, space, &crate::ID, payer, system_program_info)?;
+        // Freshly zeroed, so skip the discriminator check until the caller serializes it
+        let account = T::try_deserialize_unchecked(&mut &info.try_borrow_data()?[..])?;
+        return Ok((account, bump));
+    }
+    require_keys_eq!(*info.owner, crate::ID, ErrorCode::BundleListingMismatch);
+    let account = T::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+    Ok((account, bump))
+}
+
+/// Highest creator royalty on secondary access sales (50%)
+pub const MAX_SECONDARY_ROYALTY_BPS: u16 = 5000;
+
+/// How long a buyer waits for confirm_delivery before release_escrow refunds them
+pub const ESCROW_TIMEOUT_SECONDS: i64 = 7 * SECONDS_PER_DAY;
+
+/// How long a purchase's price stays in its hold, open to disputes, before release
+pub const DISPUTE_WINDOW_SECONDS: i64 = 7 * SECONDS_PER_DAY;
+
+/// Listings `bulk_deactivate_listings` handles per call
+pub const MAX_BULK_DEACTIVATE: usize = 10;
+
+/// Highest referral commission (20%)
+pub const MAX_REFERRAL_COMMISSION_BPS: u16 = 2000;
+
+/// remaining_accounts purchase_bundle takes per bundled listing
+pub const BUNDLE_ACCOUNTS_PER_LISTING: usize = 7;
+
+/// Listings allowed in one bundle
+pub const MAX_BUNDLE_LISTINGS: usize = 10;
+
+/// Longest bundle display name
+pub const MAX_BUNDLE_NAME_LEN: usize = 64;
+
+/// Devices kept in the trusted device registry
+pub const MAX_TRUSTED_DEVICES: usize = 32;
+
+/// Access controller program, owner of RevokedCredential PDAs
+pub const ACCESS_CONTROLLER_ID: Pubkey = pubkey!("6TjVZeXZiRxVQBHoMvNzCYraRekbM16jJj6ycg8fFggZ");
+
+/// Seed of the PDA a calling program signs `grant_access` CPIs with
+pub const ACCESS_GRANT_AUTHORITY_SEED: &[u8] = b"access_grant_authority";
+
+/// Upper bound for `reallocate_listing`
+pub const MAX_LISTING_SIZE: u64 = 65536;
+
+/// Pyth oracle program that owns price accounts
+pub const PYTH_ORACLE_PROGRAM_ID: Pubkey = pubkey!("FsJ3A3u2vn5cTVofAjvy6y5kwABJAqYWpe4975bi2epH");
+
+/// Oldest oracle price accepted for USD conversion
+pub const MAX_PRICE_AGE_SECONDS: i64 = 60;
+
+pub const LAMPORTS_PER_SOL: u64 = 1_000_000_000;
+
+/// Aggregate price read from a Pyth price account
+pub struct PythPrice {
+    pub price: i64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+// pyth-sdk-solana pins solana-program <= 1.16, so read the v2 price
+// account layout directly: expo @20, timestamp @96, prev_price @184,
+// prev_timestamp @200, agg.price @208, agg.status @224
+fn load_pyth_price(price_feed: &AccountInfo) -> Result<PythPrice> {
+    require!(price_feed.owner == &PYTH_ORACLE_PROGRAM_ID, ErrorCode::OraclePriceUnavailable);
+
+    let data = price_feed.try_borrow_data()?;
+    require!(data.len() >= 240, ErrorCode::OraclePriceUnavailable);
+
+    let read_u32 = |at: usize| u32::from_le_bytes(data[at..at + 4].try_into().unwrap());
+    let read_i64 = |at: usize| i64::from_le_bytes(data[at..at + 8].try_into().unwrap());
+    require!(
+        read_u32(0) == 0xa1b2c3d4 && read_u32(4) == 2 && read_u32(8) == 3,
+        ErrorCode::OraclePriceUnavailable
+    );
+
+    let trading = read_u32(224) == 1;
+    Ok(PythPrice {
+        price: if trading { read_i64(208) } else { read_i64(184) },
+        expo: read_u32(20) as i32,
+        publish_time: if trading { read_i64(96) } else { read_i64(200) },
+    })
+}
+
+/// Convert a US cent price to lamports at the current Pyth SOL/USD price
+pub fn usd_cents_to_lamports(usd_cents: u64, price_feed: &AccountInfo) -> Result<u64> {
+    let pyth_price = load_pyth_price(price_feed)?;
+    lamports_for_usd_cents(usd_cents, &pyth_price, Clock::get()?.unix_timestamp)
+}
+
+/// Convert a US cent price to lamports at `pyth_price`, rejecting stale or non-positive prices
+pub fn lamports_for_usd_cents(usd_cents: u64, pyth_price: &PythPrice, now: i64) -> Result<u64> {
+    require!(
+        now.saturating_sub(pyth_price.publish_time) <= MAX_PRICE_AGE_SECONDS,
+        ErrorCode::OraclePriceUnavailable
+    );
+    require!(pyth_price.price > 0 && pyth_price.expo <= 0, ErrorCode::OraclePriceUnavailable);
+
+    // lamports = cents * LAMPORTS_PER_SOL / (price * 10^expo * 100)
+    let scale = 10u128
+        .checked_pow(pyth_price.expo.unsigned_abs())
+        .ok_or(ErrorCode::ArithmeticOverflow)?;
+    let lamports = (usd_cents as u128)
+        .checked_mul(LAMPORTS_PER_SOL as u128)
+        .and_then(|v| v.checked_mul(scale))
+        .ok_or(ErrorCode::ArithmeticOverflow)?
+        / (pyth_price.price as u128 * 100);
+
+    u64::try_from(lamports).map_err(|_| error!(ErrorCode::ArithmeticOverflow))
+}
+
+/// Reports a listing can collect before purchases pause
+pub const DEFAULT_AUTO_PAUSE_THRESHOLD: u16 = 5;
+
+/// Maximum number of stake discount tiers on the registry
+pub const MAX_STAKE_TIERS: usize = 5;
+
+/// Largest encoded proposal value: a full set of stake discount tiers
+pub const MAX_PROPOSAL_VALUE_LEN: usize = 4 + MAX_STAKE_TIERS * StakeTier::LEN;
+
+/// Stake required for zero-fee listings at initialization
+pub const DEFAULT_ZERO_FEE_STAKE_THRESHOLD: u64 = 100 * LAMPORTS_PER_SOL;
+
+/// Platform fee discount (bps) of the highest tier a creator's stake qualifies for
+pub fn stake_fee_discount_bps(tiers: &[StakeTier], staked: u64) -> u16 {
+    tiers
+        .iter()
+        .filter(|tier| staked >= tier.min_stake_lamports)
+        .max_by_key(|tier| tier.min_stake_lamports)
+        .map_or(0, |tier| tier.fee_discount_bps)
+}
+
+/// SPL Governance program
+pub const SPL_GOVERNANCE_PROGRAM_ID: Pubkey = pubkey!("GovER5Lthms3bLBqWub97yVrMmEogzX7xNjdXpPPCVZw");
+
+// spl-governance GovernanceAccountType tags and ProposalState::Executing
+const GOVERNANCE_V2_TAG: u8 = 18;
+const PROPOSAL_V2_TAG: u8 = 14;
+const PROPOSAL_TRANSACTION_V2_TAG: u8 = 13;
+const PROPOSAL_STATE_EXECUTING: u8 = 4;
+
+// Check that an executing proposal of `realm` carries this set_platform_fee call.
+// spl-governance is not a dependency, so the Borsh layouts are read directly:
+//   GovernanceV2:          tag, realm
+//   ProposalV2:            tag, governance, governing_token_mint, state
+//   ProposalTransactionV2: tag, proposal, option_index: u8, transaction_index: u16,
+//                          hold_up_time: u32, instructions: Vec<{program_id, accounts, data}>
+fn verify_governance_proposal(accounts: &[AccountInfo], realm: &Pubkey, new_fee_bps: u16) -> Result<()> {
+    let [governance, proposal, transaction] = accounts else {
+        return err!(ErrorCode::InvalidGovernanceProposal);
+    };
+    for info in accounts {
+        require_keys_eq!(*info.owner, SPL_GOVERNANCE_PROGRAM_ID, ErrorCode::InvalidGovernanceProposal);
+    }
+
+    let governance_data = governance.try_borrow_data()?;
+    require!(
+        governance_data.len() >= 33
+            && governance_data[0] == GOVERNANCE_V2_TAG
+            && governance_data[1..33] == realm.to_bytes(),
+        ErrorCode::InvalidGovernanceProposal
+    );
+
+    let proposal_data = proposal.try_borrow_data()?;
+    require!(
+        proposal_data.len() > 65
+            && proposal_data[0] == PROPOSAL_V2_TAG
+            && proposal_data[1..33] == governance.key.to_bytes(),
+        ErrorCode::InvalidGovernanceProposal
+    );
+    require!(proposal_data[65] == PROPOSAL_STATE_EXECUTING, ErrorCode::ProposalNotExecuting);
+
+    let data = transaction.try_borrow_data()?;
+    require!(
+        data.len() >= 44 && data[0] == PROPOSAL_TRANSACTION_V2_TAG && data[1..33] == proposal.key.to_bytes(),
+        ErrorCode::InvalidGovernanceProposal
+    );
+    let read_u32 = |at: usize| -> Result<usize> {
+        let bytes = data.get(at..at + 4).ok_or(ErrorCode::InvalidGovernanceProposal)?;
+        Ok(u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+    };
+    // First instruction: program id, then 34-byte account metas, then data
+    require!(read_u32(40)? >= 1, ErrorCode::InvalidGovernanceProposal);
+    require!(
+        data.get(44..76) == Some(crate::ID.as_ref()),
+        ErrorCode::InvalidGovernanceProposal
+    );
+    let data_at = 80 + read_u32(76)? * 34;
+    let ix_len = read_u32(data_at)?;
+    let ix_data = data
+        .get(data_at + 4..data_at + 4 + ix_len)
+        .ok_or(ErrorCode::InvalidGovernanceProposal)?;
+
+    let mut expected = Sha256::digest(b"global:set_platform_fee")[..8].to_vec();
+    expected.extend_from_slice(&new_fee_bps.to_le_bytes());
+    require!(ix_data == expected.as_slice(), ErrorCode::InvalidGovernanceProposal);
+    Ok(())
+}
+
+/// Price floor set at initialization, in lamports
+pub const DEFAULT_MIN_PRICE_FLOOR: u64 = 1000;
+
+/// Listings a creator may register unless the authority overrides it
+pub const DEFAULT_MAX_LISTINGS_PER_CREATOR: u16 = 100;
+
+// USD-priced listings store cents, so the lamport floor only applies to lamport prices
+fn check_price_floor(pricing: &PricingConfig, min_price_floor: u64) -> Result<()> {
+    if pricing.price_denomination == PriceDenomination::Lamports {
+        require!(pricing.base_price >= min_price_floor, ErrorCode::PriceBelowFloor);
+    }
+    Ok(())
+}
+
+// Render base units as a decimal amount without trailing zeros (Solana Pay `amount`)
+fn format_token_amount(amount: u64, decimals: u8) -> String {
+    let scale = 10u64.pow(decimals as u32);
+    let fraction = amount % scale;
+    if fraction == 0 {
+        return (amount / scale).to_string();
+    }
+    let digits = format!("{:0width$}", fraction, width = decimals as usize);
+    format!("{}.{}", amount / scale, digits.trim_end_matches('0'))
+}
+
+// Percent-encode everything except RFC 3986 unreserved characters
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Platform fee owed on `price` under a listing's fee model
+///
+/// Fixed and percentage fees are creator-set, so they never come in under the
+/// platform default; only ZeroFee, gated on stake, goes below it.
+pub fn platform_fee_for(
+    fee_model: &FeeModel,
+    registry: &X402Registry,
+    staked: u64,
+    price: u64,
+) -> Result<u64> {
+    let default_bps = registry.platform_fee_bps
+        .saturating_sub(stake_fee_discount_bps(&registry.stake_discount_tiers, staked));
+    let fee_bps = match fee_model {
+        FeeModel::PlatformDefault => default_bps,
+        FeeModel::FixedFee { lamports } => {
+            return Ok((*lamports).min(price).max(bps_of(price, default_bps as u64)?));
+        }
+        FeeModel::PercentageFee { bps } => (*bps).max(default_bps),
+        FeeModel::ZeroFee => {
+            require!(
+                staked >= registry.zero_fee_stake_threshold,
+                ErrorCode::ZeroFeeStakeTooLow
+            );
+            return Ok(0);
+        }
+    };
+    bps_of(price, fee_bps as u64)
+}
+
+/// `bps` basis points of `amount`, widened so large amounts cannot overflow
+fn bps_of(amount: u64, bps: u64) -> Result<u64> {
+    let share = amount as u128 * bps as u128 / 10000;
+    Ok(u64::try_from(share).map_err(|_| ErrorCode::ArithmeticOverflow)?)
+}
+
+/// Smallest tip accepted by `tip_creator`
+pub const MIN_TIP_LAMPORTS: u64 = 100_000;
+
+// Check a proposed value's encoding and range for its parameter
+fn validate_proposed_value(parameter: &GovernanceParam, value: &[u8]) -> Result<()> {
+    match parameter {
+        GovernanceParam::PlatformFeeBps => {
+            require!(value.len() == 2, ErrorCode::InvalidProposalValue);
+            require!(u16::from_le_bytes([value[0], value[1]]) <= 1000, ErrorCode::FeeTooHigh);
+        }
+        GovernanceParam::VotingPeriodSeconds | GovernanceParam::ExecutionDelaySeconds => {
+            require!(value.len() == 8, ErrorCode::InvalidProposalValue);
+            let seconds = i64::from_le_bytes(value.try_into().unwrap());
+            require!(seconds > 0, ErrorCode::InvalidProposalValue);
+        }
+        GovernanceParam::QuorumBps => {
+            require!(value.len() == 2, ErrorCode::InvalidProposalValue);
+            let quorum = u16::from_le_bytes([value[0], value[1]]);
+            require!(quorum > 0 && quorum <= 10000, ErrorCode::InvalidProposalValue);
+        }
+        GovernanceParam::MinCreatorStake | GovernanceParam::ZeroFeeStakeThreshold => {
+            require!(value.len() == 8, ErrorCode::InvalidProposalValue);
+        }
+        GovernanceParam::StakeDiscountTiers => {
+            let tiers = Vec::<StakeTier>::try_from_slice(value)
+                .map_err(|_| ErrorCode::InvalidProposalValue)?;
+            validate_stake_tiers(&tiers)?;
+        }
+    }
+    Ok(())
+}
+
+fn validate_stake_tiers(tiers: &[StakeTier]) -> Result<()> {
+    require!(tiers.len() <= MAX_STAKE_TIERS, ErrorCode::TooManyStakeTiers);
+    require!(
+        tiers.iter().all(|tier| tier.fee_discount_bps <= 10000),
+        ErrorCode::InvalidStakeTier
+    );
+    Ok(())
+}
+
+/// Price `buyer` pays for `listing`: the base price (oracle-converted for USD
+/// listings) less credential and cross-listing discounts
+///
+/// `extra_accounts` holds the access-controller RevokedCredential PDA for each
+/// credential, optionally followed by a CrossDiscount and the buyer's
+/// PurchaseRecord for its source listing.
+pub fn quote_listing_price(
+    listing: &Account<ContentListing>,
+    min_price_floor: u64,
+    pyth_feed: Option<&AccountInfo>,
+    buyer: &Pubkey,
+    buyer_credentials: &[CredentialProof],
+    extra_accounts: &[AccountInfo],
+    now: i64,
+) -> Result<u64> {
+    // The floor may have been raised since the listing was registered
+    check_price_floor(&listing.pricing, min_price_floor)?;
+    for proof in buyer_credentials {
+        validate_credential_type(&proof.credential_type)?;
+    }
+
+    let (revocations, cross_accounts) = extra_accounts
+        .split_at_checked(buyer_credentials.len())
+        .ok_or(ErrorCode::MissingRevocationAccount)?;
+    require!(
+        cross_accounts.is_empty() || cross_accounts.len() == 2,
+        ErrorCode::MissingRevocationAccount
+    );
+    for (proof, revoked) in buyer_credentials.iter().zip(revocations) {
+        let (expected, _) = Pubkey::find_program_address(
+            &[b"revoked_cred", &credential_hash(proof)?],
+            &ACCESS_CONTROLLER_ID,
+        );
+        require_keys_eq!(revoked.key(), expected, ErrorCode::MissingRevocationAccount);
+        require!(revoked.data_is_empty(), ErrorCode::CredentialRevoked);
+    }
+
+    let base_price = match listing.pricing.price_denomination {
+        PriceDenomination::Lamports => listing.pricing.base_price,
+        PriceDenomination::UsdCents => {
+            let pyth_feed = pyth_feed.ok_or(ErrorCode::OraclePriceUnavailable)?;
+            usd_cents_to_lamports(listing.pricing.base_price, pyth_feed)?
+        }
+    };
+
+    let cross_discount = match cross_accounts {
+        [cross_info, source_purchase] => load_cross_discount(
+            cross_info,
+            source_purchase,
+            &listing.key(),
+            buyer,
+        )?,
+        _ => None,
+    };
+
+    let final_price = calculate_price_with_discounts(
+        base_price,
+        &listing.pricing,
+        &listing.required_credentials,
+        buyer_credentials,
+        cross_discount.as_ref().map_or(0, |cross| cross.discount_bps),
+        now,
+    )?;
+
+    if let Some(cross) = &cross_discount {
+        emit!(CrossDiscountApplied {
+            source_listing: cross.source_listing,
+            target_listing: cross.target_listing,
+            buyer: *buyer,
+            discount_bps: cross.discount_bps,
+        });
+    }
+
+    Ok(final_price)
+}
+
+/// Transfer `payouts` out of a purchase hold's vault, then close the vault to the buyer
+fn settle_purchase_hold<'info>(
+    hold: &Account<'info, PurchaseHold>,
+    hold_vault: &Account<'info, TokenAccount>,
+    payouts: &[(AccountInfo<'info>, u64)],
+    buyer: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+) -> Result<()> {
+    let bump = [hold.bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[b"purchase_hold", hold.purchase.as_ref(), &bump]];
+    for (destination, payout) in payouts {
+        if *payout > 0 {
+            token::transfer(
+                CpiContext::new_with_signer(
+                    token_program.to_account_info(),
+                    token::Transfer {
+                        from: hold_vault.to_account_info(),
+                        to: destination.clone(),
+                        authority: hold.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                *payout,
+            )?;
+        }
+    }
+    // The buyer funded the vault's rent at purchase time
+    token::close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        token::CloseAccount {
+            account: hold_vault.to_account_info(),
+            destination: buyer.clone(),
+            authority: hold.to_account_info(),
+        },
+        signer_seeds,
+    ))
+}
+
+/// Commission `referral` earns on a sale at `price`; buyers cannot refer themselves
+pub fn referral_commission(referral: &ReferralCode, buyer: &Pubkey, price: u64) -> Result<u64> {
+    require_keys_neq!(referral.referrer, *buyer, ErrorCode::SelfReferral);
+    bps_of(price, referral.commission_bps as u64)
+}
+
+// Helper function for dynamic pricing
+// Load a cross discount onto `target`, if the buyer owns a purchase of its source listing
+fn load_cross_discount(
+    cross_info: &AccountInfo,
+    source_purchase: &AccountInfo,
+    target: &Pubkey,
+    buyer: &Pubkey,
+) -> Result<Option<CrossDiscount>> {
+    require_keys_eq!(*cross_info.owner, crate::ID, ErrorCode::CrossDiscountMismatch);
+    let cross = CrossDiscount::try_deserialize(&mut &cross_info.try_borrow_data()?[..])?;
+    require_keys_eq!(cross.target_listing, *target, ErrorCode::CrossDiscountMismatch);
+
+    let (expected, _) = Pubkey::find_program_address(
+        &[b"purchase", cross.source_listing.as_ref(), buyer.as_ref()],
+        &crate::ID,
+    );
+    require_keys_eq!(source_purchase.key(), expected, ErrorCode::CrossDiscountMismatch);
+    if source_purchase.data_is_empty() || source_purchase.owner != &crate::ID {
+        return Ok(None);
+    }
+    Ok(Some(cross))
+}
+
+// Every required credential must be presented unexpired, and every presented
+// credential must carry its issuer's signature for `holder`
+fn check_required_credentials(
+    requirements: &[CredentialRequirement],
+    proofs: &[CredentialProof],
+    holder: &Pubkey,
+    instructions_sysvar: Option<&AccountInfo>,
+    now: i64,
+) -> Result<()> {
+    for req in requirements.iter().filter(|req| req.required) {
+        let proof = proofs
+            .iter()
+            .find(|p| p.credential_type == req.credential_type)
+            .ok_or(ErrorCode::RequiredCredentialMissing)?;
+        require!(proof.is_valid_at(now), ErrorCode::CredentialExpired);
+    }
+    verify_credential_issuers(proofs, holder, instructions_sysvar)
+}
+
+fn calculate_price_with_discounts(
+    base_price: u64,
+    pricing: &PricingConfig,
+    requirements: &[CredentialRequirement],
+    proofs: &[CredentialProof],
+    cross_discount_bps: u16,
+    now: i64,
+) -> Result<u64> {
+    // Discounts add up in basis points and are applied once, capped by the listing
+    let mut total_applied_discount_bps: u64 = 0;
+
+    // Apply credential-based discounts
+    for req in requirements {
+        if let Some(proof) = proofs.iter().find(|p| p.credential_type == req.credential_type) {
+            if !proof.is_valid_at(now) {
+                continue;
+            }
+
+            if !pricing.discount_allowed(&req.credential_type) {
+                continue;
+            }
+            let discount = pricing.credential_discounts
+                .iter()
+                .find(|d| d.credential_type == req.credential_type)
+                .map(|d| d.discount_bps)
+                .unwrap_or(0);
+            total_applied_discount_bps += discount as u64;
+        }
+    }
+
+    // Apply the cross-listing discount earned by buying the source listing
+    total_applied_discount_bps += cross_discount_bps as u64;
+
+    // Apply volume discounts
+    if let Some(_volume_discount) = &pricing.volume_discount {
+        // Volume discount logic would check purchase history
+        // For now, just apply if buyer has required volume
+        // This would be implemented with purchase history tracking
+    }
+
+    let capped_bps = total_applied_discount_bps.min(pricing.discount_cap_bps() as u64);
+    let discount_amount = bps_of(base_price, capped_bps)?;
+    Ok(base_price.saturating_sub(discount_amount))
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + X402Registry::LEN,
+        seeds = [b"x402_registry"],
+        bump
+    )]
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(content_hash: [u8; 32])]
+pub struct RegisterContent<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + ContentListing::LEN,
+        seeds = [b"listing", registry.listing_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub listing: Account<'info, ContentListing>,
+    
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + UniqueContentHash::LEN,
+        seeds = [b"unique_hash", content_hash.as_ref()],
+        bump
+    )]
+    pub unique_hash: Account<'info, UniqueContentHash>,
+    
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + DailyStats::LEN,
+        seeds = [b"daily_stats", day_index(Clock::get()?.unix_timestamp)?.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub daily_stats: Account<'info, DailyStats>,
+    
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + CreatorProfile::LEN,
+        seeds = [b"creator_profile", creator.key().as_ref()],
+        bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+    
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + CreatorListingCount::LEN,
+        seeds = [b"creator_count", creator.key().as_ref()],
+        bump
+    )]
+    pub creator_count: Account<'info, CreatorListingCount>,
+    
+    pub payment_mint: Account<'info, Mint>,
+    
+    /// CHECK: zk-meta-registry "email_domain" key entry, validated by load_registry_vk
+    pub email_domain_vk_entry: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: zk-meta-registry "gps_location" key entry, validated by load_registry_vk
+    pub gps_location_vk_entry: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: zk-meta-registry "timestamp" key entry, validated by load_registry_vk
+    pub timestamp_vk_entry: Option<UncheckedAccount<'info>>,
+    
+    #[account(seeds = [b"trusted_devices"], bump = trusted_devices.bump)]
+    pub trusted_devices: Option<Account<'info, TrustedDeviceRegistry>>,
+    
+    #[account(
+        seeds = [b"creator_stake", creator.key().as_ref()],
+        bump = creator_stake.bump
+    )]
+    pub creator_stake: Option<Account<'info, CreatorStakeAccount>>,
+    
+    /// CHECK: Creator's blacklist PDA; must not exist, checked by BlacklistEntry::exists
+    #[account(seeds = [b"blacklist", creator.key().as_ref()], bump)]
+    pub blacklist_entry: UncheckedAccount<'info>,
+    
+    /// CHECK: Creator's verification badge PDA, may not exist; read by VerifiedCreator::exists
+    /// and, for EmailDomain attestations, VerifiedCreator::verified_domain_hash
+    #[account(seeds = [b"verified_creator", creator.key().as_ref()], bump)]
+    pub verified_creator: UncheckedAccount<'info>,
+    
+    /// CHECK: Instructions sysvar, read for SensorData device signatures
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(buyer_credentials: Vec<CredentialProof>, purchase_nonce: [u8; 8], referral_code: Option<[u8; 8]>)]
+pub struct PurchaseContent<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(mut)]
+    pub listing: Account<'info, ContentListing>,
+    
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + PurchaseRecord::LEN,
+        seeds = [b"purchase", listing.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub purchase: Account<'info, PurchaseRecord>,
+    
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + PurchaseNonce::LEN,
+        seeds = [b"purchase_nonce", buyer.key().as_ref(), listing.key().as_ref(), purchase_nonce.as_ref()],
+        bump
+    )]
+    pub nonce_record: Account<'info, PurchaseNonce>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + DailyStats::LEN,
+        seeds = [b"daily_stats", day_index(Clock::get()?.unix_timestamp)?.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub daily_stats: Account<'info, DailyStats>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + TrendingIndex::LEN,
+        seeds = [b"trending", trending_epoch(Clock::get()?.unix_timestamp)?.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub trending_index: Account<'info, TrendingIndex>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + ListingAnalyticsCache::LEN,
+        seeds = [b"listing_analytics", listing.key().as_ref()],
+        bump
+    )]
+    pub listing_analytics: Account<'info, ListingAnalyticsCache>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + BuyerListingRecord::LEN,
+        seeds = [b"buyer_listing", buyer.key().as_ref(), listing.key().as_ref()],
+        bump
+    )]
+    pub buyer_listing_record: Account<'info, BuyerListingRecord>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + PurchaseHistoryAccumulator::LEN,
+        seeds = [b"purchase_history", buyer.key().as_ref()],
+        bump
+    )]
+    pub purchase_history: Account<'info, PurchaseHistoryAccumulator>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + BuyerProfile::LEN,
+        seeds = [b"buyer_profile", buyer.key().as_ref()],
+        bump
+    )]
+    pub buyer_profile: Account<'info, BuyerProfile>,
+    
+    #[account(
+        mut,
+        seeds = [b"creator_profile", listing.creator.as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Box<Account<'info, CreatorProfile>>,
+    
+    #[account(
+        mut,
+        token::authority = buyer,
+        constraint = buyer_ata.mint == listing.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub buyer_ata: Account<'info, TokenAccount>,
+    
+    #[account(address = listing.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub payment_mint: Box<Account<'info, Mint>>,
+    
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + PurchaseHold::LEN,
+        seeds = [b"purchase_hold", purchase.key().as_ref()],
+        bump
+    )]
+    pub purchase_hold: Box<Account<'info, PurchaseHold>>,
+    
+    #[account(
+        init,
+        payer = buyer,
+        seeds = [b"purchase_hold_vault", purchase_hold.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = purchase_hold
+    )]
+    pub hold_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [b"referral_code", referral_code.unwrap_or_default().as_ref()],
+        bump = referral.bump
+    )]
+    pub referral: Option<Account<'info, ReferralCode>>,
+    
+    #[account(address = registry.sol_usd_price_feed)]
+    /// CHECK: Pyth price account, parsed by load_pyth_price; only needed for USD pricing
+    pub pyth_feed: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Instructions sysvar, read for credential issuer signatures
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+    
+    #[account(
+        seeds = [b"creator_stake", listing.creator.as_ref()],
+        bump = creator_stake.bump
+    )]
+    pub creator_stake: Option<Box<Account<'info, CreatorStakeAccount>>>,
+    
+    // Access controller accounts for the inline grant_access CPI
+    #[account(mut)]
+    /// CHECK: AccessController state, validated by the access controller
+    pub access_controller: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    /// CHECK: AccessPermission PDA, initialized by the access controller
+    pub access_permission: UncheckedAccount<'info>,
+    
+    /// CHECK: AuthorizedCallers PDA; this program must be listed in it
+    pub authorized_callers: UncheckedAccount<'info>,
+    
+    /// CHECK: Access controller program
+    #[account(address = ACCESS_CONTROLLER_ID)]
+    pub access_controller_program: UncheckedAccount<'info>,
+    
+    pub registry_program: Program<'info, program::X402Registry>,
+    
+    /// CHECK: Signer PDA proving grant_access CPIs come from this program
+    #[account(seeds = [ACCESS_GRANT_AUTHORITY_SEED], bump)]
+    pub grant_authority: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(source_listing_id: u64, target_listing_id: u64)]
+pub struct RegisterCrossDiscount<'info> {
+    #[account(constraint = source_listing.listing_id == source_listing_id @ ErrorCode::ListingMismatch)]
+    pub source_listing: Account<'info, ContentListing>,
+    
+    #[account(
+        has_one = creator,
+        constraint = target_listing.listing_id == target_listing_id @ ErrorCode::ListingMismatch
+    )]
+    pub target_listing: Account<'info, ContentListing>,
+    
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + CrossDiscount::LEN,
+        seeds = [b"cross_discount", source_listing.key().as_ref(), target_listing.key().as_ref()],
+        bump
+    )]
+    pub cross_discount: Account<'info, CrossDiscount>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(code: [u8; 8])]
+pub struct CreateReferralCode<'info> {
+    #[account(
+        init,
+        payer = referrer,
+        space = 8 + ReferralCode::LEN,
+        seeds = [b"referral_code", code.as_ref()],
+        bump
+    )]
+    pub referral: Account<'info, ReferralCode>,
+    
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(buyer_credentials: Vec<CredentialProof>, referral_code: Option<[u8; 8]>)]
+pub struct InitiatePurchase<'info> {
+    pub registry: Account<'info, X402Registry>,
+    
+    pub listing: Account<'info, ContentListing>,
+    
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + PurchaseEscrow::LEN,
+        seeds = [b"escrow", listing.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, PurchaseEscrow>,
+    
+    #[account(address = listing.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub payment_mint: Account<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = buyer,
+        seeds = [b"escrow_vault", escrow.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = escrow
+    )]
+    pub escrow_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::authority = buyer,
+        constraint = buyer_ata.mint == listing.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub buyer_ata: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"referral_code", referral_code.unwrap_or_default().as_ref()],
+        bump = referral.bump
+    )]
+    pub referral: Option<Account<'info, ReferralCode>>,
+    
+    #[account(address = registry.sol_usd_price_feed)]
+    /// CHECK: Pyth price account, parsed by load_pyth_price; only needed for USD pricing
+    pub pyth_feed: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Instructions sysvar, read for credential issuer signatures
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+    
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfirmDelivery<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(mut, has_one = creator)]
+    pub listing: Account<'info, ContentListing>,
+    
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"escrow", listing.key().as_ref(), buyer.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, PurchaseEscrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_vault", escrow.key().as_ref()],
+        bump
+    )]
+    pub escrow_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + PurchaseRecord::LEN,
+        seeds = [b"purchase", listing.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub purchase: Account<'info, PurchaseRecord>,
+    
+    #[account(
+        seeds = [b"creator_stake", creator.key().as_ref()],
+        bump = creator_stake.bump
+    )]
+    pub creator_stake: Option<Account<'info, CreatorStakeAccount>>,
+    
+    #[account(address = escrow.mint @ ErrorCode::PaymentMintMismatch)]
+    pub payment_mint: Box<Account<'info, Mint>>,
+    
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + PurchaseHold::LEN,
+        seeds = [b"purchase_hold", purchase.key().as_ref()],
+        bump
+    )]
+    pub purchase_hold: Box<Account<'info, PurchaseHold>>,
+    
+    #[account(
+        init,
+        payer = creator,
+        seeds = [b"purchase_hold_vault", purchase_hold.key().as_ref()],
+        bump,
+        token::mint = payment_mint,
+        token::authority = purchase_hold
+    )]
+    pub hold_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        address = escrow.referral.unwrap_or_default() @ ErrorCode::ReferralAccountsMissing
+    )]
+    pub referral: Option<Account<'info, ReferralCode>>,
+    
+    // Access controller accounts for the grant_access CPI
+    #[account(mut)]
+    /// CHECK: AccessController state, validated by the access controller
+    pub access_controller: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    /// CHECK: AccessPermission PDA, initialized by the access controller
+    pub access_permission: UncheckedAccount<'info>,
+    
+    /// CHECK: AuthorizedCallers PDA; this program must be listed in it
+    pub authorized_callers: UncheckedAccount<'info>,
+    
+    /// CHECK: Access controller program
+    #[account(address = ACCESS_CONTROLLER_ID)]
+    pub access_controller_program: UncheckedAccount<'info>,
+    
+    pub registry_program: Program<'info, program::X402Registry>,
+    
+    /// CHECK: Signer PDA proving grant_access CPIs come from this program
+    #[account(seeds = [ACCESS_GRANT_AUTHORITY_SEED], bump)]
+    pub grant_authority: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    
+    /// CHECK: The escrow's buyer
+    #[account(address = escrow.buyer @ ErrorCode::Unauthorized)]
+    pub buyer: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseEscrow<'info> {
+    #[account(
+        mut,
+        close = buyer,
+        has_one = buyer,
+        seeds = [b"escrow", escrow.listing.as_ref(), buyer.key().as_ref()],
+        bump = escrow.bump
+    )]
+    pub escrow: Account<'info, PurchaseEscrow>,
+    
+    #[account(
+        mut,
+        seeds = [b"escrow_vault", escrow.key().as_ref()],
+        bump
+    )]
+    pub escrow_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = escrow.mint,
+        token::authority = buyer
+    )]
+    pub buyer_ata: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CreateBundle<'info> {
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(
+        mut,
+        seeds = [b"creator_profile", creator.key().as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+    
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + Bundle::LEN,
+        seeds = [b"bundle", creator.key().as_ref(), creator_profile.bundle_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub bundle: Account<'info, Bundle>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(bundle_id: u64)]
+pub struct PurchaseBundle<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(
+        mut,
+        seeds = [b"bundle", bundle.creator.as_ref(), bundle_id.to_le_bytes().as_ref()],
+        bump = bundle.bump
+    )]
+    pub bundle: Account<'info, Bundle>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + DailyStats::LEN,
+        seeds = [b"daily_stats", day_index(Clock::get()?.unix_timestamp)?.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub daily_stats: Account<'info, DailyStats>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + BuyerProfile::LEN,
+        seeds = [b"buyer_profile", buyer.key().as_ref()],
+        bump
+    )]
+    pub buyer_profile: Account<'info, BuyerProfile>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + TrendingIndex::LEN,
+        seeds = [b"trending", trending_epoch(Clock::get()?.unix_timestamp)?.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub trending_index: Box<Account<'info, TrendingIndex>>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + PurchaseHistoryAccumulator::LEN,
+        seeds = [b"purchase_history", buyer.key().as_ref()],
+        bump
+    )]
+    pub purchase_history: Box<Account<'info, PurchaseHistoryAccumulator>>,
+    
+    #[account(
+        mut,
+        seeds = [b"creator_profile", bundle.creator.as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Box<Account<'info, CreatorProfile>>,
+    
+    #[account(
+        seeds = [b"creator_stake", bundle.creator.as_ref()],
+        bump = creator_stake.bump
+    )]
+    pub creator_stake: Option<Box<Account<'info, CreatorStakeAccount>>>,
+    
+    #[account(
+        mut,
+        token::authority = buyer,
+        constraint = buyer_ata.mint == bundle.payment_mint @ ErrorCode::PaymentMintMismatch
+    )]
+    pub buyer_ata: Account<'info, TokenAccount>,
+    
+    /// Mint of the listings' purchase hold vaults
+    #[account(address = bundle.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub payment_mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    /// CHECK: AccessController state, validated by the access controller
+    pub access_controller: UncheckedAccount<'info>,
+    
+    /// CHECK: AuthorizedCallers PDA; this program must be listed in it
+    pub authorized_callers: UncheckedAccount<'info>,
+    
+    /// CHECK: Access controller program
+    #[account(address = ACCESS_CONTROLLER_ID)]
+    pub access_controller_program: UncheckedAccount<'info>,
+    
+    pub registry_program: Program<'info, program::X402Registry>,
+    
+    /// CHECK: Signer PDA proving grant_access CPIs come from this program
+    #[account(seeds = [ACCESS_GRANT_AUTHORITY_SEED], bump)]
+    pub grant_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: Instructions sysvar, read for credential issuer signatures
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+    
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct SubmitReview<'info> {
+    #[account(
+        mut,
+        constraint = listing.listing_id == listing_id @ ErrorCode::ListingMismatch
+    )]
+    pub listing: Account<'info, ContentListing>,
+    
+    #[account(
+        seeds = [b"purchase", listing.key().as_ref(), buyer.key().as_ref()],
+        bump,
+        constraint = purchase.buyer == buyer.key() @ ErrorCode::NoPurchaseRecord
+    )]
+    pub purchase: Account<'info, PurchaseRecord>,
+    
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + Review::LEN,
+        seeds = [b"review", listing.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub review: Account<'info, Review>,
+    
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct ReportListing<'info> {
+    #[account(
+        mut,
+        constraint = listing.listing_id == listing_id @ ErrorCode::ListingMismatch
+    )]
+    pub listing: Account<'info, ContentListing>,
+    
+    #[account(
+        seeds = [b"purchase", listing.key().as_ref(), reporter.key().as_ref()],
+        bump,
+        constraint = purchase.buyer == reporter.key() @ ErrorCode::NoPurchaseRecord
+    )]
+    pub purchase: Account<'info, PurchaseRecord>,
+    
+    #[account(
+        init,
+        payer = reporter,
+        space = 8 + ListingReport::LEN,
+        seeds = [b"report", listing.key().as_ref(), reporter.key().as_ref()],
+        bump
+    )]
+    pub report: Account<'info, ListingReport>,
+    
+    #[account(mut)]
+    pub reporter: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DismissReport<'info> {
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(mut)]
+    pub listing: Account<'info, ContentListing>,
+    
+    #[account(
+        mut,
+        close = reporter,
+        seeds = [b"report", listing.key().as_ref(), reporter.key().as_ref()],
+        bump = report.bump,
+        has_one = listing,
+        has_one = reporter
+    )]
+    pub report: Account<'info, ListingReport>,
+    
+    #[account(mut)]
+    /// CHECK: Rent refund recipient, checked against report.reporter
+    pub reporter: UncheckedAccount<'info>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct TipCreator<'info> {
+    #[account(constraint = listing.listing_id == listing_id @ ErrorCode::ListingMismatch)]
+    pub listing: Account<'info, ContentListing>,
+    
+    #[account(
+        init_if_needed,
+        payer = tipper,
+        space = 8 + CreatorVault::LEN,
+        seeds = [b"creator_vault", listing.creator.as_ref()],
+        bump
+    )]
+    pub creator_vault: Account<'info, CreatorVault>,
+    
+    #[account(
+        mut,
+        seeds = [b"creator_profile", listing.creator.as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+    
+    #[account(mut)]
+    pub tipper: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFromVault<'info> {
+    #[account(
+        mut,
+        seeds = [b"creator_vault", creator.key().as_ref()],
+        bump = creator_vault.bump,
+        has_one = creator @ ErrorCode::Unauthorized
+    )]
+    pub creator_vault: Account<'info, CreatorVault>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawVaultTokens<'info> {
+    #[account(
+        seeds = [b"creator_vault", creator.key().as_ref()],
+        bump = creator_vault.bump,
+        has_one = creator @ ErrorCode::Unauthorized
+    )]
+    pub creator_vault: Account<'info, CreatorVault>,
+    
+    #[account(
+        mut,
+        token::authority = creator_vault
+    )]
+    pub vault_token: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = vault_token.mint,
+        token::authority = creator
+    )]
+    pub creator_token: Account<'info, TokenAccount>,
+    
+    pub creator: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(purchase_key: Pubkey)]
+pub struct OpenDispute<'info> {
+    #[account(
+        address = purchase_key,
+        has_one = buyer @ ErrorCode::Unauthorized,
+        seeds = [b"purchase", listing.key().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub purchase: Account<'info, PurchaseRecord>,
+    
+    pub listing: Account<'info, ContentListing>,
+    
+    #[account(
+        mut,
+        seeds = [b"purchase_hold", purchase_key.as_ref()],
+        bump = purchase_hold.bump
+    )]
+    pub purchase_hold: Account<'info, PurchaseHold>,
+    
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + Dispute::LEN,
+        seeds = [b"dispute", purchase_key.as_ref()],
+        bump
+    )]
+    pub dispute: Account<'info, Dispute>,
+    
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ResolveDispute<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(
+        mut,
+        seeds = [b"dispute", dispute.purchase.as_ref()],
+        bump = dispute.bump,
+        has_one = buyer
+    )]
+    pub dispute: Account<'info, Dispute>,
+    
+    #[account(
+        mut,
+        close = buyer,
+        seeds = [b"purchase_hold", dispute.purchase.as_ref()],
+        bump = purchase_hold.bump
+    )]
+    pub purchase_hold: Account<'info, PurchaseHold>,
+    
+    #[account(
+        mut,
+        seeds = [b"purchase_hold_vault", purchase_hold.key().as_ref()],
+        bump
+    )]
+    pub hold_vault: Account<'info, TokenAccount>,
+    
+    #[account(mut, address = dispute.purchase)]
+    pub purchase: Box<Account<'info, PurchaseRecord>>,
+    
+    #[account(
+        mut,
+        constraint = listing.listing_id == purchase.listing_id @ ErrorCode::ListingMismatch
+    )]
+    pub listing: Box<Account<'info, ContentListing>>,
+    
+    #[account(
+        mut,
+        seeds = [b"creator_profile", purchase_hold.creator.as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Box<Account<'info, CreatorProfile>>,
+    
+    /// CHECK: The buyer's access-controller AccessPermission for the listing; must be
+    /// revoked or absent for BuyerWins, checked by access_revoked
+    #[account(
+        seeds = [b"access", dispute.buyer.as_ref(), listing.content_hash.as_ref()],
+        seeds::program = ACCESS_CONTROLLER_ID,
+        bump
+    )]
+    pub access_permission: UncheckedAccount<'info>,
+    
+    /// Refund destination when the buyer wins
+    #[account(
+        mut,
+        token::mint = purchase_hold.mint,
+        constraint = buyer_ata.owner == purchase_hold.buyer @ ErrorCode::InvalidDisputeRecipient
+    )]
+    pub buyer_ata: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = purchase_hold.mint,
+        constraint = creator_ata.owner == purchase_hold.creator @ ErrorCode::InvalidDisputeRecipient
+    )]
+    pub creator_ata: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = purchase_hold.mint,
+        token::authority = registry.authority
+    )]
+    pub platform_fee_ata: Account<'info, TokenAccount>,
+    
+    #[account(address = purchase_hold.referral.unwrap_or_default() @ ErrorCode::ReferralAccountsMissing)]
+    pub referral: Option<Account<'info, ReferralCode>>,
+    
+    #[account(
+        mut,
+        token::mint = purchase_hold.mint,
+        token::authority = referral.as_ref().map(|r| r.referrer).unwrap_or_default()
+    )]
+    pub referrer_ata: Option<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    /// CHECK: Receives the hold's rent, checked against dispute.buyer
+    pub buyer: UncheckedAccount<'info>,
+    
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ReleasePurchaseHold<'info> {
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(
+        mut,
+        close = buyer,
+        has_one = buyer,
+        seeds = [b"purchase_hold", purchase_hold.purchase.as_ref()],
+        bump = purchase_hold.bump
+    )]
+    pub purchase_hold: Account<'info, PurchaseHold>,
+    
+    #[account(
+        mut,
+        seeds = [b"purchase_hold_vault", purchase_hold.key().as_ref()],
+        bump
+    )]
+    pub hold_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = purchase_hold.mint,
+        token::authority = purchase_hold.creator
+    )]
+    pub creator_ata: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = purchase_hold.mint,
+        token::authority = registry.authority
+    )]
+    pub platform_fee_ata: Account<'info, TokenAccount>,
+    
+    #[account(address = purchase_hold.referral.unwrap_or_default() @ ErrorCode::ReferralAccountsMissing)]
+    pub referral: Option<Account<'info, ReferralCode>>,
+    
+    #[account(
+        mut,
+        token::mint = purchase_hold.mint,
+        token::authority = referral.as_ref().map(|r| r.referrer).unwrap_or_default()
+    )]
+    pub referrer_ata: Option<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    /// CHECK: Receives the hold's rent, checked against purchase_hold.buyer
+    pub buyer: UncheckedAccount<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ReallocateListing<'info> {
+    #[account(
+        mut,
+        has_one = creator @ ErrorCode::Unauthorized
+    )]
+    pub listing: Account<'info, ContentListing>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct StakeAsCreator<'info> {
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + CreatorStakeAccount::LEN,
+        seeds = [b"creator_stake", creator.key().as_ref()],
+        bump
+    )]
+    pub creator_stake: Account<'info, CreatorStakeAccount>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Unstake<'info> {
+    #[account(
+        mut,
+        seeds = [b"creator_stake", creator.key().as_ref()],
+        bump = creator_stake.bump,
+        has_one = creator @ ErrorCode::Unauthorized
+    )]
+    pub creator_stake: Account<'info, CreatorStakeAccount>,
+    
+    /// CHECK: Creator's profile PDA, may not exist; parsed in unstake when it does
+    #[account(seeds = [b"creator_profile", creator.key().as_ref()], bump)]
+    pub creator_profile: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReattestListing<'info> {
+    #[account(
+        mut,
+        has_one = creator @ ErrorCode::Unauthorized
+    )]
+    pub listing: Account<'info, ContentListing>,
+    
+    /// CHECK: zk-meta-registry "email_domain" key entry, validated by load_registry_vk
+    pub email_domain_vk_entry: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: zk-meta-registry "gps_location" key entry, validated by load_registry_vk
+    pub gps_location_vk_entry: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: zk-meta-registry "timestamp" key entry, validated by load_registry_vk
+    pub timestamp_vk_entry: Option<UncheckedAccount<'info>>,
+    
+    #[account(seeds = [b"trusted_devices"], bump = trusted_devices.bump)]
+    pub trusted_devices: Option<Account<'info, TrustedDeviceRegistry>>,
+    
+    /// CHECK: Instructions sysvar, read for SensorData device signatures
+    #[account(address = solana_instructions_sysvar::ID)]
+    pub instructions_sysvar: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Creator's verification badge PDA, may not exist; read by VerifiedCreator::verified_domain_hash
+    #[account(seeds = [b"verified_creator", creator.key().as_ref()], bump)]
+    pub verified_creator: UncheckedAccount<'info>,
+    
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(content_hash: [u8; 32])]
+pub struct DeregisterContentHash<'info> {
+    #[account(
+        has_one = creator @ ErrorCode::Unauthorized
+    )]
+    pub listing: Account<'info, ContentListing>,
+    
+    /// Any unique hash PDA this listing still holds, current or superseded
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"unique_hash", content_hash.as_ref()],
+        bump = unique_hash.bump,
+        constraint = unique_hash.listing_id == listing.listing_id @ ErrorCode::ListingMismatch
+    )]
+    pub unique_hash: Account<'info, UniqueContentHash>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateListing<'info> {
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(mut)]
+    pub listing: Account<'info, ContentListing>,
+    
+    #[account(
+        mut,
+        seeds = [b"creator_profile", listing.creator.as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+    
+    /// Required to (re)activate a listing when the registry sets a minimum stake
+    #[account(
+        seeds = [b"creator_stake", listing.creator.as_ref()],
+        bump = creator_stake.bump
+    )]
+    pub creator_stake: Option<Account<'info, CreatorStakeAccount>>,
+    
+    /// Listing creator, or the registry operator to suspend or unsuspend the listing
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPriceFloor<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, X402Registry>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(creator: Pubkey)]
+pub struct SetCreatorListingLimit<'info> {
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + CreatorListingCount::LEN,
+        seeds = [b"creator_count", creator.as_ref()],
+        bump
+    )]
+    pub creator_count: Account<'info, CreatorListingCount>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPlatformFee<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, X402Registry>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ManageTrustedDevices<'info> {
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + TrustedDeviceRegistry::LEN,
+        seeds = [b"trusted_devices"],
+        bump
+    )]
+    pub trusted_devices: Account<'info, TrustedDeviceRegistry>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(new_content_hash: [u8; 32])]
+pub struct UpgradeContent<'info> {
+    #[account(
+        mut,
+        has_one = creator @ ErrorCode::Unauthorized
+    )]
+    pub listing: Account<'info, ContentListing>,
+    
+    /// Released so the old file can be listed again
+    #[account(
+        mut,
+        close = creator,
+        seeds = [b"unique_hash", listing.content_hash.as_ref()],
+        bump = old_unique_hash.bump,
+        constraint = old_unique_hash.listing_id == listing.listing_id @ ErrorCode::ListingMismatch
+    )]
+    pub old_unique_hash: Account<'info, UniqueContentHash>,
+    
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + UniqueContentHash::LEN,
+        seeds = [b"unique_hash", new_content_hash.as_ref()],
+        bump
+    )]
+    pub new_unique_hash: Account<'info, UniqueContentHash>,
+    
+    #[account(
+        init_if_needed,
+        payer = creator,
+        space = 8 + ListingVersionHistory::LEN,
+        seeds = [b"version_history", listing.key().as_ref()],
+        bump
+    )]
+    pub version_history: Account<'info, ListingVersionHistory>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct GetVersionHistory<'info> {
+    pub listing: Account<'info, ContentListing>,
+    
+    #[account(
+        seeds = [b"version_history", listing.key().as_ref()],
+        bump = version_history.bump
+    )]
+    pub version_history: Account<'info, ListingVersionHistory>,
+}
 
 #[derive(Accounts)]
-pub struct SetPlatformFee<'info> {
+pub struct SetAvailabilityWindows<'info> {
+    #[account(
+        mut,
+        has_one = creator @ ErrorCode::Unauthorized
+    )]
+    pub listing: Account<'info, ContentListing>,
+    
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BulkDeactivateListings<'info> {
+    pub registry: Account<'info, X402Registry>,
+    
+    /// Listing creator, registry authority or operator
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct PauseRegistry<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, X402Registry>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UnpauseRegistry<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, X402Registry>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetOperator<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, X402Registry>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(creator: Pubkey)]
+pub struct BlacklistCreator<'info> {
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + BlacklistEntry::LEN,
+        seeds = [b"blacklist", creator.as_ref()],
+        bump
+    )]
+    pub blacklist_entry: Account<'info, BlacklistEntry>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(creator: Pubkey)]
+pub struct RemoveFromBlacklist<'info> {
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"blacklist", creator.as_ref()],
+        bump = blacklist_entry.bump
+    )]
+    pub blacklist_entry: Account<'info, BlacklistEntry>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(creator: Pubkey)]
+pub struct VerifyCreator<'info> {
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + VerifiedCreator::LEN,
+        seeds = [b"verified_creator", creator.as_ref()],
+        bump
+    )]
+    pub verified_creator: Account<'info, VerifiedCreator>,
+    
+    #[account(
+        mut,
+        seeds = [b"creator_profile", creator.as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Option<Account<'info, CreatorProfile>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(creator: Pubkey)]
+pub struct RevokeCreatorVerification<'info> {
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"verified_creator", creator.as_ref()],
+        bump = verified_creator.bump
+    )]
+    pub verified_creator: Account<'info, VerifiedCreator>,
+    
+    #[account(
+        mut,
+        seeds = [b"creator_profile", creator.as_ref()],
+        bump = creator_profile.bump
+    )]
+    pub creator_profile: Option<Account<'info, CreatorProfile>>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetPriceFeed<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, X402Registry>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CreateBuyerProfile<'info> {
+    #[account(
+        init,
+        payer = buyer,
+        space = 8 + BuyerProfile::LEN,
+        seeds = [b"buyer_profile", buyer.key().as_ref()],
+        bump
+    )]
+    pub buyer_profile: Account<'info, BuyerProfile>,
+    
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateCreatorProfile<'info> {
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + CreatorProfile::LEN,
+        seeds = [b"creator_profile", creator.key().as_ref()],
+        bump
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+    
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateCreatorProfile<'info> {
+    #[account(
+        mut,
+        seeds = [b"creator_profile", creator.key().as_ref()],
+        bump = creator_profile.bump,
+        has_one = creator @ ErrorCode::Unauthorized
+    )]
+    pub creator_profile: Account<'info, CreatorProfile>,
+    
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetBuyerProfile<'info> {
+    #[account(
+        seeds = [b"buyer_profile", buyer_profile.buyer.as_ref()],
+        bump = buyer_profile.bump
+    )]
+    pub buyer_profile: Account<'info, BuyerProfile>,
+}
+
+#[derive(Accounts)]
+#[instruction(date: u32)]
+pub struct GetDailyStats<'info> {
+    #[account(seeds = [b"daily_stats", date.to_le_bytes().as_ref()], bump)]
+    pub daily_stats: Account<'info, DailyStats>,
+}
+
+#[derive(Accounts)]
+#[instruction(listing_id: u64)]
+pub struct GenerateSolanaPayUrl<'info> {
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(constraint = listing.listing_id == listing_id @ ErrorCode::ListingMismatch)]
+    pub listing: Account<'info, ContentListing>,
+    
+    /// Needed for the decimals of SPL-priced listings
+    #[account(address = listing.payment_mint @ ErrorCode::PaymentMintMismatch)]
+    pub payment_mint: Option<Account<'info, Mint>>,
+    
+    #[account(address = registry.sol_usd_price_feed)]
+    /// CHECK: Pyth price account, parsed by load_pyth_price; only needed for USD pricing
+    pub pyth_feed: Option<UncheckedAccount<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyContentHash<'info> {
+    pub listing: Account<'info, ContentListing>,
+}
+
+#[derive(Accounts)]
+pub struct GetPurchaseAccumulator<'info> {
+    /// CHECK: Only used to derive the accumulator address
+    pub buyer: UncheckedAccount<'info>,
+    
+    #[account(seeds = [b"purchase_history", buyer.key().as_ref()], bump = purchase_history.bump)]
+    pub purchase_history: Account<'info, PurchaseHistoryAccumulator>,
+}
+
+#[derive(Accounts)]
+pub struct GetListingAnalytics<'info> {
+    pub listing: Account<'info, ContentListing>,
+    
+    #[account(seeds = [b"listing_analytics", listing.key().as_ref()], bump = listing_analytics.bump)]
+    pub listing_analytics: Account<'info, ListingAnalyticsCache>,
+}
+
+#[derive(Accounts)]
+#[instruction(epoch: u64)]
+pub struct GetTrending<'info> {
+    #[account(seeds = [b"trending", epoch.to_le_bytes().as_ref()], bump = trending_index.bump)]
+    pub trending_index: Account<'info, TrendingIndex>,
+}
+
+#[derive(Accounts)]
+pub struct SetGovernanceMint<'info> {
     #[account(mut)]
     pub registry: Account<'info, X402Registry>,
     
+    pub governance_mint: Account<'info, Mint>,
+    
     pub authority: Signer<'info>,
 }
 
-#[account]
-pub struct X402Registry {
-    pub authority: Pubkey,
-    pub listing_count: u64,
-    pub total_revenue: u64,
-    pub platform_fee_bps: u16, // Basis points (100 = 1%)
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(
+        mut,
+        constraint = registry.governance_mint != Pubkey::default() @ ErrorCode::GovernanceNotEnabled
+    )]
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + Proposal::LEN,
+        seeds = [b"proposal", registry.proposal_count.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    #[account(
+        token::mint = registry.governance_mint,
+        token::authority = proposer
+    )]
+    pub proposer_tokens: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VoteOnProposal<'info> {
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    #[account(
+        init,
+        payer = voter,
+        space = 8 + VoteRecord::LEN,
+        seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    
+    #[account(
+        mut,
+        token::mint = registry.governance_mint,
+        token::authority = voter
+    )]
+    pub voter_tokens: Account<'info, TokenAccount>,
+    
+    /// Holds the voting balance until the deadline, owned by the vote record
+    #[account(
+        init,
+        payer = voter,
+        token::mint = governance_mint,
+        token::authority = vote_record,
+        seeds = [b"vote_escrow", vote_record.key().as_ref()],
+        bump
+    )]
+    pub vote_escrow: Account<'info, TokenAccount>,
+    
+    #[account(address = registry.governance_mint)]
+    pub governance_mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawVoteTokens<'info> {
+    #[account(
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    #[account(
+        mut,
+        close = voter,
+        has_one = voter @ ErrorCode::Unauthorized,
+        seeds = [b"vote", proposal.key().as_ref(), voter.key().as_ref()],
+        bump = vote_record.bump
+    )]
+    pub vote_record: Account<'info, VoteRecord>,
+    
+    #[account(
+        mut,
+        seeds = [b"vote_escrow", vote_record.key().as_ref()],
+        bump
+    )]
+    pub vote_escrow: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = vote_escrow.mint,
+        token::authority = voter
+    )]
+    pub voter_tokens: Account<'info, TokenAccount>,
+    
+    #[account(mut)]
+    pub voter: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, X402Registry>,
+    
+    #[account(
+        mut,
+        seeds = [b"proposal", proposal.proposal_id.to_le_bytes().as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, Proposal>,
+    
+    #[account(address = registry.governance_mint)]
+    pub governance_mint: Account<'info, Mint>,
+}
+
+#[account]
+pub struct X402Registry {
+    pub authority: Pubkey,
+    pub listing_count: u64,
+    pub total_revenue: u64,
+    pub platform_fee_bps: u16, // Basis points (100 = 1%)
+    pub governance: GovernanceConfig,
+    pub governance_mint: Pubkey, // Default until governance is enabled
+    pub proposal_count: u64,
+    pub sol_usd_price_feed: Pubkey, // Pyth SOL/USD feed for USD-priced listings
+    pub min_creator_stake: u64,     // Lamports a creator must stake before listing
+    pub stake_discount_tiers: Vec<StakeTier>,
+    pub auto_pause_threshold: u16, // Reports that pause purchases of a listing
+    pub operator: Pubkey,          // Day-to-day admin key, separate from authority
+    pub is_paused: bool,           // Halts register_content and purchase_content
+    pub zero_fee_stake_threshold: u64, // Stake required for FeeModel::ZeroFee listings
+    pub governance_realm: Option<Pubkey>, // SPL Governance realm that must approve fee changes
+    pub min_price_floor: u64,             // Lowest lamport-denominated base price
+    pub max_listings_per_creator: u16,    // Default cap, overridable per creator
+}
+
+impl X402Registry {
+    pub const LEN: usize = 32 + 8 + 8 + 2 + GovernanceConfig::LEN + 32 + 8 + 32 + 8
+        + (4 + MAX_STAKE_TIERS * StakeTier::LEN) + 2 + 32 + 1 + 8 + (1 + 32) + 8 + 2;
+}
+
+#[account]
+pub struct Dispute {
+    pub purchase: Pubkey,
+    pub buyer: Pubkey,
+    pub creator: Pubkey,
+    pub mint: Pubkey,         // Payment mint of the disputed purchase
+    pub disputed_amount: u64, // Held in the purchase hold's vault until resolved
+    pub reason: String,
+    pub status: DisputeStatus,
+    pub opened_at: i64,
+    pub bump: u8,
+}
+
+impl Dispute {
+    pub const LEN: usize = 32 + 32 + 32 + 32 + 8 + (4 + 256) + 1 + 8 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum DisputeStatus {
+    Open,
+    RefundedToBuyer,
+    ReleasedToCreator,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum DisputeVerdict {
+    BuyerWins,
+    CreatorWins,
+}
+
+#[account]
+pub struct CreatorProfile {
+    pub creator: Pubkey,
+    pub display_name: String,
+    pub bio: String,
+    pub total_earned: u64, // Sale proceeds net of platform fee
+    pub listing_count: u64,
+    pub joined_at: i64,
+    pub verified: bool,
+    pub bump: u8,
+    pub total_tips_received: u64,
+    pub active_listing_count: u64,
+    pub bundle_count: u64, // Next bundle id
+}
+
+impl CreatorProfile {
+    pub const LEN: usize = 32 + (4 + 64) + (4 + 256) + 8 + 8 + 8 + 1 + 1 + 8 + 8 + 8;
+}
+
+#[account]
+pub struct CreatorListingCount {
+    pub creator: Pubkey,
+    pub count: u16,                  // Listings registered by this creator
+    pub limit_override: Option<u16>, // Set by the authority, replaces the registry default
+    pub bump: u8,
+}
+
+impl CreatorListingCount {
+    pub const LEN: usize = 32 + 2 + (1 + 2) + 1;
+
+    pub fn limit(&self, registry_default: u16) -> u16 {
+        self.limit_override.unwrap_or(registry_default)
+    }
+}
+
+#[account]
+pub struct BlacklistEntry {
+    pub creator: Pubkey,
+    pub reason: String,
+    pub blacklisted_at: i64,
+    pub blacklisted_by: Pubkey,
+    pub bump: u8,
+}
+
+impl BlacklistEntry {
+    pub const LEN: usize = 32 + (4 + 256) + 8 + 32 + 1;
+
+    /// Whether a blacklist entry has been created at this PDA
+    pub fn exists(info: &AccountInfo) -> bool {
+        info.owner == &crate::ID && !info.data_is_empty()
+    }
+}
+
+#[account]
+pub struct VerifiedCreator {
+    pub creator: Pubkey,
+    pub verified_by: Pubkey,
+    pub verified_at: i64,
+    pub verification_type: VerificationType,
+    pub bump: u8,
+}
+
+impl VerifiedCreator {
+    pub const LEN: usize = 32 + 32 + 8 + (1 + 32) + 1;
+
+    /// Whether a verification badge has been created at this PDA
+    pub fn exists(info: &AccountInfo) -> bool {
+        info.owner == &crate::ID && !info.data_is_empty()
+    }
+
+    /// Domain hash the registry verified for the badge at this PDA, if it is a domain badge
+    pub fn verified_domain_hash(info: &AccountInfo) -> Option<[u8; 32]> {
+        if !Self::exists(info) {
+            return None;
+        }
+        let badge = Self::try_deserialize(&mut &info.try_borrow_data().ok()?[..]).ok()?;
+        match badge.verification_type {
+            VerificationType::Domain { domain_hash } => Some(domain_hash),
+            _ => None,
+        }
+    }
+}
+
+/// How the registry confirmed a creator's identity
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum VerificationType {
+    Identity,     // KYC or government ID review
+    Domain { domain_hash: [u8; 32] }, // Control of a web domain; the hash EmailDomain attestations must prove
+    SocialAccount,
+    Organization, // Registered business or institution
+}
+
+#[account]
+pub struct UniqueContentHash {
+    pub listing_id: u64,
+    pub creator: Pubkey,
+    pub bump: u8,
+}
+
+impl UniqueContentHash {
+    pub const LEN: usize = 8 + 32 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ContentVersionEntry {
+    pub content_hash: [u8; 32],
+    pub changelog: String,
+    pub upgraded_at: i64,
+    pub version_number: u8,
+}
+
+impl ContentVersionEntry {
+    pub const LEN: usize = 32 + (4 + MAX_CHANGELOG_LEN) + 8 + 1;
+}
+
+#[account]
+pub struct ListingVersionHistory {
+    pub listing: Pubkey,
+    pub entries: Vec<ContentVersionEntry>, // Ring buffer of the last MAX_VERSION_HISTORY upgrades
+    pub next_index: u8,                    // Slot the next upgrade overwrites once full
+    pub version_count: u8,                 // Upgrades recorded so far
+    pub bump: u8,
+}
+
+impl ListingVersionHistory {
+    pub const LEN: usize = 32 + (4 + MAX_VERSION_HISTORY * ContentVersionEntry::LEN) + 1 + 1 + 1;
+
+    pub fn record(&mut self, content_hash: [u8; 32], changelog: String, upgraded_at: i64) -> Result<()> {
+        self.version_count = self.version_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        let entry = ContentVersionEntry {
+            content_hash,
+            changelog,
+            upgraded_at,
+            version_number: self.version_count,
+        };
+
+        if self.entries.len() < MAX_VERSION_HISTORY {
+            self.entries.push(entry);
+        } else {
+            self.entries[self.next_index as usize] = entry;
+        }
+        self.next_index = ((self.next_index as usize + 1) % MAX_VERSION_HISTORY) as u8;
+        Ok(())
+    }
+
+    pub fn ordered(&self) -> Vec<ContentVersionEntry> {
+        let mut entries = self.entries.clone();
+        if entries.len() == MAX_VERSION_HISTORY {
+            entries.rotate_left(self.next_index as usize);
+        }
+        entries
+    }
+}
+
+#[account]
+pub struct CreatorStakeAccount {
+    pub creator: Pubkey,
+    pub staked: u64, // Lamports held on this account above rent
+    pub staked_at: i64,
+    pub bump: u8,
+}
+
+impl CreatorStakeAccount {
+    pub const LEN: usize = 32 + 8 + 8 + 1;
+}
+
+#[account]
+pub struct CreatorVault {
+    pub creator: Pubkey,
+    pub bump: u8,
+}
+
+impl CreatorVault {
+    pub const LEN: usize = 32 + 1;
+}
+
+#[account]
+pub struct BuyerProfile {
+    pub buyer: Pubkey,
+    pub total_spent: u64,
+    pub purchase_count: u64,
+    pub reputation_score: u32,
+    pub joined_at: i64,
+    pub last_purchase_at: i64,
+    pub bump: u8,
+}
+
+impl BuyerProfile {
+    pub const LEN: usize = 32 + 8 + 8 + 4 + 8 + 8 + 1;
+}
+
+#[account]
+pub struct TrustedDeviceRegistry {
+    pub devices: Vec<[u8; 32]>, // Ed25519 device keys
+    pub bump: u8,
+}
+
+impl TrustedDeviceRegistry {
+    pub const LEN: usize = (4 + 32 * MAX_TRUSTED_DEVICES) + 1;
+}
+
+#[account]
+pub struct DailyStats {
+    pub date: u32, // Days since the Unix epoch
+    pub listings_created: u32,
+    pub purchases: u32,
+    pub revenue: u64,
+    pub unique_buyers_bitset: [u64; 4], // Bloom filter over buyer keys
+}
+
+impl DailyStats {
+    pub const LEN: usize = 4 + 4 + 4 + 8 + 8 * 4;
+
+    /// Set one filter bit per leading byte of the buyer key
+    pub fn record_buyer(&mut self, buyer: &Pubkey) {
+        for byte in &buyer.as_ref()[0..4] {
+            self.unique_buyers_bitset[(*byte / 64) as usize] |= 1u64 << (byte % 64);
+        }
+    }
+
+    /// Whether the buyer may have purchased today (no false negatives)
+    pub fn may_contain_buyer(&self, buyer: &Pubkey) -> bool {
+        buyer.as_ref()[0..4]
+            .iter()
+            .all(|byte| self.unique_buyers_bitset[(*byte / 64) as usize] & (1u64 << (byte % 64)) != 0)
+    }
+}
+
+#[account]
+pub struct ListingAnalyticsCache {
+    pub listing_id: u64,
+    pub unique_buyer_count: u64,
+    pub avg_price: u64, // Mean final price over all purchases
+    pub last_purchase_at: i64,
+    pub max_price: u64,
+    pub min_price: u64,
+    pub purchase_count: u64, // Purchases folded into avg_price
+    pub bump: u8,
+}
+
+impl ListingAnalyticsCache {
+    pub const LEN: usize = 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1;
+
+    /// Fold a purchase into the running average, price range and buyer count
+    pub fn record_purchase(
+        &mut self,
+        listing_id: u64,
+        price: u64,
+        new_buyer: bool,
+        purchased_at: i64,
+    ) -> Result<()> {
+        if self.purchase_count == 0 {
+            self.listing_id = listing_id;
+            self.min_price = price;
+        }
+        let previous_total = self.avg_price as u128 * self.purchase_count as u128;
+        self.purchase_count = self.purchase_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.avg_price = ((previous_total + price as u128) / self.purchase_count as u128) as u64;
+        self.max_price = self.max_price.max(price);
+        self.min_price = self.min_price.min(price);
+        self.last_purchase_at = purchased_at;
+        if new_buyer {
+            self.unique_buyer_count = self.unique_buyer_count
+                .checked_add(1)
+                .ok_or(ErrorCode::ArithmeticOverflow)?;
+        }
+        Ok(())
+    }
+}
+
+/// Hash chain over a buyer's purchases; clients replay their receipts to check it
+#[account]
+pub struct PurchaseHistoryAccumulator {
+    pub buyer: Pubkey,
+    pub accumulator_hash: [u8; 32], // Zero before the first purchase
+    pub entry_count: u64,
+    pub bump: u8,
+}
+
+impl PurchaseHistoryAccumulator {
+    pub const LEN: usize = 32 + 32 + 8 + 1;
+
+    /// accumulator_hash = sha256(previous || listing_id || purchased_at || final_price), all LE
+    pub fn append(&mut self, listing_id: u64, purchased_at: i64, final_price: u64) -> Result<()> {
+        let mut hasher = Sha256::new();
+        hasher.update(self.accumulator_hash);
+        hasher.update(listing_id.to_le_bytes());
+        hasher.update(purchased_at.to_le_bytes());
+        hasher.update(final_price.to_le_bytes());
+        self.accumulator_hash = hasher.finalize().into();
+        self.entry_count = self.entry_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+/// Marks that a buyer has purchased a listing, for unique buyer counts
+#[account]
+pub struct BuyerListingRecord {
+    pub buyer: Pubkey,
+    pub listing: Pubkey,
+    pub first_purchased_at: i64,
+    pub bump: u8,
+}
+
+impl BuyerListingRecord {
+    pub const LEN: usize = 32 + 32 + 8 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct TrendingEntry {
+    pub listing_id: u64,
+    pub purchase_count: u64, // 0 marks an empty slot
+}
+
+impl TrendingEntry {
+    pub const LEN: usize = 8 + 8;
+}
+
+#[account]
+pub struct TrendingIndex {
+    pub epoch: u64,
+    pub entries: [TrendingEntry; TRENDING_INDEX_SIZE], // Sorted by purchase_count, descending
+    pub bump: u8,
+}
+
+impl TrendingIndex {
+    pub const LEN: usize = 8 + TrendingEntry::LEN * TRENDING_INDEX_SIZE + 1;
+
+    /// Count a purchase, keeping entries sorted; a new listing only displaces
+    /// an empty slot, so ties favour listings already ranked
+    pub fn record_purchase(&mut self, listing_id: u64) -> Result<()> {
+        let slot = match self.entries
+            .iter()
+            .position(|e| e.purchase_count > 0 && e.listing_id == listing_id)
+        {
+            Some(i) => i,
+            None => match self.entries.iter().position(|e| e.purchase_count == 0) {
+                Some(i) => {
+                    self.entries[i].listing_id = listing_id;
+                    i
+                }
+                None => return Ok(()),
+            },
+        };
+        let entry = &mut self.entries[slot];
+        entry.purchase_count = entry.purchase_count
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+
+        // Bubble the updated entry up past lower counts
+        let mut i = slot;
+        while i > 0 && self.entries[i - 1].purchase_count < self.entries[i].purchase_count {
+            self.entries.swap(i - 1, i);
+            i -= 1;
+        }
+        Ok(())
+    }
+
+    /// Ranked entries, without empty slots
+    pub fn ranked(&self) -> &[TrendingEntry] {
+        let len = self.entries.iter().take_while(|e| e.purchase_count > 0).count();
+        &self.entries[..len]
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct StakeTier {
+    pub min_stake_lamports: u64,
+    pub fee_discount_bps: u16, // Subtracted from platform_fee_bps, floored at zero
+}
+
+impl StakeTier {
+    pub const LEN: usize = 8 + 2;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct GovernanceConfig {
+    pub voting_period_seconds: i64,
+    pub execution_delay_seconds: i64, // Time-lock after voting closes
+    pub quorum_bps: u16,              // Share of governance token supply that must vote
+}
+
+impl GovernanceConfig {
+    pub const LEN: usize = 8 + 8 + 2;
+}
+
+#[account]
+pub struct Proposal {
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub parameter: GovernanceParam,
+    pub proposed_value: Vec<u8>, // Little-endian encoding of the new value, Borsh for stake tiers
+    pub votes_for: u64,
+    pub votes_against: u64,
+    pub deadline: i64,
+    pub executed: bool,
+    pub proposed_at: i64,
+    pub bump: u8,
+}
+
+impl Proposal {
+    pub const LEN: usize = 8 + 32 + 1 + (4 + MAX_PROPOSAL_VALUE_LEN) + 8 + 8 + 8 + 1 + 8 + 1;
+}
+
+#[account]
+pub struct VoteRecord {
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub weight: u64,
+    pub support: bool,
+    pub bump: u8,
+}
+
+impl VoteRecord {
+    pub const LEN: usize = 32 + 32 + 8 + 1 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum GovernanceParam {
+    PlatformFeeBps,        // u16
+    VotingPeriodSeconds,   // i64
+    ExecutionDelaySeconds, // i64
+    QuorumBps,             // u16
+    MinCreatorStake,       // u64
+    ZeroFeeStakeThreshold, // u64
+    StakeDiscountTiers,    // Borsh-encoded Vec<StakeTier>
+}
+
+#[account]
+pub struct ContentListing {
+    pub listing_id: u64,
+    pub creator: Pubkey,
+    pub content_hash: [u8; 32],
+    pub pricing: PricingConfig,
+    pub required_credentials: Vec<CredentialRequirement>,
+    pub zk_attestations: Vec<ZkAttestation>,
+    pub metadata: ContentMetadata,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub purchase_count: u64,
+    pub total_revenue: u64,
+    pub state: ContentListingState,
+    pub average_rating: u32, // Mean rating x100 (e.g. 450 = 4.5 stars)
+    pub review_count: u32,
+    pub payment_mint: Pubkey, // SPL mint prices are denominated in
+    pub report_count: u16,    // Open moderation reports; purchases pause at the registry threshold
+    pub secondary_royalty_bps: u16, // Creator's cut of access resales
+    pub escrow_required: bool,      // Purchases go through initiate_purchase / confirm_delivery
+    pub fee_model: FeeModel,
+    pub hash_algorithm: HashAlgorithm, // Function that produced content_hash
+    pub is_verified_creator: bool,     // Creator held a VerifiedCreator badge at registration
+    pub availability_windows: Vec<TimeWindow>, // Empty = purchasable any time
+}
+
+impl ContentListing {
+    pub const LEN: usize = 8 + 32 + 32 + PricingConfig::LEN + 
+                           (4 + CredentialRequirement::LEN * 10) + 
+                           (4 + ZkAttestation::LEN * 5) + 
+                           ContentMetadata::LEN + 8 + 8 + 8 + 8 + 1 + 4 + 4 + 32 + 2 + 2 + 1 + FeeModel::LEN + 1 + 1 +
+                           (4 + TimeWindow::LEN * MAX_AVAILABILITY_WINDOWS);
+
+    /// Whether `now` falls in one of the listing's sale windows (inclusive)
+    pub fn is_available(&self, now: i64) -> bool {
+        self.availability_windows.is_empty()
+            || self.availability_windows
+                .iter()
+                .any(|window| window.start <= now && now <= window.end)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TimeWindow {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl TimeWindow {
+    pub const LEN: usize = 8 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha256,
+    Sha3_256,
+    Blake3,
+    Keccak256,
+}
+
+/// How the platform fee on a listing's sales is computed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum FeeModel {
+    PlatformDefault,                 // Registry fee less any stake tier discount
+    FixedFee { lamports: u64 },      // Flat fee per sale, capped at the price
+    PercentageFee { bps: u16 },      // At least registry.platform_fee_bps
+    ZeroFee,                         // Requires registry.zero_fee_stake_threshold staked
+}
+
+impl FeeModel {
+    pub const LEN: usize = 1 + 8;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ContentListingState {
+    Draft,
+    Active,
+    Paused,
+    Archived,
+}
+
+impl ContentListingState {
+    /// Draft -> Active, Active <-> Paused, Paused -> Archived; Archived is final
+    pub fn can_transition_to(&self, next: &ContentListingState) -> bool {
+        use ContentListingState::*;
+        matches!(
+            (self, next),
+            (Draft, Active) | (Active, Paused) | (Paused, Active) | (Paused, Archived)
+        )
+    }
+}
+
+#[account]
+pub struct Review {
+    pub listing: Pubkey,
+    pub buyer: Pubkey,
+    pub rating: u8,
+    pub review_text: String,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Review {
+    pub const LEN: usize = 32 + 32 + 1 + (4 + 512) + 8 + 1;
+}
+
+#[account]
+pub struct ListingReport {
+    pub listing: Pubkey,
+    pub reporter: Pubkey,
+    pub category: ReportCategory,
+    pub description: String,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl ListingReport {
+    pub const LEN: usize = 32 + 32 + 1 + (4 + 256) + 8 + 1;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ReportCategory {
+    Spam,
+    Fraud,
+    Copyright,
+    Inappropriate,
+    Other,
+}
+
+#[account]
+pub struct PurchaseRecord {
+    pub listing_id: u64,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub final_price: u64,
+    pub purchased_at: i64,
+    pub credentials_used: Vec<CredentialProof>,
+    pub access_granted: bool,
+    pub solana_pay_reference: Option<Pubkey>, // Reference key from the Solana Pay request, if any
+}
+
+impl PurchaseRecord {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + (4 + CredentialProof::LEN * 5) + 1 + (1 + 32);
+}
+
+#[account]
+pub struct ReferralCode {
+    pub code: [u8; 8],
+    pub referrer: Pubkey,
+    pub commission_bps: u16, // Share of the sale price, taken from the creator's proceeds
+    pub uses: u64,
+    pub total_earned: u64,
+    pub bump: u8,
+}
+
+impl ReferralCode {
+    pub const LEN: usize = 8 + 32 + 2 + 8 + 8 + 1;
+
+    pub fn record_use(&mut self, commission: u64) -> Result<()> {
+        self.uses = self.uses
+            .checked_add(1)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        self.total_earned = self.total_earned
+            .checked_add(commission)
+            .ok_or(ErrorCode::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+#[account]
+pub struct CrossDiscount {
+    pub source_listing: Pubkey,
+    pub target_listing: Pubkey, // Discounted listing
+    pub discount_bps: u16,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl CrossDiscount {
+    pub const LEN: usize = 32 + 32 + 2 + 8 + 1;
+}
+
+#[account]
+pub struct PurchaseEscrow {
+    pub listing: Pubkey,
+    pub buyer: Pubkey,
+    pub creator: Pubkey,
+    pub mint: Pubkey,              // Listing's payment mint
+    pub amount: u64,               // Held in the escrow_vault token account
+    pub referral: Option<Pubkey>,  // ReferralCode credited on delivery
+    pub commission: u64,           // Referral's cut of `amount`, fixed at initiation
+    pub opened_at: i64,
+    pub bump: u8,
+}
+
+impl PurchaseEscrow {
+    pub const LEN: usize = 32 + 32 + 32 + 32 + 8 + (1 + 32) + 8 + 8 + 1;
+}
+
+/// A purchase_content or purchase_bundle payment held until its dispute window closes
+#[account]
+pub struct PurchaseHold {
+    pub purchase: Pubkey,
+    pub buyer: Pubkey,
+    pub creator: Pubkey,
+    pub mint: Pubkey,              // Listing's payment mint
+    pub amount: u64,               // Full price, held in the hold_vault token account
+    pub platform_fee: u64,         // Platform's cut of `amount`, fixed at purchase
+    pub referral: Option<Pubkey>,  // ReferralCode paid on release
+    pub commission: u64,           // Referral's cut of `amount`, fixed at purchase
+    pub release_at: i64,           // End of the dispute window
+    pub disputed: bool,
+    pub bump: u8,
+}
+
+impl PurchaseHold {
+    pub const LEN: usize = 32 + 32 + 32 + 32 + 8 + 8 + (1 + 32) + 8 + 8 + 1 + 1;
+
+    /// Whether a dispute can still be opened at `now`
+    pub fn check_disputable(&self, now: i64) -> Result<()> {
+        require!(now < self.release_at, ErrorCode::DisputeWindowClosed);
+        require!(!self.disputed, ErrorCode::PurchaseDisputed);
+        Ok(())
+    }
+
+    /// Whether the hold can be paid out without a dispute verdict at `now`
+    pub fn check_releasable(&self, now: i64) -> Result<()> {
+        require!(now >= self.release_at, ErrorCode::DisputeWindowOpen);
+        require!(!self.disputed, ErrorCode::PurchaseDisputed);
+        Ok(())
+    }
+
+    /// What is left of the held amount for the creator after the platform fee and commission
+    pub fn creator_amount(&self) -> Result<u64> {
+        Ok(self.amount
+            .checked_sub(self.platform_fee)
+            .and_then(|amount| amount.checked_sub(self.commission))
+            .ok_or(ErrorCode::ArithmeticOverflow)?)
+    }
+
+    /// Creator, platform and referrer shares of the held amount
+    pub fn payouts<'info>(
+        &self,
+        creator_ata: &Account<'info, TokenAccount>,
+        platform_fee_ata: &Account<'info, TokenAccount>,
+        referrer_ata: Option<&Account<'info, TokenAccount>>,
+    ) -> Result<Vec<(AccountInfo<'info>, u64)>> {
+        let mut payouts = vec![
+            (creator_ata.to_account_info(), self.creator_amount()?),
+            (platform_fee_ata.to_account_info(), self.platform_fee),
+        ];
+        if self.commission > 0 {
+            let referrer_ata = referrer_ata.ok_or(ErrorCode::ReferralAccountsMissing)?;
+            payouts.push((referrer_ata.to_account_info(), self.commission));
+        }
+        Ok(payouts)
+    }
 }
 
-impl X402Registry {
-    pub const LEN: usize = 32 + 8 + 8 + 2;
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BundleConfig {
+    pub listing_ids: Vec<u64>,
+    pub bundle_price: u64, // In the listings' payment mint
+    pub bundle_name: String,
 }
 
 #[account]
-pub struct ContentListing {
-    pub listing_id: u64,
+pub struct Bundle {
+    pub bundle_id: u64,
     pub creator: Pubkey,
-    pub content_hash: [u8; 32],
-    pub pricing: PricingConfig,
-    pub required_credentials: Vec<CredentialRequirement>,
-    pub zk_attestations: Vec<ZkAttestation>,
-    pub metadata: ContentMetadata,
+    pub listing_ids: Vec<u64>,
+    pub bundle_price: u64,
+    pub bundle_name: String,
+    pub payment_mint: Pubkey,
     pub created_at: i64,
-    pub updated_at: i64,
     pub purchase_count: u64,
-    pub total_revenue: u64,
-    pub is_active: bool,
+    pub bump: u8,
 }
 
-impl ContentListing {
-    pub const LEN: usize = 8 + 32 + 32 + PricingConfig::LEN + 
-                           (4 + CredentialRequirement::LEN * 10) + 
-                           (4 + ZkAttestation::LEN * 5) + 
-                           ContentMetadata::LEN + 8 + 8 + 8 + 8 + 1;
+impl Bundle {
+    pub const LEN: usize = 8 + 32 + (4 + 8 * MAX_BUNDLE_LISTINGS) + 8 + (4 + MAX_BUNDLE_NAME_LEN) + 32 + 8 + 8 + 1;
 }
 
 #[account]
-pub struct PurchaseRecord {
-    pub listing_id: u64,
+pub struct PurchaseNonce {
     pub buyer: Pubkey,
-    pub seller: Pubkey,
-    pub final_price: u64,
-    pub purchased_at: i64,
-    pub credentials_used: Vec<CredentialProof>,
-    pub access_granted: bool,
+    pub listing: Pubkey,
+    pub nonce: [u8; 8],
+    pub used_at: i64,
+    pub bump: u8,
 }
 
-impl PurchaseRecord {
-    pub const LEN: usize = 8 + 32 + 32 + 8 + 8 + (4 + CredentialProof::LEN * 5) + 1;
+impl PurchaseNonce {
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 1;
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -343,131 +6076,534 @@ pub struct CredentialDiscount {
     pub discount_bps: u16, // Discount in basis points
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct PricingConfig {
-    pub base_price: u64,
-    pub credential_discounts: Vec<CredentialDiscount>,
-    pub volume_discount: Option<VolumeDiscount>,
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PricingConfig {
+    pub base_price: u64, // In lamports or US cents, per price_denomination
+    pub credential_discounts: Vec<CredentialDiscount>,
+    pub volume_discount: Option<VolumeDiscount>,
+    pub price_denomination: PriceDenomination,
+    pub allowed_discount_credential_types: Option<Vec<CredentialType>>, // None or empty = all types
+    pub max_total_discount_bps: u16, // Cap on stacked discounts; 0 = DEFAULT_MAX_TOTAL_DISCOUNT_BPS
+}
+
+impl PricingConfig {
+    pub const LEN: usize = 8 + (4 + CredentialDiscount::LEN * 10) + (1 + VolumeDiscount::LEN) + 1
+        + (1 + 4 + CredentialType::LEN * MAX_DISCOUNT_CREDENTIAL_TYPES) + 2;
+
+    /// Effective cap on the sum of all discounts applied to a purchase
+    pub fn discount_cap_bps(&self) -> u16 {
+        match self.max_total_discount_bps {
+            0 => DEFAULT_MAX_TOTAL_DISCOUNT_BPS,
+            cap => cap,
+        }
+    }
+
+    /// Whether proofs of `credential_type` may earn a credential discount
+    pub fn discount_allowed(&self, credential_type: &CredentialType) -> bool {
+        self.allowed_discount_credential_types
+            .as_ref()
+            .is_none_or(|allowed| allowed.is_empty() || allowed.contains(credential_type))
+    }
+}
+
+/// Discount cap used when a listing leaves max_total_discount_bps at 0
+pub const DEFAULT_MAX_TOTAL_DISCOUNT_BPS: u16 = 5000;
+
+/// Highest discount cap a listing may set
+pub const MAX_TOTAL_DISCOUNT_CAP_BPS: u16 = 9000;
+
+/// Credential types a listing can whitelist for discounts
+pub const MAX_DISCOUNT_CREDENTIAL_TYPES: usize = 10;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PriceDenomination {
+    Lamports,
+    UsdCents, // Converted at purchase time; listing must be paid in wrapped SOL
+}
+
+impl CredentialDiscount {
+    pub const LEN: usize = CredentialType::LEN + 2; // CredentialType + u16
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VolumeDiscount {
+    pub min_purchases: u32,
+    pub discount_bps: u16,
+}
+
+impl VolumeDiscount {
+    pub const LEN: usize = 4 + 2;
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum CredentialType {
+    Journalist,
+    Human,
+    Organization,
+    Developer,
+    Custom(String), // Max MAX_CUSTOM_CREDENTIAL_LEN bytes
+}
+
+impl CredentialType {
+    pub const LEN: usize = 1 + (4 + MAX_CUSTOM_CREDENTIAL_LEN); // tag + Custom string
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CredentialRequirement {
+    pub credential_type: CredentialType,
+    pub required: bool, // true = required, false = optional for discounts
+}
+
+impl CredentialRequirement {
+    pub const LEN: usize = CredentialType::LEN + 1; // enum + bool
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct CredentialProof {
+    pub credential_type: CredentialType,
+    pub proof_data: Vec<u8>, // ZK proof of credential ownership
+    pub issuer_pubkey: Pubkey,
+    pub issuer_signature: [u8; 64], // Issuer's Ed25519 signature over credential_message
+    pub issued_at: i64,
+    pub valid_for_seconds: Option<i64>, // None = never expires
+}
+
+impl CredentialProof {
+    pub const LEN: usize = CredentialType::LEN + (4 + 256) + 32 + 64 + 8 + (1 + 8); // enum + proof + pubkey + signature + issued_at + validity
+
+    /// Whether the credential is still valid at `now`
+    pub fn is_valid_at(&self, now: i64) -> bool {
+        now <= self.issued_at.saturating_add(self.valid_for_seconds.unwrap_or(i64::MAX))
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ZkAttestation {
+    pub attestation_type: AttestationType,
+    pub proof_data: Vec<u8>,
+    pub verified_at: i64,
+    pub valid_for_seconds: i64, // How long after verified_at the attestation is trusted
+}
+
+impl ZkAttestation {
+    pub const LEN: usize = 1 + (4 + MAX_ATTESTATION_PROOF_LEN) + 8 + 8;
+
+    /// Fail once the attestation is older than its validity window
+    pub fn check_fresh(&self, now: i64) -> Result<()> {
+        require!(
+            now.saturating_sub(self.verified_at) <= self.valid_for_seconds,
+            ErrorCode::AttestationExpired
+        );
+        Ok(())
+    }
+}
+
+/// `proof_data` layout for `AttestationType::GpsLocation`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct GpsAttestationData {
+    pub proof: Groth16Proof,
+    pub region_commitment: [u8; 32], // Commitment to the allowed region, the proof's public signal
+}
+
+/// `proof_data` layout for `AttestationType::Timestamp` (C2PA-style capture time)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TimestampAttestationData {
+    pub proof: Groth16Proof,
+    pub timestamp_commitment: [u8; 32], // Commitment to the capture time, the proof's public signal
+    pub max_age_seconds: i64,           // How long after verified_at the attestation is accepted
+}
+
+/// `proof_data` layout for `AttestationType::SensorData`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct SensorAttestationData {
+    pub device_pubkey: [u8; 32],
+    pub reading_hash: [u8; 32],
+    pub device_signature: [u8; 64], // Ed25519 signature by device_pubkey over reading_hash
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum AttestationType {
+    EmailDomain,    // Proves email from specific domain
+    Timestamp,      // Proves content created at specific time
+    GpsLocation,    // Proves content created at location
+    SensorData,     // Proves IoT sensor authenticity
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ContentMetadata {
+    pub title: String,
+    pub description: String,
+    pub category: ContentCategory,
+    pub tags: Vec<String>,
+    pub ipfs_cid: Option<String>,
+    pub content_type: Option<ContentMimeType>,
+    pub delivery_channel: DeliveryChannel, // Where buyers fetch the content after grant_access
+}
+
+impl ContentMetadata {
+    pub const LEN: usize = (4 + 128) + (4 + 512) + 1 + (4 + 32 * 10) + (1 + 4 + MAX_IPFS_CID_LEN)
+        + (1 + ContentMimeType::LEN) + DeliveryChannel::LEN;
+}
+
+/// Longest IPFS CID accepted in `ContentMetadata`
+pub const MAX_IPFS_CID_LEN: usize = 64;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum DeliveryChannel {
+    IpfsHash(String),
+    ArweaveId(String),
+    HttpsUrl(String),
+    S3Key(String),
+}
+
+impl DeliveryChannel {
+    pub const LEN: usize = 1 + (4 + MAX_DELIVERY_LOCATOR_LEN);
+
+    pub fn locator(&self) -> &str {
+        match self {
+            DeliveryChannel::IpfsHash(s)
+            | DeliveryChannel::ArweaveId(s)
+            | DeliveryChannel::HttpsUrl(s)
+            | DeliveryChannel::S3Key(s) => s,
+        }
+    }
+}
+
+/// Longest delivery locator (CID, Arweave id, URL or S3 key)
+pub const MAX_DELIVERY_LOCATOR_LEN: usize = 200;
+
+/// Arweave transaction ids are 43 base64url characters
+pub const ARWEAVE_ID_LEN: usize = 43;
+
+/// Sale windows a listing may define
+pub const MAX_AVAILABILITY_WINDOWS: usize = 5;
+
+/// Content versions kept per listing by upgrade_content
+pub const MAX_VERSION_HISTORY: usize = 5;
+
+/// Longest changelog stored with a content version
+pub const MAX_CHANGELOG_LEN: usize = 128;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum ContentCategory {
+    Documents,
+    DataFeed,
+    Media,
+    Code,
+    Other,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum ContentMimeType {
+    ApplicationPdf,
+    ApplicationJson,
+    ImageJpeg,
+    ImagePng,
+    VideoMp4,
+    AudioMp3,
+    TextPlain,
+    Custom(String), // Max MAX_CUSTOM_MIME_LEN bytes
+}
+
+impl ContentMimeType {
+    pub const LEN: usize = 1 + (4 + MAX_CUSTOM_MIME_LEN); // tag + Custom string
+}
+
+/// Longest custom MIME type accepted in `ContentMetadata`
+pub const MAX_CUSTOM_MIME_LEN: usize = 64;
+
+#[event]
+pub struct BulkDeactivated {
+    pub count: u8,
+    pub deactivated_by: Pubkey,
+    pub deactivated_at: i64,
+}
+
+#[event]
+pub struct ListingStateChanged {
+    pub listing_id: u64,
+    pub old_state: ContentListingState,
+    pub new_state: ContentListingState,
+    pub changed_at: i64,
+}
+
+#[event]
+pub struct CreatorVerificationUpdated {
+    pub creator: Pubkey,
+    pub verified: bool,
+    pub updated_by: Pubkey,
+}
+
+#[event]
+pub struct CreatorBlacklistUpdated {
+    pub creator: Pubkey,
+    pub blacklisted: bool,
+    pub updated_by: Pubkey,
+}
+
+#[event]
+pub struct RegistryPaused {
+    pub by: Pubkey,
+    pub at: i64,
+}
+
+#[event]
+pub struct RegistryUnpaused {
+    pub by: Pubkey,
+    pub at: i64,
+}
+
+#[event]
+pub struct OperatorUpdated {
+    pub old_operator: Pubkey,
+    pub new_operator: Pubkey,
+}
+
+#[event]
+pub struct ContentRegistered {
+    pub listing_id: u64,
+    pub creator: Pubkey,
+    pub content_hash: [u8; 32],
+    pub base_price: u64,
+    pub zk_proofs: u8,
+}
+
+#[event]
+pub struct ContentPurchased {
+    pub listing_id: u64,
+    pub buyer: Pubkey,
+    pub seller: Pubkey,
+    pub price_paid: u64,
+    pub platform_fee: u64,
+}
+
+#[event]
+pub struct SolanaPayPurchase {
+    pub reference: Pubkey,
+    pub listing_id: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ReferralCommissionPaid {
+    pub code: [u8; 8],
+    pub referrer: Pubkey,
+    pub listing_id: u64,
+    pub buyer: Pubkey,
+    pub commission: u64,
+}
+
+#[event]
+pub struct CrossDiscountRegistered {
+    pub source_listing_id: u64,
+    pub target_listing_id: u64,
+    pub creator: Pubkey,
+    pub discount_bps: u16,
+}
+
+#[event]
+pub struct CrossDiscountApplied {
+    pub source_listing: Pubkey,
+    pub target_listing: Pubkey,
+    pub buyer: Pubkey,
+    pub discount_bps: u16,
+}
+
+#[event]
+pub struct EscrowOpened {
+    pub listing_id: u64,
+    pub buyer: Pubkey,
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub opened_at: i64,
+}
+
+#[event]
+pub struct EscrowReleased {
+    pub listing_id: u64,
+    pub buyer: Pubkey,
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub platform_fee: u64,
+    pub released_at: i64,
+}
+
+#[event]
+pub struct PurchaseHoldReleased {
+    pub purchase: Pubkey,
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub released_at: i64,
+}
+
+#[event]
+pub struct EscrowRefunded {
+    pub listing: Pubkey,
+    pub buyer: Pubkey,
+    pub amount: u64,
+    pub refunded_at: i64,
 }
 
-impl PricingConfig {
-    pub const LEN: usize = 8 + (4 + CredentialDiscount::LEN * 10) + (1 + VolumeDiscount::LEN);
+#[event]
+pub struct BundleCreated {
+    pub bundle_id: u64,
+    pub creator: Pubkey,
+    pub listing_ids: Vec<u64>,
+    pub bundle_price: u64,
 }
 
-impl CredentialDiscount {
-    pub const LEN: usize = 32 + 2; // CredentialType + u16
+#[event]
+pub struct BundlePurchased {
+    pub bundle_id: u64,
+    pub creator: Pubkey,
+    pub buyer: Pubkey,
+    pub listing_count: u8,
+    pub bundle_price: u64,
+    pub platform_fee: u64,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct VolumeDiscount {
-    pub min_purchases: u32,
-    pub discount_bps: u16,
+#[event]
+pub struct PaymentTokenUsed {
+    pub listing_id: u64,
+    pub mint: Pubkey,
 }
 
-impl VolumeDiscount {
-    pub const LEN: usize = 4 + 2;
+#[event]
+pub struct AvailabilityWindowsUpdated {
+    pub listing_id: u64,
+    pub window_count: u8,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
-pub enum CredentialType {
-    Journalist,
-    Human,
-    Organization,
-    Developer,
-    Custom(String),
+#[event]
+pub struct VersionHistoryUpdated {
+    pub listing_id: u64,
+    pub entries_count: u8,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct CredentialRequirement {
-    pub credential_type: CredentialType,
-    pub required: bool, // true = required, false = optional for discounts
+#[event]
+pub struct ListingUpdated {
+    pub listing_id: u64,
+    pub creator: Pubkey,
+    pub updated_at: i64,
 }
 
-impl CredentialRequirement {
-    pub const LEN: usize = 1 + 32 + 1; // enum + string + bool
+#[event]
+pub struct PriceFloorUpdated {
+    pub old_floor: u64,
+    pub new_floor: u64,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct CredentialProof {
-    pub credential_type: CredentialType,
-    pub proof_data: Vec<u8>, // ZK proof of credential ownership
-    pub issuer_pubkey: Pubkey,
+#[event]
+pub struct CreatorListingLimitSet {
+    pub creator: Pubkey,
+    pub new_limit: u16,
 }
 
-impl CredentialProof {
-    pub const LEN: usize = 1 + 32 + (4 + 256) + 32; // enum + string + proof + pubkey
+#[event]
+pub struct PlatformFeeUpdated {
+    pub old_fee_bps: u16,
+    pub new_fee_bps: u16,
+    pub updated_by: Pubkey,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct ZkAttestation {
-    pub attestation_type: AttestationType,
-    pub proof_data: Vec<u8>,
-    pub verified_at: i64,
+#[event]
+pub struct ListingReported {
+    pub listing_id: u64,
+    pub reporter: Pubkey,
+    pub category: ReportCategory,
 }
 
-impl ZkAttestation {
-    pub const LEN: usize = 1 + 8 + (4 + 256) + 8;
+#[event]
+pub struct ReviewSubmitted {
+    pub listing_id: u64,
+    pub buyer: Pubkey,
+    pub rating: u8,
+    pub average_rating: u32,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub enum AttestationType {
-    EmailDomain,    // Proves email from specific domain
-    Timestamp,      // Proves content created at specific time
-    GpsLocation,    // Proves content created at location
-    SensorData,     // Proves IoT sensor authenticity
+#[event]
+pub struct CreatorTipped {
+    pub listing_id: u64,
+    pub tipper: Pubkey,
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub tipped_at: i64,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct ContentMetadata {
-    pub title: String,
-    pub description: String,
-    pub category: ContentCategory,
-    pub tags: Vec<String>,
+#[event]
+pub struct DisputeOpened {
+    pub purchase: Pubkey,
+    pub buyer: Pubkey,
+    pub creator: Pubkey,
+    pub disputed_amount: u64,
 }
 
-impl ContentMetadata {
-    pub const LEN: usize = (4 + 128) + (4 + 512) + 1 + (4 + 32 * 10);
+#[event]
+pub struct DisputeResolved {
+    pub purchase: Pubkey,
+    pub verdict: DisputeVerdict,
+    pub amount: u64,
+    pub resolved_at: i64,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub enum ContentCategory {
-    Documents,
-    DataFeed,
-    Media,
-    Code,
-    Other,
+#[event]
+pub struct CreatorStaked {
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub total_staked: u64,
 }
 
 #[event]
-pub struct ContentRegistered {
-    pub listing_id: u64,
+pub struct CreatorUnstaked {
     pub creator: Pubkey,
-    pub content_hash: [u8; 32],
-    pub base_price: u64,
-    pub zk_proofs: u8,
+    pub amount: u64,
+    pub remaining_staked: u64,
 }
 
 #[event]
-pub struct ContentPurchased {
-    pub listing_id: u64,
+pub struct CreatorProfileCreated {
+    pub creator: Pubkey,
+    pub display_name: String,
+    pub joined_at: i64,
+}
+
+#[event]
+pub struct BuyerProfileUpdated {
     pub buyer: Pubkey,
-    pub seller: Pubkey,
-    pub price_paid: u64,
-    pub platform_fee: u64,
+    pub total_spent: u64,
+    pub purchase_count: u64,
+    pub reputation_score: u32,
 }
 
 #[event]
-pub struct ListingUpdated {
-    pub listing_id: u64,
-    pub creator: Pubkey,
-    pub updated_at: i64,
+pub struct ProposalCreated {
+    pub proposal_id: u64,
+    pub proposer: Pubkey,
+    pub parameter: GovernanceParam,
+    pub deadline: i64,
 }
 
 #[event]
-pub struct PlatformFeeUpdated {
-    pub old_fee_bps: u16,
-    pub new_fee_bps: u16,
-    pub updated_by: Pubkey,
+pub struct VoteTokensWithdrawn {
+    pub proposal_id: u64,
+    pub voter: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct VoteCast {
+    pub proposal_id: u64,
+    pub voter: Pubkey,
+    pub support: bool,
+    pub weight: u64,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub proposal_id: u64,
+    pub parameter: GovernanceParam,
+    pub votes_for: u64,
+    pub votes_against: u64,
 }
 
 #[error_code]
@@ -480,10 +6616,886 @@ pub enum ErrorCode {
     TitleTooLong,
     #[msg("Description too long (max 512 chars)")]
     DescriptionTooLong,
+    #[msg("Display name too long (max 64 chars)")]
+    DisplayNameTooLong,
+    #[msg("Bio too long (max 256 chars)")]
+    BioTooLong,
+    #[msg("Rating must be between 1 and 5")]
+    InvalidRating,
+    #[msg("Review too long (max 512 chars)")]
+    ReviewTooLong,
+    #[msg("Buyer has already reviewed this listing")]
+    AlreadyReviewed,
+    #[msg("Buyer has no purchase record for this listing")]
+    NoPurchaseRecord,
+    #[msg("Listing does not match listing_id")]
+    ListingMismatch,
+    #[msg("Tip is below the minimum amount")]
+    TipTooSmall,
+    #[msg("Vault balance is too low")]
+    InsufficientVaultBalance,
+    #[msg("Dispute reason too long (max 256 chars)")]
+    ReasonTooLong,
+    #[msg("Dispute is not open")]
+    DisputeNotOpen,
+    #[msg("Token account does not belong to the party the verdict pays")]
+    InvalidDisputeRecipient,
+    #[msg("Dispute window for this purchase has closed")]
+    DisputeWindowClosed,
+    #[msg("Purchase hold is still inside its dispute window")]
+    DisputeWindowOpen,
+    #[msg("Purchase is under dispute")]
+    PurchaseDisputed,
+    #[msg("Listing size exceeds the maximum (65536 bytes)")]
+    ListingTooLarge,
+    #[msg("Listing accounts can only grow")]
+    ListingShrinkNotAllowed,
+    #[msg("Custom credential type too long (max 64 chars)")]
+    CustomCredentialTypeTooLong,
+    #[msg("Token account mint does not match the listing's payment mint")]
+    PaymentMintMismatch,
+    #[msg("Oracle price is missing, invalid or older than 60 seconds")]
+    OraclePriceUnavailable,
+    #[msg("USD-priced listings must be paid in wrapped SOL")]
+    UsdPricingRequiresNativeMint,
+    #[msg("Required credential has expired")]
+    CredentialExpired,
+    #[msg("The operator may only suspend or unsuspend a listing")]
+    OperatorStatusOnly,
+    #[msg("Required credential was not presented")]
+    RequiredCredentialMissing,
+    #[msg("Credential has been revoked by its issuer")]
+    CredentialRevoked,
+    #[msg("Credential is not signed by its issuer for this buyer")]
+    CredentialSignatureInvalid,
+    #[msg("Missing or mismatched credential revocation account")]
+    MissingRevocationAccount,
+    #[msg("Too many ZK attestations (max 5)")]
+    TooManyAttestations,
+    #[msg("Attestation proof data too large (max 384 bytes)")]
+    AttestationTooLarge,
+    #[msg("Attestation proof data is malformed")]
+    MalformedAttestation,
+    #[msg("Attestation verification key account is required")]
+    MissingAttestationKey,
+    #[msg("Attestation verification key is missing, inactive or malformed")]
+    InvalidAttestationKey,
+    #[msg("Email domain attestation failed verification")]
+    EmailDomainAttestationInvalid,
+    #[msg("GPS location attestation failed verification")]
+    GpsAttestationInvalid,
+    #[msg("Timestamp attestation is older than its max age")]
+    TimestampAttestationExpired,
+    #[msg("ZK attestation is past its validity window")]
+    AttestationExpired,
+    #[msg("Attestation validity window must be positive")]
+    InvalidAttestationValidity,
+    #[msg("Listing is not on sale at this time")]
+    OutsideAvailabilityWindow,
+    #[msg("Too many availability windows (max 5)")]
+    TooManyAvailabilityWindows,
+    #[msg("Availability window must end after it starts")]
+    InvalidAvailabilityWindow,
+    #[msg("Timestamp attestation failed verification")]
+    TimestampAttestationInvalid,
+    #[msg("Sensor device is not in the trusted device registry")]
+    UntrustedDevice,
+    #[msg("Sensor device signature is invalid")]
+    DeviceSignatureInvalid,
+    #[msg("Device is already trusted")]
+    DeviceAlreadyTrusted,
+    #[msg("Trusted device registry is full")]
+    TooManyTrustedDevices,
+    #[msg("Creator stake is below the registry minimum")]
+    InsufficientStake,
+    #[msg("Stake amount must be positive and within the staked balance")]
+    InvalidStakeAmount,
+    #[msg("Creator still has active listings")]
+    ActiveListingsExist,
+    #[msg("Registry is paused")]
+    RegistryPaused,
+    #[msg("Creator is blacklisted")]
+    CreatorBlacklisted,
+    #[msg("Listing is under review after community reports")]
+    ListingUnderReview,
+    #[msg("Report description too long")]
+    ReportTooLong,
+    #[msg("Referral commission must be between 1 and MAX_REFERRAL_COMMISSION_BPS")]
+    InvalidReferralCommission,
+    #[msg("Referral code accounts are missing")]
+    ReferralAccountsMissing,
+    #[msg("Buyers cannot use their own referral code")]
+    SelfReferral,
+    #[msg("Discount cap exceeds MAX_TOTAL_DISCOUNT_CAP_BPS")]
+    DiscountCapTooHigh,
+    #[msg("Discount credential whitelist is too long or has duplicates")]
+    InvalidDiscountWhitelist,
+    #[msg("Price is below the registry price floor")]
+    PriceBelowFloor,
+    #[msg("Creator has reached their listing limit")]
+    CreatorListingLimitReached,
+    #[msg("Governance accounts do not authorize this fee change")]
+    InvalidGovernanceProposal,
+    #[msg("Governance proposal is not executing")]
+    ProposalNotExecuting,
+    #[msg("Too many listings in one call")]
+    TooManyListings,
+    #[msg("Creator stake is below the zero-fee threshold")]
+    ZeroFeeStakeTooLow,
+    #[msg("Invalid fee model")]
+    InvalidFeeModel,
+    #[msg("Cross discount accounts do not match this purchase")]
+    CrossDiscountMismatch,
+    #[msg("Discount must be between 1 and 10000 basis points")]
+    InvalidDiscount,
+    #[msg("Listing requires an escrow purchase")]
+    EscrowRequired,
+    #[msg("Listing does not use escrow purchases")]
+    EscrowNotRequired,
+    #[msg("Escrow timeout has not elapsed")]
+    EscrowNotExpired,
+    #[msg("Invalid delivery channel locator")]
+    InvalidDeliveryChannel,
+    #[msg("Content hash does not match the delivery channel")]
+    DeliveryChannelHashMismatch,
+    #[msg("Secondary royalty exceeds the maximum")]
+    RoyaltyTooHigh,
+    #[msg("Bundles hold between 2 and MAX_BUNDLE_LISTINGS listings")]
+    InvalidBundleSize,
+    #[msg("Bundle name too long")]
+    BundleNameTooLong,
+    #[msg("Bundle listing accounts do not match the bundle")]
+    BundleListingMismatch,
+    #[msg("Content type does not match the listing category")]
+    ContentTypeCategoryMismatch,
+    #[msg("Invalid custom content type")]
+    InvalidContentType,
+    #[msg("Invalid IPFS CID")]
+    InvalidIpfsCid,
+    #[msg("This content hash is already registered as a listing")]
+    ContentAlreadyRegistered,
+    #[msg("Listing must be archived first")]
+    ListingStillActive,
+    #[msg("Archived listings cannot be upgraded")]
+    ListingArchived,
+    #[msg("Changelog too long (max 128 chars)")]
+    ChangelogTooLong,
+    #[msg("Invalid listing state transition")]
+    InvalidStateTransition,
+    #[msg("Too many stake discount tiers")]
+    TooManyStakeTiers,
+    #[msg("Stake tier discount exceeds 100%")]
+    InvalidStakeTier,
+    #[msg("Invalid signature")]
+    InvalidSignature,
+    #[msg("Signature verification failed")]
+    SignatureVerificationFailed,
     #[msg("Listing is inactive")]
     ListingInactive,
     #[msg("Unauthorized access")]
     Unauthorized,
     #[msg("Platform fee too high (max 10%)")]
     FeeTooHigh,
+    #[msg("Arithmetic overflow")]
+    ArithmeticOverflow,
+    #[msg("Platform fee is controlled by governance")]
+    GovernanceRequired,
+    #[msg("Governance is already enabled")]
+    GovernanceAlreadyEnabled,
+    #[msg("Governance is not enabled")]
+    GovernanceNotEnabled,
+    #[msg("Proposed value is malformed or out of range")]
+    InvalidProposalValue,
+    #[msg("Voter holds no governance tokens")]
+    NoVotingPower,
+    #[msg("Voting period has ended")]
+    VotingClosed,
+    #[msg("Vote tokens stay locked until voting closes")]
+    VotingStillOpen,
+    #[msg("Proposal has already been executed")]
+    ProposalAlreadyExecuted,
+    #[msg("Execution time-lock has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Proposal did not reach quorum")]
+    QuorumNotReached,
+    #[msg("Proposal was rejected")]
+    ProposalRejected,
+    #[msg("Bundled listings must share one fee model")]
+    BundleFeeModelMismatch,
+    #[msg("Attestation verified_at is in the future")]
+    AttestationFromFuture,
+    #[msg("EmailDomain attestations need a domain verification badge")]
+    DomainNotVerified,
+    #[msg("Buyer's access must be revoked before a dispute refunds them")]
+    AccessNotRevoked,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const SIGNATURE: [u8; 64] = [0x11; 64];
+    const PUBLIC_KEY: [u8; 32] = [0x22; 32];
+    const MESSAGE: [u8; 32] = [0x33; 32];
+
+    /// Single-signature Ed25519 program data laid out like `new_ed25519_instruction`
+    fn ed25519_data(signature: &[u8; 64], public_key: &[u8; 32], message: &[u8; 32]) -> Vec<u8> {
+        let public_key_offset: u16 = 2 + 14;
+        let signature_offset = public_key_offset + 32;
+        let message_offset = signature_offset + 64;
+
+        let mut data = vec![1u8, 0];
+        for field in [
+            signature_offset,
+            u16::MAX,
+            public_key_offset,
+            u16::MAX,
+            message_offset,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(public_key);
+        data.extend_from_slice(signature);
+        data.extend_from_slice(message);
+        data
+    }
+
+    #[test]
+    fn ed25519_instruction_covers_matching_signature() {
+        let data = ed25519_data(&SIGNATURE, &PUBLIC_KEY, &MESSAGE);
+        assert!(ed25519_instruction_covers(&data, &MESSAGE, &SIGNATURE, &PUBLIC_KEY));
+    }
+
+    #[test]
+    fn ed25519_instruction_rejects_wrong_key() {
+        let data = ed25519_data(&SIGNATURE, &[0x44; 32], &MESSAGE);
+        assert!(!ed25519_instruction_covers(&data, &MESSAGE, &SIGNATURE, &PUBLIC_KEY));
+    }
+
+    #[test]
+    fn ed25519_instruction_rejects_wrong_message() {
+        let data = ed25519_data(&SIGNATURE, &PUBLIC_KEY, &[0x55; 32]);
+        assert!(!ed25519_instruction_covers(&data, &MESSAGE, &SIGNATURE, &PUBLIC_KEY));
+    }
+
+    #[test]
+    fn ed25519_instruction_rejects_wrong_offset() {
+        // Signature offset shifted by one byte so it no longer lines up
+        let mut data = ed25519_data(&SIGNATURE, &PUBLIC_KEY, &MESSAGE);
+        data[2] += 1;
+        assert!(!ed25519_instruction_covers(&data, &MESSAGE, &SIGNATURE, &PUBLIC_KEY));
+
+        // Offsets pointing at another instruction are not followed
+        let mut data = ed25519_data(&SIGNATURE, &PUBLIC_KEY, &MESSAGE);
+        data[4..6].copy_from_slice(&0u16.to_le_bytes());
+        assert!(!ed25519_instruction_covers(&data, &MESSAGE, &SIGNATURE, &PUBLIC_KEY));
+
+        // Offset records truncated
+        let data = ed25519_data(&SIGNATURE, &PUBLIC_KEY, &MESSAGE);
+        assert!(!ed25519_instruction_covers(&data[..10], &MESSAGE, &SIGNATURE, &PUBLIC_KEY));
+    }
+
+    // RFC 8032 TEST 1 public key
+    const RFC8032_PUBLIC_KEY: [u8; 32] = [
+        0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64, 0x07, 0x3a,
+        0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68, 0xf7, 0x07, 0x51, 0x1a,
+    ];
+
+    // Signature by the RFC 8032 TEST 1 secret key over the credential message below
+    const RFC8032_CREDENTIAL_SIGNATURE: [u8; 64] = [
+        0x38, 0x4d, 0xee, 0xa0, 0x69, 0x82, 0x5d, 0x16, 0xd0, 0x15, 0x1e, 0xcb, 0x50, 0x7a, 0x94, 0x6e,
+        0x18, 0xaa, 0xe6, 0x55, 0x94, 0x89, 0x62, 0x0a, 0x0b, 0x54, 0x1c, 0x8d, 0xd0, 0x8e, 0x6e, 0x23,
+        0xfa, 0x05, 0x79, 0x2d, 0x19, 0x62, 0xeb, 0x41, 0x2a, 0x6e, 0x9c, 0xee, 0xe6, 0x31, 0xee, 0xaa,
+        0x34, 0x3a, 0x62, 0xbb, 0xe6, 0x10, 0x6d, 0xeb, 0xbb, 0x82, 0xa8, 0xe4, 0x69, 0xa6, 0x82, 0x0f,
+    ];
+
+    #[test]
+    fn ed25519_known_vector_credential() {
+        let proof = CredentialProof {
+            credential_type: CredentialType::Journalist,
+            proof_data: vec![1, 2, 3, 4],
+            issuer_pubkey: Pubkey::new_from_array(RFC8032_PUBLIC_KEY),
+            issuer_signature: RFC8032_CREDENTIAL_SIGNATURE,
+            issued_at: 1_700_000_000,
+            valid_for_seconds: Some(86_400),
+        };
+        let message = credential_message(&proof, &Pubkey::new_from_array([7; 32])).unwrap();
+        assert_eq!(
+            message,
+            [
+                0xc4, 0x8f, 0x32, 0x15, 0x8a, 0xa6, 0xea, 0xb7, 0x0e, 0x07, 0xfe, 0xd0, 0x58, 0xe6, 0xa5, 0x13,
+                0x9d, 0xea, 0x19, 0x0c, 0x5a, 0x51, 0xfe, 0x62, 0x31, 0x79, 0x64, 0x27, 0x9b, 0x90, 0xb0, 0xb5,
+            ]
+        );
+
+        // The instruction a client builds for this vector is the one we accept
+        let data = ed25519_data(&RFC8032_CREDENTIAL_SIGNATURE, &RFC8032_PUBLIC_KEY, &message);
+        assert!(ed25519_instruction_covers(&data, &message, &RFC8032_CREDENTIAL_SIGNATURE, &RFC8032_PUBLIC_KEY));
+
+        let mut tampered = RFC8032_CREDENTIAL_SIGNATURE;
+        tampered[0] ^= 1;
+        assert!(!ed25519_instruction_covers(&data, &message, &tampered, &RFC8032_PUBLIC_KEY));
+        let other_holder = credential_message(&proof, &Pubkey::new_unique()).unwrap();
+        assert!(!ed25519_instruction_covers(&data, &other_holder, &RFC8032_CREDENTIAL_SIGNATURE, &RFC8032_PUBLIC_KEY));
+    }
+
+    fn registry(platform_fee_bps: u16, tiers: Vec<StakeTier>, zero_fee_stake_threshold: u64) -> X402Registry {
+        X402Registry {
+            authority: Pubkey::default(),
+            listing_count: 0,
+            total_revenue: 0,
+            platform_fee_bps,
+            governance: GovernanceConfig {
+                voting_period_seconds: 0,
+                execution_delay_seconds: 0,
+                quorum_bps: 0,
+            },
+            governance_mint: Pubkey::default(),
+            proposal_count: 0,
+            sol_usd_price_feed: Pubkey::default(),
+            min_creator_stake: 0,
+            stake_discount_tiers: tiers,
+            auto_pause_threshold: 0,
+            operator: Pubkey::default(),
+            is_paused: false,
+            zero_fee_stake_threshold,
+            governance_realm: None,
+            min_price_floor: 0,
+            max_listings_per_creator: 0,
+        }
+    }
+
+    fn credential_type(tag: u8) -> CredentialType {
+        match tag % 5 {
+            0 => CredentialType::Journalist,
+            1 => CredentialType::Human,
+            2 => CredentialType::Organization,
+            3 => CredentialType::Developer,
+            _ => CredentialType::Custom("member".to_string()),
+        }
+    }
+
+    fn fee_model(tag: u8, value: u64) -> FeeModel {
+        match tag % 4 {
+            0 => FeeModel::PlatformDefault,
+            1 => FeeModel::FixedFee { lamports: value },
+            2 => FeeModel::PercentageFee { bps: (value % 10001) as u16 },
+            _ => FeeModel::ZeroFee,
+        }
+    }
+
+    proptest! {
+        // Arbitrary amounts through the purchase_content pricing and payout split,
+        // restricted to configurations the registry accepts: every step must
+        // either succeed with amounts bounded by the price or fail cleanly
+        #[test]
+        fn purchase_arithmetic_never_overflows(
+            base_price in any::<u64>(),
+            discounts in prop::collection::vec((0u8..5, 0u16..=10000), 0..10),
+            held in prop::collection::vec((0u8..5, any::<i64>(), prop::option::of(any::<i64>())), 0..5),
+            cross_discount_bps in 0u16..=10000,
+            max_total_discount_bps in 0u16..=MAX_TOTAL_DISCOUNT_CAP_BPS,
+            now in any::<i64>(),
+            platform_fee_bps in 0u16..=1000,
+            tiers in prop::collection::vec((any::<u64>(), 0u16..=10000), 0..MAX_STAKE_TIERS),
+            staked in any::<u64>(),
+            fee_tag in any::<u8>(),
+            fee_value in any::<u64>(),
+            commission_bps in 1u16..=MAX_REFERRAL_COMMISSION_BPS,
+            uses in any::<u64>(),
+            total_earned in any::<u64>(),
+        ) {
+            let pricing = PricingConfig {
+                base_price,
+                credential_discounts: discounts
+                    .iter()
+                    .map(|&(tag, discount_bps)| CredentialDiscount { credential_type: credential_type(tag), discount_bps })
+                    .collect(),
+                volume_discount: None,
+                price_denomination: PriceDenomination::Lamports,
+                allowed_discount_credential_types: None,
+                max_total_discount_bps,
+            };
+            let requirements: Vec<CredentialRequirement> = (0..5)
+                .map(|tag| CredentialRequirement { credential_type: credential_type(tag), required: false })
+                .collect();
+            let proofs: Vec<CredentialProof> = held
+                .iter()
+                .map(|&(tag, issued_at, valid_for_seconds)| CredentialProof {
+                    credential_type: credential_type(tag),
+                    proof_data: Vec::new(),
+                    issuer_pubkey: Pubkey::default(),
+                    issuer_signature: [0u8; 64],
+                    issued_at,
+                    valid_for_seconds,
+                })
+                .collect();
+
+            let price = calculate_price_with_discounts(
+                base_price,
+                &pricing,
+                &requirements,
+                &proofs,
+                cross_discount_bps,
+                now,
+            );
+            let Ok(price) = price else {
+                return Err(TestCaseError::fail("discounted price failed"));
+            };
+            prop_assert!(price <= base_price);
+
+            let tiers = tiers
+                .into_iter()
+                .map(|(min_stake_lamports, fee_discount_bps)| StakeTier { min_stake_lamports, fee_discount_bps })
+                .collect();
+            let registry = registry(platform_fee_bps, tiers, fee_value);
+            let Ok(platform_fee) = platform_fee_for(&fee_model(fee_tag, fee_value), &registry, staked, price) else {
+                return Ok(());
+            };
+            prop_assert!(platform_fee <= price);
+
+            let mut referral = ReferralCode {
+                code: [0u8; 8],
+                referrer: Pubkey::new_unique(),
+                commission_bps,
+                uses,
+                total_earned,
+                bump: 0,
+            };
+            let commission = referral_commission(&referral, &Pubkey::default(), price);
+            let Ok(commission) = commission else {
+                return Err(TestCaseError::fail("referral commission failed"));
+            };
+            prop_assert!(commission <= price);
+            let _ = referral.record_use(commission);
+
+            // Same split as purchase_content: fee and commission come out of the price
+            let creator_amount = price
+                .checked_sub(platform_fee)
+                .and_then(|amount| amount.checked_sub(commission));
+            if let Some(creator_amount) = creator_amount {
+                prop_assert_eq!(creator_amount + platform_fee + commission, price);
+            }
+        }
+    }
+
+    #[test]
+    fn creator_fee_models_never_undercut_the_platform_fee() {
+        let registry = registry(200, Vec::new(), u64::MAX);
+        let default_fee = platform_fee_for(&FeeModel::PlatformDefault, &registry, 0, 10_000).unwrap();
+        assert_eq!(default_fee, 200);
+        for fee_model in [FeeModel::FixedFee { lamports: 0 }, FeeModel::PercentageFee { bps: 0 }] {
+            assert_eq!(platform_fee_for(&fee_model, &registry, 0, 10_000).unwrap(), default_fee);
+        }
+        assert_eq!(platform_fee_for(&FeeModel::FixedFee { lamports: 500 }, &registry, 0, 10_000).unwrap(), 500);
+        assert_eq!(platform_fee_for(&FeeModel::PercentageFee { bps: 500 }, &registry, 0, 10_000).unwrap(), 500);
+        assert_eq!(
+            platform_fee_for(&FeeModel::ZeroFee, &registry, 0, 10_000).unwrap_err(),
+            error!(ErrorCode::ZeroFeeStakeTooLow)
+        );
+    }
+
+    #[test]
+    fn attestations_cannot_be_dated_in_the_future() {
+        let attestation = |verified_at| ZkAttestation {
+            attestation_type: AttestationType::EmailDomain,
+            proof_data: Vec::new(),
+            verified_at,
+            valid_for_seconds: 60,
+        };
+        assert_eq!(
+            verify_zk_attestations(&[attestation(1_001)], 1_000, None, None, None, None, None, None).unwrap_err(),
+            error!(ErrorCode::AttestationFromFuture)
+        );
+        assert_eq!(
+            verify_zk_attestations(&[attestation(900)], 1_000, None, None, None, None, None, None).unwrap_err(),
+            error!(ErrorCode::AttestationExpired)
+        );
+        // Current attestations get as far as proof verification, which
+        // needs a registry-verified domain rather than the proof's own signal
+        assert_eq!(
+            verify_zk_attestations(&[attestation(1_000)], 1_000, None, None, None, None, None, None).unwrap_err(),
+            error!(ErrorCode::DomainNotVerified)
+        );
+        assert_eq!(
+            verify_zk_attestations(&[attestation(1_000)], 1_000, Some([7u8; 32]), None, None, None, None, None)
+                .unwrap_err(),
+            error!(ErrorCode::MissingAttestationKey)
+        );
+    }
+
+    #[test]
+    fn buyer_wins_needs_access_revoked() {
+        let key = Pubkey::new_unique();
+        let permission = |is_active| {
+            let mut data = vec![0u8; 8];
+            AccessPermissionView {
+                buyer: Pubkey::new_unique(),
+                content_hash: [1u8; 32],
+                granted_at: 0,
+                expires_at: None,
+                is_active,
+            }
+            .serialize(&mut data)
+            .unwrap();
+            data
+        };
+        let revoked = |owner: &Pubkey, mut data: Vec<u8>| {
+            let mut lamports = 1;
+            let info = AccountInfo::new(&key, false, false, &mut lamports, &mut data, owner, false, 0);
+            access_revoked(&info)
+        };
+
+        // Still granted: resolve_dispute refuses to refund
+        assert!(!revoked(&ACCESS_CONTROLLER_ID, permission(true)).unwrap());
+        // Revoked through the access controller, or never granted
+        assert!(revoked(&ACCESS_CONTROLLER_ID, permission(false)).unwrap());
+        assert!(revoked(&ACCESS_CONTROLLER_ID, Vec::new()).unwrap());
+        // Only the access controller's own accounts count
+        assert_eq!(
+            revoked(&crate::ID, permission(false)).unwrap_err(),
+            error!(ErrorCode::AccessNotRevoked)
+        );
+    }
+
+    #[test]
+    fn custom_credential_type_length_is_enforced() {
+        let longest = CredentialType::Custom("x".repeat(MAX_CUSTOM_CREDENTIAL_LEN));
+        assert!(validate_credential_type(&longest).is_ok());
+        assert!(validate_credential_type(&CredentialType::Journalist).is_ok());
+        assert_eq!(
+            validate_credential_type(&CredentialType::Custom("x".repeat(65))).unwrap_err(),
+            error!(ErrorCode::CustomCredentialTypeTooLong)
+        );
+
+        // The longest accepted name fits the space reserved for it
+        let requirement = CredentialRequirement { credential_type: longest.clone(), required: true };
+        assert_eq!(requirement.try_to_vec().unwrap().len(), CredentialRequirement::LEN);
+        let proof = CredentialProof {
+            credential_type: longest,
+            proof_data: vec![0u8; 256],
+            issuer_pubkey: Pubkey::default(),
+            issuer_signature: [0u8; 64],
+            issued_at: 0,
+            valid_for_seconds: Some(0),
+        };
+        assert_eq!(proof.try_to_vec().unwrap().len(), CredentialProof::LEN);
+    }
+
+    #[test]
+    fn stacked_credential_discounts_are_capped() {
+        let stacked = [CredentialType::Journalist, CredentialType::Human, CredentialType::Developer];
+        let pricing = PricingConfig {
+            base_price: 10_000,
+            credential_discounts: stacked
+                .iter()
+                .map(|credential_type| CredentialDiscount { credential_type: credential_type.clone(), discount_bps: 3000 })
+                .collect(),
+            volume_discount: None,
+            price_denomination: PriceDenomination::Lamports,
+            allowed_discount_credential_types: None,
+            max_total_discount_bps: 0,
+        };
+        let requirements: Vec<CredentialRequirement> = stacked
+            .iter()
+            .map(|credential_type| CredentialRequirement { credential_type: credential_type.clone(), required: false })
+            .collect();
+        let proofs: Vec<CredentialProof> = stacked
+            .iter()
+            .map(|credential_type| CredentialProof {
+                credential_type: credential_type.clone(),
+                proof_data: Vec::new(),
+                issuer_pubkey: Pubkey::new_unique(),
+                issuer_signature: [0x11; 64],
+                issued_at: 0,
+                valid_for_seconds: None,
+            })
+            .collect();
+
+        // 3 x 30% stacks to 90%, held to the default 50% cap
+        let price = calculate_price_with_discounts(10_000, &pricing, &requirements, &proofs, 0, 0).unwrap();
+        assert_eq!(price, 10_000 - 10_000 * DEFAULT_MAX_TOTAL_DISCOUNT_BPS as u64 / 10_000);
+
+        // A cross-listing discount on top cannot exceed the cap either
+        let price = calculate_price_with_discounts(10_000, &pricing, &requirements, &proofs, 2000, 0).unwrap();
+        assert_eq!(price, 5_000);
+
+        let capped = PricingConfig { max_total_discount_bps: 7000, ..pricing };
+        let price = calculate_price_with_discounts(10_000, &capped, &requirements, &proofs, 0, 0).unwrap();
+        assert_eq!(price, 3_000);
+    }
+
+    #[test]
+    fn credential_message_binds_the_holder() {
+        let proof = CredentialProof {
+            credential_type: CredentialType::Journalist,
+            proof_data: vec![1, 2, 3],
+            issuer_pubkey: Pubkey::new_unique(),
+            issuer_signature: [0x11; 64],
+            issued_at: 100,
+            valid_for_seconds: Some(60),
+        };
+        let holder = Pubkey::new_unique();
+        let message = credential_message(&proof, &holder).unwrap();
+        assert_ne!(message, credential_message(&proof, &Pubkey::new_unique()).unwrap());
+
+        // Extending the validity period invalidates the issuer's signature
+        let extended = CredentialProof { valid_for_seconds: None, ..proof.clone() };
+        assert_ne!(message, credential_message(&extended, &holder).unwrap());
+
+        // Without the instructions sysvar no signature can be checked
+        assert_eq!(
+            verify_credential_issuers(&[proof], &holder, None).unwrap_err(),
+            error!(ErrorCode::CredentialSignatureInvalid)
+        );
+        assert!(verify_credential_issuers(&[], &holder, None).is_ok());
+    }
+
+    #[test]
+    fn required_credentials_must_be_present_and_signed() {
+        let requirements = [
+            CredentialRequirement { credential_type: CredentialType::Journalist, required: true },
+            CredentialRequirement { credential_type: CredentialType::Human, required: false },
+        ];
+        let holder = Pubkey::new_unique();
+        let proof = |credential_type| CredentialProof {
+            credential_type,
+            proof_data: Vec::new(),
+            issuer_pubkey: Pubkey::new_unique(),
+            issuer_signature: [0x11; 64],
+            issued_at: 100,
+            valid_for_seconds: Some(60),
+        };
+
+        // Only the optional credential is presented
+        assert_eq!(
+            check_required_credentials(&requirements, &[proof(CredentialType::Human)], &holder, None, 120)
+                .unwrap_err(),
+            error!(ErrorCode::RequiredCredentialMissing)
+        );
+        assert_eq!(
+            check_required_credentials(&requirements, &[proof(CredentialType::Journalist)], &holder, None, 161)
+                .unwrap_err(),
+            error!(ErrorCode::CredentialExpired)
+        );
+        // Present and unexpired, but nothing vouches for the issuer's signature
+        assert_eq!(
+            check_required_credentials(&requirements, &[proof(CredentialType::Journalist)], &holder, None, 120)
+                .unwrap_err(),
+            error!(ErrorCode::CredentialSignatureInvalid)
+        );
+        assert!(check_required_credentials(&requirements[1..], &[], &holder, None, 120).is_ok());
+    }
+
+    #[test]
+    fn ipfs_cid_formats() {
+        let cid_v0 = "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+        let cid_v1 = "bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi";
+        assert_eq!(cid_v0.len(), 46);
+
+        assert!(validate_ipfs_cid(None).is_ok());
+        assert!(validate_ipfs_cid(Some(cid_v0)).is_ok());
+        assert!(validate_ipfs_cid(Some(cid_v1)).is_ok());
+        assert!(validate_ipfs_cid(Some(&format!("bafy{}", "a".repeat(MAX_IPFS_CID_LEN - 4)))).is_ok());
+
+        for invalid in [
+            String::new(),
+            cid_v0[..45].to_string(),                            // one byte short
+            format!("bafy{}", "a".repeat(MAX_IPFS_CID_LEN - 3)), // one byte too long
+            format!("zdj7W{}", &cid_v1[5..]),                    // base58btc CIDv1
+            cid_v1.to_uppercase(),                               // prefixes are case sensitive
+            cid_v0.replacen("Qm", "QM", 1),
+        ] {
+            assert_eq!(
+                validate_ipfs_cid(Some(&invalid)).unwrap_err(),
+                error!(ErrorCode::InvalidIpfsCid),
+                "{invalid}"
+            );
+        }
+    }
+
+    fn purchase_hold(amount: u64, platform_fee: u64, commission: u64) -> PurchaseHold {
+        PurchaseHold {
+            purchase: Pubkey::new_unique(),
+            buyer: Pubkey::new_unique(),
+            creator: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            amount,
+            platform_fee,
+            referral: (commission > 0).then(Pubkey::new_unique),
+            commission,
+            release_at: 1_000 + DISPUTE_WINDOW_SECONDS,
+            disputed: false,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn purchase_hold_releases_only_after_dispute_window() {
+        let hold = purchase_hold(10_000, 250, 500);
+        let release_at = hold.release_at;
+
+        assert_eq!(hold.check_releasable(1_000).unwrap_err(), error!(ErrorCode::DisputeWindowOpen));
+        assert_eq!(
+            hold.check_releasable(release_at - 1).unwrap_err(),
+            error!(ErrorCode::DisputeWindowOpen)
+        );
+        assert!(hold.check_releasable(release_at).is_ok());
+        assert!(hold.check_releasable(release_at + SECONDS_PER_DAY).is_ok());
+
+        // Disputes are only accepted while the price is still held back
+        assert!(hold.check_disputable(release_at - 1).is_ok());
+        assert_eq!(
+            hold.check_disputable(release_at).unwrap_err(),
+            error!(ErrorCode::DisputeWindowClosed)
+        );
+
+        // Creator, platform and referrer shares add up to the held amount
+        assert_eq!(hold.creator_amount().unwrap(), 9_250);
+        assert_eq!(hold.creator_amount().unwrap() + hold.platform_fee + hold.commission, hold.amount);
+        assert_eq!(
+            purchase_hold(100, 80, 30).creator_amount().unwrap_err(),
+            error!(ErrorCode::ArithmeticOverflow)
+        );
+    }
+
+    #[test]
+    fn disputed_purchase_hold_waits_for_verdict() {
+        let mut hold = purchase_hold(10_000, 250, 0);
+        let release_at = hold.release_at;
+
+        hold.check_disputable(release_at - 1).unwrap();
+        hold.disputed = true;
+
+        // The window closing does not pay the creator out from under an open dispute
+        assert_eq!(
+            hold.check_releasable(release_at + SECONDS_PER_DAY).unwrap_err(),
+            error!(ErrorCode::PurchaseDisputed)
+        );
+        assert_eq!(
+            hold.check_disputable(release_at - 1).unwrap_err(),
+            error!(ErrorCode::PurchaseDisputed)
+        );
+        // A BuyerWins verdict refunds the whole hold, fee included
+        assert_eq!(hold.amount, hold.creator_amount().unwrap() + hold.platform_fee);
+    }
+
+    #[test]
+    fn bundle_fee_split_is_proportional_and_capped() {
+        assert_eq!(split_bundle_fee(&[100, 200, 700], 100).unwrap(), vec![10, 20, 70]);
+
+        // Rounding leftovers go to the first listings that can still take them
+        assert_eq!(split_bundle_fee(&[1, 1, 1], 2).unwrap(), vec![1, 1, 0]);
+        assert_eq!(split_bundle_fee(&[1, 999], 999).unwrap(), vec![1, 998]);
+
+        for (prices, fee) in [
+            (vec![3u64, 5, 7, 11], 13u64),
+            (vec![u64::MAX / 4, 1, u64::MAX / 4], u64::MAX / 3),
+            (vec![0, 50, 0], 50),
+        ] {
+            let fees = split_bundle_fee(&prices, fee).unwrap();
+            assert_eq!(fees.iter().sum::<u64>(), fee, "{prices:?}");
+            assert!(fees.iter().zip(&prices).all(|(fee, price)| fee <= price), "{prices:?}");
+        }
+
+        assert_eq!(split_bundle_fee(&[0, 0], 0).unwrap(), vec![0, 0]);
+        assert_eq!(split_bundle_fee(&[10, 20], 31).unwrap_err(), error!(ErrorCode::ArithmeticOverflow));
+        assert_eq!(
+            split_bundle_fee(&[u64::MAX, 1], 0).unwrap_err(),
+            error!(ErrorCode::ArithmeticOverflow)
+        );
+    }
+
+    #[test]
+    fn proposed_values_are_bounded_per_parameter() {
+        use GovernanceParam::*;
+
+        assert!(validate_proposed_value(&PlatformFeeBps, &1000u16.to_le_bytes()).is_ok());
+        assert_eq!(
+            validate_proposed_value(&PlatformFeeBps, &1001u16.to_le_bytes()).unwrap_err(),
+            error!(ErrorCode::FeeTooHigh)
+        );
+        assert!(validate_proposed_value(&QuorumBps, &10000u16.to_le_bytes()).is_ok());
+        for quorum in [0u16, 10001] {
+            assert_eq!(
+                validate_proposed_value(&QuorumBps, &quorum.to_le_bytes()).unwrap_err(),
+                error!(ErrorCode::InvalidProposalValue)
+            );
+        }
+        for parameter in [VotingPeriodSeconds, ExecutionDelaySeconds] {
+            assert!(validate_proposed_value(&parameter, &1i64.to_le_bytes()).is_ok());
+            for seconds in [0i64, -1] {
+                assert_eq!(
+                    validate_proposed_value(&parameter, &seconds.to_le_bytes()).unwrap_err(),
+                    error!(ErrorCode::InvalidProposalValue)
+                );
+            }
+        }
+        for parameter in [MinCreatorStake, ZeroFeeStakeThreshold] {
+            assert!(validate_proposed_value(&parameter, &u64::MAX.to_le_bytes()).is_ok());
+        }
+
+        // Values must be encoded at their parameter's width
+        for (parameter, width) in [
+            (PlatformFeeBps, 2),
+            (QuorumBps, 2),
+            (VotingPeriodSeconds, 8),
+            (MinCreatorStake, 8),
+        ] {
+            for len in [0, width - 1, width + 1] {
+                assert_eq!(
+                    validate_proposed_value(&parameter, &vec![0; len]).unwrap_err(),
+                    error!(ErrorCode::InvalidProposalValue),
+                    "{parameter:?} {len}"
+                );
+            }
+        }
+
+        let tier = |fee_discount_bps| StakeTier { min_stake_lamports: LAMPORTS_PER_SOL, fee_discount_bps };
+        let tiers = vec![tier(10000); MAX_STAKE_TIERS];
+        assert!(validate_proposed_value(&StakeDiscountTiers, &tiers.try_to_vec().unwrap()).is_ok());
+        assert_eq!(
+            validate_proposed_value(&StakeDiscountTiers, &vec![tier(10001)].try_to_vec().unwrap()).unwrap_err(),
+            error!(ErrorCode::InvalidStakeTier)
+        );
+        assert_eq!(
+            validate_proposed_value(&StakeDiscountTiers, &vec![tier(0); MAX_STAKE_TIERS + 1].try_to_vec().unwrap())
+                .unwrap_err(),
+            error!(ErrorCode::TooManyStakeTiers)
+        );
+        assert_eq!(
+            validate_proposed_value(&StakeDiscountTiers, &[1, 0, 0, 0, 7]).unwrap_err(),
+            error!(ErrorCode::InvalidProposalValue)
+        );
+    }
+
+    #[test]
+    fn usd_conversion_rejects_stale_and_non_positive_prices() {
+        // SOL at $150.00
+        let price = |price, publish_time| PythPrice { price, expo: -2, publish_time };
+        let now = 10_000;
+
+        assert_eq!(lamports_for_usd_cents(15_000, &price(15_000, now), now).unwrap(), LAMPORTS_PER_SOL);
+        assert_eq!(lamports_for_usd_cents(100, &price(15_000, now), now).unwrap(), 6_666_666);
+        assert!(lamports_for_usd_cents(100, &price(15_000, now - MAX_PRICE_AGE_SECONDS), now).is_ok());
+
+        for stale_or_bad in [
+            price(15_000, now - MAX_PRICE_AGE_SECONDS - 1),
+            price(0, now),
+            price(-15_000, now),
+            PythPrice { price: 15_000, expo: 2, publish_time: now },
+        ] {
+            assert_eq!(
+                lamports_for_usd_cents(100, &stale_or_bad, now).unwrap_err(),
+                error!(ErrorCode::OraclePriceUnavailable)
+            );
+        }
+        assert_eq!(
+            lamports_for_usd_cents(u64::MAX, &price(1, now), now).unwrap_err(),
+            error!(ErrorCode::ArithmeticOverflow)
+        );
+    }
 }
@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 
+use anchor_lang::solana_program::program::MAX_RETURN_DATA;
 use sha2::{Sha256, Digest};
 
 
@@ -16,6 +17,8 @@ pub mod zk_meta_registry {
         let registry = &mut ctx.accounts.registry;
         registry.authority = ctx.accounts.authority.key();
         registry.circuit_count = 0;
+        registry.authorized_approvers = vec![registry.authority];
+        registry.approval_threshold = 1;
 
         msg!("ZK Meta Registry initialized with authority: {}", registry.authority);
         Ok(())
@@ -32,35 +35,37 @@ pub mod zk_meta_registry {
             ctx.accounts.authority.key() == ctx.accounts.registry.authority,
             ErrorCode::Unauthorized
         );
+        // Once several approvers are required, keys only change through proposals
+        require!(
+            ctx.accounts.registry.approval_threshold <= 1,
+            ErrorCode::MultiPartyApprovalRequired
+        );
         
         require!(circuit_name.len() <= 32, ErrorCode::CircuitNameTooLong);
         require!(circuit_version.len() <= 16, ErrorCode::VersionTooLong);
-        require!(verification_key_data.len() <= 8192, ErrorCode::VerificationKeyTooLarge);
-        require!(!verification_key_data.is_empty(), ErrorCode::EmptyVerificationKey);
-
-        // Validate verification key by checking basic structure
-        // Ensure the verification key data has a reasonable size and structure
-        if verification_key_data.len() < 32 {
-            return Err(ErrorCode::InvalidVerificationKey.into());
-        }
-        
-        // Basic validation: ensure it's not all zeros
-        if verification_key_data.iter().all(|&x| x == 0) {
-            return Err(ErrorCode::InvalidVerificationKey.into());
-        }
-        
-        // Compute verification key hash for integrity
-        let mut hasher = Sha256::new();
-        hasher.update(&verification_key_data);
-        let vk_hash: [u8; 32] = hasher.finalize().into();
+        parse_circuit_version(&circuit_version)?;
+        let vk_hash = validate_verification_key(&verification_key_data)?;
 
         let vk_entry = &mut ctx.accounts.verification_key_entry;
         vk_entry.circuit_name = circuit_name.clone();
         vk_entry.circuit_version = circuit_version.clone();
         vk_entry.verification_key = verification_key_data;
         vk_entry.verification_key_hash = vk_hash;
+        vk_entry.previous_vk_hash = None;
         vk_entry.registered_at = Clock::get()?.unix_timestamp;
         vk_entry.is_active = true;
+        ctx.accounts.vk_version_record.set(vk_entry, ctx.bumps.vk_version_record);
+
+        let audit_log = &mut ctx.accounts.audit_log;
+        audit_log.init_if_empty(&vk_entry.circuit_name, ctx.bumps.audit_log);
+        audit_log.record(AuditEntry {
+            actor: ctx.accounts.authority.key(),
+            action: VKAuditAction::Register,
+            circuit_name: vk_entry.circuit_name.clone(),
+            timestamp: vk_entry.registered_at,
+            old_vk_hash: None,
+            new_vk_hash: vk_hash,
+        });
 
         let registry = &mut ctx.accounts.registry;
         registry.circuit_count += 1;
@@ -86,16 +91,34 @@ pub mod zk_meta_registry {
             ctx.accounts.authority.key() == ctx.accounts.registry.authority,
             ErrorCode::Unauthorized
         );
+        require!(
+            ctx.accounts.registry.approval_threshold <= 1,
+            ErrorCode::MultiPartyApprovalRequired
+        );
 
         require!(new_version.len() <= 16, ErrorCode::VersionTooLong);
-        require!(verification_key_data.len() <= 8192, ErrorCode::VerificationKeyTooLarge);
+        parse_circuit_version(&new_version)?;
+        let new_vk_hash = validate_verification_key(&verification_key_data)?;
 
         let vk_entry = &mut ctx.accounts.verification_key_entry;
         let old_version = vk_entry.circuit_version.clone();
+        let old_vk_hash = vk_entry.verification_key_hash;
         
         vk_entry.circuit_version = new_version.clone();
         vk_entry.verification_key = verification_key_data;
+        vk_entry.verification_key_hash = new_vk_hash;
+        vk_entry.previous_vk_hash = Some(old_vk_hash);
         vk_entry.registered_at = Clock::get()?.unix_timestamp;
+        ctx.accounts.vk_version_record.set(vk_entry, ctx.bumps.vk_version_record);
+
+        ctx.accounts.audit_log.record(AuditEntry {
+            actor: ctx.accounts.authority.key(),
+            action: VKAuditAction::Update,
+            circuit_name: vk_entry.circuit_name.clone(),
+            timestamp: vk_entry.registered_at,
+            old_vk_hash: Some(old_vk_hash),
+            new_vk_hash,
+        });
 
         emit!(VerificationKeyUpdated {
             circuit_name: vk_entry.circuit_name.clone(),
@@ -116,18 +139,487 @@ pub mod zk_meta_registry {
             ctx.accounts.authority.key() == ctx.accounts.registry.authority,
             ErrorCode::Unauthorized
         );
+        require!(
+            ctx.accounts.registry.approval_threshold <= 1,
+            ErrorCode::MultiPartyApprovalRequired
+        );
+
+        deactivate_key(
+            &mut ctx.accounts.verification_key_entry,
+            &mut ctx.accounts.audit_log,
+            ctx.accounts.authority.key(),
+        )
+    }
+
+    /// Set the approvers and how many of them must sign off a verification key (authority only).
+    ///
+    /// Once several approvers are required, the set only changes through a
+    /// registry proposal.
+    pub fn set_approvers(
+        ctx: Context<SetApprovers>,
+        approvers: Vec<Pubkey>,
+        approval_threshold: u8,
+    ) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        require!(
+            ctx.accounts.authority.key() == registry.authority,
+            ErrorCode::Unauthorized
+        );
+        require!(registry.approval_threshold <= 1, ErrorCode::MultiPartyApprovalRequired);
+        validate_approvers(&approvers, approval_threshold)?;
+
+        registry.authorized_approvers = approvers;
+        registry.approval_threshold = approval_threshold;
+
+        msg!("Approvers updated: count={}, threshold={}", registry.authorized_approvers.len(), approval_threshold);
+        Ok(())
+    }
+
+    /// Propose an approver set change or a key deactivation (authorized approvers only)
+    pub fn propose_registry_action(
+        ctx: Context<ProposeRegistryAction>,
+        action: RegistryAction,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.registry.authorized_approvers.contains(&ctx.accounts.proposer.key()),
+            ErrorCode::Unauthorized
+        );
+        match &action {
+            RegistryAction::SetApprovers { approvers, approval_threshold } => {
+                validate_approvers(approvers, *approval_threshold)?;
+            }
+            RegistryAction::DeactivateKey { circuit_name } => {
+                require!(circuit_name.len() <= 32, ErrorCode::CircuitNameTooLong);
+            }
+        }
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.action = action;
+        proposal.approvals = Vec::new();
+        proposal.created_at = Clock::get()?.unix_timestamp;
+        proposal.bump = ctx.bumps.proposal;
+
+        emit!(RegistryProposalCreated {
+            proposer: proposal.proposer,
+            action: proposal.action.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Approve a pending registry proposal (authorized approvers only)
+    pub fn approve_registry_proposal(ctx: Context<ApproveRegistryProposal>) -> Result<()> {
+        let approver = ctx.accounts.approver.key();
+        require!(
+            ctx.accounts.registry.authorized_approvers.contains(&approver),
+            ErrorCode::Unauthorized
+        );
+
+        let proposal = &mut ctx.accounts.proposal;
+        require!(!proposal.approvals.contains(&approver), ErrorCode::AlreadyApproved);
+        proposal.approvals.push(approver);
+
+        emit!(RegistryProposalApproved {
+            proposer: proposal.proposer,
+            approver,
+            approval_count: proposal.approvals.len() as u8,
+        });
+
+        Ok(())
+    }
+
+    /// Apply an approved registry proposal and close it, refunding the proposer
+    pub fn finalize_registry_proposal(ctx: Context<FinalizeRegistryProposal>) -> Result<()> {
+        let proposal = &ctx.accounts.proposal;
+        require!(
+            current_approvals(&ctx.accounts.registry, &proposal.approvals)
+                >= ctx.accounts.registry.approval_threshold as usize,
+            ErrorCode::InsufficientApprovals
+        );
+
+        match proposal.action.clone() {
+            RegistryAction::SetApprovers { approvers, approval_threshold } => {
+                let registry = &mut ctx.accounts.registry;
+                registry.authorized_approvers = approvers;
+                registry.approval_threshold = approval_threshold;
+
+                msg!("Approvers updated: count={}, threshold={}", registry.authorized_approvers.len(), approval_threshold);
+            }
+            RegistryAction::DeactivateKey { circuit_name } => {
+                let vk_entry = ctx
+                    .accounts
+                    .verification_key_entry
+                    .as_mut()
+                    .ok_or(ErrorCode::MissingVerificationKeyEntry)?;
+                require!(vk_entry.circuit_name == circuit_name, ErrorCode::MissingVerificationKeyEntry);
+                let audit_log = ctx
+                    .accounts
+                    .audit_log
+                    .as_mut()
+                    .ok_or(ErrorCode::MissingVerificationKeyEntry)?;
+                require!(audit_log.circuit_name == circuit_name, ErrorCode::MissingVerificationKeyEntry);
+
+                deactivate_key(vk_entry, audit_log, proposal.proposer)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Propose a verification key for a circuit; it is written only after enough approvals
+    pub fn propose_verification_key(
+        ctx: Context<ProposeVerificationKey>,
+        circuit_name: String,
+        circuit_version: String,
+        verification_key_data: Vec<u8>,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.registry.authorized_approvers.contains(&ctx.accounts.proposer.key()),
+            ErrorCode::Unauthorized
+        );
+        require!(circuit_name.len() <= 32, ErrorCode::CircuitNameTooLong);
+        require!(circuit_version.len() <= 16, ErrorCode::VersionTooLong);
+        parse_circuit_version(&circuit_version)?;
+        let vk_hash = validate_verification_key(&verification_key_data)?;
+
+        let proposal = &mut ctx.accounts.proposal;
+        proposal.proposer = ctx.accounts.proposer.key();
+        proposal.circuit_name = circuit_name.clone();
+        proposal.circuit_version = circuit_version.clone();
+        proposal.verification_key = verification_key_data;
+        proposal.verification_key_hash = vk_hash;
+        proposal.approvals = Vec::new();
+        proposal.created_at = Clock::get()?.unix_timestamp;
+        proposal.finalized = false;
+        proposal.bump = ctx.bumps.proposal;
+
+        emit!(VKProposalCreated {
+            circuit_name,
+            circuit_version,
+            verification_key_hash: vk_hash,
+            proposer: proposal.proposer,
+        });
+
+        Ok(())
+    }
+
+    /// Approve a pending verification key proposal (authorized approvers only)
+    pub fn approve_vk_proposal(ctx: Context<ApproveVKProposal>) -> Result<()> {
+        let approver = ctx.accounts.approver.key();
+        require!(
+            ctx.accounts.registry.authorized_approvers.contains(&approver),
+            ErrorCode::Unauthorized
+        );
 
+        let proposal = &mut ctx.accounts.proposal;
+        require!(!proposal.finalized, ErrorCode::ProposalAlreadyFinalized);
+        require!(!proposal.approvals.contains(&approver), ErrorCode::AlreadyApproved);
+        proposal.approvals.push(approver);
+
+        emit!(VKProposalApproved {
+            circuit_name: proposal.circuit_name.clone(),
+            circuit_version: proposal.circuit_version.clone(),
+            approver,
+            approval_count: proposal.approvals.len() as u8,
+        });
+
+        Ok(())
+    }
+
+    /// Write an approved proposal to its circuit's verification key entry
+    pub fn finalize_vk_proposal(ctx: Context<FinalizeVKProposal>) -> Result<()> {
+        let registry = &mut ctx.accounts.registry;
+        let proposal = &mut ctx.accounts.proposal;
+        require!(!proposal.finalized, ErrorCode::ProposalAlreadyFinalized);
+
+        require!(
+            current_approvals(registry, &proposal.approvals) >= registry.approval_threshold as usize,
+            ErrorCode::InsufficientApprovals
+        );
+        proposal.finalized = true;
+
+        let now = Clock::get()?.unix_timestamp;
         let vk_entry = &mut ctx.accounts.verification_key_entry;
-        vk_entry.is_active = false;
+        let is_new = vk_entry.circuit_name.is_empty();
+        let old_version = vk_entry.circuit_version.clone();
+        let old_vk_hash = (!is_new).then_some(vk_entry.verification_key_hash);
+
+        vk_entry.circuit_name = proposal.circuit_name.clone();
+        vk_entry.circuit_version = proposal.circuit_version.clone();
+        vk_entry.verification_key = proposal.verification_key.clone();
+        vk_entry.verification_key_hash = proposal.verification_key_hash;
+        vk_entry.previous_vk_hash = old_vk_hash;
+        vk_entry.registered_at = now;
+        vk_entry.is_active = true;
+        ctx.accounts.vk_version_record.set(vk_entry, ctx.bumps.vk_version_record);
 
-        emit!(VerificationKeyDeactivated {
+        let audit_log = &mut ctx.accounts.audit_log;
+        audit_log.init_if_empty(&vk_entry.circuit_name, ctx.bumps.audit_log);
+        audit_log.record(AuditEntry {
+            actor: proposal.proposer,
+            action: if is_new { VKAuditAction::Register } else { VKAuditAction::Update },
             circuit_name: vk_entry.circuit_name.clone(),
-            circuit_version: vk_entry.circuit_version.clone(),
+            timestamp: now,
+            old_vk_hash,
+            new_vk_hash: vk_entry.verification_key_hash,
+        });
+
+        if is_new {
+            registry.circuit_count += 1;
+
+            emit!(VerificationKeyRegistered {
+                circuit_name: vk_entry.circuit_name.clone(),
+                circuit_version: vk_entry.circuit_version.clone(),
+                authority: proposal.proposer,
+                registered_at: now,
+            });
+        } else {
+            emit!(VerificationKeyUpdated {
+                circuit_name: vk_entry.circuit_name.clone(),
+                old_version,
+                new_version: vk_entry.circuit_version.clone(),
+                updated_at: now,
+            });
+        }
+
+        msg!("Verification key proposal finalized for circuit: {}", vk_entry.circuit_name);
+        Ok(())
+    }
+
+    /// Check that a circuit's key history links back unbroken to `expected_root_hash`.
+    ///
+    /// Remaining accounts are the `VKVersionRecord`s of each previous key,
+    /// newest first, down to the genesis key.
+    pub fn verify_vk_chain<'info>(
+        ctx: Context<'_, '_, 'info, 'info, VerifyVKChain<'info>>,
+        circuit_name: String,
+        expected_root_hash: [u8; 32],
+    ) -> Result<bool> {
+        let vk_entry = &ctx.accounts.verification_key_entry;
+        let name_hash = seed_hash(&circuit_name);
+
+        let mut current = vk_entry.verification_key_hash;
+        let mut previous = vk_entry.previous_vk_hash;
+        let mut records = ctx.remaining_accounts.iter();
+
+        while let Some(previous_hash) = previous {
+            let Some(record_info) = records.next() else {
+                return Ok(false);
+            };
+            let (expected_address, _) = Pubkey::find_program_address(
+                &[b"vk_version", &name_hash, &previous_hash],
+                &crate::ID,
+            );
+            if record_info.key() != expected_address {
+                return Ok(false);
+            }
+
+            let record: Account<VKVersionRecord> = Account::try_from(record_info)?;
+            current = record.vk_hash;
+            previous = record.previous_vk_hash;
+        }
+
+        Ok(current == expected_root_hash)
+    }
+
+    /// Parse the structural parameters from a verification key's header
+    pub fn extract_circuit_params(ctx: Context<ExtractCircuitParams>) -> Result<CircuitParams> {
+        CircuitParams::parse(&ctx.accounts.verification_key_entry.verification_key)
+    }
+
+    /// Return a circuit's audit log, oldest first.
+    ///
+    /// Return data is capped at 1 KB, so only the most recent entries that fit
+    /// are returned; the full log can be read from the account.
+    pub fn get_audit_log(ctx: Context<GetAuditLog>) -> Result<Vec<AuditEntry>> {
+        let mut entries = ctx.accounts.audit_log.chronological();
+
+        let mut size = 4; // Vec length prefix
+        let mut keep = 0;
+        for entry in entries.iter().rev() {
+            size += entry.try_to_vec()?.len();
+            if size > MAX_RETURN_DATA {
+                break;
+            }
+            keep += 1;
+        }
+        entries.drain(..entries.len() - keep);
+
+        Ok(entries)
+    }
+
+    /// Record that proofs of `proof_version` verify against keys of `vk_version`
+    pub fn register_compatibility(
+        ctx: Context<RegisterCompatibility>,
+        circuit_name: String,
+        proof_version: String,
+        vk_version: String,
+    ) -> Result<()> {
+        require!(
+            ctx.accounts.authority.key() == ctx.accounts.registry.authority,
+            ErrorCode::Unauthorized
+        );
+
+        require!(circuit_name.len() <= 32, ErrorCode::CircuitNameTooLong);
+        validate_compatibility_versions(&proof_version, &vk_version)?;
+
+        let entry = &mut ctx.accounts.compatibility_entry;
+        entry.circuit_name = circuit_name.clone();
+        entry.proof_version = proof_version.clone();
+        entry.vk_version = vk_version.clone();
+        entry.registered_at = Clock::get()?.unix_timestamp;
+        entry.bump = ctx.bumps.compatibility_entry;
+
+        emit!(CompatibilityRegistered {
+            circuit_name,
+            proof_version,
+            vk_version,
+            registered_at: entry.registered_at,
         });
 
-        msg!("Verification key deactivated for circuit: {}", vk_entry.circuit_name);
         Ok(())
     }
+
+    /// Whether proofs of `proof_version` are compatible with keys of `vk_version` (for CPI callers)
+    pub fn check_compatibility(
+        ctx: Context<CheckCompatibility>,
+        circuit_name: String,
+        proof_version: String,
+        vk_version: String,
+    ) -> Result<bool> {
+        let entry = &ctx.accounts.compatibility_entry;
+        Ok(entry.circuit_name == circuit_name
+            && entry.proof_version == proof_version
+            && entry.vk_version == vk_version)
+    }
+
+    /// Report this build's version so clients can check compatibility
+    pub fn get_program_version(_ctx: Context<GetProgramVersion>) -> Result<ProgramVersion> {
+        Ok(PROGRAM_VERSION)
+    }
+}
+
+#[derive(Accounts)]
+pub struct GetProgramVersion<'info> {
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ProgramVersion {
+    pub major: u8,
+    pub minor: u8,
+    pub patch: u8,
+    pub build_commit: [u8; 8], // First 8 chars of GIT_SHA at build time, "00000000" if unset
+}
+
+/// Version compiled into this program from its Cargo.toml
+pub const PROGRAM_VERSION: ProgramVersion = ProgramVersion {
+    major: program_version::version_component(env!("CARGO_PKG_VERSION"), 0),
+    minor: program_version::version_component(env!("CARGO_PKG_VERSION"), 1),
+    patch: program_version::version_component(env!("CARGO_PKG_VERSION"), 2),
+    build_commit: program_version::build_commit(option_env!("GIT_SHA")),
+};
+
+// Only approvals from current approvers count towards the threshold
+fn current_approvals(registry: &ZkMetaRegistry, approvals: &[Pubkey]) -> usize {
+    approvals
+        .iter()
+        .filter(|approver| registry.authorized_approvers.contains(approver))
+        .count()
+}
+
+fn validate_approvers(approvers: &[Pubkey], approval_threshold: u8) -> Result<()> {
+    require!(approvers.len() <= MAX_APPROVERS, ErrorCode::TooManyApprovers);
+    require!(
+        approval_threshold > 0 && approval_threshold as usize <= approvers.len(),
+        ErrorCode::InvalidApprovalThreshold
+    );
+    Ok(())
+}
+
+fn deactivate_key(
+    vk_entry: &mut VerificationKeyEntry,
+    audit_log: &mut AuditLog,
+    actor: Pubkey,
+) -> Result<()> {
+    vk_entry.is_active = false;
+
+    audit_log.record(AuditEntry {
+        actor,
+        action: VKAuditAction::Deactivate,
+        circuit_name: vk_entry.circuit_name.clone(),
+        timestamp: Clock::get()?.unix_timestamp,
+        old_vk_hash: Some(vk_entry.verification_key_hash),
+        new_vk_hash: vk_entry.verification_key_hash,
+    });
+
+    emit!(VerificationKeyDeactivated {
+        circuit_name: vk_entry.circuit_name.clone(),
+        circuit_version: vk_entry.circuit_version.clone(),
+    });
+
+    msg!("Verification key deactivated for circuit: {}", vk_entry.circuit_name);
+    Ok(())
+}
+
+/// Check both versions of a compatibility entry are `vMAJOR.MINOR` strings
+fn validate_compatibility_versions(proof_version: &str, vk_version: &str) -> Result<()> {
+    require!(proof_version.len() <= 16, ErrorCode::VersionTooLong);
+    require!(vk_version.len() <= 16, ErrorCode::VersionTooLong);
+    parse_circuit_version(proof_version)?;
+    parse_circuit_version(vk_version)?;
+    Ok(())
+}
+
+/// Check size and basic structure of serialized key data and return its SHA256 hash
+fn validate_verification_key(verification_key_data: &[u8]) -> Result<[u8; 32]> {
+    require!(verification_key_data.len() <= 8192, ErrorCode::VerificationKeyTooLarge);
+    require!(!verification_key_data.is_empty(), ErrorCode::EmptyVerificationKey);
+
+    // Validate verification key by checking basic structure
+    // Ensure the verification key data has a reasonable size and structure
+    if verification_key_data.len() < 32 {
+        return Err(ErrorCode::InvalidVerificationKey.into());
+    }
+    
+    // Basic validation: ensure it's not all zeros
+    if verification_key_data.iter().all(|&x| x == 0) {
+        return Err(ErrorCode::InvalidVerificationKey.into());
+    }
+    
+    // Compute verification key hash for integrity
+    let mut hasher = Sha256::new();
+    hasher.update(verification_key_data);
+    Ok(hasher.finalize().into())
+}
+
+/// Parse a `vMAJOR.MINOR` circuit version, each part 1-3 decimal digits
+pub fn parse_circuit_version(version: &str) -> Result<(u16, u16)> {
+    let parse_part = |part: &str| -> Result<u16> {
+        require!(
+            (1..=3).contains(&part.len()) && part.bytes().all(|b| b.is_ascii_digit()),
+            ErrorCode::InvalidVersionFormat
+        );
+        part.parse().map_err(|_| error!(ErrorCode::InvalidVersionFormat))
+    };
+
+    let (major, minor) = version
+        .strip_prefix('v')
+        .and_then(|rest| rest.split_once('.'))
+        .ok_or(ErrorCode::InvalidVersionFormat)?;
+
+    Ok((parse_part(major)?, parse_part(minor)?))
+}
+
+/// SHA256 of a name or version string, used to keep PDA seeds within 32 bytes
+pub fn seed_hash(value: &str) -> [u8; 32] {
+    data_hash(value.as_bytes())
+}
+
+/// SHA256 of raw bytes, e.g. serialized verification key data
+pub fn data_hash(value: &[u8]) -> [u8; 32] {
+    Sha256::digest(value).into()
 }
 
 #[derive(Accounts)]
@@ -147,7 +639,7 @@ pub struct Initialize<'info> {
 }
 
 #[derive(Accounts)]
-#[instruction(circuit_name: String)]
+#[instruction(circuit_name: String, circuit_version: String, verification_key_data: Vec<u8>)]
 pub struct RegisterVerificationKey<'info> {
     #[account(mut)]
     pub registry: Account<'info, ZkMetaRegistry>,
@@ -161,12 +653,31 @@ pub struct RegisterVerificationKey<'info> {
     )]
     pub verification_key_entry: Account<'info, VerificationKeyEntry>,
     
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + AuditLog::LEN,
+        seeds = [b"audit_log".as_ref(), &seed_hash(&circuit_name)],
+        bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + VKVersionRecord::LEN,
+        seeds = [b"vk_version".as_ref(), &seed_hash(&circuit_name), &data_hash(&verification_key_data)],
+        bump
+    )]
+    pub vk_version_record: Account<'info, VKVersionRecord>,
+    
     #[account(mut)]
     pub authority: Signer<'info>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
+#[instruction(new_version: String, verification_key_data: Vec<u8>)]
 pub struct UpdateVerificationKey<'info> {
     pub registry: Account<'info, ZkMetaRegistry>,
     
@@ -177,7 +688,29 @@ pub struct UpdateVerificationKey<'info> {
     )]
     pub verification_key_entry: Account<'info, VerificationKeyEntry>,
     
+    #[account(
+        mut,
+        seeds = [b"audit_log".as_ref(), &seed_hash(&verification_key_entry.circuit_name)],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + VKVersionRecord::LEN,
+        seeds = [
+            b"vk_version".as_ref(),
+            &seed_hash(&verification_key_entry.circuit_name),
+            &data_hash(&verification_key_data)
+        ],
+        bump
+    )]
+    pub vk_version_record: Account<'info, VKVersionRecord>,
+    
+    #[account(mut)]
     pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
@@ -191,17 +724,284 @@ pub struct DeactivateVerificationKey<'info> {
     )]
     pub verification_key_entry: Account<'info, VerificationKeyEntry>,
     
+    #[account(
+        mut,
+        seeds = [b"audit_log".as_ref(), &seed_hash(&verification_key_entry.circuit_name)],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+    
     pub authority: Signer<'info>,
 }
 
+#[derive(Accounts)]
+#[instruction(circuit_name: String)]
+pub struct VerifyVKChain<'info> {
+    #[account(
+        seeds = [b"vk_entry", circuit_name.as_bytes()],
+        bump
+    )]
+    pub verification_key_entry: Account<'info, VerificationKeyEntry>,
+}
+
+#[derive(Accounts)]
+pub struct ExtractCircuitParams<'info> {
+    #[account(
+        seeds = [b"vk_entry", verification_key_entry.circuit_name.as_bytes()],
+        bump
+    )]
+    pub verification_key_entry: Account<'info, VerificationKeyEntry>,
+}
+
+#[derive(Accounts)]
+pub struct GetAuditLog<'info> {
+    #[account(
+        seeds = [b"audit_log".as_ref(), &seed_hash(&audit_log.circuit_name)],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+}
+
+#[derive(Accounts)]
+pub struct SetApprovers<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, ZkMetaRegistry>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(circuit_name: String, circuit_version: String)]
+pub struct ProposeVerificationKey<'info> {
+    pub registry: Account<'info, ZkMetaRegistry>,
+    
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + VKProposal::LEN,
+        seeds = [b"vk_proposal", circuit_name.as_bytes(), circuit_version.as_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, VKProposal>,
+    
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveVKProposal<'info> {
+    pub registry: Account<'info, ZkMetaRegistry>,
+    
+    #[account(
+        mut,
+        seeds = [b"vk_proposal", proposal.circuit_name.as_bytes(), proposal.circuit_version.as_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, VKProposal>,
+    
+    pub approver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeVKProposal<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, ZkMetaRegistry>,
+    
+    #[account(
+        mut,
+        seeds = [b"vk_proposal", proposal.circuit_name.as_bytes(), proposal.circuit_version.as_bytes()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, VKProposal>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + VerificationKeyEntry::LEN,
+        seeds = [b"vk_entry", proposal.circuit_name.as_bytes()],
+        bump
+    )]
+    pub verification_key_entry: Account<'info, VerificationKeyEntry>,
+    
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + AuditLog::LEN,
+        seeds = [b"audit_log".as_ref(), &seed_hash(&proposal.circuit_name)],
+        bump
+    )]
+    pub audit_log: Account<'info, AuditLog>,
+    
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + VKVersionRecord::LEN,
+        seeds = [
+            b"vk_version".as_ref(),
+            &seed_hash(&proposal.circuit_name),
+            &proposal.verification_key_hash
+        ],
+        bump
+    )]
+    pub vk_version_record: Account<'info, VKVersionRecord>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ProposeRegistryAction<'info> {
+    pub registry: Account<'info, ZkMetaRegistry>,
+    
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + RegistryProposal::LEN,
+        seeds = [b"registry_proposal", proposer.key().as_ref()],
+        bump
+    )]
+    pub proposal: Account<'info, RegistryProposal>,
+    
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveRegistryProposal<'info> {
+    pub registry: Account<'info, ZkMetaRegistry>,
+    
+    #[account(
+        mut,
+        seeds = [b"registry_proposal", proposal.proposer.as_ref()],
+        bump = proposal.bump
+    )]
+    pub proposal: Account<'info, RegistryProposal>,
+    
+    pub approver: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeRegistryProposal<'info> {
+    #[account(mut)]
+    pub registry: Account<'info, ZkMetaRegistry>,
+    
+    #[account(
+        mut,
+        seeds = [b"registry_proposal", proposal.proposer.as_ref()],
+        bump = proposal.bump,
+        close = proposer
+    )]
+    pub proposal: Account<'info, RegistryProposal>,
+    
+    /// CHECK: Receives the proposal's rent
+    #[account(mut, address = proposal.proposer)]
+    pub proposer: UncheckedAccount<'info>,
+    
+    /// Only needed for `RegistryAction::DeactivateKey`
+    #[account(
+        mut,
+        seeds = [b"vk_entry", verification_key_entry.circuit_name.as_bytes()],
+        bump
+    )]
+    pub verification_key_entry: Option<Account<'info, VerificationKeyEntry>>,
+    
+    #[account(
+        mut,
+        seeds = [b"audit_log".as_ref(), &seed_hash(&audit_log.circuit_name)],
+        bump = audit_log.bump
+    )]
+    pub audit_log: Option<Account<'info, AuditLog>>,
+}
+
+#[derive(Accounts)]
+#[instruction(circuit_name: String, proof_version: String)]
+pub struct RegisterCompatibility<'info> {
+    pub registry: Account<'info, ZkMetaRegistry>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + CompatibilityEntry::LEN,
+        seeds = [b"compat".as_ref(), &seed_hash(&circuit_name), &seed_hash(&proof_version)],
+        bump
+    )]
+    pub compatibility_entry: Account<'info, CompatibilityEntry>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CheckCompatibility<'info> {
+    #[account(
+        seeds = [
+            b"compat".as_ref(),
+            &seed_hash(&compatibility_entry.circuit_name),
+            &seed_hash(&compatibility_entry.proof_version)
+        ],
+        bump = compatibility_entry.bump
+    )]
+    pub compatibility_entry: Account<'info, CompatibilityEntry>,
+}
+
 #[account]
 pub struct ZkMetaRegistry {
     pub authority: Pubkey,
     pub circuit_count: u64,
+    pub authorized_approvers: Vec<Pubkey>, // Keys allowed to propose and approve verification keys
+    pub approval_threshold: u8,            // Approvals needed to finalize a proposal
 }
 
+/// Maximum number of verification key approvers
+pub const MAX_APPROVERS: usize = 10;
+
 impl ZkMetaRegistry {
-    pub const LEN: usize = 32 + 8;
+    pub const LEN: usize = 32 + 8 + (4 + 32 * MAX_APPROVERS) + 1;
+}
+
+#[account]
+pub struct VKProposal {
+    pub proposer: Pubkey,
+    pub circuit_name: String,
+    pub circuit_version: String,
+    pub verification_key: Vec<u8>,
+    pub verification_key_hash: [u8; 32],
+    pub approvals: Vec<Pubkey>,
+    pub created_at: i64,
+    pub finalized: bool,
+    pub bump: u8,
+}
+
+impl VKProposal {
+    pub const LEN: usize = 32 + 4 + 32 + 4 + 16 + 4 + 8192 + 32 + (4 + 32 * MAX_APPROVERS) + 8 + 1 + 1;
+}
+
+/// Registry change that needs the approval threshold once several approvers are required
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub enum RegistryAction {
+    SetApprovers { approvers: Vec<Pubkey>, approval_threshold: u8 },
+    DeactivateKey { circuit_name: String },
+}
+
+impl RegistryAction {
+    pub const LEN: usize = 1 + (4 + 32 * MAX_APPROVERS) + 1;
+}
+
+#[account]
+pub struct RegistryProposal {
+    pub proposer: Pubkey,
+    pub action: RegistryAction,
+    pub approvals: Vec<Pubkey>,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl RegistryProposal {
+    pub const LEN: usize = 32 + RegistryAction::LEN + (4 + 32 * MAX_APPROVERS) + 8 + 1;
 }
 
 #[account]
@@ -212,10 +1012,179 @@ pub struct VerificationKeyEntry {
     pub verification_key_hash: [u8; 32], // SHA256 hash of verification key for integrity
     pub registered_at: i64,        // Timestamp
     pub is_active: bool,           // Active/inactive status
+    pub previous_vk_hash: Option<[u8; 32]>, // Hash of the key this one replaced, None for the genesis key
 }
 
 impl VerificationKeyEntry {
-    pub const LEN: usize = 4 + 32 + 4 + 16 + 4 + 8192 + 32 + 8 + 1; // Dynamic strings + VK data + hash
+    pub const LEN: usize = 4 + 32 + 4 + 16 + 4 + 8192 + 32 + 8 + 1 + (1 + 32); // Dynamic strings + VK data + hashes
+}
+
+/// Link in a circuit's verification key hash chain, one per key ever written
+#[account]
+pub struct VKVersionRecord {
+    pub vk_hash: [u8; 32],
+    pub previous_vk_hash: Option<[u8; 32]>,
+    pub circuit_version: String,
+    pub recorded_at: i64,
+    pub bump: u8,
+}
+
+impl VKVersionRecord {
+    pub const LEN: usize = 32 + (1 + 32) + (4 + 16) + 8 + 1;
+
+    /// Capture the key currently held by `vk_entry`
+    pub fn set(&mut self, vk_entry: &VerificationKeyEntry, bump: u8) {
+        self.vk_hash = vk_entry.verification_key_hash;
+        self.previous_vk_hash = vk_entry.previous_vk_hash;
+        self.circuit_version = vk_entry.circuit_version.clone();
+        self.recorded_at = vk_entry.registered_at;
+        self.bump = bump;
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CurveType {
+    Bn254,
+    Bls12_381,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProtocolType {
+    Groth16,
+    Plonk,
+}
+
+/// Magic bytes opening every serialized verification key
+pub const VK_HEADER_MAGIC: [u8; 4] = *b"ZKVK";
+pub const VK_HEADER_LEN: usize = 64;
+
+/// Circuit parameters carried in the 64-byte verification key header:
+///
+/// | bytes  | field                                   |
+/// |--------|-----------------------------------------|
+/// | 0..4   | magic `b"ZKVK"`                         |
+/// | 4      | curve (0 = BN254, 1 = BLS12-381)        |
+/// | 5      | protocol (0 = Groth16, 1 = PLONK)       |
+/// | 6      | number of public inputs                 |
+/// | 7      | IC length (Groth16: public inputs + 1)  |
+/// | 8..64  | reserved, zero                          |
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitParams {
+    pub num_public_inputs: u8,
+    pub ic_length: u8,
+    pub curve: CurveType,
+    pub protocol: ProtocolType,
+}
+
+impl CircuitParams {
+    pub fn parse(verification_key: &[u8]) -> Result<Self> {
+        require!(verification_key.len() >= VK_HEADER_LEN, ErrorCode::InvalidVerificationKey);
+        let header = &verification_key[..VK_HEADER_LEN];
+        require!(header[0..4] == VK_HEADER_MAGIC, ErrorCode::InvalidVerificationKey);
+        require!(header[8..].iter().all(|&b| b == 0), ErrorCode::InvalidVerificationKey);
+
+        let curve = match header[4] {
+            0 => CurveType::Bn254,
+            1 => CurveType::Bls12_381,
+            _ => return Err(ErrorCode::InvalidVerificationKey.into()),
+        };
+        let protocol = match header[5] {
+            0 => ProtocolType::Groth16,
+            1 => ProtocolType::Plonk,
+            _ => return Err(ErrorCode::InvalidVerificationKey.into()),
+        };
+        let num_public_inputs = header[6];
+        let ic_length = header[7];
+
+        if protocol == ProtocolType::Groth16 {
+            require!(
+                ic_length as u16 == num_public_inputs as u16 + 1,
+                ErrorCode::InvalidVerificationKey
+            );
+        }
+
+        Ok(CircuitParams {
+            num_public_inputs,
+            ic_length,
+            curve,
+            protocol,
+        })
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VKAuditAction {
+    Register,
+    Update,
+    Deactivate,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AuditEntry {
+    pub actor: Pubkey,
+    pub action: VKAuditAction,
+    pub circuit_name: String,
+    pub timestamp: i64,
+    pub old_vk_hash: Option<[u8; 32]>,
+    pub new_vk_hash: [u8; 32],
+}
+
+impl AuditEntry {
+    pub const LEN: usize = 32 + 1 + (4 + 32) + 8 + (1 + 32) + 32;
+}
+
+/// Maximum audit entries kept per circuit before the oldest are overwritten
+pub const MAX_AUDIT_ENTRIES: usize = 50;
+
+#[account]
+pub struct AuditLog {
+    pub circuit_name: String,
+    pub entries: Vec<AuditEntry>, // Ring buffer, oldest entry at next_index once full
+    pub next_index: u8,
+    pub bump: u8,
+}
+
+impl AuditLog {
+    pub const LEN: usize = (4 + 32) + (4 + AuditEntry::LEN * MAX_AUDIT_ENTRIES) + 1 + 1;
+
+    /// Set up a freshly created log
+    pub fn init_if_empty(&mut self, circuit_name: &str, bump: u8) {
+        if self.circuit_name.is_empty() {
+            self.circuit_name = circuit_name.to_string();
+            self.bump = bump;
+        }
+    }
+
+    /// Append an entry, overwriting the oldest once the log is full
+    pub fn record(&mut self, entry: AuditEntry) {
+        if self.entries.len() < MAX_AUDIT_ENTRIES {
+            self.entries.push(entry);
+        } else {
+            self.entries[self.next_index as usize] = entry;
+        }
+        self.next_index = ((self.next_index as usize + 1) % MAX_AUDIT_ENTRIES) as u8;
+    }
+
+    /// Entries ordered from oldest to newest
+    pub fn chronological(&self) -> Vec<AuditEntry> {
+        let split = if self.entries.len() < MAX_AUDIT_ENTRIES { 0 } else { self.next_index as usize };
+        let mut entries = self.entries[split..].to_vec();
+        entries.extend_from_slice(&self.entries[..split]);
+        entries
+    }
+}
+
+#[account]
+pub struct CompatibilityEntry {
+    pub circuit_name: String,
+    pub proof_version: String, // Circuit version the proof was generated with
+    pub vk_version: String,    // Verification key version that accepts it
+    pub registered_at: i64,
+    pub bump: u8,
+}
+
+impl CompatibilityEntry {
+    pub const LEN: usize = 4 + 32 + 4 + 16 + 4 + 16 + 8 + 1;
 }
 
 #[event]
@@ -240,6 +1209,43 @@ pub struct VerificationKeyDeactivated {
     pub circuit_version: String,
 }
 
+#[event]
+pub struct VKProposalCreated {
+    pub circuit_name: String,
+    pub circuit_version: String,
+    pub verification_key_hash: [u8; 32],
+    pub proposer: Pubkey,
+}
+
+#[event]
+pub struct VKProposalApproved {
+    pub circuit_name: String,
+    pub circuit_version: String,
+    pub approver: Pubkey,
+    pub approval_count: u8,
+}
+
+#[event]
+pub struct RegistryProposalCreated {
+    pub proposer: Pubkey,
+    pub action: RegistryAction,
+}
+
+#[event]
+pub struct RegistryProposalApproved {
+    pub proposer: Pubkey,
+    pub approver: Pubkey,
+    pub approval_count: u8,
+}
+
+#[event]
+pub struct CompatibilityRegistered {
+    pub circuit_name: String,
+    pub proof_version: String,
+    pub vk_version: String,
+    pub registered_at: i64,
+}
+
 #[error_code]
 pub enum ErrorCode {
     #[msg("Unauthorized access")]
@@ -248,10 +1254,86 @@ pub enum ErrorCode {
     CircuitNameTooLong,
     #[msg("Version string too long (max 16 chars)")]
     VersionTooLong,
+    #[msg("Circuit version must look like vMAJOR.MINOR")]
+    InvalidVersionFormat,
     #[msg("Verification key too large (max 8KB)")]
     VerificationKeyTooLarge,
     #[msg("Verification key cannot be empty")]
     EmptyVerificationKey,
     #[msg("Invalid verification key format")]
     InvalidVerificationKey,
+    #[msg("Too many approvers (max 10)")]
+    TooManyApprovers,
+    #[msg("Approval threshold must be between 1 and the number of approvers")]
+    InvalidApprovalThreshold,
+    #[msg("Verification key changes require a multi-party proposal")]
+    MultiPartyApprovalRequired,
+    #[msg("Proposal already finalized")]
+    ProposalAlreadyFinalized,
+    #[msg("Approver has already approved this proposal")]
+    AlreadyApproved,
+    #[msg("Not enough approvals to finalize the proposal")]
+    InsufficientApprovals,
+    #[msg("Deactivation needs the proposal's verification key entry and audit log")]
+    MissingVerificationKeyEntry,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_current_approvers_count_towards_threshold() {
+        let (a, b, removed) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let registry = ZkMetaRegistry {
+            authority: a,
+            circuit_count: 0,
+            authorized_approvers: vec![a, b],
+            approval_threshold: 2,
+        };
+
+        assert_eq!(current_approvals(&registry, &[a, removed]), 1);
+        assert_eq!(current_approvals(&registry, &[a, b]), 2);
+    }
+
+    #[test]
+    fn validate_approvers_bounds_threshold() {
+        let approvers = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        assert!(validate_approvers(&approvers, 2).is_ok());
+        assert_eq!(validate_approvers(&approvers, 0).unwrap_err(), error!(ErrorCode::InvalidApprovalThreshold));
+        assert_eq!(validate_approvers(&approvers, 3).unwrap_err(), error!(ErrorCode::InvalidApprovalThreshold));
+        assert_eq!(
+            validate_approvers(&vec![Pubkey::new_unique(); MAX_APPROVERS + 1], 1).unwrap_err(),
+            error!(ErrorCode::TooManyApprovers)
+        );
+    }
+
+    #[test]
+    fn parse_circuit_version_accepts_v_major_minor() {
+        assert_eq!(parse_circuit_version("v2.0").unwrap(), (2, 0));
+        assert_eq!(parse_circuit_version("v10.5").unwrap(), (10, 5));
+        assert_eq!(parse_circuit_version("v999.999").unwrap(), (999, 999));
+        assert!(validate_compatibility_versions("v2.0", "v2.1").is_ok());
+    }
+
+    #[test]
+    fn parse_circuit_version_rejects_malformed() {
+        for invalid in ["2.0", "v.0", "", "v", "v2", "v2.", "v1000.0", "v2.0.1", "v+1.0", "V2.0"] {
+            assert_eq!(
+                parse_circuit_version(invalid).unwrap_err(),
+                error!(ErrorCode::InvalidVersionFormat),
+                "{invalid:?}"
+            );
+            assert_eq!(
+                validate_compatibility_versions(invalid, "v2.0").unwrap_err(),
+                error!(ErrorCode::InvalidVersionFormat),
+                "{invalid:?}"
+            );
+            assert_eq!(
+                validate_compatibility_versions("v2.0", invalid).unwrap_err(),
+                error!(ErrorCode::InvalidVersionFormat),
+                "{invalid:?}"
+            );
+        }
+    }
 }
@@ -0,0 +1,32 @@
+//! Field element encoding shared with `spend-verifier/build.rs`.
+//!
+//! The build script includes this file with `#[path]`, so it must not depend
+//! on anything outside `core`/`std`.
+
+/// Convert a base10 bignum string to 32 little-endian bytes.
+///
+/// Returns `None` for an empty string, a non-digit character or a value that
+/// does not fit in 256 bits.
+pub fn decimal_to_le_bytes(digits: &str) -> Option<[u8; 32]> {
+    if digits.is_empty() {
+        return None;
+    }
+
+    let mut out = [0u8; 32];
+    for digit in digits.bytes() {
+        if !digit.is_ascii_digit() {
+            return None;
+        }
+        let mut carry = (digit - b'0') as u32;
+        for byte in out.iter_mut() {
+            let acc = *byte as u32 * 10 + carry;
+            *byte = acc as u8;
+            carry = acc >> 8;
+        }
+        if carry != 0 {
+            return None;
+        }
+    }
+
+    Some(out)
+}
@@ -0,0 +1,161 @@
+//! Off-chain helpers for x402 clients.
+//!
+//! Converts proofs produced by snarkjs into the `Groth16Proof` layout the
+//! spend verifier expects, using the same little-endian field encoding as the
+//! verification key embedded by `spend-verifier/build.rs`.
+
+mod field;
+
+use serde_json::Value;
+use spend_verifier::{G1Point, G2Point, Groth16Proof};
+
+/// Value to pass as the spend circuit's `recipient` input for a payee
+pub use spend_verifier::recipient_signal;
+
+/// Value to pass as the spend circuit's `relayer` input for the submitting relayer
+pub use spend_verifier::relayer_signal;
+
+#[derive(Debug, thiserror::Error)]
+pub enum SdkError {
+    #[error("invalid proof JSON: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+    #[error("missing or malformed field `{0}`")]
+    MissingField(&'static str),
+    #[error("invalid decimal field element: {0}")]
+    InvalidFieldElement(String),
+}
+
+pub type Result<T> = std::result::Result<T, SdkError>;
+
+/// Parse snarkjs proof output
+pub trait SnarkjsProof: Sized {
+    /// Build from snarkjs `proof.json` bytes (`pi_a`, `pi_b`, `pi_c` as decimal strings)
+    fn from_snarkjs_json(json_bytes: &[u8]) -> Result<Self>;
+}
+
+impl SnarkjsProof for Groth16Proof {
+    fn from_snarkjs_json(json_bytes: &[u8]) -> Result<Self> {
+        let proof: Value = serde_json::from_slice(json_bytes)?;
+
+        Ok(Groth16Proof {
+            pi_a: g1(&proof["pi_a"], "pi_a")?,
+            pi_b: g2(&proof["pi_b"], "pi_b")?,
+            pi_c: g1(&proof["pi_c"], "pi_c")?,
+            // Not part of the snarkjs output; set by the caller for change-note spends
+            change_commitment: [0u8; 32],
+        })
+    }
+}
+
+/// snarkjs G1: `[x, y, z]` in affine form with `z = "1"`
+fn g1(point: &Value, name: &'static str) -> Result<G1Point> {
+    Ok(G1Point {
+        x: field(&point[0], name)?,
+        y: field(&point[1], name)?,
+    })
+}
+
+/// snarkjs G2: `[[x.c0, x.c1], [y.c0, y.c1], [z.c0, z.c1]]`
+fn g2(point: &Value, name: &'static str) -> Result<G2Point> {
+    Ok(G2Point {
+        x: [field(&point[0][0], name)?, field(&point[0][1], name)?],
+        y: [field(&point[1][0], name)?, field(&point[1][1], name)?],
+    })
+}
+
+fn field(value: &Value, name: &'static str) -> Result<[u8; 32]> {
+    let digits = value.as_str().ok_or(SdkError::MissingField(name))?;
+    decimal_to_le_bytes(digits)
+}
+
+/// Convert a base10 bignum string to 32 little-endian bytes
+pub fn decimal_to_le_bytes(digits: &str) -> Result<[u8; 32]> {
+    field::decimal_to_le_bytes(digits).ok_or_else(|| SdkError::InvalidFieldElement(digits.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{AnchorDeserialize, AnchorSerialize};
+    use ark_bn254::{Fq, G1Affine, G2Affine};
+    use ark_ec::AffineRepr;
+    use ark_ff::{BigInteger, PrimeField};
+    use ark_serialize::CanonicalSerialize;
+
+    fn fq_bytes(value: &Fq) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        value.serialize_uncompressed(&mut bytes[..]).unwrap();
+        bytes
+    }
+
+    #[test]
+    fn decimal_to_le_bytes_matches_integer_encoding() {
+        for value in [0u128, 1, 255, 256, 65_535, u64::MAX as u128, u128::MAX] {
+            let mut expected = [0u8; 32];
+            expected[..16].copy_from_slice(&value.to_le_bytes());
+            assert_eq!(decimal_to_le_bytes(&value.to_string()).unwrap(), expected);
+        }
+
+        let modulus = Fq::MODULUS;
+        assert_eq!(
+            decimal_to_le_bytes(&modulus.to_string()).unwrap()[..],
+            modulus.to_bytes_le()[..]
+        );
+
+        let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        assert_eq!(decimal_to_le_bytes(max).unwrap(), [0xff; 32]);
+        let overflow = "115792089237316195423570985008687907853269984665640564039457584007913129639936";
+        assert!(decimal_to_le_bytes(overflow).is_err());
+        assert!(decimal_to_le_bytes("").is_err());
+        assert!(decimal_to_le_bytes("12a").is_err());
+        assert!(decimal_to_le_bytes("-1").is_err());
+    }
+
+    #[test]
+    fn snarkjs_proof_round_trips_through_on_chain_encoding() {
+        // BN254 generators in snarkjs' projective JSON layout
+        let json = br#"{
+            "pi_a": ["1", "2", "1"],
+            "pi_b": [
+                ["10857046999023057135944570762232829481370756359578518086990519993285655852781",
+                 "11559732032986387107991004021392285783925812861821192530917403151452391805634"],
+                ["8495653923123431417604973247489272438418190587263600148770280649306958101930",
+                 "4082367875863433681332203403145435568316851327593401208105741076214120093531"],
+                ["1", "0"]
+            ],
+            "pi_c": ["1", "21888242871839275222246405745257275088696311157297823662689037894645226208581", "1"],
+            "protocol": "groth16",
+            "curve": "bn128"
+        }"#;
+        let proof = Groth16Proof::from_snarkjs_json(json).unwrap();
+
+        // Coordinates land in the little-endian encoding ark-serialize uses on-chain
+        let g1 = G1Affine::generator();
+        let g2 = G2Affine::generator();
+        assert_eq!(proof.pi_a.x, fq_bytes(&g1.x));
+        assert_eq!(proof.pi_a.y, fq_bytes(&g1.y));
+        assert_eq!(proof.pi_b.x, [fq_bytes(&g2.x.c0), fq_bytes(&g2.x.c1)]);
+        assert_eq!(proof.pi_b.y, [fq_bytes(&g2.y.c0), fq_bytes(&g2.y.c1)]);
+        assert_eq!(proof.pi_c.y, fq_bytes(&-g1.y));
+
+        // and survive the instruction data encoding unchanged
+        let encoded = proof.try_to_vec().unwrap();
+        let decoded = Groth16Proof::try_from_slice(&encoded).unwrap();
+        assert_eq!(decoded.try_to_vec().unwrap(), encoded);
+        assert_eq!(decoded.pi_b.x, proof.pi_b.x);
+        assert_eq!(decoded.change_commitment, [0u8; 32]);
+    }
+
+    #[test]
+    fn snarkjs_proof_rejects_malformed_fields() {
+        assert!(matches!(
+            Groth16Proof::from_snarkjs_json(br#"{"pi_a": ["1", "2", "1"]}"#),
+            Err(SdkError::MissingField("pi_b"))
+        ));
+        assert!(matches!(
+            Groth16Proof::from_snarkjs_json(br#"{"pi_a": ["1", "0x2", "1"], "pi_b": [], "pi_c": []}"#),
+            Err(SdkError::InvalidFieldElement(_))
+        ));
+        assert!(matches!(Groth16Proof::from_snarkjs_json(b"{"), Err(SdkError::InvalidJson(_))));
+    }
+}
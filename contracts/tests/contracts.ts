@@ -42,7 +42,9 @@ describe("Solana Omni-Shield x402", () => {
 
   it("Can initialize Shielded Pool", async () => {
     try {
-      const tx = await shieldedPool.methods.initialize().rpc();
+      // Wrapped SOL mint
+      const acceptedMint = new anchor.web3.PublicKey("So11111111111111111111111111111111111111112");
+      const tx = await shieldedPool.methods.initialize(acceptedMint).rpc();
       console.log("✅ Shielded Pool initialized:", tx);
     } catch (error) {
       console.log("ℹ️  Shielded Pool may already be initialized");
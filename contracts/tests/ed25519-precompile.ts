@@ -0,0 +1,69 @@
+import * as anchor from "@coral-xyz/anchor";
+import { expect } from "chai";
+
+// RFC 8032 TEST 1: empty message
+const RFC8032_PUBLIC_KEY = Buffer.from(
+  "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+  "hex"
+);
+const RFC8032_SIGNATURE = Buffer.from(
+  "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065" +
+    "224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+  "hex"
+);
+
+describe("Ed25519 precompile verification", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  // Simulate `instructions` and return the error and compute units consumed
+  async function simulate(instructions: anchor.web3.TransactionInstruction[]) {
+    const { blockhash } = await provider.connection.getLatestBlockhash();
+    const message = new anchor.web3.TransactionMessage({
+      payerKey: provider.wallet.publicKey,
+      recentBlockhash: blockhash,
+      instructions,
+    }).compileToV0Message();
+    const tx = await provider.wallet.signTransaction(new anchor.web3.VersionedTransaction(message));
+    const { value } = await provider.connection.simulateTransaction(tx);
+    return { err: value.err, unitsConsumed: value.unitsConsumed ?? 0 };
+  }
+
+  function ed25519Instruction(signature: Buffer) {
+    return anchor.web3.Ed25519Program.createInstructionWithPublicKey({
+      publicKey: RFC8032_PUBLIC_KEY,
+      message: Buffer.alloc(0),
+      signature,
+    });
+  }
+
+  it("Accepts the RFC 8032 known vector", async () => {
+    const { err } = await simulate([ed25519Instruction(RFC8032_SIGNATURE)]);
+    expect(err).to.be.null;
+  });
+
+  it("Rejects a tampered signature", async () => {
+    const tampered = Buffer.from(RFC8032_SIGNATURE);
+    tampered[0] ^= 1;
+    const { err } = await simulate([ed25519Instruction(tampered)]);
+    expect(err).to.not.be.null;
+  });
+
+  it("Benchmarks compute units of precompile verification", async () => {
+    const memo = new anchor.web3.TransactionInstruction({
+      programId: new anchor.web3.PublicKey("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"),
+      keys: [],
+      data: Buffer.from("x402"),
+    });
+    const baseline = await simulate([memo]);
+    const verified = await simulate([ed25519Instruction(RFC8032_SIGNATURE), memo]);
+    expect(baseline.err).to.be.null;
+    expect(verified.err).to.be.null;
+
+    // The signature check runs natively; programs only scan the instructions sysvar for it
+    const precompileUnits = verified.unitsConsumed - baseline.unitsConsumed;
+    console.log("Ed25519 precompile compute units:", precompileUnits);
+    console.log("Baseline (memo only) compute units:", baseline.unitsConsumed);
+    expect(precompileUnits).to.be.lessThan(10_000);
+  });
+});
@@ -0,0 +1,99 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { expect } from "chai";
+import * as fs from "fs";
+import * as path from "path";
+
+// Written by circuits/test_spend_proper.js after a successful snarkjs verify
+const PROOF_PATH = path.join(__dirname, "../circuits/build/proof_spend.json");
+const PUBLIC_PATH = path.join(__dirname, "../circuits/build/public_spend.json");
+
+// Mirrors spend_verifier::MAX_BATCH_PROOFS
+const MAX_BATCH_PROOFS = 3;
+const MAX_COMPUTE_UNITS = 1_400_000;
+
+describe("Spend verifier compute units", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const spendVerifier = anchor.workspace.spendVerifier as Program<any>;
+
+  // snarkjs decimal field element to the little-endian bytes the program decodes
+  const field = (decimal: string) => new anchor.BN(decimal).toArray("le", 32);
+  const g1 = (point: string[]) => ({ x: field(point[0]), y: field(point[1]) });
+  const g2 = (point: string[][]) => ({
+    x: [field(point[0][0]), field(point[0][1])],
+    y: [field(point[1][0]), field(point[1][1])],
+  });
+
+  // Simulate `instructions` under the maximum compute budget and return the error and units consumed
+  async function simulate(instructions: anchor.web3.TransactionInstruction[]) {
+    const { blockhash } = await provider.connection.getLatestBlockhash();
+    const message = new anchor.web3.TransactionMessage({
+      payerKey: provider.wallet.publicKey,
+      recentBlockhash: blockhash,
+      instructions: [
+        anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({ units: MAX_COMPUTE_UNITS }),
+        ...instructions,
+      ],
+    }).compileToV0Message();
+    const tx = await provider.wallet.signTransaction(new anchor.web3.VersionedTransaction(message));
+    const { value } = await provider.connection.simulateTransaction(tx);
+    return { err: value.err, unitsConsumed: value.unitsConsumed ?? 0 };
+  }
+
+  let proof: any;
+  let signals: number[][];
+
+  before(async function () {
+    if (!fs.existsSync(PROOF_PATH) || !fs.existsSync(PUBLIC_PATH)) {
+      console.log("ℹ️  No spend proof fixture, run circuits/test_spend_proper.js first");
+      this.skip();
+    }
+    const snarkjsProof = JSON.parse(fs.readFileSync(PROOF_PATH, "utf8"));
+    proof = {
+      piA: g1(snarkjsProof.pi_a),
+      piB: g2(snarkjsProof.pi_b),
+      piC: g1(snarkjsProof.pi_c),
+      changeCommitment: Array(32).fill(0),
+    };
+    signals = JSON.parse(fs.readFileSync(PUBLIC_PATH, "utf8")).map(field);
+
+    try {
+      await spendVerifier.methods.initialize(Array(32).fill(0)).rpc();
+    } catch (error) {
+      console.log("ℹ️  Spend verifier may already be initialized");
+    }
+  });
+
+  // A one-proof batch runs the same four-pair Miller loop and final
+  // exponentiation as the Groth16 check in verify_spend_proof
+  it("Benchmarks compute units of Groth16 spend verification", async () => {
+    const ix = await spendVerifier.methods
+      .batchVerifySpendProofs([proof], [signals])
+      .instruction();
+    const { err, unitsConsumed } = await simulate([ix]);
+
+    console.log("Groth16 spend verification compute units:", unitsConsumed);
+    expect(err).to.be.null;
+    expect(unitsConsumed).to.be.lessThan(MAX_COMPUTE_UNITS);
+  });
+
+  it("Benchmarks compute units of batch spend verification", async () => {
+    for (let count = 2; count <= MAX_BATCH_PROOFS; count++) {
+      const ix = await spendVerifier.methods
+        .batchVerifySpendProofs(Array(count).fill(proof), Array(count).fill(signals))
+        .instruction();
+
+      // Each proof and its signals take ~0.5 KB, so larger batches can exceed the packet size
+      const { err, unitsConsumed } = await simulate([ix]).catch((error) => {
+        console.log(`Batch of ${count} proofs does not fit in one transaction:`, error.message);
+        return { err: null, unitsConsumed: 0 };
+      });
+      if (unitsConsumed === 0) continue;
+
+      console.log(`Batch of ${count} proofs compute units:`, unitsConsumed);
+      expect(err).to.be.null;
+      expect(unitsConsumed).to.be.lessThan(MAX_COMPUTE_UNITS);
+    }
+  });
+});